@@ -1,6 +1,14 @@
 use super::style_rule::StyleRule;
+use crate::serializer::ToCss;
 
 #[derive(Debug, PartialEq)]
 pub enum CSSRule {
     Style(StyleRule),
 }
+
+impl CSSRule {
+    /// https://www.w3.org/TR/cssom-1/#dom-cssrule-csstext
+    pub fn css_text(&self) -> String {
+        self.to_css_string()
+    }
+}
@@ -1,4 +1,5 @@
 use super::css_rule::CSSRule;
+use super::error::CSSOMError;
 use std::ops::Deref;
 
 #[derive(Debug, PartialEq)]
@@ -12,6 +13,28 @@ impl CSSRuleList {
     pub fn append_rule(&mut self, rule: CSSRule) {
         self.0.push(rule);
     }
+
+    /// Insert a rule at the index and return the index
+    /// https://www.w3.org/TR/cssom-1/#insert-a-css-rule
+    pub fn insert_rule(&mut self, rule: CSSRule, index: usize) -> Result<usize, CSSOMError> {
+        if index > self.0.len() {
+            return Err(CSSOMError::IndexSize);
+        }
+        self.0.insert(index, rule);
+        Ok(index)
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#remove-a-css-rule
+    pub fn delete_rule(&mut self, index: usize) -> Result<CSSRule, CSSOMError> {
+        if index >= self.0.len() {
+            return Err(CSSOMError::IndexSize);
+        }
+        Ok(self.0.remove(index))
+    }
+
+    pub fn item_mut(&mut self, index: usize) -> Option<&mut CSSRule> {
+        self.0.get_mut(index)
+    }
 }
 
 impl Deref for CSSRuleList {
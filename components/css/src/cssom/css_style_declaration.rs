@@ -0,0 +1,242 @@
+use crate::parser::structs::{ComponentValue, Declaration, DeclarationOrAtRule};
+use crate::parser::Parser;
+use crate::serializer::ToCss;
+use crate::tokenizer::token::Token;
+use crate::tokenizer::Tokenizer;
+use io::data_stream::DataStream;
+use std::ops::Deref;
+
+/// The declarations block of a style rule
+/// https://www.w3.org/TR/cssom-1/#the-cssstyledeclaration-interface
+#[derive(Debug, Default, PartialEq)]
+pub struct CSSStyleDeclaration(Vec<Declaration>);
+
+impl CSSStyleDeclaration {
+    pub fn new(declarations: Vec<Declaration>) -> Self {
+        Self(declarations)
+    }
+
+    /// Parse a list of declarations, e.g. the value of a `style` attribute.
+    /// Invalid declarations and nested rules are dropped.
+    /// https://drafts.csswg.org/css-syntax-3/#parse-a-list-of-declarations
+    pub fn parse(css_text: &str) -> Self {
+        let tokenizer = Tokenizer::new(css_text.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        let values = parser.parse_a_list_of_component_values();
+        let mut parser = Parser::<ComponentValue>::new(DataStream::new(values));
+        let declarations = parser
+            .parse_a_list_of_declarations()
+            .into_iter()
+            .filter_map(|content| match content {
+                DeclarationOrAtRule::Declaration(declaration) => Some(declaration),
+                _ => None,
+            })
+            .collect();
+        Self(declarations)
+    }
+
+    /// Replace the declarations with the ones parsed from the text
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-csstext
+    pub fn set_css_text(&mut self, css_text: &str) {
+        *self = Self::parse(css_text);
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-getpropertyvalue
+    pub fn get_property_value(&self, property: &str) -> String {
+        match self.get_declaration(property) {
+            Some(declaration) => declaration
+                .value
+                .iter()
+                .map(|value| value.to_css_string())
+                .collect(),
+            None => String::new(),
+        }
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-getpropertypriority
+    pub fn get_property_priority(&self, property: &str) -> String {
+        match self.get_declaration(property) {
+            Some(declaration) if declaration.important => "important".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// Set the value of a property. An empty value removes the property.
+    /// Returns false if the value or the priority is invalid, or if the
+    /// declaration is rejected by `is_supported`, which checks the
+    /// property and its value against the supported ones.
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-setproperty
+    pub fn set_property(
+        &mut self,
+        property: &str,
+        value: &str,
+        priority: &str,
+        is_supported: impl Fn(&Declaration) -> bool,
+    ) -> bool {
+        let property = normalize_property_name(property);
+
+        if value.trim().is_empty() {
+            self.remove_property(&property);
+            return true;
+        }
+
+        let important = if priority.is_empty() {
+            false
+        } else if priority.eq_ignore_ascii_case("important") {
+            true
+        } else {
+            return false;
+        };
+
+        let declaration = match parse_declaration_value(value) {
+            Some(value) => Declaration {
+                name: property,
+                value,
+                important,
+                location: None,
+            },
+            None => return false,
+        };
+        if !is_supported(&declaration) {
+            return false;
+        }
+
+        match self.0.iter_mut().rev().find(|d| d.name == declaration.name) {
+            Some(existing) => {
+                existing.value = declaration.value;
+                existing.important = declaration.important;
+            }
+            None => self.0.push(declaration),
+        }
+        true
+    }
+
+    /// Remove every declaration of a property and return its previous value
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-removeproperty
+    pub fn remove_property(&mut self, property: &str) -> String {
+        let property = normalize_property_name(property);
+        let value = self.get_property_value(&property);
+        self.0.retain(|declaration| declaration.name != property);
+        value
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstyledeclaration-csstext
+    pub fn css_text(&self) -> String {
+        self.0
+            .iter()
+            .map(|declaration| declaration.to_css_string())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// The last declaration of a property wins
+    fn get_declaration(&self, property: &str) -> Option<&Declaration> {
+        let property = normalize_property_name(property);
+        self.0.iter().rev().find(|d| d.name == property)
+    }
+}
+
+impl Deref for CSSStyleDeclaration {
+    type Target = Vec<Declaration>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a> IntoIterator for &'a CSSStyleDeclaration {
+    type Item = &'a Declaration;
+    type IntoIter = std::slice::Iter<'a, Declaration>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Custom property names are case-sensitive, other properties are not
+fn normalize_property_name(property: &str) -> String {
+    if property.starts_with("--") {
+        property.to_string()
+    } else {
+        property.to_ascii_lowercase()
+    }
+}
+
+/// Parse the value of a declaration, rejecting values that would
+/// end the declaration early
+fn parse_declaration_value(value: &str) -> Option<Vec<ComponentValue>> {
+    let tokenizer = Tokenizer::new(value.chars());
+    let mut parser = Parser::<Token>::new(tokenizer.run());
+    let mut values = parser.parse_a_list_of_component_values();
+
+    let is_invalid = values.iter().any(|value| {
+        matches!(
+            value,
+            ComponentValue::PerservedToken(
+                Token::Semicolon | Token::BadStr | Token::BadUrl | Token::Delim('!')
+            )
+        )
+    });
+    if is_invalid {
+        return None;
+    }
+
+    while let Some(ComponentValue::PerservedToken(Token::Whitespace)) = values.first() {
+        values.remove(0);
+    }
+    while let Some(ComponentValue::PerservedToken(Token::Whitespace)) = values.last() {
+        values.pop();
+    }
+    Some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_remove_properties() {
+        let mut style = CSSStyleDeclaration::default();
+        let any = |_: &Declaration| true;
+
+        assert!(style.set_property("Color", " red ", "", any));
+        assert!(style.set_property("margin", "0 auto", "important", any));
+        assert!(style.set_property("--Gap", "1px", "", any));
+        assert_eq!(style.get_property_value("color"), "red");
+        assert_eq!(style.get_property_priority("margin"), "important");
+        assert_eq!(style.get_property_value("--gap"), "");
+        assert_eq!(
+            style.css_text(),
+            "color: red; margin: 0 auto !important; --Gap: 1px;"
+        );
+
+        // invalid values and priorities are ignored
+        assert!(!style.set_property("color", "red; display: none", "", any));
+        assert!(!style.set_property("color", "blue", "urgent", any));
+        assert!(!style.set_property("color", "blue", "", |d| d.name != "color"));
+        assert_eq!(style.get_property_value("color"), "red");
+
+        // existing declarations are updated in place
+        assert!(style.set_property("color", "blue", "", any));
+        assert_eq!(style.len(), 3);
+        assert_eq!(style[0].name, "color");
+
+        assert_eq!(style.remove_property("MARGIN"), "0 auto");
+        assert_eq!(style.get_property_priority("margin"), "");
+        assert!(style.set_property("color", "", "", any));
+        assert_eq!(style.css_text(), "--Gap: 1px;");
+    }
+
+    #[test]
+    fn parse_declaration_list() {
+        let mut style =
+            CSSStyleDeclaration::parse("color: rgb(0, 0, 255); ; width: 10px !important; 5px");
+
+        assert_eq!(style.len(), 2);
+        assert_eq!(style.get_property_value("color"), "rgb(0, 0, 255)");
+        assert_eq!(style.get_property_priority("width"), "important");
+
+        style.set_css_text("display: none");
+        assert_eq!(style.css_text(), "display: none;");
+    }
+}
@@ -0,0 +1,9 @@
+/// Exceptions thrown by the CSSOM methods
+/// https://webidl.spec.whatwg.org/#idl-DOMException-error-names
+#[derive(Debug, Clone, PartialEq)]
+pub enum CSSOMError {
+    /// The index is not in the allowed range
+    IndexSize,
+    /// The string did not match the expected pattern
+    Syntax,
+}
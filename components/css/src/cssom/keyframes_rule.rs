@@ -0,0 +1,99 @@
+use super::css_style_declaration::CSSStyleDeclaration;
+use crate::media_queries::{Device, MediaQueryList};
+use crate::parser::structs::{ComponentValue, Declaration};
+use crate::tokenizer::token::Token;
+
+/// A `@keyframes` rule
+/// https://www.w3.org/TR/css-animations-1/#keyframes
+#[derive(Debug, PartialEq)]
+pub struct KeyframesRule {
+    pub name: String,
+    pub keyframes: Vec<Keyframe>,
+    /// The media conditions of the `@media` rules this rule
+    /// was nested in, all of them must match
+    pub media: Vec<MediaQueryList>,
+}
+
+/// A keyframe block, e.g. `from, 50% { opacity: 0 }`
+/// https://www.w3.org/TR/css-animations-1/#interface-csskeyframerule
+#[derive(Debug, PartialEq)]
+pub struct Keyframe {
+    /// The keyframe selectors as offsets between 0 and 1
+    pub offsets: Vec<f32>,
+    pub declarations: CSSStyleDeclaration,
+}
+
+impl KeyframesRule {
+    pub fn new(name: String, keyframes: Vec<Keyframe>) -> Self {
+        Self {
+            name,
+            keyframes,
+            media: Vec::new(),
+        }
+    }
+
+    /// Parse the name of a `@keyframes` rule, either an identifier or a string
+    pub fn parse_name(prelude: &[ComponentValue]) -> Option<String> {
+        let mut values = prelude
+            .iter()
+            .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)));
+        let name = match values.next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(name)))
+                if !name.eq_ignore_ascii_case("none") =>
+            {
+                name.clone()
+            }
+            Some(ComponentValue::PerservedToken(Token::Str(name))) => name.clone(),
+            _ => return None,
+        };
+        match values.next() {
+            None => Some(name),
+            Some(_) => None,
+        }
+    }
+
+    pub fn matches_media(&self, device: &Device) -> bool {
+        self.media.iter().all(|media| media.matches(device))
+    }
+}
+
+impl Keyframe {
+    pub fn new(offsets: Vec<f32>, declarations: Vec<Declaration>) -> Self {
+        Self {
+            offsets,
+            declarations: CSSStyleDeclaration::new(declarations),
+        }
+    }
+
+    /// Parse a comma separated list of keyframe selectors
+    /// https://www.w3.org/TR/css-animations-1/#typedef-keyframe-selector
+    pub fn parse_offsets(prelude: &[ComponentValue]) -> Option<Vec<f32>> {
+        prelude
+            .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+            .map(|values| {
+                let mut values = values.iter().filter(|value| {
+                    !matches!(value, ComponentValue::PerservedToken(Token::Whitespace))
+                });
+                let offset = match values.next()? {
+                    ComponentValue::PerservedToken(Token::Ident(ident)) => {
+                        match ident.to_ascii_lowercase().as_str() {
+                            "from" => 0.,
+                            "to" => 1.,
+                            _ => return None,
+                        }
+                    }
+                    ComponentValue::PerservedToken(Token::Percentage(value))
+                        if (0. ..=100.).contains(value) =>
+                    {
+                        value / 100.
+                    }
+                    _ => return None,
+                };
+                match values.next() {
+                    None => Some(offset),
+                    Some(_) => None,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,85 @@
+use crate::media_queries::{Device, MediaQueryList};
+use crate::parser::structs::ComponentValue;
+use crate::tokenizer::token::Token;
+
+/// A part of the name of a cascade layer
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LayerSegment {
+    Named(String),
+    /// A `@layer` block without a name, which is a new layer every time.
+    /// Anonymous layers are numbered in the order of their stylesheet.
+    Anonymous(usize),
+}
+
+/// A layer declared by a `@layer` rule, with the `@media` rules around it
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerRule {
+    pub name: LayerName,
+    pub media: Vec<MediaQueryList>,
+}
+
+impl LayerRule {
+    pub fn matches_media(&self, device: &Device) -> bool {
+        self.media.iter().all(|media| media.matches(device))
+    }
+}
+
+/// The full name of a cascade layer, e.g. `base.reset` for the
+/// layer `reset` nested in `base`. Rules that are not in a layer
+/// have an empty name.
+/// https://www.w3.org/TR/css-cascade-5/#layering
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LayerName(pub Vec<LayerSegment>);
+
+impl LayerName {
+    pub fn is_unlayered(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The anonymous layer with the number in its stylesheet
+    pub fn anonymous(id: usize) -> Self {
+        Self(vec![LayerSegment::Anonymous(id)])
+    }
+
+    /// The name of a layer nested in this one
+    pub fn join(&self, nested: &LayerName) -> Self {
+        Self(self.0.iter().chain(nested.0.iter()).cloned().collect())
+    }
+
+    /// Parse the prelude of a `@layer` rule, a comma separated list of
+    /// dotted layer names. The list is empty for an anonymous layer.
+    /// https://www.w3.org/TR/css-cascade-5/#at-layer
+    pub fn parse_list(prelude: &[ComponentValue]) -> Option<Vec<LayerName>> {
+        let is_whitespace = |value: &&ComponentValue| {
+            matches!(value, ComponentValue::PerservedToken(Token::Whitespace))
+        };
+        if prelude.iter().all(|value| is_whitespace(&value)) {
+            return Some(Vec::new());
+        }
+        prelude
+            .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+            .map(|values| {
+                let values = values
+                    .iter()
+                    .skip_while(is_whitespace)
+                    .collect::<Vec<&ComponentValue>>();
+                let end = values.iter().rposition(|value| !is_whitespace(value))?;
+                // identifiers separated by dots without whitespace
+                let mut segments = Vec::new();
+                for (index, value) in values[..=end].iter().enumerate() {
+                    match (index % 2, value) {
+                        (0, ComponentValue::PerservedToken(Token::Ident(name))) => {
+                            segments.push(LayerSegment::Named(name.clone()))
+                        }
+                        (1, ComponentValue::PerservedToken(Token::Delim('.'))) => {}
+                        _ => return None,
+                    }
+                }
+                match end % 2 {
+                    0 => Some(LayerName(segments)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
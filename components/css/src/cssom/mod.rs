@@ -1,4 +1,8 @@
 pub mod css_rule;
 pub mod css_rule_list;
+pub mod css_style_declaration;
+pub mod error;
+pub mod keyframes_rule;
+pub mod layer;
 pub mod style_rule;
 pub mod stylesheet;
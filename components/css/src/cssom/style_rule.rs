@@ -1,20 +1,35 @@
+use super::css_style_declaration::CSSStyleDeclaration;
+use super::layer::LayerName;
+use crate::media_queries::{Device, MediaQueryList};
 use crate::parser::structs::Declaration;
 use crate::selector::structs::{Selector, Specificity};
+use crate::serializer::ToCss;
 
 #[derive(Debug, PartialEq)]
 pub struct StyleRule {
     pub selectors: Vec<Selector>,
-    pub declarations: Vec<Declaration>,
+    pub declarations: CSSStyleDeclaration,
+    /// The media conditions of the `@media` rules this rule
+    /// was nested in, all of them must match
+    pub media: Vec<MediaQueryList>,
+    /// The cascade layer of the `@layer` block this rule was nested in
+    pub layer: LayerName,
 }
 
 impl StyleRule {
     pub fn new(selectors: Vec<Selector>, declarations: Vec<Declaration>) -> Self {
         Self {
             selectors,
-            declarations,
+            declarations: CSSStyleDeclaration::new(declarations),
+            media: Vec::new(),
+            layer: LayerName::default(),
         }
     }
 
+    pub fn matches_media(&self, device: &Device) -> bool {
+        self.media.iter().all(|media| media.matches(device))
+    }
+
     pub fn specificity(&self) -> Specificity {
         let specificities = self
             .selectors
@@ -24,4 +39,18 @@ impl StyleRule {
 
         specificities.into_iter().max().unwrap()
     }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstylerule-selectortext
+    pub fn selector_text(&self) -> String {
+        self.selectors
+            .iter()
+            .map(|selector| selector.to_css_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssrule-csstext
+    pub fn css_text(&self) -> String {
+        self.to_css_string()
+    }
 }
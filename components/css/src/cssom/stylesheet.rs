@@ -1,22 +1,92 @@
 use super::css_rule::CSSRule;
 use super::css_rule_list::CSSRuleList;
+use super::error::CSSOMError;
+use super::keyframes_rule::KeyframesRule;
+use super::layer::LayerRule;
+use crate::diagnostics::Diagnostic;
+use crate::media_queries::{Device, MediaQueryList};
+use crate::parser::Parser;
+use crate::tokenizer::token::Token;
+use crate::tokenizer::Tokenizer;
 use std::ops::Deref;
 
 #[derive(Debug, PartialEq)]
 pub struct StyleSheet {
     pub css_rules: CSSRuleList,
+    /// The `@keyframes` rules, kept apart from the style rules
+    pub keyframes: Vec<KeyframesRule>,
+    /// The cascade layers declared by `@layer` rules, in source order
+    pub layers: Vec<LayerRule>,
+    /// Number of anonymous layers, to number the next one
+    pub anonymous_layers: usize,
+    /// The media the whole stylesheet applies to, e.g. from a `media` attribute
+    pub media: MediaQueryList,
+    /// Problems found while parsing and validating, in source order
+    diagnostics: Vec<Diagnostic>,
+    /// Whether the declarations were checked against the supported properties
+    validated: bool,
 }
 
 impl StyleSheet {
     pub fn new() -> Self {
         Self {
             css_rules: CSSRuleList::new(),
+            keyframes: Vec::new(),
+            layers: Vec::new(),
+            anonymous_layers: 0,
+            media: MediaQueryList::all(),
+            diagnostics: Vec::new(),
+            validated: false,
         }
     }
 
     pub fn append_rule(&mut self, rule: CSSRule) {
         self.css_rules.append_rule(rule);
     }
+
+    pub fn matches_media(&self, device: &Device) -> bool {
+        self.media.matches(device)
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn add_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
+        self.diagnostics.extend(diagnostics);
+        self.diagnostics
+            .sort_by_key(|diagnostic| diagnostic.location);
+    }
+
+    pub fn is_validated(&self) -> bool {
+        self.validated
+    }
+
+    pub fn mark_validated(&mut self) {
+        self.validated = true;
+    }
+
+    /// Parse a rule and insert it at the index
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstylesheet-insertrule
+    pub fn insert_rule(&mut self, rule: &str, index: usize) -> Result<usize, CSSOMError> {
+        let tokenizer = Tokenizer::new(rule.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        parser.set_anonymous_layers(self.anonymous_layers);
+        let rules = parser.parse_a_css_rule().map_err(|_| CSSOMError::Syntax)?;
+        if index > self.css_rules.len() {
+            return Err(CSSOMError::IndexSize);
+        }
+        self.anonymous_layers = parser.anonymous_layers();
+        for (offset, rule) in rules.into_iter().enumerate() {
+            self.css_rules.insert_rule(rule, index + offset)?;
+        }
+        Ok(index)
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstylesheet-deleterule
+    pub fn delete_rule(&mut self, index: usize) -> Result<(), CSSOMError> {
+        self.css_rules.delete_rule(index).map(|_| ())
+    }
 }
 
 impl Deref for StyleSheet {
@@ -26,3 +96,57 @@ impl Deref for StyleSheet {
         &self.css_rules
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cssom::layer::LayerName;
+
+    #[test]
+    fn insert_and_delete_rules() {
+        let mut stylesheet = StyleSheet::new();
+
+        assert_eq!(stylesheet.insert_rule("a { color: red }", 0), Ok(0));
+        assert_eq!(stylesheet.insert_rule("p { margin: 0 }", 0), Ok(0));
+        assert_eq!(
+            stylesheet.insert_rule("div {}", 3),
+            Err(CSSOMError::IndexSize)
+        );
+        assert_eq!(
+            stylesheet.insert_rule("a {} b {}", 0),
+            Err(CSSOMError::Syntax)
+        );
+        assert_eq!(stylesheet.insert_rule("", 0), Err(CSSOMError::Syntax));
+        assert_eq!(stylesheet.len(), 2);
+        assert_eq!(stylesheet[0].css_text(), "p { margin: 0; }");
+
+        if let Some(CSSRule::Style(rule)) = stylesheet.css_rules.item_mut(1) {
+            rule.declarations
+                .set_property("color", "blue", "important", |_| true);
+        }
+        assert_eq!(stylesheet[1].css_text(), "a { color: blue !important; }");
+
+        assert_eq!(stylesheet.delete_rule(2), Err(CSSOMError::IndexSize));
+        assert_eq!(stylesheet.delete_rule(0), Ok(()));
+        assert_eq!(stylesheet.len(), 1);
+    }
+
+    #[test]
+    fn number_inserted_anonymous_layers_after_others() {
+        let mut stylesheet = StyleSheet::new();
+        stylesheet
+            .insert_rule("@layer { a { color: red } }", 0)
+            .unwrap();
+        stylesheet
+            .insert_rule("@layer { b { color: red } }", 1)
+            .unwrap();
+
+        let layers = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => style.layer.clone(),
+            })
+            .collect::<Vec<LayerName>>();
+        assert_eq!(layers, [LayerName::anonymous(0), LayerName::anonymous(1)]);
+    }
+}
@@ -0,0 +1,57 @@
+use crate::tokenizer::token::SourceLocation;
+use std::fmt;
+
+/// A problem found while parsing or validating a stylesheet.
+/// The offending rule or declaration is dropped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub location: SourceLocation,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiagnosticKind {
+    UnknownProperty(String),
+    InvalidValue { property: String, value: String },
+    InvalidDeclaration,
+    BadSelector(String),
+    BadLayerName(String),
+    UnsupportedAtRule(String),
+    UnclosedBlock,
+    UnexpectedEof,
+}
+
+impl Diagnostic {
+    pub fn new(kind: DiagnosticKind, location: SourceLocation) -> Self {
+        Self { kind, location }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.kind)
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticKind::UnknownProperty(name) => write!(f, "unknown property `{}`", name),
+            DiagnosticKind::InvalidValue { property, value } => {
+                write!(f, "invalid value `{}` for property `{}`", value, property)
+            }
+            DiagnosticKind::InvalidDeclaration => write!(f, "invalid declaration"),
+            DiagnosticKind::BadSelector(selector) if selector.is_empty() => {
+                write!(f, "missing selector")
+            }
+            DiagnosticKind::BadSelector(selector) => write!(f, "bad selector `{}`", selector),
+            DiagnosticKind::BadLayerName(name) if name.is_empty() => {
+                write!(f, "missing layer name")
+            }
+            DiagnosticKind::BadLayerName(name) => write!(f, "bad layer name `{}`", name),
+            DiagnosticKind::UnsupportedAtRule(name) => write!(f, "unsupported at-rule `@{}`", name),
+            DiagnosticKind::UnclosedBlock => write!(f, "unclosed block"),
+            DiagnosticKind::UnexpectedEof => write!(f, "unexpected end of input"),
+        }
+    }
+}
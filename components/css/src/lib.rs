@@ -1,4 +1,7 @@
 pub mod cssom;
+pub mod diagnostics;
+pub mod media_queries;
 pub mod parser;
 pub mod selector;
+pub mod serializer;
 pub mod tokenizer;
@@ -0,0 +1,366 @@
+use super::parser::structs::ComponentValue;
+use super::parser::Parser;
+use super::tokenizer::token::Token;
+use super::tokenizer::Tokenizer;
+
+/// The font size used to resolve `em` and `rem` in media queries
+const DEFAULT_FONT_SIZE: f32 = 16.;
+
+/// A comma separated list of media queries, matching if any query matches.
+/// An empty list matches all devices.
+/// https://www.w3.org/TR/mediaqueries-4/#mq-list
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaQueryList(pub Vec<MediaQuery>);
+
+/// https://www.w3.org/TR/mediaqueries-4/#media-query
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaQuery {
+    pub negated: bool,
+    pub media_type: MediaType,
+    pub features: Vec<MediaFeature>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaType {
+    All,
+    Screen,
+    Print,
+    Unknown(String),
+}
+
+/// A media feature test such as `(min-width: 600px)`. The `min-` and
+/// `max-` prefixes and the range syntax are stored as a comparison.
+/// https://www.w3.org/TR/mediaqueries-4/#media-feature
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaFeature {
+    pub name: String,
+    pub comparison: MediaComparison,
+    pub value: Option<MediaFeatureValue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaComparison {
+    Equal,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaFeatureValue {
+    /// A length resolved to px
+    Length(f32),
+    Number(f32),
+    Ident(String),
+}
+
+/// The device that media queries are evaluated against
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    pub media_type: MediaType,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Device {
+    pub fn screen(width: f32, height: f32) -> Self {
+        Self {
+            media_type: MediaType::Screen,
+            width,
+            height,
+        }
+    }
+}
+
+impl MediaQueryList {
+    pub fn all() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Parse a media query list, an invalid query becomes `not all`
+    /// https://www.w3.org/TR/mediaqueries-4/#error-handling
+    pub fn parse(values: &[ComponentValue]) -> Self {
+        let values = values
+            .iter()
+            .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+            .collect::<Vec<&ComponentValue>>();
+
+        if values.is_empty() {
+            return Self::all();
+        }
+
+        let queries = values
+            .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+            .map(|values| MediaQuery::parse(values).unwrap_or_else(MediaQuery::not_all))
+            .collect();
+        Self(queries)
+    }
+
+    /// Parse a media query list from text, e.g. the `media` attribute
+    pub fn parse_str(text: &str) -> Self {
+        let tokenizer = Tokenizer::new(text.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        Self::parse(&parser.parse_a_list_of_component_values())
+    }
+
+    pub fn matches(&self, device: &Device) -> bool {
+        self.0.is_empty() || self.0.iter().any(|query| query.matches(device))
+    }
+}
+
+impl MediaQuery {
+    pub fn not_all() -> Self {
+        Self {
+            negated: true,
+            media_type: MediaType::All,
+            features: Vec::new(),
+        }
+    }
+
+    fn parse(values: &[&ComponentValue]) -> Option<Self> {
+        let mut values = values.iter().peekable();
+        let mut negated = false;
+        let mut media_type = MediaType::All;
+        let mut features = Vec::new();
+
+        if let Some(ComponentValue::PerservedToken(Token::Ident(ident))) = values.peek() {
+            let ident = ident.to_ascii_lowercase();
+            if ident == "not" || ident == "only" {
+                negated = ident == "not";
+                values.next();
+            }
+            // a negated media condition, e.g. `not (min-width: 600px)`
+            if let (true, Some(ComponentValue::SimpleBlock(block))) = (negated, values.peek()) {
+                if block.token != Token::ParentheseOpen {
+                    return None;
+                }
+                let feature = MediaFeature::parse(&block.value)?;
+                values.next();
+                if values.next().is_some() {
+                    return None;
+                }
+                return Some(Self {
+                    negated,
+                    media_type,
+                    features: vec![feature],
+                });
+            }
+            media_type = match values.next() {
+                Some(ComponentValue::PerservedToken(Token::Ident(name))) => MediaType::parse(name),
+                _ => return None,
+            };
+            if values.peek().is_none() {
+                return Some(Self {
+                    negated,
+                    media_type,
+                    features,
+                });
+            }
+            expect_and(values.next())?;
+        }
+
+        loop {
+            match values.next() {
+                Some(ComponentValue::SimpleBlock(block))
+                    if block.token == Token::ParentheseOpen =>
+                {
+                    features.push(MediaFeature::parse(&block.value)?);
+                }
+                _ => return None,
+            }
+            match values.next() {
+                None => break,
+                value => expect_and(value)?,
+            }
+        }
+
+        Some(Self {
+            negated,
+            media_type,
+            features,
+        })
+    }
+
+    pub fn matches(&self, device: &Device) -> bool {
+        let type_matches = match &self.media_type {
+            MediaType::All => true,
+            media_type => *media_type == device.media_type,
+        };
+        let matches = type_matches && self.features.iter().all(|f| f.matches(device));
+        matches != self.negated
+    }
+}
+
+fn expect_and(value: Option<&&ComponentValue>) -> Option<()> {
+    match value {
+        Some(ComponentValue::PerservedToken(Token::Ident(ident)))
+            if ident.eq_ignore_ascii_case("and") =>
+        {
+            Some(())
+        }
+        _ => None,
+    }
+}
+
+impl MediaType {
+    fn parse(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "all" => MediaType::All,
+            "screen" => MediaType::Screen,
+            "print" => MediaType::Print,
+            name => MediaType::Unknown(name.to_string()),
+        }
+    }
+}
+
+impl MediaFeature {
+    /// Parse the content of a media feature block, either `name`,
+    /// `name: value` or the range form `name <op> value`
+    fn parse(values: &[ComponentValue]) -> Option<Self> {
+        let values = values
+            .iter()
+            .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+            .collect::<Vec<&ComponentValue>>();
+
+        let name = match values.first() {
+            Some(ComponentValue::PerservedToken(Token::Ident(name))) => name.to_ascii_lowercase(),
+            _ => return None,
+        };
+
+        match &values[1..] {
+            [] => Some(Self {
+                name,
+                comparison: MediaComparison::Equal,
+                value: None,
+            }),
+            [ComponentValue::PerservedToken(Token::Colon), value] => {
+                let (name, comparison) = if let Some(name) = name.strip_prefix("min-") {
+                    (name.to_string(), MediaComparison::GreaterThanOrEqual)
+                } else if let Some(name) = name.strip_prefix("max-") {
+                    (name.to_string(), MediaComparison::LessThanOrEqual)
+                } else {
+                    (name, MediaComparison::Equal)
+                };
+                Some(Self {
+                    name,
+                    comparison,
+                    value: Some(MediaFeatureValue::parse(value)?),
+                })
+            }
+            [operator @ .., value] => Some(Self {
+                name,
+                comparison: parse_comparison(operator)?,
+                value: Some(MediaFeatureValue::parse(value)?),
+            }),
+        }
+    }
+
+    pub fn matches(&self, device: &Device) -> bool {
+        let size = match self.name.as_str() {
+            "width" => device.width,
+            "height" => device.height,
+            "orientation" => {
+                let orientation = if device.height >= device.width {
+                    "portrait"
+                } else {
+                    "landscape"
+                };
+                return match &self.value {
+                    Some(MediaFeatureValue::Ident(value)) => value == orientation,
+                    _ => false,
+                };
+            }
+            _ => return false,
+        };
+
+        let value = match &self.value {
+            Some(MediaFeatureValue::Length(value)) => *value,
+            Some(MediaFeatureValue::Number(value)) if *value == 0. => 0.,
+            // boolean context, e.g. `(width)`
+            None => return size != 0.,
+            _ => return false,
+        };
+
+        match self.comparison {
+            MediaComparison::Equal => size == value,
+            MediaComparison::LessThan => size < value,
+            MediaComparison::LessThanOrEqual => size <= value,
+            MediaComparison::GreaterThan => size > value,
+            MediaComparison::GreaterThanOrEqual => size >= value,
+        }
+    }
+}
+
+fn parse_comparison(values: &[&ComponentValue]) -> Option<MediaComparison> {
+    let delims = values
+        .iter()
+        .map(|value| match value {
+            ComponentValue::PerservedToken(Token::Delim(ch)) => Some(*ch),
+            _ => None,
+        })
+        .collect::<Option<String>>()?;
+
+    match delims.as_str() {
+        "=" => Some(MediaComparison::Equal),
+        "<" => Some(MediaComparison::LessThan),
+        "<=" => Some(MediaComparison::LessThanOrEqual),
+        ">" => Some(MediaComparison::GreaterThan),
+        ">=" => Some(MediaComparison::GreaterThanOrEqual),
+        _ => None,
+    }
+}
+
+impl MediaFeatureValue {
+    fn parse(value: &ComponentValue) -> Option<Self> {
+        match value {
+            ComponentValue::PerservedToken(Token::Dimension { value, unit, .. }) => {
+                match unit.to_ascii_lowercase().as_str() {
+                    "px" => Some(MediaFeatureValue::Length(*value)),
+                    "em" | "rem" => Some(MediaFeatureValue::Length(value * DEFAULT_FONT_SIZE)),
+                    _ => None,
+                }
+            }
+            ComponentValue::PerservedToken(Token::Number { value, .. }) => {
+                Some(MediaFeatureValue::Number(*value))
+            }
+            ComponentValue::PerservedToken(Token::Ident(ident)) => {
+                Some(MediaFeatureValue::Ident(ident.to_ascii_lowercase()))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    fn parse_media(media: &str) -> MediaQueryList {
+        let tokenizer = Tokenizer::new(media.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        MediaQueryList::parse(&parser.parse_a_list_of_component_values())
+    }
+
+    #[test]
+    fn match_media_queries() {
+        let device = Device::screen(800., 600.);
+
+        assert!(parse_media("").matches(&device));
+        assert!(parse_media("screen").matches(&device));
+        assert!(!parse_media("print").matches(&device));
+        assert!(parse_media("not print").matches(&device));
+        assert!(parse_media("print, (min-width: 50em)").matches(&device));
+        assert!(!parse_media("screen and (max-width: 799px)").matches(&device));
+        assert!(parse_media("(width >= 800px) and (orientation: landscape)").matches(&device));
+        assert!(!parse_media("(height > 600px)").matches(&device));
+        assert!(parse_media("not (max-width: 600px)").matches(&device));
+        assert!(!parse_media("not (min-width: 600px)").matches(&device));
+        assert!(!parse_media("not (width) and (height)").matches(&device));
+        // invalid queries never match
+        assert!(!parse_media("screen (min-width: 1px)").matches(&device));
+        assert!(!parse_media("(min-resolution: 2dppx)").matches(&device));
+    }
+}
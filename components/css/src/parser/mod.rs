@@ -1,10 +1,15 @@
 pub mod structs;
 
 use super::cssom::css_rule::CSSRule;
+use super::cssom::keyframes_rule::{Keyframe, KeyframesRule};
+use super::cssom::layer::{LayerName, LayerRule};
 use super::cssom::style_rule::StyleRule;
 use super::cssom::stylesheet::StyleSheet;
-use super::selector::parse_selectors;
-use super::tokenizer::token::Token;
+use super::diagnostics::{Diagnostic, DiagnosticKind};
+use super::media_queries::MediaQueryList;
+use super::selector::{parse_selectors, resolve_nested_selectors};
+use super::serializer::ToCss;
+use super::tokenizer::token::{SourceLocation, Span, Token};
 use io::data_stream::DataStream;
 use std::env;
 use structs::*;
@@ -53,15 +58,326 @@ pub struct Parser<T: Clone> {
     reconsume: bool,
     /// Current token to return if being reconsumed
     current_token: Option<T>,
+    /// Source location of each input token, empty if unknown
+    locations: Vec<SourceLocation>,
+    /// Number of tokens consumed from the stream
+    consumed: usize,
+    /// Parse errors found so far
+    diagnostics: Vec<Diagnostic>,
+    /// `@keyframes` rules found so far
+    keyframes: Vec<KeyframesRule>,
+    /// Cascade layers declared so far
+    layers: Vec<LayerRule>,
+    /// Number of anonymous layers of the stylesheet so far
+    anonymous_layers: usize,
+}
+
+impl<T: Clone> Parser<T> {
+    /// Take the parse errors found so far
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Location of the current token
+    fn current_location(&self) -> Option<SourceLocation> {
+        self.consumed
+            .checked_sub(1)
+            .and_then(|index| self.locations.get(index).copied())
+    }
+
+    /// Location of the token that the next consume will return
+    fn next_location(&self) -> Option<SourceLocation> {
+        if self.reconsume {
+            return self.current_location();
+        }
+        self.locations.get(self.consumed).copied()
+    }
+
+    /// Number the anonymous layers after the ones already in the
+    /// stylesheet, e.g. when a rule is inserted with the CSSOM
+    pub fn set_anonymous_layers(&mut self, count: usize) {
+        self.anonymous_layers = count;
+    }
+
+    /// Number of anonymous layers of the stylesheet so far
+    pub fn anonymous_layers(&self) -> usize {
+        self.anonymous_layers
+    }
+
+    fn diagnose(&mut self, kind: DiagnosticKind, location: Option<SourceLocation>) {
+        self.diagnostics
+            .push(Diagnostic::new(kind, location.unwrap_or_default()));
+    }
+}
+
+impl Parser<Token> {
+    /// Create CSSOM rules from a parsed rule. Nested style rules, `@media`
+    /// and `@layer` rules are flattened into style rules, unsupported or
+    /// invalid rules are dropped.
+    fn create_css_rules(
+        &mut self,
+        rule: Rule,
+        parent: Option<&[ComponentValue]>,
+        media: &[MediaQueryList],
+        layer: &LayerName,
+        result: &mut Vec<CSSRule>,
+    ) {
+        let rule = match rule {
+            Rule::QualifiedRule(rule) => {
+                return self.create_style_rules(rule, parent, media, layer, result)
+            }
+            Rule::AtRule(rule) if rule.name.eq_ignore_ascii_case("layer") => {
+                return self.create_layer_rules(rule, parent, media, layer, result)
+            }
+            Rule::AtRule(rule) if rule.name.eq_ignore_ascii_case("media") => rule,
+            Rule::AtRule(rule) if rule.name.eq_ignore_ascii_case("keyframes") => {
+                return self.create_keyframes_rule(rule, media)
+            }
+            Rule::AtRule(rule) => {
+                self.diagnose(DiagnosticKind::UnsupportedAtRule(rule.name), rule.location);
+                return;
+            }
+        };
+
+        let mut media = media.to_vec();
+        media.push(MediaQueryList::parse(&rule.prelude));
+        if let Some(block) = rule.block {
+            self.create_block_rules(block, parent, &media, layer, result);
+        }
+    }
+
+    /// Declare the layers of a `@layer` statement, or create the rules
+    /// of a `@layer` block in its layer
+    /// https://www.w3.org/TR/css-cascade-5/#at-layer
+    fn create_layer_rules(
+        &mut self,
+        rule: AtRule,
+        parent: Option<&[ComponentValue]>,
+        media: &[MediaQueryList],
+        layer: &LayerName,
+        result: &mut Vec<CSSRule>,
+    ) {
+        let names = LayerName::parse_list(&rule.prelude).filter(|names| match &rule.block {
+            Some(_) => names.len() <= 1,
+            None => !names.is_empty(),
+        });
+        let mut names = match names {
+            Some(names) => names,
+            None => {
+                let prelude = rule
+                    .prelude
+                    .iter()
+                    .map(|value| value.to_css_string())
+                    .collect::<String>();
+                self.diagnose(
+                    DiagnosticKind::BadLayerName(prelude.trim().to_string()),
+                    rule.location,
+                );
+                return;
+            }
+        };
+
+        match rule.block {
+            Some(block) => {
+                let name = names.pop().unwrap_or_else(|| {
+                    self.anonymous_layers += 1;
+                    LayerName::anonymous(self.anonymous_layers - 1)
+                });
+                let layer = layer.join(&name);
+                self.declare_layer(layer.clone(), media);
+                self.create_block_rules(block, parent, media, &layer, result);
+            }
+            None => {
+                for name in names {
+                    self.declare_layer(layer.join(&name), media);
+                }
+            }
+        }
+    }
+
+    fn declare_layer(&mut self, name: LayerName, media: &[MediaQueryList]) {
+        self.layers.push(LayerRule {
+            name,
+            media: media.to_vec(),
+        });
+    }
+
+    /// Create the rules of a conditional or layer block
+    fn create_block_rules(
+        &mut self,
+        block: SimpleBlock,
+        parent: Option<&[ComponentValue]>,
+        media: &[MediaQueryList],
+        layer: &LayerName,
+        result: &mut Vec<CSSRule>,
+    ) {
+        let (declarations, rules) = self.parse_block_contents(block);
+
+        if !declarations.is_empty() {
+            match parent {
+                // declarations in a nested block apply to the parent
+                // rule as if they were in `& { }`
+                Some(_) => {
+                    let nesting_selector = [ComponentValue::PerservedToken(Token::Delim('&'))];
+                    let prelude = resolve_nested_selectors(&nesting_selector, parent);
+                    let mut style = StyleRule::new(parse_selectors(&prelude), declarations);
+                    style.media = media.to_vec();
+                    style.layer = layer.clone();
+                    result.push(CSSRule::Style(style));
+                }
+                None => {
+                    for declaration in declarations {
+                        self.diagnose(DiagnosticKind::InvalidDeclaration, declaration.location);
+                    }
+                }
+            }
+        }
+
+        for rule in rules {
+            self.create_css_rules(rule, parent, media, layer, result);
+        }
+    }
+
+    /// Create a `@keyframes` rule, keyframes with invalid selectors are dropped
+    /// https://www.w3.org/TR/css-animations-1/#keyframes
+    fn create_keyframes_rule(&mut self, rule: AtRule, media: &[MediaQueryList]) {
+        let name = match KeyframesRule::parse_name(&rule.prelude) {
+            Some(name) => name,
+            None => {
+                let prelude = rule
+                    .prelude
+                    .iter()
+                    .map(|value| value.to_css_string())
+                    .collect::<String>();
+                self.diagnose(
+                    DiagnosticKind::BadSelector(prelude.trim().to_string()),
+                    rule.location,
+                );
+                return;
+            }
+        };
+        let (declarations, rules) = match rule.block {
+            Some(block) => self.parse_block_contents(block),
+            None => return,
+        };
+        for declaration in declarations {
+            self.diagnose(DiagnosticKind::InvalidDeclaration, declaration.location);
+        }
+
+        let mut keyframes = Vec::new();
+        for rule in rules {
+            match rule {
+                Rule::QualifiedRule(rule) => match Keyframe::parse_offsets(&rule.prelude) {
+                    Some(offsets) => {
+                        let declarations = match rule.block {
+                            Some(block) => self.parse_block_contents(block).0,
+                            None => Vec::new(),
+                        };
+                        keyframes.push(Keyframe::new(offsets, declarations));
+                    }
+                    None => {
+                        let selector = rule
+                            .prelude
+                            .iter()
+                            .map(|value| value.to_css_string())
+                            .collect::<String>();
+                        self.diagnose(
+                            DiagnosticKind::BadSelector(selector.trim().to_string()),
+                            rule.location,
+                        );
+                    }
+                },
+                Rule::AtRule(rule) => {
+                    self.diagnose(DiagnosticKind::UnsupportedAtRule(rule.name), rule.location)
+                }
+            }
+        }
+
+        let mut keyframes = KeyframesRule::new(name, keyframes);
+        keyframes.media = media.to_vec();
+        self.keyframes.push(keyframes);
+    }
+
+    /// Create a style rule followed by the rules nested in its block
+    /// https://www.w3.org/TR/css-nesting-1/#nesting
+    fn create_style_rules(
+        &mut self,
+        rule: QualifiedRule,
+        parent: Option<&[ComponentValue]>,
+        media: &[MediaQueryList],
+        layer: &LayerName,
+        result: &mut Vec<CSSRule>,
+    ) {
+        let prelude = resolve_nested_selectors(&rule.prelude, parent);
+        let selectors = parse_selectors(&prelude);
+        if selectors.is_empty() {
+            let selector = rule
+                .prelude
+                .iter()
+                .map(|value| value.to_css_string())
+                .collect::<String>();
+            self.diagnose(
+                DiagnosticKind::BadSelector(selector.trim().to_string()),
+                rule.location,
+            );
+            return;
+        }
+
+        let (declarations, rules) = match rule.block {
+            Some(block) => self.parse_block_contents(block),
+            None => (Vec::new(), Vec::new()),
+        };
+
+        let mut style = StyleRule::new(selectors, declarations);
+        style.media = media.to_vec();
+        style.layer = layer.clone();
+        result.push(CSSRule::Style(style));
+
+        for rule in rules {
+            self.create_css_rules(rule, Some(&prelude), media, layer, result);
+        }
+    }
+
+    /// Parse the content of a style rule or `@media` block into
+    /// declarations and nested rules
+    fn parse_block_contents(&mut self, block: SimpleBlock) -> (Vec<Declaration>, Vec<Rule>) {
+        let mut parser =
+            Parser::<ComponentValue>::with_locations(DataStream::new(block.value), block.locations);
+        let contents = parser.parse_a_list_of_declarations();
+        self.diagnostics.append(&mut parser.diagnostics);
+
+        let mut declarations = Vec::new();
+        let mut rules = Vec::new();
+        for content in contents {
+            match content {
+                DeclarationOrAtRule::Declaration(declaration) => declarations.push(declaration),
+                DeclarationOrAtRule::AtRule(rule) => rules.push(Rule::AtRule(rule)),
+                DeclarationOrAtRule::QualifiedRule(rule) => rules.push(Rule::QualifiedRule(rule)),
+            }
+        }
+        (declarations, rules)
+    }
 }
 
 impl Parser<Token> {
     pub fn new(tokens: DataStream<Token>) -> Self {
+        Self::with_spans(tokens, Vec::new())
+    }
+
+    /// Create a parser for tokens with their source spans so that
+    /// parsed rules and parse errors carry their location
+    pub fn with_spans(tokens: DataStream<Token>, spans: Vec<Span>) -> Self {
         Self {
             tokens,
             top_level: false,
             reconsume: false,
             current_token: None,
+            locations: spans.into_iter().map(|span| span.start).collect(),
+            consumed: 0,
+            diagnostics: Vec::new(),
+            keyframes: Vec::new(),
+            layers: Vec::new(),
+            anonymous_layers: 0,
         }
     }
 
@@ -70,6 +386,7 @@ impl Parser<Token> {
             self.reconsume = false;
             return self.current_token.clone().unwrap();
         }
+        self.consumed += 1;
         let token = self.tokens.next().unwrap_or(&Token::EOF);
         self.current_token = Some(token.clone());
         return token.clone();
@@ -97,12 +414,14 @@ impl Parser<Token> {
 
     fn consume_a_qualified_rule(&mut self) -> Option<QualifiedRule> {
         let mut qualified_rule = QualifiedRule::new();
+        qualified_rule.location = self.next_location();
 
         loop {
             let next_token = self.consume_next_token();
 
             if let Token::EOF = next_token {
                 emit_error!("Unexpected EOF while consuming a qualified rule");
+                self.diagnose(DiagnosticKind::UnexpectedEof, self.current_location());
                 return None;
             }
 
@@ -149,6 +468,7 @@ impl Parser<Token> {
                     result.push(DeclarationOrAtRule::AtRule(rule));
                 }
                 Token::Ident(_) => {
+                    let location = self.current_location();
                     let mut tmp = vec![self.current_token.clone().unwrap()];
                     loop {
                         match self.peek_next_token() {
@@ -163,12 +483,17 @@ impl Parser<Token> {
                         }
                     }
                     let mut parser = Parser::<Token>::new(DataStream::new(tmp));
-                    if let Some(declaration) = parser.consume_a_declaration() {
-                        result.push(DeclarationOrAtRule::Declaration(declaration));
+                    match parser.consume_a_declaration() {
+                        Some(mut declaration) => {
+                            declaration.location = location;
+                            result.push(DeclarationOrAtRule::Declaration(declaration));
+                        }
+                        None => self.diagnose(DiagnosticKind::InvalidDeclaration, location),
                     }
                 }
                 _ => {
                     emit_error!("Unexpected token while consuming a list of declarations");
+                    self.diagnose(DiagnosticKind::InvalidDeclaration, self.current_location());
                     self.reconsume();
                     loop {
                         match self.peek_next_token() {
@@ -217,6 +542,7 @@ impl Parser<Token> {
 
     fn consume_a_simple_block(&mut self) -> SimpleBlock {
         let ending_token = self.ending_token();
+        let block_location = self.current_location();
         let mut simple_block = SimpleBlock::new(self.current_token.clone().unwrap());
 
         loop {
@@ -228,11 +554,13 @@ impl Parser<Token> {
 
             if let Token::EOF = next_token {
                 emit_error!("Unexpected EOF while consuming a simple block");
+                self.diagnose(DiagnosticKind::UnclosedBlock, block_location);
                 return simple_block;
             }
 
             self.reconsume();
-            simple_block.append_value(self.consume_a_component_value());
+            let location = self.next_location();
+            simple_block.append_value_at(self.consume_a_component_value(), location);
         }
     }
 
@@ -245,6 +573,7 @@ impl Parser<Token> {
             panic!("The current token is not a function");
         };
         let mut at_rule = AtRule::new(keyword_name);
+        at_rule.location = self.current_location();
 
         loop {
             let next_token = self.consume_next_token();
@@ -329,6 +658,11 @@ impl Parser<Token> {
             declaration.append_value(self.consume_a_component_value());
         }
 
+        // whitespace before the end of the block is part of the value
+        while let Some(Token::Whitespace) = declaration.last_token() {
+            declaration.pop_last(1);
+        }
+
         let last_two_tokens = declaration.last_values(2);
 
         if last_two_tokens.len() == 2 {
@@ -366,39 +700,34 @@ impl Parser<Token> {
     pub fn parse_a_css_stylesheet(&mut self) -> StyleSheet {
         let mut stylesheet = StyleSheet::new();
         let rules = self.parse_a_stylesheet();
+        let mut css_rules = Vec::new();
         for rule in rules {
-            if let Rule::QualifiedRule(rule) = rule {
-                let selectors = parse_selectors(&rule.prelude);
-                if selectors.len() == 0 {
-                    // invalid rule
-                    continue;
-                }
-                let content = if let Some(block) = rule.block {
-                    let mut parser =
-                        Parser::<ComponentValue>::new(DataStream::new(block.value.clone()));
-
-                    let declarations = parser.parse_a_list_of_declarations();
-
-                    // take only declaration
-                    declarations
-                        .into_iter()
-                        .filter_map(|declaration| match declaration {
-                            DeclarationOrAtRule::Declaration(d) => Some(d),
-                            _ => None,
-                        })
-                        .collect()
-                } else {
-                    Vec::new()
-                };
-                let style_rule = StyleRule::new(selectors, content);
-                stylesheet.append_rule(CSSRule::Style(style_rule));
-            } else {
-                continue;
-            }
+            self.create_css_rules(rule, None, &[], &LayerName::default(), &mut css_rules);
         }
+        for rule in css_rules {
+            stylesheet.append_rule(rule);
+        }
+        stylesheet.keyframes = std::mem::take(&mut self.keyframes);
+        stylesheet.layers = std::mem::take(&mut self.layers);
+        stylesheet.anonymous_layers = self.anonymous_layers;
+        stylesheet.add_diagnostics(self.take_diagnostics());
         stylesheet
     }
 
+    /// Parse a single rule for the CSSOM, e.g. for `insertRule()`.
+    /// A rule with nested rules results in multiple flat rules.
+    /// https://www.w3.org/TR/cssom-1/#parse-a-css-rule
+    pub fn parse_a_css_rule(&mut self) -> Result<Vec<CSSRule>, SyntaxError> {
+        let rule = self.parse_a_rule()?;
+        let mut css_rules = Vec::new();
+        let layer = LayerName::default();
+        self.create_css_rules(rule, None, &[], &layer, &mut css_rules);
+        match css_rules.is_empty() {
+            true => Err(SyntaxError),
+            false => Ok(css_rules),
+        }
+    }
+
     pub fn parse_a_list_of_rules(&mut self) -> ListOfRules {
         self.top_level = false;
         let rules = self.consume_a_list_of_rules();
@@ -494,11 +823,25 @@ impl Parser<Token> {
 
 impl Parser<ComponentValue> {
     pub fn new(tokens: DataStream<ComponentValue>) -> Self {
+        Self::with_locations(tokens, Vec::new())
+    }
+
+    /// Create a parser for component values with their source locations
+    pub fn with_locations(
+        tokens: DataStream<ComponentValue>,
+        locations: Vec<SourceLocation>,
+    ) -> Self {
         Self {
             tokens,
             top_level: false,
             reconsume: false,
             current_token: None,
+            locations,
+            consumed: 0,
+            diagnostics: Vec::new(),
+            keyframes: Vec::new(),
+            layers: Vec::new(),
+            anonymous_layers: 0,
         }
     }
 
@@ -507,6 +850,7 @@ impl Parser<ComponentValue> {
             self.reconsume = false;
             return self.current_token.clone().unwrap();
         }
+        self.consumed += 1;
         let token = self
             .tokens
             .next()
@@ -530,7 +874,7 @@ impl Parser<ComponentValue> {
         self.reconsume = true;
     }
 
-    fn parse_a_list_of_declarations(&mut self) -> Vec<DeclarationOrAtRule> {
+    pub(crate) fn parse_a_list_of_declarations(&mut self) -> Vec<DeclarationOrAtRule> {
         self.consume_a_list_of_declarations()
     }
 
@@ -548,6 +892,7 @@ impl Parser<ComponentValue> {
                 panic!("The current token is not a function");
             };
         let mut at_rule = AtRule::new(keyword_name);
+        at_rule.location = self.current_location();
 
         loop {
             let next_token = self.consume_next_token();
@@ -558,7 +903,10 @@ impl Parser<ComponentValue> {
                     emit_error!("Unexpected EOF while consuming an at-rule");
                     return at_rule;
                 }
-                // TODO: How is a simple block a token?
+                ComponentValue::SimpleBlock(block) if block.token == Token::BraceOpen => {
+                    at_rule.set_block(block);
+                    return at_rule;
+                }
                 _ => {
                     self.reconsume();
                     at_rule.append_prelude(self.consume_a_component_value());
@@ -567,6 +915,9 @@ impl Parser<ComponentValue> {
         }
     }
 
+    /// Consume the content of a style rule block, which is a mix of
+    /// declarations and nested rules
+    /// https://www.w3.org/TR/css-syntax-3/#consume-block-contents
     fn consume_a_list_of_declarations(&mut self) -> Vec<DeclarationOrAtRule> {
         let mut result = Vec::new();
 
@@ -584,31 +935,49 @@ impl Parser<ComponentValue> {
                     let rule = self.consume_an_at_rule();
                     result.push(DeclarationOrAtRule::AtRule(rule));
                 }
+                // custom properties are always declarations, even with a {}-block
+                ComponentValue::PerservedToken(Token::Ident(name)) if name.starts_with("--") => {
+                    let location = self.current_location();
+                    let tmp = self.consume_until_semicolon(false);
+                    self.push_declaration(tmp, location, &mut result);
+                }
                 ComponentValue::PerservedToken(Token::Ident(_)) => {
-                    let mut tmp = vec![self.current_token.clone().unwrap()];
-                    loop {
-                        match self.peek_next_token() {
-                            ComponentValue::PerservedToken(Token::Semicolon)
-                            | ComponentValue::PerservedToken(Token::EOF) => break,
-                            _ => tmp.push(self.consume_a_component_value()),
+                    let location = self.current_location();
+                    let mut tmp = self.consume_until_semicolon(true);
+                    match tmp.pop() {
+                        // a {}-block makes it a nested rule, e.g. `a:hover { }`
+                        Some(ComponentValue::SimpleBlock(block))
+                            if block.token == Token::BraceOpen =>
+                        {
+                            let mut rule = QualifiedRule::new();
+                            rule.prelude = tmp;
+                            rule.location = location;
+                            rule.set_block(block);
+                            result.push(DeclarationOrAtRule::QualifiedRule(rule));
+                        }
+                        value => {
+                            tmp.extend(value);
+                            self.push_declaration(tmp, location, &mut result);
                         }
-                    }
-                    let mut parser = Parser::<ComponentValue>::new(DataStream::new(tmp));
-                    if let Some(declaration) = parser.consume_a_declaration() {
-                        result.push(DeclarationOrAtRule::Declaration(declaration));
                     }
                 }
                 _ => {
-                    emit_error!("Unexpected token while consuming a list of declarations");
-                    self.reconsume();
-                    loop {
-                        match self.peek_next_token() {
-                            ComponentValue::PerservedToken(Token::Semicolon)
-                            | ComponentValue::PerservedToken(Token::EOF) => break,
-                            _ => {
-                                // throw away
-                                self.consume_a_component_value();
-                            }
+                    let location = self.current_location();
+                    let mut tmp = self.consume_until_semicolon(true);
+                    match tmp.pop() {
+                        // a nested rule, e.g. `& > a { }`
+                        Some(ComponentValue::SimpleBlock(block))
+                            if block.token == Token::BraceOpen =>
+                        {
+                            let mut rule = QualifiedRule::new();
+                            rule.prelude = tmp;
+                            rule.location = location;
+                            rule.set_block(block);
+                            result.push(DeclarationOrAtRule::QualifiedRule(rule));
+                        }
+                        _ => {
+                            emit_error!("Unexpected token while consuming a list of declarations");
+                            self.diagnose(DiagnosticKind::InvalidDeclaration, location);
                         }
                     }
                 }
@@ -616,6 +985,42 @@ impl Parser<ComponentValue> {
         }
     }
 
+    /// Consume the current and next component values until a semicolon,
+    /// the end of input or optionally a {}-block which is included
+    fn consume_until_semicolon(&mut self, stop_at_block: bool) -> Vec<ComponentValue> {
+        let mut values = vec![self.current_token.clone().unwrap()];
+        loop {
+            match self.peek_next_token() {
+                ComponentValue::PerservedToken(Token::Semicolon)
+                | ComponentValue::PerservedToken(Token::EOF) => break,
+                ComponentValue::SimpleBlock(block)
+                    if stop_at_block && block.token == Token::BraceOpen =>
+                {
+                    values.push(self.consume_a_component_value());
+                    break;
+                }
+                _ => values.push(self.consume_a_component_value()),
+            }
+        }
+        values
+    }
+
+    fn push_declaration(
+        &mut self,
+        values: Vec<ComponentValue>,
+        location: Option<SourceLocation>,
+        result: &mut Vec<DeclarationOrAtRule>,
+    ) {
+        let mut parser = Parser::<ComponentValue>::new(DataStream::new(values));
+        match parser.consume_a_declaration() {
+            Some(mut declaration) => {
+                declaration.location = location;
+                result.push(DeclarationOrAtRule::Declaration(declaration));
+            }
+            None => self.diagnose(DiagnosticKind::InvalidDeclaration, location),
+        }
+    }
+
     fn consume_a_declaration(&mut self) -> Option<Declaration> {
         let next_token = self.consume_next_token();
         let declaration_name =
@@ -647,6 +1052,11 @@ impl Parser<ComponentValue> {
             declaration.append_value(self.consume_a_component_value());
         }
 
+        // whitespace before the end of the block is part of the value
+        while let Some(Token::Whitespace) = declaration.last_token() {
+            declaration.pop_last(1);
+        }
+
         let last_two_tokens = declaration.last_values(2);
 
         if last_two_tokens.len() == 2 {
@@ -680,6 +1090,7 @@ mod tests {
     use super::*;
     use crate::cssom::css_rule::CSSRule;
     use crate::cssom::css_rule_list::CSSRuleList;
+    use crate::cssom::layer::LayerSegment;
     use crate::cssom::style_rule::StyleRule;
     use crate::selector::structs::*;
     use crate::tokenizer::token::HashType;
@@ -711,8 +1122,10 @@ mod tests {
                         ComponentValue::PerservedToken(Token::Ident("black".to_string())),
                         ComponentValue::PerservedToken(Token::Semicolon),
                         ComponentValue::PerservedToken(Token::Whitespace),
-                    ]
-                })
+                    ],
+                    locations: Vec::new(),
+                }),
+                location: None,
             })
         );
     }
@@ -757,8 +1170,10 @@ mod tests {
                         ComponentValue::PerservedToken(Token::Ident("black".to_string())),
                         ComponentValue::PerservedToken(Token::Semicolon),
                         ComponentValue::PerservedToken(Token::Whitespace),
-                    ]
-                })
+                    ],
+                    locations: Vec::new(),
+                }),
+                location: None,
             })
         );
     }
@@ -792,8 +1207,10 @@ mod tests {
                         ComponentValue::PerservedToken(Token::Ident("black".to_string())),
                         ComponentValue::PerservedToken(Token::Semicolon),
                         ComponentValue::PerservedToken(Token::Whitespace),
-                    ]
-                })
+                    ],
+                    locations: Vec::new(),
+                }),
+                location: None,
             })
         );
     }
@@ -820,12 +1237,31 @@ mod tests {
                     important: true,
                     value: vec![ComponentValue::PerservedToken(Token::Ident(
                         "black".to_string()
-                    ))]
+                    ))],
+                    location: None,
                 }]
             ))])
         );
     }
 
+    #[test]
+    fn parse_important_at_end_of_block() {
+        let tokenizer = Tokenizer::new("a { color: black !important }".chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        let stylesheet = parser.parse_a_css_stylesheet();
+        match &stylesheet[0] {
+            CSSRule::Style(style) => {
+                assert!(style.declarations[0].important);
+                assert_eq!(
+                    style.declarations[0].value,
+                    vec![ComponentValue::PerservedToken(Token::Ident(
+                        "black".to_string()
+                    ))]
+                );
+            }
+        }
+    }
+
     #[test]
     fn parse_function() {
         let css = "#elementId { color: rgba(0 0 0 0); }";
@@ -869,9 +1305,241 @@ mod tests {
                                 type_: crate::tokenizer::token::NumberType::Integer,
                             })
                         ]
-                    })]
+                    })],
+                    location: None,
                 }]
             ))])
         );
     }
+
+    #[test]
+    fn parse_trailing_function() {
+        let css = "#elementId { margin: 0 min(1px); }";
+        let tokenizer = Tokenizer::new(css.chars());
+        let tokens = tokenizer.run();
+        let mut parser = Parser::<Token>::new(tokens);
+        let stylesheet = parser.parse_a_css_stylesheet();
+        let CSSRule::Style(rule) = &stylesheet.css_rules[0];
+        assert_eq!(rule.declarations[0].value.len(), 3);
+        assert_eq!(
+            rule.declarations[0].value[2],
+            ComponentValue::Function(Function {
+                name: "min".to_string(),
+                value: vec![ComponentValue::PerservedToken(Token::Dimension {
+                    value: 1.0,
+                    type_: crate::tokenizer::token::NumberType::Integer,
+                    unit: "px".to_string(),
+                })]
+            })
+        );
+    }
+
+    #[test]
+    fn parse_diagnostics() {
+        let css = "a { color: red; width }\n@font-face { }\n{ top: 0 }\n  p { margin: 0;\n";
+        let tokenizer = Tokenizer::new(css.chars());
+        let (tokens, spans) = tokenizer.run_with_spans();
+        let mut parser = Parser::<Token>::with_spans(tokens, spans);
+        let stylesheet = parser.parse_a_css_stylesheet();
+
+        assert_eq!(stylesheet.len(), 2);
+        let CSSRule::Style(rule) = &stylesheet.css_rules[1];
+        assert_eq!(
+            rule.declarations[0].location,
+            Some(SourceLocation::new(4, 7))
+        );
+        assert_eq!(
+            stylesheet
+                .diagnostics()
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "1:17: invalid declaration",
+                "2:1: unsupported at-rule `@font-face`",
+                "3:1: missing selector",
+                "4:5: unclosed block",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_nested_rules() {
+        let css = r"
+            .card, #main {
+                color: red;
+                &:hover { color: blue; }
+                > p { margin: 0 }
+                .title & { top: 0 }
+                a:focus { left: 0 }
+                @media (min-width: 600px) {
+                    width: 50%;
+                    span { right: 0 }
+                }
+            }
+            @media print { p { display: none } }
+        ";
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        let stylesheet = parser.parse_a_css_stylesheet();
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| {
+                let CSSRule::Style(rule) = rule;
+                (rule.css_text(), rule.specificity(), rule.media.len())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rules,
+            vec![
+                (
+                    ".card, #main { color: red; }".to_string(),
+                    Specificity::new(1, 0, 0),
+                    0
+                ),
+                (
+                    ":is(.card, #main):hover { color: blue; }".to_string(),
+                    Specificity::new(1, 1, 0),
+                    0
+                ),
+                (
+                    ":is(.card, #main) > p { margin: 0; }".to_string(),
+                    Specificity::new(1, 0, 1),
+                    0
+                ),
+                (
+                    ".title :is(.card, #main) { top: 0; }".to_string(),
+                    Specificity::new(1, 1, 0),
+                    0
+                ),
+                (
+                    ":is(.card, #main) a:focus { left: 0; }".to_string(),
+                    Specificity::new(1, 1, 1),
+                    0
+                ),
+                (
+                    ":is(.card, #main) { width: 50%; }".to_string(),
+                    Specificity::new(1, 0, 0),
+                    1
+                ),
+                (
+                    ":is(.card, #main) span { right: 0; }".to_string(),
+                    Specificity::new(1, 0, 1),
+                    1
+                ),
+                (
+                    "p { display: none; }".to_string(),
+                    Specificity::new(0, 0, 1),
+                    1
+                ),
+            ]
+        );
+        assert_eq!(
+            stylesheet[5].css_text(),
+            "@media (min-width: 600px) { :is(.card, #main) { width: 50%; } }"
+        );
+    }
+
+    #[test]
+    fn parse_keyframes() {
+        let css = r"
+            @keyframes slide {
+                from { left: 0 }
+                50%, 75% { left: 20px; opacity: 0.5 }
+                to { left: 100px }
+                120% { left: 0 }
+            }
+            @keyframes none {}
+        ";
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        let stylesheet = parser.parse_a_css_stylesheet();
+
+        assert!(stylesheet.is_empty());
+        assert_eq!(stylesheet.keyframes.len(), 1);
+        let keyframes = &stylesheet.keyframes[0];
+        assert_eq!(keyframes.name, "slide");
+        assert_eq!(
+            keyframes
+                .keyframes
+                .iter()
+                .map(|keyframe| keyframe.offsets.clone())
+                .collect::<Vec<_>>(),
+            vec![vec![0.], vec![0.5, 0.75], vec![1.]]
+        );
+        assert_eq!(
+            keyframes.to_css_string(),
+            "@keyframes slide { 0% { left: 0; } 50%, 75% { left: 20px; opacity: 0.5; } \
+             100% { left: 100px; } }"
+        );
+        assert_eq!(stylesheet.diagnostics().len(), 2);
+    }
+
+    #[test]
+    fn parse_layers() {
+        let css = r"
+            @layer reset, base;
+            @layer base {
+                p { color: red }
+                @layer links { a { color: blue } }
+            }
+            @layer { div { color: green } }
+            @layer a, b { span { color: red } }
+            @layer a. { span { color: red } }
+            @media print { @layer print; }
+            em { color: black }
+        ";
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        let stylesheet = parser.parse_a_css_stylesheet();
+
+        let named = |names: &[&str]| {
+            LayerName(
+                names
+                    .iter()
+                    .map(|name| LayerSegment::Named(name.to_string()))
+                    .collect(),
+            )
+        };
+        let layers = stylesheet
+            .layers
+            .iter()
+            .map(|layer| layer.name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(layers.len(), 6);
+        assert_eq!(
+            layers[..3],
+            [named(&["reset"]), named(&["base"]), named(&["base"])]
+        );
+        assert_eq!(layers[3], named(&["base", "links"]));
+        assert_eq!(layers[4], LayerName::anonymous(0));
+        assert_eq!(layers[5], named(&["print"]));
+        assert!(stylesheet.layers[4].media.is_empty());
+        assert_eq!(stylesheet.layers[5].media.len(), 1);
+        assert_eq!(stylesheet.anonymous_layers, 1);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => (style.layer.clone(), rule.css_text()),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(rules.len(), 4);
+        assert_eq!(rules[0].0, named(&["base"]));
+        assert_eq!(rules[1].0, named(&["base", "links"]));
+        assert_eq!(
+            rules[1].1,
+            "@layer base { @layer links { a { color: blue; } } }"
+        );
+        assert_eq!(rules[2].0, layers[4]);
+        assert_eq!(rules[2].1, "@layer { div { color: green; } }");
+        assert!(rules[3].0.is_unlayered());
+        let kinds = stylesheet
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic.kind.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, ["bad layer name `a, b`", "bad layer name `a.`"]);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::tokenizer::token::Token;
+use crate::tokenizer::token::{SourceLocation, Token};
 
 #[derive(Debug, PartialEq)]
 pub enum Rule {
@@ -11,6 +11,9 @@ pub type ListOfRules = Vec<Rule>;
 pub enum DeclarationOrAtRule {
     Declaration(Declaration),
     AtRule(AtRule),
+    /// A style rule nested in a style rule block
+    /// https://www.w3.org/TR/css-nesting-1/#nested-style-rule
+    QualifiedRule(QualifiedRule),
 }
 
 /// A simple block
@@ -21,6 +24,8 @@ pub struct SimpleBlock {
     pub token: Token,
     /// Block value
     pub value: Vec<ComponentValue>,
+    /// Source location of each block value, empty if unknown
+    pub locations: Vec<SourceLocation>,
 }
 
 /// Function
@@ -37,6 +42,7 @@ pub struct Function {
 pub struct QualifiedRule {
     pub prelude: Vec<ComponentValue>,
     pub block: Option<SimpleBlock>,
+    pub location: Option<SourceLocation>,
 }
 
 /// AtRule
@@ -46,6 +52,7 @@ pub struct AtRule {
     pub name: String,
     pub prelude: Vec<ComponentValue>,
     pub block: Option<SimpleBlock>,
+    pub location: Option<SourceLocation>,
 }
 
 /// Declaration
@@ -55,6 +62,7 @@ pub struct Declaration {
     pub name: String,
     pub value: Vec<ComponentValue>,
     pub important: bool,
+    pub location: Option<SourceLocation>,
 }
 
 /// ComponentValue
@@ -71,6 +79,7 @@ impl QualifiedRule {
         Self {
             prelude: Vec::new(),
             block: None,
+            location: None,
         }
     }
 
@@ -89,6 +98,7 @@ impl AtRule {
             name,
             prelude: Vec::new(),
             block: None,
+            location: None,
         }
     }
 
@@ -106,12 +116,20 @@ impl SimpleBlock {
         Self {
             token,
             value: Vec::new(),
+            locations: Vec::new(),
         }
     }
 
     pub fn append_value(&mut self, value: ComponentValue) {
         self.value.push(value);
     }
+
+    pub fn append_value_at(&mut self, value: ComponentValue, location: Option<SourceLocation>) {
+        self.value.push(value);
+        if let Some(location) = location {
+            self.locations.push(location);
+        }
+    }
 }
 
 impl Declaration {
@@ -120,6 +138,7 @@ impl Declaration {
             name,
             value: Vec::new(),
             important: false,
+            location: None,
         }
     }
 
@@ -132,12 +151,10 @@ impl Declaration {
     }
 
     pub fn last_token(&self) -> Option<&Token> {
-        for value in self.value.iter().rev() {
-            if let ComponentValue::PerservedToken(token) = value {
-                return Some(token);
-            }
+        match self.value.last() {
+            Some(ComponentValue::PerservedToken(token)) => Some(token),
+            _ => None,
         }
-        return None;
     }
 
     pub fn pop_last(&mut self, len: usize) {
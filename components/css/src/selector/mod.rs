@@ -1,8 +1,8 @@
 pub mod structs;
 
-use super::parser::structs::ComponentValue;
+use super::parser::structs::{ComponentValue, Function};
 use super::parser::Parser;
-use super::tokenizer::token::Token;
+use super::tokenizer::token::{NumberType, Token};
 use super::tokenizer::Tokenizer;
 use io::data_stream::DataStream;
 use structs::*;
@@ -44,11 +44,8 @@ pub fn parse_selectors(values: &Vec<ComponentValue>) -> Vec<Selector> {
             // no comma? the sequence ends here
             break;
         } else {
-            data_stream.next();
-        }
-
-        if data_stream.is_eos() {
-            break;
+            // an invalid selector makes the whole list invalid
+            return Vec::new();
         }
     }
     return selectors;
@@ -59,11 +56,17 @@ pub fn parse_selector(data_stream: &mut DataStream<ComponentValue>) -> Option<Se
     loop {
         if let Some(selector_seq) = parse_simple_selector_seq(data_stream) {
             if let Some(combinator) = parse_combinator(data_stream) {
+                // pseudo-elements are only valid in the last compound selector
+                if selector_seq.pseudo_element().is_some() {
+                    return None;
+                }
                 selector_seqs.push((selector_seq, Some(combinator)));
                 continue;
             }
             selector_seqs.push((selector_seq, None));
             break;
+        } else if is_invalid_pseudo_class(data_stream) {
+            return None;
         } else {
             data_stream.next();
         }
@@ -79,80 +82,34 @@ pub fn parse_selector(data_stream: &mut DataStream<ComponentValue>) -> Option<Se
     }
 }
 
+/// The parsing of a simple selector sequence stops at the colon of
+/// a pseudo-class or pseudo-element that couldn't be parsed
+fn is_invalid_pseudo_class(data_stream: &DataStream<ComponentValue>) -> bool {
+    matches!(data_stream.peek(), Some(token_value!(Token::Colon)))
+}
+
 pub fn parse_combinator(data_stream: &mut DataStream<ComponentValue>) -> Option<Combinator> {
-    let next_values = data_stream.peek_next(4);
-
-    if next_values.len() == 4 {
-        return match (
-            next_values[0],
-            next_values[1],
-            next_values[2],
-            next_values[3],
-        ) {
-            // With space between combinator
-            (
-                token_value!(Token::Whitespace),
-                token_value!(Token::Delim('+')),
-                token_value!(Token::Whitespace),
-                _,
-            ) => {
-                data_stream.next();
-                data_stream.next();
-                data_stream.next();
-                Some(Combinator::NextSibling)
-            }
-            (
-                token_value!(Token::Whitespace),
-                token_value!(Token::Delim('~')),
-                token_value!(Token::Whitespace),
-                _,
-            ) => {
-                data_stream.next();
-                data_stream.next();
-                data_stream.next();
-                Some(Combinator::SubsequentSibling)
-            }
-            (
-                token_value!(Token::Whitespace),
-                token_value!(Token::Delim('>')),
-                token_value!(Token::Whitespace),
-                _,
-            ) => {
-                data_stream.next();
-                data_stream.next();
-                data_stream.next();
-                Some(Combinator::Child)
-            }
-            _ => None,
-        };
+    let mut has_whitespace = false;
+    while let Some(token_value!(Token::Whitespace)) = data_stream.peek() {
+        data_stream.next();
+        has_whitespace = true;
     }
 
-    let next_values = data_stream.peek_next(2);
+    let combinator = match data_stream.peek() {
+        Some(token_value!(Token::Delim('>'))) => Combinator::Child,
+        Some(token_value!(Token::Delim('+'))) => Combinator::NextSibling,
+        Some(token_value!(Token::Delim('~'))) => Combinator::SubsequentSibling,
+        // whitespace before the end of the selector is not a combinator
+        Some(token_value!(Token::Comma)) | None => return None,
+        Some(_) if has_whitespace => return Some(Combinator::Descendant),
+        Some(_) => return None,
+    };
 
-    if next_values.len() == 2 {
-        return match (next_values[0], next_values[1]) {
-            // No space between combinator
-            (token_value!(Token::Whitespace), _) => {
-                data_stream.next();
-                Some(Combinator::Descendant)
-            }
-            (token_value!(Token::Delim('+')), _) => {
-                data_stream.next();
-                Some(Combinator::NextSibling)
-            }
-            (token_value!(Token::Delim('~')), _) => {
-                data_stream.next();
-                Some(Combinator::SubsequentSibling)
-            }
-            (token_value!(Token::Delim('>')), _) => {
-                data_stream.next();
-                Some(Combinator::Child)
-            }
-            _ => None,
-        };
+    data_stream.next();
+    while let Some(token_value!(Token::Whitespace)) = data_stream.peek() {
+        data_stream.next();
     }
-
-    None
+    Some(combinator)
 }
 
 pub fn parse_simple_selector_seq(
@@ -165,8 +122,12 @@ pub fn parse_simple_selector_seq(
         } else {
             if let Some(value) = data_stream.peek() {
                 match value {
+                    // an unknown or invalid pseudo-class makes the selector invalid
+                    // https://www.w3.org/TR/selectors-4/#invalid
+                    ComponentValue::PerservedToken(Token::Colon) => return None,
                     ComponentValue::PerservedToken(Token::Whitespace) => break,
                     ComponentValue::PerservedToken(Token::Comma) => break,
+                    ComponentValue::PerservedToken(Token::Delim('>' | '+' | '~')) => break,
                     _ => {
                         data_stream.next();
                     }
@@ -213,11 +174,407 @@ pub fn parse_simple_selector(
             }
             None
         }
+        Some(ComponentValue::SimpleBlock(block)) if block.token == Token::BracketOpen => {
+            let attribute = parse_attribute_selector(&block.value)?;
+            data_stream.next();
+            Some(SimpleSelector::new(
+                SimpleSelectorType::Attribute(attribute),
+                None,
+            ))
+        }
+        Some(token_value!(Token::Colon)) => {
+            let next_values = data_stream.peek_next(3);
+            if next_values.len() == 3 {
+                if let (token_value!(Token::Colon), token_value!(Token::Ident(name))) =
+                    (next_values[1], next_values[2])
+                {
+                    let pseudo_element = parse_pseudo_element(name)?;
+                    data_stream.next();
+                    data_stream.next();
+                    data_stream.next();
+                    return Some(SimpleSelector::new(
+                        SimpleSelectorType::PseudoElement(pseudo_element),
+                        None,
+                    ));
+                }
+            }
+            let next_values = data_stream.peek_next(2);
+            if next_values.len() != 2 {
+                return None;
+            }
+            // legacy single-colon syntax for CSS 2 pseudo-elements
+            if let token_value!(Token::Ident(name)) = next_values[1] {
+                if let Some(pseudo_element) = parse_pseudo_element(name) {
+                    data_stream.next();
+                    data_stream.next();
+                    return Some(SimpleSelector::new(
+                        SimpleSelectorType::PseudoElement(pseudo_element),
+                        None,
+                    ));
+                }
+            }
+            let pseudo_class = match next_values[1] {
+                token_value!(Token::Ident(name)) => parse_pseudo_class(name)?,
+                ComponentValue::Function(function) => parse_functional_pseudo_class(function)?,
+                _ => return None,
+            };
+            data_stream.next();
+            data_stream.next();
+            Some(SimpleSelector::new(
+                SimpleSelectorType::PseudoClass(pseudo_class),
+                None,
+            ))
+        }
         // TODO: Support other selectors too
         _ => None,
     }
 }
 
+fn parse_pseudo_element(name: &str) -> Option<PseudoElement> {
+    match name.to_ascii_lowercase().as_str() {
+        "before" => Some(PseudoElement::Before),
+        "after" => Some(PseudoElement::After),
+        _ => None,
+    }
+}
+
+fn parse_pseudo_class(name: &str) -> Option<PseudoClass> {
+    let pseudo_class = match name.to_ascii_lowercase().as_str() {
+        "root" => PseudoClass::Root,
+        "empty" => PseudoClass::Empty,
+        "first-child" => PseudoClass::FirstChild,
+        "last-child" => PseudoClass::LastChild,
+        "only-child" => PseudoClass::OnlyChild,
+        "first-of-type" => PseudoClass::FirstOfType,
+        "last-of-type" => PseudoClass::LastOfType,
+        "only-of-type" => PseudoClass::OnlyOfType,
+        "hover" => PseudoClass::Hover,
+        "active" => PseudoClass::Active,
+        "focus" => PseudoClass::Focus,
+        "focus-within" => PseudoClass::FocusWithin,
+        "focus-visible" => PseudoClass::FocusVisible,
+        "checked" => PseudoClass::Checked,
+        "disabled" => PseudoClass::Disabled,
+        "visited" => PseudoClass::Visited,
+        _ => return None,
+    };
+    Some(pseudo_class)
+}
+
+fn parse_functional_pseudo_class(function: &Function) -> Option<PseudoClass> {
+    let name = function.name.to_ascii_lowercase();
+    match name.as_str() {
+        "nth-child" | "nth-last-child" => {
+            // split `An+B of S`
+            let of_index = function.value.iter().position(|value| match value {
+                token_value!(Token::Ident(ident)) => ident.eq_ignore_ascii_case("of"),
+                _ => false,
+            });
+            let (an_plus_b, selectors) = match of_index {
+                Some(index) => {
+                    let selectors = parse_selectors(&function.value[index + 1..].to_vec());
+                    if selectors.is_empty() {
+                        return None;
+                    }
+                    (parse_an_plus_b(&function.value[..index])?, selectors)
+                }
+                None => (parse_an_plus_b(&function.value)?, Vec::new()),
+            };
+            if name == "nth-child" {
+                Some(PseudoClass::NthChild(an_plus_b, selectors))
+            } else {
+                Some(PseudoClass::NthLastChild(an_plus_b, selectors))
+            }
+        }
+        "is" => Some(PseudoClass::Is(parse_selector_list(&function.value, true)?)),
+        "where" => Some(PseudoClass::Where(parse_selector_list(
+            &function.value,
+            true,
+        )?)),
+        "not" => Some(PseudoClass::Not(parse_selector_list(
+            &function.value,
+            false,
+        )?)),
+        "has" => Some(PseudoClass::Has(parse_relative_selector_list(
+            &function.value,
+        )?)),
+        "nth-of-type" => Some(PseudoClass::NthOfType(parse_an_plus_b(&function.value)?)),
+        "nth-last-of-type" => Some(PseudoClass::NthLastOfType(parse_an_plus_b(
+            &function.value,
+        )?)),
+        _ => None,
+    }
+}
+
+fn trim_whitespace(values: &[ComponentValue]) -> &[ComponentValue] {
+    let is_whitespace = |value: &ComponentValue| matches!(value, token_value!(Token::Whitespace));
+    let start = values
+        .iter()
+        .position(|value| !is_whitespace(value))
+        .unwrap_or(values.len());
+    let end = values
+        .iter()
+        .rposition(|value| !is_whitespace(value))
+        .map(|index| index + 1)
+        .unwrap_or(start);
+    &values[start..end]
+}
+
+/// Resolve the selector list of a nested style rule against the
+/// selector list of its parent rule. `&` is replaced by `:is(<parent>)`
+/// so it has the specificity of the most specific parent selector, and
+/// a selector without `&` is made relative to the parent, e.g. `> a`
+/// becomes `:is(<parent>) > a`. Without a parent rule, `&` is `:root`.
+/// https://www.w3.org/TR/css-nesting-1/#nest-selector
+pub fn resolve_nested_selectors(
+    values: &[ComponentValue],
+    parent: Option<&[ComponentValue]>,
+) -> Vec<ComponentValue> {
+    let mut result = Vec::new();
+    for (index, part) in values
+        .split(|value| matches!(value, token_value!(Token::Comma)))
+        .enumerate()
+    {
+        if index > 0 {
+            result.push(ComponentValue::PerservedToken(Token::Comma));
+        }
+        let part = trim_whitespace(part);
+        if parent.is_some() && !contains_nesting_selector(part) {
+            result.extend(nesting_selector(parent));
+            result.push(ComponentValue::PerservedToken(Token::Whitespace));
+        }
+        result.extend(replace_nesting_selector(part, parent));
+    }
+    result
+}
+
+fn nesting_selector(parent: Option<&[ComponentValue]>) -> Vec<ComponentValue> {
+    let selector = match parent {
+        Some(parent) => ComponentValue::Function(Function {
+            name: "is".to_string(),
+            value: trim_whitespace(parent).to_vec(),
+        }),
+        None => ComponentValue::PerservedToken(Token::Ident("root".to_string())),
+    };
+    vec![ComponentValue::PerservedToken(Token::Colon), selector]
+}
+
+fn contains_nesting_selector(values: &[ComponentValue]) -> bool {
+    values.iter().any(|value| match value {
+        token_value!(Token::Delim('&')) => true,
+        ComponentValue::Function(function) => contains_nesting_selector(&function.value),
+        _ => false,
+    })
+}
+
+fn replace_nesting_selector(
+    values: &[ComponentValue],
+    parent: Option<&[ComponentValue]>,
+) -> Vec<ComponentValue> {
+    values
+        .iter()
+        .flat_map(|value| match value {
+            token_value!(Token::Delim('&')) => nesting_selector(parent),
+            ComponentValue::Function(function) => vec![ComponentValue::Function(Function {
+                name: function.name.clone(),
+                value: replace_nesting_selector(&function.value, parent),
+            })],
+            value => vec![value.clone()],
+        })
+        .collect()
+}
+
+/// Parse a comma separated list of selectors, a forgiving list
+/// drops the invalid selectors instead of failing entirely
+/// https://www.w3.org/TR/selectors-4/#forgiving-selector
+pub fn parse_selector_list(values: &[ComponentValue], forgiving: bool) -> Option<Vec<Selector>> {
+    let mut selectors = Vec::new();
+    for part in values.split(|value| matches!(value, token_value!(Token::Comma))) {
+        let mut data_stream = DataStream::new(trim_whitespace(part).to_vec());
+        match parse_selector(&mut data_stream) {
+            Some(selector) => selectors.push(selector),
+            None if forgiving => continue,
+            None => return None,
+        }
+    }
+    Some(selectors)
+}
+
+/// Parse a comma separated list of relative selectors
+/// https://www.w3.org/TR/selectors-4/#relative
+pub fn parse_relative_selector_list(values: &[ComponentValue]) -> Option<Vec<RelativeSelector>> {
+    values
+        .split(|value| matches!(value, token_value!(Token::Comma)))
+        .map(|part| {
+            let part = trim_whitespace(part);
+            let (combinator, rest) = match part.first() {
+                Some(token_value!(Token::Delim('>'))) => (Combinator::Child, &part[1..]),
+                Some(token_value!(Token::Delim('+'))) => (Combinator::NextSibling, &part[1..]),
+                Some(token_value!(Token::Delim('~'))) => {
+                    (Combinator::SubsequentSibling, &part[1..])
+                }
+                _ => (Combinator::Descendant, part),
+            };
+            let mut data_stream = DataStream::new(trim_whitespace(rest).to_vec());
+            let selector = parse_selector(&mut data_stream)?;
+            Some(RelativeSelector {
+                combinator,
+                selector,
+            })
+        })
+        .collect()
+}
+
+/// Parse the An+B microsyntax
+/// https://www.w3.org/TR/css-syntax-3/#anb-microsyntax
+pub fn parse_an_plus_b(values: &[ComponentValue]) -> Option<AnPlusB> {
+    // the `+` of `+n` must not be followed by whitespace
+    if let [token_value!(Token::Delim('+')), token_value!(Token::Whitespace), ..] =
+        trim_whitespace(values)
+    {
+        return None;
+    }
+    let values = values
+        .iter()
+        .filter(|value| !matches!(value, token_value!(Token::Whitespace)))
+        .collect::<Vec<&ComponentValue>>();
+
+    let integer = |value: &ComponentValue| match value {
+        token_value!(Token::Number {
+            value,
+            type_: NumberType::Integer,
+        }) => Some(*value as i32),
+        _ => None,
+    };
+
+    // the `A` part with the rest of the ident that starts with `n`
+    let (a, n_part, rest) = match values.as_slice() {
+        [token_value!(Token::Ident(ident))] if ident.eq_ignore_ascii_case("odd") => {
+            return Some(AnPlusB(2, 1))
+        }
+        [token_value!(Token::Ident(ident))] if ident.eq_ignore_ascii_case("even") => {
+            return Some(AnPlusB(2, 0))
+        }
+        [value] if integer(value).is_some() => return Some(AnPlusB(0, integer(value)?)),
+        [token_value!(Token::Dimension {
+            value,
+            type_: NumberType::Integer,
+            unit,
+        }), rest @ ..] => (*value as i32, unit.to_ascii_lowercase(), rest),
+        [token_value!(Token::Delim('+')), token_value!(Token::Ident(ident)), rest @ ..]
+            if !ident.starts_with('-') =>
+        {
+            (1, ident.to_ascii_lowercase(), rest)
+        }
+        [token_value!(Token::Ident(ident)), rest @ ..] => match ident.strip_prefix('-') {
+            Some(ident) => (-1, ident.to_ascii_lowercase(), rest),
+            None => (1, ident.to_ascii_lowercase(), rest),
+        },
+        _ => return None,
+    };
+
+    // the `B` part
+    let b = match (n_part.as_str(), rest) {
+        ("n", []) => 0,
+        // `An+B` where the sign is part of the number token
+        ("n", [value]) => integer(value)?,
+        ("n", [token_value!(Token::Delim(sign @ ('+' | '-'))), value]) => {
+            let b = integer(value)?;
+            if b < 0 {
+                return None;
+            }
+            if *sign == '-' {
+                -b
+            } else {
+                b
+            }
+        }
+        ("n-", [value]) => {
+            let b = integer(value)?;
+            if b < 0 {
+                return None;
+            }
+            -b
+        }
+        (n_part, []) => {
+            let digits = n_part.strip_prefix("n-")?;
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            -digits.parse::<i32>().ok()?
+        }
+        _ => return None,
+    };
+
+    Some(AnPlusB(a, b))
+}
+
+/// Parse the content of an attribute selector block
+/// https://www.w3.org/TR/selectors-4/#attribute-selectors
+pub fn parse_attribute_selector(values: &[ComponentValue]) -> Option<AttributeSelector> {
+    let mut values = values
+        .iter()
+        .filter(|value| !matches!(value, token_value!(Token::Whitespace)))
+        .peekable();
+
+    let name = match values.next() {
+        Some(token_value!(Token::Ident(name))) => name.clone(),
+        _ => return None,
+    };
+
+    let operator = match values.next() {
+        None => {
+            return Some(AttributeSelector {
+                name,
+                operator: AttributeOperator::Exists,
+                value: String::new(),
+                case_insensitive: false,
+            })
+        }
+        Some(token_value!(Token::Delim('='))) => AttributeOperator::Equals,
+        Some(token_value!(Token::Delim(delim))) => {
+            let operator = match delim {
+                '~' => AttributeOperator::Includes,
+                '|' => AttributeOperator::DashMatch,
+                '^' => AttributeOperator::Prefix,
+                '$' => AttributeOperator::Suffix,
+                '*' => AttributeOperator::Substring,
+                _ => return None,
+            };
+            match values.next() {
+                Some(token_value!(Token::Delim('='))) => operator,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    let value = match values.next() {
+        Some(token_value!(Token::Ident(value))) | Some(token_value!(Token::Str(value))) => {
+            value.clone()
+        }
+        _ => return None,
+    };
+
+    let case_insensitive = match values.next() {
+        None => false,
+        Some(token_value!(Token::Ident(flag))) if flag.eq_ignore_ascii_case("i") => true,
+        Some(token_value!(Token::Ident(flag))) if flag.eq_ignore_ascii_case("s") => false,
+        _ => return None,
+    };
+
+    if values.next().is_some() {
+        return None;
+    }
+
+    Some(AttributeSelector {
+        name,
+        operator,
+        value,
+        case_insensitive,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -430,4 +787,203 @@ mod tests {
 
         assert_eq!(specificity, Specificity::new(2, 1, 0));
     }
+
+    #[test]
+    fn parse_attribute() {
+        let selector = parse_selector_str("a[href^='https' i][target]").unwrap();
+        let expected = Selector::new(vec![(
+            SimpleSelectorSequence::new(vec![
+                SimpleSelector::new(SimpleSelectorType::Type, Some("a".to_string())),
+                SimpleSelector::new(
+                    SimpleSelectorType::Attribute(AttributeSelector {
+                        name: "href".to_string(),
+                        operator: AttributeOperator::Prefix,
+                        value: "https".to_string(),
+                        case_insensitive: true,
+                    }),
+                    None,
+                ),
+                SimpleSelector::new(
+                    SimpleSelectorType::Attribute(AttributeSelector {
+                        name: "target".to_string(),
+                        operator: AttributeOperator::Exists,
+                        value: String::new(),
+                        case_insensitive: false,
+                    }),
+                    None,
+                ),
+            ]),
+            None,
+        )]);
+        assert_eq!(selector, expected);
+        assert_eq!(selector.specificity(), Specificity::new(0, 2, 1));
+    }
+
+    #[test]
+    fn parse_attribute_operators() {
+        let operators = [
+            ("[lang|=en]", AttributeOperator::DashMatch),
+            ("[class~=a]", AttributeOperator::Includes),
+            ("[src$=\".png\"]", AttributeOperator::Suffix),
+            ("[title*=hello s]", AttributeOperator::Substring),
+            ("[type=text]", AttributeOperator::Equals),
+        ];
+        for (css, operator) in operators {
+            let selector = parse_selector_str(css).unwrap();
+            let simple = &selector.values()[0].0.values()[0];
+            match simple.selector_type() {
+                SimpleSelectorType::Attribute(attribute) => {
+                    assert_eq!(attribute.operator, operator)
+                }
+                _ => panic!("Expected an attribute selector for {}", css),
+            }
+        }
+
+        assert_eq!(parse_selector_str("[a=b c]"), None);
+        assert_eq!(parse_selector_str("[a=]"), None);
+        assert_eq!(parse_selector_str("[=b]"), None);
+    }
+
+    #[test]
+    fn parse_an_plus_b_syntax() {
+        let parse = |css: &str| {
+            let tokenizer = Tokenizer::new(css.chars());
+            let mut parser = Parser::<Token>::new(tokenizer.run());
+            parse_an_plus_b(&parser.parse_a_list_of_component_values())
+        };
+
+        assert_eq!(parse("odd"), Some(AnPlusB(2, 1)));
+        assert_eq!(parse("EVEN"), Some(AnPlusB(2, 0)));
+        assert_eq!(parse("5 "), Some(AnPlusB(0, 5)));
+        assert_eq!(parse("-n+3"), Some(AnPlusB(-1, 3)));
+        assert_eq!(parse("+n"), Some(AnPlusB(1, 0)));
+        assert_eq!(parse("2n+1"), Some(AnPlusB(2, 1)));
+        assert_eq!(parse("2n-1"), Some(AnPlusB(2, -1)));
+        assert_eq!(parse("3n - 2"), Some(AnPlusB(3, -2)));
+        assert_eq!(parse("-2n + 4"), Some(AnPlusB(-2, 4)));
+        assert_eq!(parse("n- 1"), Some(AnPlusB(1, -1)));
+        assert_eq!(parse("-n-2"), Some(AnPlusB(-1, -2)));
+        assert_eq!(parse("2n + -1"), None);
+        assert_eq!(parse("+ n"), None);
+        assert_eq!(parse(" + 2n"), None);
+        assert_eq!(parse("2.5n"), None);
+        assert_eq!(parse("foo"), None);
+    }
+
+    #[test]
+    fn parse_structural_pseudo_classes() {
+        let selector = parse_selector_str("li:first-child:nth-child(2n+1 of .item, #a)").unwrap();
+        let values = selector.values()[0].0.values();
+        assert_eq!(
+            values[1].selector_type(),
+            &SimpleSelectorType::PseudoClass(PseudoClass::FirstChild)
+        );
+        match values[2].selector_type() {
+            SimpleSelectorType::PseudoClass(PseudoClass::NthChild(an_plus_b, selectors)) => {
+                assert_eq!(an_plus_b, &AnPlusB(2, 1));
+                assert_eq!(selectors.len(), 2);
+            }
+            _ => panic!("Expected :nth-child"),
+        }
+        // li + :first-child + :nth-child + the most specific selector in S (#a)
+        assert_eq!(selector.specificity(), Specificity::new(1, 2, 1));
+
+        assert_eq!(parse_selector_str(":nth-child(of .item)"), None);
+        assert_eq!(parse_selector_str(":nth-child(2n of)"), None);
+        assert_eq!(parse_selector_str(":unknown"), None);
+        assert_eq!(parse_selector_str("a:unknown"), None);
+    }
+
+    #[test]
+    fn parse_pseudo_elements() {
+        let selector = parse_selector_str("p.note::before").unwrap();
+        assert_eq!(
+            selector.values()[0].0.values()[2].selector_type(),
+            &SimpleSelectorType::PseudoElement(PseudoElement::Before)
+        );
+        assert_eq!(selector.pseudo_element(), Some(&PseudoElement::Before));
+        assert_eq!(selector.specificity(), Specificity::new(0, 1, 2));
+
+        let selector = parse_selector_str("div > a:after").unwrap();
+        assert_eq!(selector.pseudo_element(), Some(&PseudoElement::After));
+
+        let selector = parse_selector_str("a:first-child").unwrap();
+        assert_eq!(selector.pseudo_element(), None);
+
+        assert_eq!(parse_selector_str("::before .a"), None);
+        assert_eq!(parse_selector_str("a::after > b"), None);
+    }
+
+    #[test]
+    fn parse_invalid_selector_list() {
+        let parse = |css: &str| {
+            let tokenizer = Tokenizer::new(css.chars());
+            let mut parser = Parser::<Token>::new(tokenizer.run());
+            match parser.parse_a_stylesheet().remove(0) {
+                Rule::QualifiedRule(rule) => parse_selectors(&rule.prelude),
+                _ => panic!("Expected a qualified rule"),
+            }
+        };
+        assert_eq!(parse("a, p::before {}").len(), 2);
+        assert!(parse("a, ::before .b {}").is_empty());
+        assert!(parse("a, :unknown {}").is_empty());
+    }
+
+    #[test]
+    fn parse_logical_pseudo_classes() {
+        let selector = parse_selector_str("div:is(#a, .b):where(#c)").unwrap();
+        assert_eq!(selector.specificity(), Specificity::new(1, 0, 1));
+
+        let selector = parse_selector_str("a:not(.b, .c.d)").unwrap();
+        assert_eq!(selector.specificity(), Specificity::new(0, 2, 1));
+
+        let selector = parse_selector_str("section:has(> h1, + p img)").unwrap();
+        match selector.values()[0].0.values()[1].selector_type() {
+            SimpleSelectorType::PseudoClass(PseudoClass::Has(selectors)) => {
+                assert_eq!(selectors.len(), 2);
+                assert_eq!(selectors[0].combinator, Combinator::Child);
+                assert_eq!(selectors[1].combinator, Combinator::NextSibling);
+                assert_eq!(selectors[1].selector.values().len(), 2);
+            }
+            _ => panic!("Expected :has"),
+        }
+        assert_eq!(selector.specificity(), Specificity::new(0, 0, 3));
+
+        // forgiving parsing drops the invalid arguments
+        let selector = parse_selector_str(":is(, .a)").unwrap();
+        match selector.values()[0].0.values()[0].selector_type() {
+            SimpleSelectorType::PseudoClass(PseudoClass::Is(selectors)) => {
+                assert_eq!(selectors.len(), 1)
+            }
+            _ => panic!("Expected :is"),
+        }
+        assert_eq!(parse_selector_str(":not(, .a)"), None);
+        // but an unknown pseudo-class inside them is only dropped
+        let selector = parse_selector_str(":where(:unknown, .a)").unwrap();
+        match selector.values()[0].0.values()[0].selector_type() {
+            SimpleSelectorType::PseudoClass(PseudoClass::Where(selectors)) => {
+                assert_eq!(selectors.len(), 1)
+            }
+            _ => panic!("Expected :where"),
+        }
+    }
+
+    #[test]
+    fn parse_multiple_descendants() {
+        let selector = parse_selector_str(".a b > c d").unwrap();
+        let combinators = selector
+            .values()
+            .iter()
+            .map(|(_, combinator)| combinator.as_ref())
+            .collect::<Vec<Option<&Combinator>>>();
+        assert_eq!(
+            combinators,
+            vec![
+                Some(&Combinator::Descendant),
+                Some(&Combinator::Child),
+                Some(&Combinator::Descendant),
+                None
+            ]
+        );
+    }
 }
@@ -2,10 +2,10 @@ use std::cmp::{Ord, Ordering};
 
 pub type SelectorData = Vec<(SimpleSelectorSequence, Option<Combinator>)>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Selector(SelectorData);
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Combinator {
     Descendant,
     Child,
@@ -13,20 +13,108 @@ pub enum Combinator {
     SubsequentSibling,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SimpleSelectorSequence(Vec<SimpleSelector>);
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SimpleSelectorType {
     Type,
     Universal,
-    Attribute,
+    Attribute(AttributeSelector),
     Class,
     ID,
-    Pseudo,
+    PseudoClass(PseudoClass),
+    PseudoElement(PseudoElement),
 }
 
-#[derive(Debug, PartialEq)]
+/// Pseudo-element selector
+/// https://www.w3.org/TR/css-pseudo-4/#generated-content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PseudoElement {
+    Before,
+    After,
+}
+
+/// Pseudo-class selector
+/// https://www.w3.org/TR/selectors-4/#structural-pseudos
+#[derive(Debug, Clone, PartialEq)]
+pub enum PseudoClass {
+    Root,
+    Empty,
+    FirstChild,
+    LastChild,
+    OnlyChild,
+    FirstOfType,
+    LastOfType,
+    OnlyOfType,
+    /// `:nth-child(An+B [of S]?)`
+    NthChild(AnPlusB, Vec<Selector>),
+    /// `:nth-last-child(An+B [of S]?)`
+    NthLastChild(AnPlusB, Vec<Selector>),
+    NthOfType(AnPlusB),
+    NthLastOfType(AnPlusB),
+    Hover,
+    Active,
+    Focus,
+    FocusWithin,
+    FocusVisible,
+    Checked,
+    Disabled,
+    Visited,
+    /// `:is(S)`, matches with the specificity of the most specific argument
+    Is(Vec<Selector>),
+    /// `:where(S)`, same as `:is()` but with zero specificity
+    Where(Vec<Selector>),
+    /// `:not(S)`
+    Not(Vec<Selector>),
+    /// `:has(S)`, matches if any relative selector matches
+    /// relative to the element
+    Has(Vec<RelativeSelector>),
+}
+
+/// A selector that is anchored to another element with a combinator
+/// https://www.w3.org/TR/selectors-4/#relative
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelativeSelector {
+    pub combinator: Combinator,
+    pub selector: Selector,
+}
+
+/// The An+B notation, matching every index `a*n + b` for `n >= 0`
+/// https://www.w3.org/TR/css-syntax-3/#anb-microsyntax
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnPlusB(pub i32, pub i32);
+
+/// Attribute selector
+/// https://www.w3.org/TR/selectors-4/#attribute-selectors
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeSelector {
+    pub name: String,
+    pub operator: AttributeOperator,
+    pub value: String,
+    /// Set by the `i` flag, values are compared ASCII case-insensitively
+    pub case_insensitive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeOperator {
+    /// `[attr]`
+    Exists,
+    /// `[attr=value]`
+    Equals,
+    /// `[attr~=value]`
+    Includes,
+    /// `[attr|=value]`
+    DashMatch,
+    /// `[attr^=value]`
+    Prefix,
+    /// `[attr$=value]`
+    Suffix,
+    /// `[attr*=value]`
+    Substring,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct SimpleSelector {
     type_: SimpleSelectorType,
     value: Option<String>,
@@ -80,6 +168,13 @@ impl Selector {
         });
         Specificity::new(a, b, c)
     }
+
+    /// The pseudo-element this selector targets, if any. Pseudo-elements
+    /// are only valid in the last compound selector.
+    pub fn pseudo_element(&self) -> Option<&PseudoElement> {
+        let (last, _) = self.values().last()?;
+        last.pseudo_element()
+    }
 }
 
 impl SimpleSelectorSequence {
@@ -87,6 +182,15 @@ impl SimpleSelectorSequence {
         Self(data)
     }
 
+    pub fn pseudo_element(&self) -> Option<&PseudoElement> {
+        self.values()
+            .iter()
+            .find_map(|selector| match selector.selector_type() {
+                SimpleSelectorType::PseudoElement(pseudo) => Some(pseudo),
+                _ => None,
+            })
+    }
+
     pub fn values(&self) -> &Vec<SimpleSelector> {
         &self.0
     }
@@ -97,16 +201,93 @@ impl SimpleSelectorSequence {
                 .iter()
                 .fold((0, 0, 0), |acc, curr| match curr.selector_type() {
                     SimpleSelectorType::ID => (acc.0 + 1, acc.1, acc.2),
-                    SimpleSelectorType::Class | SimpleSelectorType::Attribute => {
+                    SimpleSelectorType::Class | SimpleSelectorType::Attribute(_) => {
                         (acc.0, acc.1 + 1, acc.2)
                     }
-                    SimpleSelectorType::Type => (acc.0, acc.1, acc.2 + 1),
+                    SimpleSelectorType::Type | SimpleSelectorType::PseudoElement(_) => {
+                        (acc.0, acc.1, acc.2 + 1)
+                    }
+                    SimpleSelectorType::PseudoClass(pseudo_class) => {
+                        let Specificity(sa, sb, sc) = pseudo_class.specificity();
+                        (acc.0 + sa, acc.1 + sb, acc.2 + sc)
+                    }
                     _ => acc,
                 });
         Specificity(a, b, c)
     }
 }
 
+impl PseudoClass {
+    /// https://www.w3.org/TR/selectors-4/#specificity-rules
+    pub fn specificity(&self) -> Specificity {
+        let max_specificity = |selectors: &mut dyn Iterator<Item = &Selector>| {
+            selectors
+                .map(|selector| selector.specificity())
+                .max()
+                .unwrap_or(Specificity(0, 0, 0))
+        };
+
+        match self {
+            // the specificity of the most specific argument
+            PseudoClass::Is(selectors) | PseudoClass::Not(selectors) => {
+                max_specificity(&mut selectors.iter())
+            }
+            PseudoClass::Has(selectors) => {
+                max_specificity(&mut selectors.iter().map(|relative| &relative.selector))
+            }
+            PseudoClass::Where(_) => Specificity(0, 0, 0),
+            // the specificity of a pseudo-class plus the most specific argument
+            PseudoClass::NthChild(_, selectors) | PseudoClass::NthLastChild(_, selectors) => {
+                let Specificity(a, b, c) = max_specificity(&mut selectors.iter());
+                Specificity(a, b + 1, c)
+            }
+            _ => Specificity(0, 1, 0),
+        }
+    }
+}
+
+impl AnPlusB {
+    /// Check if a 1-based index is matched
+    pub fn matches(&self, index: i32) -> bool {
+        let AnPlusB(a, b) = *self;
+        if a == 0 {
+            return index == b;
+        }
+        let n = index - b;
+        n % a == 0 && n / a >= 0
+    }
+}
+
+impl AttributeSelector {
+    /// Check if the attribute value matches the selector
+    pub fn matches(&self, attribute_value: &str) -> bool {
+        let (value, expected) = if self.case_insensitive {
+            (
+                attribute_value.to_ascii_lowercase(),
+                self.value.to_ascii_lowercase(),
+            )
+        } else {
+            (attribute_value.to_string(), self.value.clone())
+        };
+
+        match self.operator {
+            AttributeOperator::Exists => true,
+            AttributeOperator::Equals => value == expected,
+            AttributeOperator::Includes => {
+                !expected.is_empty()
+                    && !expected.contains(char::is_whitespace)
+                    && value.split_whitespace().any(|item| item == expected)
+            }
+            AttributeOperator::DashMatch => {
+                value == expected || value.starts_with(&format!("{}-", expected))
+            }
+            AttributeOperator::Prefix => !expected.is_empty() && value.starts_with(&expected),
+            AttributeOperator::Suffix => !expected.is_empty() && value.ends_with(&expected),
+            AttributeOperator::Substring => !expected.is_empty() && value.contains(&expected),
+        }
+    }
+}
+
 impl SimpleSelector {
     pub fn new(type_: SimpleSelectorType, value: Option<String>) -> Self {
         Self { type_, value }
@@ -130,4 +311,18 @@ mod tests {
         let b = Specificity::new(0, 0, 1);
         assert!(a < b);
     }
+
+    #[test]
+    fn test_an_plus_b() {
+        let matched = |an_plus_b: AnPlusB| {
+            (1..=10)
+                .filter(|index| an_plus_b.matches(*index))
+                .collect::<Vec<i32>>()
+        };
+        assert_eq!(matched(AnPlusB(2, 1)), vec![1, 3, 5, 7, 9]);
+        assert_eq!(matched(AnPlusB(3, -1)), vec![2, 5, 8]);
+        assert_eq!(matched(AnPlusB(-1, 3)), vec![1, 2, 3]);
+        assert_eq!(matched(AnPlusB(0, 4)), vec![4]);
+        assert_eq!(matched(AnPlusB(-2, 0)), Vec::<i32>::new());
+    }
 }
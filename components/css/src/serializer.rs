@@ -0,0 +1,591 @@
+use crate::cssom::css_rule::CSSRule;
+use crate::cssom::keyframes_rule::{Keyframe, KeyframesRule};
+use crate::cssom::layer::LayerSegment;
+use crate::cssom::style_rule::StyleRule;
+use crate::cssom::stylesheet::StyleSheet;
+use crate::media_queries::*;
+use crate::parser::structs::{ComponentValue, Declaration, Function, SimpleBlock};
+use crate::selector::structs::*;
+use crate::tokenizer::token::{HashType, NumberType, Token};
+
+/// Serialize a CSS object back to CSS text
+/// https://www.w3.org/TR/cssom-1/#serializing-css-values
+pub trait ToCss {
+    fn to_css(&self, dest: &mut String);
+
+    fn to_css_string(&self) -> String {
+        let mut result = String::new();
+        self.to_css(&mut result);
+        result
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-an-identifier
+pub fn serialize_identifier(value: &str, dest: &mut String) {
+    let chars = value.chars().collect::<Vec<char>>();
+
+    if chars == ['-'] {
+        dest.push_str("\\-");
+        return;
+    }
+
+    for (index, &ch) in chars.iter().enumerate() {
+        match ch {
+            '\0' => dest.push('\u{FFFD}'),
+            '\u{1}'..='\u{1F}' | '\u{7F}' => escape_code_point(ch, dest),
+            '0'..='9' if index == 0 || (index == 1 && chars[0] == '-') => {
+                escape_code_point(ch, dest)
+            }
+            ch if ch >= '\u{80}' || ch == '-' || ch == '_' || ch.is_ascii_alphanumeric() => {
+                dest.push(ch)
+            }
+            ch => {
+                dest.push('\\');
+                dest.push(ch);
+            }
+        }
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-string
+pub fn serialize_string(value: &str, dest: &mut String) {
+    dest.push('"');
+    for ch in value.chars() {
+        match ch {
+            '\0' => dest.push('\u{FFFD}'),
+            '\u{1}'..='\u{1F}' | '\u{7F}' => escape_code_point(ch, dest),
+            '"' | '\\' => {
+                dest.push('\\');
+                dest.push(ch);
+            }
+            ch => dest.push(ch),
+        }
+    }
+    dest.push('"');
+}
+
+fn escape_code_point(ch: char, dest: &mut String) {
+    dest.push_str(&format!("\\{:x} ", ch as u32));
+}
+
+fn serialize_number(value: f32, type_: &NumberType, dest: &mut String) {
+    match type_ {
+        NumberType::Integer => dest.push_str(&(value as i64).to_string()),
+        NumberType::Number => dest.push_str(&value.to_string()),
+    }
+}
+
+fn serialize_list<T: ToCss>(values: &[T], separator: &str, dest: &mut String) {
+    for (index, value) in values.iter().enumerate() {
+        if index > 0 {
+            dest.push_str(separator);
+        }
+        value.to_css(dest);
+    }
+}
+
+impl ToCss for Token {
+    fn to_css(&self, dest: &mut String) {
+        match self {
+            Token::Ident(value) => serialize_identifier(value, dest),
+            Token::Function(name) => {
+                serialize_identifier(name, dest);
+                dest.push('(');
+            }
+            Token::AtKeyword(name) => {
+                dest.push('@');
+                serialize_identifier(name, dest);
+            }
+            Token::Hash(value, HashType::Id) => {
+                dest.push('#');
+                serialize_identifier(value, dest);
+            }
+            Token::Hash(value, HashType::Unrestricted) => {
+                dest.push('#');
+                dest.push_str(value);
+            }
+            Token::Str(value) => serialize_string(value, dest),
+            Token::Url(value) => {
+                dest.push_str("url(");
+                serialize_string(value, dest);
+                dest.push(')');
+            }
+            Token::Delim(ch) => dest.push(*ch),
+            Token::Number { value, type_ } => serialize_number(*value, type_, dest),
+            Token::Percentage(value) => {
+                dest.push_str(&value.to_string());
+                dest.push('%');
+            }
+            Token::Dimension { value, type_, unit } => {
+                serialize_number(*value, type_, dest);
+                serialize_identifier(unit, dest);
+            }
+            Token::Whitespace => dest.push(' '),
+            Token::CDO => dest.push_str("<!--"),
+            Token::CDC => dest.push_str("-->"),
+            Token::Colon => dest.push(':'),
+            Token::Semicolon => dest.push(';'),
+            Token::Comma => dest.push(','),
+            Token::BracketOpen => dest.push('['),
+            Token::BracketClose => dest.push(']'),
+            Token::ParentheseOpen => dest.push('('),
+            Token::ParentheseClose => dest.push(')'),
+            Token::BraceOpen => dest.push('{'),
+            Token::BraceClose => dest.push('}'),
+            Token::BadStr | Token::BadUrl | Token::EOF => {}
+        }
+    }
+}
+
+impl ToCss for ComponentValue {
+    fn to_css(&self, dest: &mut String) {
+        match self {
+            ComponentValue::PerservedToken(token) => token.to_css(dest),
+            ComponentValue::Function(function) => function.to_css(dest),
+            ComponentValue::SimpleBlock(block) => block.to_css(dest),
+        }
+    }
+}
+
+impl ToCss for Function {
+    fn to_css(&self, dest: &mut String) {
+        serialize_identifier(&self.name, dest);
+        dest.push('(');
+        serialize_list(&self.value, "", dest);
+        dest.push(')');
+    }
+}
+
+impl ToCss for SimpleBlock {
+    fn to_css(&self, dest: &mut String) {
+        let closing = match self.token {
+            Token::BracketOpen => Token::BracketClose,
+            Token::ParentheseOpen => Token::ParentheseClose,
+            _ => Token::BraceClose,
+        };
+        self.token.to_css(dest);
+        serialize_list(&self.value, "", dest);
+        closing.to_css(dest);
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-css-declaration
+impl ToCss for Declaration {
+    fn to_css(&self, dest: &mut String) {
+        serialize_identifier(&self.name, dest);
+        dest.push_str(": ");
+        serialize_list(&self.value, "", dest);
+        if self.important {
+            dest.push_str(" !important");
+        }
+        dest.push(';');
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-selector
+impl ToCss for Selector {
+    fn to_css(&self, dest: &mut String) {
+        for (sequence, combinator) in self.values() {
+            sequence.to_css(dest);
+            if let Some(combinator) = combinator {
+                combinator.to_css(dest);
+            }
+        }
+    }
+}
+
+impl ToCss for Combinator {
+    fn to_css(&self, dest: &mut String) {
+        dest.push_str(match self {
+            Combinator::Descendant => " ",
+            Combinator::Child => " > ",
+            Combinator::NextSibling => " + ",
+            Combinator::SubsequentSibling => " ~ ",
+        });
+    }
+}
+
+impl ToCss for SimpleSelectorSequence {
+    fn to_css(&self, dest: &mut String) {
+        for selector in self.values() {
+            selector.to_css(dest);
+        }
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-simple-selector
+impl ToCss for SimpleSelector {
+    fn to_css(&self, dest: &mut String) {
+        let value = self.value().as_deref().unwrap_or_default();
+        match self.selector_type() {
+            SimpleSelectorType::Type => serialize_identifier(value, dest),
+            SimpleSelectorType::Universal => dest.push('*'),
+            SimpleSelectorType::ID => {
+                dest.push('#');
+                serialize_identifier(value, dest);
+            }
+            SimpleSelectorType::Class => {
+                dest.push('.');
+                serialize_identifier(value, dest);
+            }
+            SimpleSelectorType::Attribute(attribute) => attribute.to_css(dest),
+            SimpleSelectorType::PseudoClass(pseudo_class) => pseudo_class.to_css(dest),
+            SimpleSelectorType::PseudoElement(pseudo_element) => pseudo_element.to_css(dest),
+        }
+    }
+}
+
+impl ToCss for AttributeSelector {
+    fn to_css(&self, dest: &mut String) {
+        dest.push('[');
+        serialize_identifier(&self.name, dest);
+        let operator = match self.operator {
+            AttributeOperator::Exists => None,
+            AttributeOperator::Equals => Some("="),
+            AttributeOperator::Includes => Some("~="),
+            AttributeOperator::DashMatch => Some("|="),
+            AttributeOperator::Prefix => Some("^="),
+            AttributeOperator::Suffix => Some("$="),
+            AttributeOperator::Substring => Some("*="),
+        };
+        if let Some(operator) = operator {
+            dest.push_str(operator);
+            serialize_string(&self.value, dest);
+            if self.case_insensitive {
+                dest.push_str(" i");
+            }
+        }
+        dest.push(']');
+    }
+}
+
+impl ToCss for PseudoElement {
+    fn to_css(&self, dest: &mut String) {
+        dest.push_str(match self {
+            PseudoElement::Before => "::before",
+            PseudoElement::After => "::after",
+        });
+    }
+}
+
+impl ToCss for PseudoClass {
+    fn to_css(&self, dest: &mut String) {
+        dest.push(':');
+        let name = match self {
+            PseudoClass::Root => "root",
+            PseudoClass::Empty => "empty",
+            PseudoClass::FirstChild => "first-child",
+            PseudoClass::LastChild => "last-child",
+            PseudoClass::OnlyChild => "only-child",
+            PseudoClass::FirstOfType => "first-of-type",
+            PseudoClass::LastOfType => "last-of-type",
+            PseudoClass::OnlyOfType => "only-of-type",
+            PseudoClass::Hover => "hover",
+            PseudoClass::Active => "active",
+            PseudoClass::Focus => "focus",
+            PseudoClass::FocusWithin => "focus-within",
+            PseudoClass::FocusVisible => "focus-visible",
+            PseudoClass::Checked => "checked",
+            PseudoClass::Disabled => "disabled",
+            PseudoClass::Visited => "visited",
+            PseudoClass::NthChild(an_plus_b, selectors)
+            | PseudoClass::NthLastChild(an_plus_b, selectors) => {
+                dest.push_str(match self {
+                    PseudoClass::NthChild(..) => "nth-child(",
+                    _ => "nth-last-child(",
+                });
+                an_plus_b.to_css(dest);
+                if !selectors.is_empty() {
+                    dest.push_str(" of ");
+                    serialize_list(selectors, ", ", dest);
+                }
+                dest.push(')');
+                return;
+            }
+            PseudoClass::NthOfType(an_plus_b) | PseudoClass::NthLastOfType(an_plus_b) => {
+                dest.push_str(match self {
+                    PseudoClass::NthOfType(_) => "nth-of-type(",
+                    _ => "nth-last-of-type(",
+                });
+                an_plus_b.to_css(dest);
+                dest.push(')');
+                return;
+            }
+            PseudoClass::Is(selectors)
+            | PseudoClass::Where(selectors)
+            | PseudoClass::Not(selectors) => {
+                dest.push_str(match self {
+                    PseudoClass::Is(_) => "is(",
+                    PseudoClass::Where(_) => "where(",
+                    _ => "not(",
+                });
+                serialize_list(selectors, ", ", dest);
+                dest.push(')');
+                return;
+            }
+            PseudoClass::Has(selectors) => {
+                dest.push_str("has(");
+                serialize_list(selectors, ", ", dest);
+                dest.push(')');
+                return;
+            }
+        };
+        dest.push_str(name);
+    }
+}
+
+impl ToCss for RelativeSelector {
+    fn to_css(&self, dest: &mut String) {
+        if self.combinator != Combinator::Descendant {
+            dest.push_str(self.combinator.to_css_string().trim_start());
+        }
+        self.selector.to_css(dest);
+    }
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#serializing-anb
+impl ToCss for AnPlusB {
+    fn to_css(&self, dest: &mut String) {
+        let AnPlusB(a, b) = *self;
+        match a {
+            0 => {
+                dest.push_str(&b.to_string());
+                return;
+            }
+            1 => dest.push('n'),
+            -1 => dest.push_str("-n"),
+            a => dest.push_str(&format!("{}n", a)),
+        }
+        if b > 0 {
+            dest.push_str(&format!("+{}", b));
+        } else if b < 0 {
+            dest.push_str(&b.to_string());
+        }
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-css-rule
+impl ToCss for StyleRule {
+    fn to_css(&self, dest: &mut String) {
+        serialize_list(&self.selectors, ", ", dest);
+        dest.push_str(" {");
+        for declaration in &self.declarations {
+            dest.push(' ');
+            declaration.to_css(dest);
+        }
+        dest.push_str(" }");
+    }
+}
+
+/// Style rules from `@media` and `@layer` rules are wrapped back in them
+impl ToCss for CSSRule {
+    fn to_css(&self, dest: &mut String) {
+        match self {
+            CSSRule::Style(style) => {
+                for media in &style.media {
+                    dest.push_str("@media ");
+                    media.to_css(dest);
+                    dest.push_str(" { ");
+                }
+                for segment in &style.layer.0 {
+                    dest.push_str("@layer ");
+                    if let LayerSegment::Named(name) = segment {
+                        serialize_identifier(name, dest);
+                        dest.push(' ');
+                    }
+                    dest.push_str("{ ");
+                }
+                style.to_css(dest);
+                for _ in 0..style.media.len() + style.layer.0.len() {
+                    dest.push_str(" }");
+                }
+            }
+        }
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-media-query-list
+impl ToCss for MediaQueryList {
+    fn to_css(&self, dest: &mut String) {
+        if self.0.is_empty() {
+            dest.push_str("all");
+        }
+        serialize_list(&self.0, ", ", dest);
+    }
+}
+
+/// https://www.w3.org/TR/cssom-1/#serialize-a-media-query
+impl ToCss for MediaQuery {
+    fn to_css(&self, dest: &mut String) {
+        let has_type =
+            self.negated || self.features.is_empty() || self.media_type != MediaType::All;
+        if self.negated {
+            dest.push_str("not ");
+        }
+        if has_type {
+            match &self.media_type {
+                MediaType::All => dest.push_str("all"),
+                MediaType::Screen => dest.push_str("screen"),
+                MediaType::Print => dest.push_str("print"),
+                MediaType::Unknown(name) => serialize_identifier(name, dest),
+            }
+        }
+        for (index, feature) in self.features.iter().enumerate() {
+            if has_type || index > 0 {
+                dest.push_str(" and ");
+            }
+            feature.to_css(dest);
+        }
+    }
+}
+
+impl ToCss for MediaFeature {
+    fn to_css(&self, dest: &mut String) {
+        let value = match &self.value {
+            Some(value) => value,
+            None => {
+                dest.push('(');
+                serialize_identifier(&self.name, dest);
+                dest.push(')');
+                return;
+            }
+        };
+        let (prefix, operator) = match self.comparison {
+            MediaComparison::Equal => ("", None),
+            MediaComparison::GreaterThanOrEqual => ("min-", None),
+            MediaComparison::LessThanOrEqual => ("max-", None),
+            MediaComparison::GreaterThan => ("", Some(" > ")),
+            MediaComparison::LessThan => ("", Some(" < ")),
+        };
+        dest.push('(');
+        dest.push_str(prefix);
+        serialize_identifier(&self.name, dest);
+        dest.push_str(operator.unwrap_or(": "));
+        match value {
+            MediaFeatureValue::Length(length) => dest.push_str(&format!("{}px", length)),
+            MediaFeatureValue::Number(number) => dest.push_str(&number.to_string()),
+            MediaFeatureValue::Ident(ident) => serialize_identifier(ident, dest),
+        }
+        dest.push(')');
+    }
+}
+
+/// https://www.w3.org/TR/css-animations-1/#interface-csskeyframesrule
+impl ToCss for KeyframesRule {
+    fn to_css(&self, dest: &mut String) {
+        for media in &self.media {
+            dest.push_str("@media ");
+            media.to_css(dest);
+            dest.push_str(" { ");
+        }
+        dest.push_str("@keyframes ");
+        serialize_identifier(&self.name, dest);
+        dest.push_str(" {");
+        for keyframe in &self.keyframes {
+            dest.push(' ');
+            keyframe.to_css(dest);
+        }
+        dest.push_str(" }");
+        for _ in &self.media {
+            dest.push_str(" }");
+        }
+    }
+}
+
+impl ToCss for Keyframe {
+    fn to_css(&self, dest: &mut String) {
+        for (index, offset) in self.offsets.iter().enumerate() {
+            if index > 0 {
+                dest.push_str(", ");
+            }
+            dest.push_str(&(offset * 100.).to_string());
+            dest.push('%');
+        }
+        dest.push_str(" {");
+        for declaration in &self.declarations {
+            dest.push(' ');
+            declaration.to_css(dest);
+        }
+        dest.push_str(" }");
+    }
+}
+
+impl ToCss for StyleSheet {
+    fn to_css(&self, dest: &mut String) {
+        serialize_list(self.css_rules.as_slice(), "\n", dest);
+        for keyframes in &self.keyframes {
+            if !dest.is_empty() {
+                dest.push('\n');
+            }
+            keyframes.to_css(dest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::selector::parse_selector_str;
+    use crate::tokenizer::Tokenizer;
+
+    fn parse_stylesheet(css: &str) -> StyleSheet {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        parser.parse_a_css_stylesheet()
+    }
+
+    fn round_trip_selector(selector: &str) -> String {
+        parse_selector_str(selector).unwrap().to_css_string()
+    }
+
+    #[test]
+    fn serialize_selectors() {
+        assert_eq!(round_trip_selector("div#a.b  >  p"), "div#a.b > p");
+        assert_eq!(round_trip_selector("a+b ~ c d"), "a + b ~ c d");
+        assert_eq!(
+            round_trip_selector("[href^='http' i][title]"),
+            "[href^=\"http\" i][title]"
+        );
+        assert_eq!(
+            round_trip_selector("li:nth-child( odd ):nth-last-of-type(-n+3)"),
+            "li:nth-child(2n+1):nth-last-of-type(-n+3)"
+        );
+        assert_eq!(
+            round_trip_selector(":is(a,b):not(.c):has(> img, + p)::after"),
+            ":is(a, b):not(.c):has(> img, + p)::after"
+        );
+        assert_eq!(round_trip_selector("p:before"), "p::before");
+    }
+
+    #[test]
+    fn serialize_identifiers_and_strings() {
+        let serialize = |value: &str| {
+            let mut result = String::new();
+            serialize_identifier(value, &mut result);
+            result
+        };
+        assert_eq!(serialize("1a"), "\\31 a");
+        assert_eq!(serialize("-2"), "-\\32 ");
+        assert_eq!(serialize("-"), "\\-");
+        assert_eq!(serialize("a.b"), "a\\.b");
+
+        let mut result = String::new();
+        serialize_string("say \"hi\"\\\n", &mut result);
+        assert_eq!(result, "\"say \\\"hi\\\"\\\\\\a \"");
+    }
+
+    #[test]
+    fn serialize_stylesheet_round_trip() {
+        let css = "h1 , .title{color:red;margin:0 auto!important}\
+                   a:hover{background:url(\"a.png\") rgba(0,0,0,.5);content:\"x\"}";
+        let stylesheet = parse_stylesheet(css);
+        let serialized = stylesheet.to_css_string();
+        assert_eq!(
+            serialized,
+            "h1, .title { color: red; margin: 0 auto !important; }\n\
+             a:hover { background: url(\"a.png\") rgba(0,0,0,0.5); content: \"x\"; }"
+        );
+        assert_eq!(parse_stylesheet(&serialized), stylesheet);
+    }
+}
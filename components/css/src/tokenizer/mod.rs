@@ -6,7 +6,7 @@ use std::env;
 use std::str::FromStr;
 use token::HashType;
 use token::NumberType;
-use token::Token;
+use token::{SourceLocation, Span, Token};
 
 fn is_trace() -> bool {
     match env::var("TRACE_CSS_TOKENIZER") {
@@ -163,6 +163,15 @@ where
 
     /// Output tokens
     output: Vec<Token>,
+
+    /// Source span of each output token
+    spans: Vec<Span>,
+
+    /// Location of the next character to consume
+    location: SourceLocation,
+
+    /// Location of the last consumed character, restored on reconsume
+    last_location: SourceLocation,
 }
 
 impl<T> Tokenizer<T>
@@ -174,20 +183,45 @@ where
             input: CharInputStream::new(input),
             current_character: '\0',
             output: Vec::new(),
+            spans: Vec::new(),
+            location: SourceLocation::new(1, 1),
+            last_location: SourceLocation::new(1, 1),
         }
     }
 
     /// Constantly running the tokenizer and produce a list of tokens
-    pub fn run(mut self) -> DataStream<Token> {
+    pub fn run(self) -> DataStream<Token> {
+        self.run_with_spans().0
+    }
+
+    /// Run the tokenizer and produce a list of tokens along with
+    /// the source span of each token
+    pub fn run_with_spans(mut self) -> (DataStream<Token>, Vec<Span>) {
         loop {
+            self.consume_comments();
+            let start = self.location;
             let token = self.consume_token();
             self.output.push(token.clone());
+            self.spans.push(Span {
+                start,
+                end: self.location,
+            });
 
-            match token {
-                Token::EOF => return DataStream::new(self.output),
-                _ => {}
+            if token == Token::EOF {
+                return (DataStream::new(self.output), self.spans);
+            }
+        }
+    }
+
+    /// Peek the next `n` characters, padding with whitespace
+    /// when there are fewer characters left in the input
+    fn peek_next_padded(&mut self, n: usize) -> String {
+        for len in (1..=n).rev() {
+            if let Some(chars) = self.input.peek_next_as::<String>(len) {
+                return format!("{:<width$}", chars, width = n);
             }
         }
+        " ".repeat(n)
     }
 
     fn consume_next(&mut self) -> Char {
@@ -196,6 +230,12 @@ where
         match ch {
             Some(c) => {
                 self.current_character = c;
+                self.last_location = self.location;
+                if c == '\n' {
+                    self.location = SourceLocation::new(self.location.line + 1, 1);
+                } else {
+                    self.location.column += 1;
+                }
                 Char::ch(c)
             }
             None => Char::eof,
@@ -213,6 +253,7 @@ where
 
     fn reconsume(&mut self) {
         self.input.reconsume();
+        self.location = self.last_location;
     }
 }
 
@@ -399,14 +440,12 @@ where
 
     fn consume_numeric(&mut self) -> Token {
         let (number, type_) = self.consume_number();
-        if let Some(next_3_chars) = self.input.peek_next_as::<String>(3) {
-            if is_start_identifier(&next_3_chars) {
-                return Token::Dimension {
-                    value: number,
-                    type_,
-                    unit: self.consume_name(),
-                };
-            }
+        if is_start_identifier(&self.peek_next_padded(3)) {
+            return Token::Dimension {
+                value: number,
+                type_,
+                unit: self.consume_name(),
+            };
         }
         if let Some('%') = self.input.peek() {
             self.consume_next();
@@ -477,26 +516,12 @@ where
         let string = self.consume_name();
         if string.eq_ignore_ascii_case("url") {
             if let Some('(') = self.input.peek() {
-                self.consume_next();
-                loop {
-                    if let Some(next_2_chars) = self.input.peek_next_as::<String>(2) {
-                        let mut chars = next_2_chars.chars();
-                        let first = chars.next().unwrap();
-                        let second = chars.next().unwrap();
-                        if is_whitespace(first) && is_whitespace(second) {
-                            self.consume_next();
-                        } else {
-                            break;
-                        }
-                    }
-                }
-                if let Some(next_2_chars) = self.input.peek_next_as::<String>(2) {
-                    let re = Regex::new("^ ?('|\")$").unwrap();
-                    if re.is_match(&next_2_chars) {
-                        return Token::Function(string);
-                    }
-                    return self.consume_url();
+                // a quoted url is a function with a string argument
+                if self.is_quoted_url() {
+                    return Token::Function(string);
                 }
+                self.consume_next();
+                return self.consume_url();
             }
         }
         if let Some('(') = self.input.peek() {
@@ -545,6 +570,19 @@ where
         }
     }
 
+    /// Check if the next non-whitespace code point after `(` is a quote
+    fn is_quoted_url(&mut self) -> bool {
+        let mut len = 2;
+        while let Some(next_chars) = self.input.peek_next_as::<String>(len) {
+            match next_chars.chars().last() {
+                Some(c) if is_whitespace(c) => len += 1,
+                Some(c) => return c == '"' || c == '\'',
+                None => return false,
+            }
+        }
+        false
+    }
+
     fn consume_url(&mut self) -> Token {
         let mut token = Token::Url(String::new());
         self.consume_while(is_whitespace);
@@ -786,4 +824,62 @@ mod tests {
         assert_eq!(tokenizer.consume_token(), Token::BraceClose);
         assert_eq!(tokenizer.consume_token(), Token::EOF);
     }
+
+    #[test]
+    fn tokenize_dimension_at_end_of_input() {
+        let mut tokenizer = Tokenizer::new("2n".chars());
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::Dimension {
+                value: 2.0,
+                type_: NumberType::Integer,
+                unit: "n".to_string()
+            }
+        );
+        assert_eq!(tokenizer.consume_token(), Token::EOF);
+    }
+
+    #[test]
+    fn tokenize_quoted_url() {
+        let mut tokenizer = Tokenizer::new("url( 'a.png')".chars());
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::Function("url".to_string())
+        );
+        assert_eq!(tokenizer.consume_token(), Token::ParentheseOpen);
+        assert_eq!(tokenizer.consume_token(), Token::Whitespace);
+        assert_eq!(tokenizer.consume_token(), Token::Str("a.png".to_string()));
+        assert_eq!(tokenizer.consume_token(), Token::ParentheseClose);
+    }
+
+    #[test]
+    fn tokenize_with_spans() {
+        let css = "a {\n  /* comment */ color: red;\n}".chars();
+        let tokenizer = Tokenizer::new(css);
+        let (tokens, spans) = tokenizer.run_with_spans();
+        let spans = tokens
+            .peek_next(usize::MAX)
+            .into_iter()
+            .zip(spans)
+            .filter(|(token, _)| !matches!(token, Token::Whitespace))
+            .map(|(_, span)| (span.start.to_string(), span.end.to_string()))
+            .collect::<Vec<(String, String)>>();
+        let expected = [
+            ("1:1", "1:2"),
+            ("1:3", "1:4"),
+            ("2:17", "2:22"),
+            ("2:22", "2:23"),
+            ("2:24", "2:27"),
+            ("2:27", "2:28"),
+            ("3:1", "3:2"),
+            ("3:2", "3:2"),
+        ];
+        assert_eq!(
+            spans,
+            expected
+                .iter()
+                .map(|(start, end)| (start.to_string(), end.to_string()))
+                .collect::<Vec<(String, String)>>()
+        );
+    }
 }
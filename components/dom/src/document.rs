@@ -1,7 +1,8 @@
-use super::node::NodeHooks;
+use super::node::{Node, NodeHooks};
+use super::style_sheet_list::{StyleSheetEntry, StyleSheetList};
 use css::cssom::stylesheet::StyleSheet;
 use document_loader::DocumentLoader;
-use std::cell::RefCell;
+use std::cell::{Cell, Ref, RefCell};
 use std::ops::Deref;
 use std::rc::Rc;
 use url::Url;
@@ -10,7 +11,8 @@ pub struct Document {
     doctype: RefCell<Option<DocumentType>>,
     mode: RefCell<QuirksMode>,
     loader: RefCell<Option<Rc<RefCell<dyn DocumentLoader>>>>,
-    stylesheets: RefCell<Vec<Rc<StyleSheet>>>,
+    stylesheets: RefCell<StyleSheetList>,
+    style_dirty: Cell<bool>,
     base: RefCell<Option<Url>>,
 }
 
@@ -41,7 +43,8 @@ impl Document {
             doctype: RefCell::new(None),
             mode: RefCell::new(QuirksMode::NoQuirks),
             loader: RefCell::new(None),
-            stylesheets: RefCell::new(Vec::new()),
+            stylesheets: RefCell::new(StyleSheetList::new()),
+            style_dirty: Cell::new(false),
             base: RefCell::new(None),
         }
     }
@@ -66,12 +69,75 @@ impl Document {
         *self.loader.borrow_mut() = Some(Rc::new(RefCell::new(loader)));
     }
 
-    pub fn append_stylesheet(&self, stylesheet: StyleSheet) {
-        self.stylesheets.borrow_mut().push(Rc::new(stylesheet));
+    /// Add a stylesheet at its position in the stylesheet list
+    pub fn add_stylesheet(&self, entry: StyleSheetEntry) -> Rc<RefCell<StyleSheet>> {
+        let stylesheet = self.stylesheets.borrow_mut().add(entry);
+        self.mark_style_dirty();
+        stylesheet
     }
 
-    pub fn stylesheets(&self) -> Vec<Rc<StyleSheet>> {
-        self.stylesheets.borrow().deref().to_vec()
+    /// Remove the stylesheets of an owner node that left the document
+    pub fn remove_stylesheets_owned_by(&self, node: &Node) {
+        if self.stylesheets.borrow_mut().remove_owned_by(node) {
+            self.mark_style_dirty();
+        }
+    }
+
+    /// All the stylesheets, including disabled ones, in order
+    pub fn stylesheets(&self) -> Vec<Rc<RefCell<StyleSheet>>> {
+        self.stylesheets
+            .borrow()
+            .iter()
+            .map(|entry| entry.sheet())
+            .collect()
+    }
+
+    /// The stylesheets taking part in the cascade, in order
+    pub fn enabled_stylesheets(&self) -> Vec<StyleSheetEntry> {
+        self.stylesheets.borrow().enabled()
+    }
+
+    pub fn style_sheet_list(&self) -> Ref<'_, StyleSheetList> {
+        self.stylesheets.borrow()
+    }
+
+    /// Returns `false` if there is no stylesheet at the index.
+    pub fn set_stylesheet_disabled(&self, index: usize, disabled: bool) -> bool {
+        let updated = self.stylesheets.borrow_mut().set_disabled(index, disabled);
+        if updated {
+            self.mark_style_dirty();
+        }
+        updated
+    }
+
+    pub fn select_style_sheet_set(&self, name: &str) {
+        self.stylesheets.borrow_mut().select_style_sheet_set(name);
+        self.mark_style_dirty();
+    }
+
+    /// Mutate a stylesheet through the CSSOM and mark the styles dirty.
+    /// Returns `None` if there is no stylesheet at the index.
+    pub fn update_stylesheet<F, R>(&self, index: usize, update: F) -> Option<R>
+    where
+        F: FnOnce(&mut StyleSheet) -> R,
+    {
+        let stylesheet = self.stylesheets.borrow().get(index)?.sheet();
+        let result = update(&mut stylesheet.borrow_mut());
+        self.mark_style_dirty();
+        Some(result)
+    }
+
+    /// Whether the styles need to be recalculated
+    pub fn is_style_dirty(&self) -> bool {
+        self.style_dirty.get()
+    }
+
+    pub fn mark_style_dirty(&self) {
+        self.style_dirty.set(true);
+    }
+
+    pub fn clear_style_dirty(&self) {
+        self.style_dirty.set(false);
     }
 
     pub fn base(&self) -> Option<Url> {
@@ -98,3 +164,31 @@ impl DocumentType {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::style_sheet_list::StyleSheetOwner;
+
+    #[test]
+    fn cssom_mutation_marks_style_dirty() {
+        let document = Document::new();
+        assert!(!document.is_style_dirty());
+
+        document.add_stylesheet(StyleSheetEntry::new(
+            StyleSheet::new(),
+            StyleSheetOwner::UserAgent,
+        ));
+        assert!(document.is_style_dirty());
+        document.clear_style_dirty();
+
+        let inserted = document.update_stylesheet(0, |stylesheet| {
+            stylesheet.insert_rule("p { color: red }", 0)
+        });
+        assert_eq!(inserted, Some(Ok(0)));
+        assert!(document.is_style_dirty());
+        assert_eq!(document.stylesheets()[0].borrow().len(), 1);
+
+        assert!(document.update_stylesheet(1, |_| ()).is_none());
+    }
+}
@@ -20,7 +20,7 @@ impl DOMTokenList {
     }
 
     pub fn contains(&self, token: &str) -> bool {
-        self.items.contains(&token.to_owned())
+        self.items.iter().any(|item| item == token)
     }
 
     pub fn add(&mut self, tokens: Vec<String>) {
@@ -32,6 +32,10 @@ impl DOMTokenList {
         self.items.retain(|item| !tokens.contains(item));
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.items.iter()
+    }
+
     pub fn value(&self) -> String {
         self.items.join(" ")
     }
@@ -1,9 +1,11 @@
 use crate::node::Node;
 
 use super::dom_token_list::DOMTokenList;
+use super::element_state::ElementState;
 use super::elements::{ElementData, ElementMethods};
 use super::node::NodeHooks;
-use std::cell::RefCell;
+use css::cssom::css_style_declaration::CSSStyleDeclaration;
+use std::cell::{Cell, Ref, RefCell};
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 use std::rc::Rc;
@@ -15,6 +17,9 @@ pub struct Element {
     attributes: RefCell<AttributeMap>,
     id: RefCell<Option<String>>,
     class_list: RefCell<DOMTokenList>,
+    state: Cell<ElementState>,
+    /// The declarations of the `style` attribute
+    style: RefCell<CSSStyleDeclaration>,
     data: ElementData,
 }
 
@@ -60,8 +65,16 @@ impl core::fmt::Debug for Element {
 }
 
 impl NodeHooks for Element {
-    fn on_inserted(&self, document: Rc<Node>) {
-        self.handle_on_inserted(document);
+    fn on_inserted(&self, node: Rc<Node>, document: Rc<Node>) {
+        self.data.handle_on_inserted(node, document);
+    }
+
+    fn on_removed(&self, node: Rc<Node>, document: Rc<Node>) {
+        self.data.handle_on_removed(node, document);
+    }
+
+    fn on_children_changed(&self, node: Rc<Node>) {
+        self.data.handle_on_children_changed(node);
     }
 }
 
@@ -71,6 +84,8 @@ impl Element {
             attributes: RefCell::new(AttributeMap::new()),
             id: RefCell::new(None),
             class_list: RefCell::new(DOMTokenList::new()),
+            state: Cell::new(ElementState::empty()),
+            style: RefCell::new(CSSStyleDeclaration::default()),
             data,
         }
     }
@@ -79,7 +94,8 @@ impl Element {
         self.data.tag_name()
     }
 
-    pub fn set_attribute(&self, name: &str, value: &str) {
+    /// Set an attribute without recording the change, see `Node::set_attribute`
+    pub(crate) fn set_attribute(&self, name: &str, value: &str) {
         if name == "id" {
             *self.id.borrow_mut() = Some(value.to_string());
             return;
@@ -88,12 +104,71 @@ impl Element {
             *self.class_list.borrow_mut() = DOMTokenList::from(value);
             return;
         }
+        if name == "style" {
+            self.style.borrow_mut().set_css_text(value);
+        }
         self.attributes
             .borrow_mut()
             .insert(name.to_owned(), value.to_owned());
+        self.update_state_from_attribute(name);
         self.data.handle_attribute_change(name, value);
     }
 
+    pub(crate) fn remove_attribute(&self, name: &str) {
+        match name {
+            "id" => *self.id.borrow_mut() = None,
+            "class" => *self.class_list.borrow_mut() = DOMTokenList::new(),
+            "style" => {
+                self.attributes.borrow_mut().remove(name);
+                *self.style.borrow_mut() = CSSStyleDeclaration::default();
+            }
+            _ => {
+                self.attributes.borrow_mut().remove(name);
+                self.update_state_from_attribute(name);
+            }
+        }
+    }
+
+    /// Get the inline style declared by the `style` attribute
+    /// https://www.w3.org/TR/cssom-1/#dom-elementcssinlinestyle-style
+    pub fn style(&self) -> Ref<'_, CSSStyleDeclaration> {
+        self.style.borrow()
+    }
+
+    /// Get the dynamic state of the element
+    pub fn state(&self) -> ElementState {
+        self.state.get()
+    }
+
+    /// Add or remove states of the element, see `Node::set_state`
+    pub(crate) fn set_state(&self, state: ElementState, value: bool) {
+        let mut current = self.state.get();
+        if value {
+            current.insert(state);
+        } else {
+            current.remove(state);
+        }
+        self.state.set(current);
+    }
+
+    /// Reflect the `checked` and `disabled` content attributes
+    /// of form controls in the element state
+    fn update_state_from_attribute(&self, name: &str) {
+        let tag_name = self.tag_name();
+        let present = self.has_attribute(name);
+        match name {
+            "checked" if tag_name == "input" => self.set_state(ElementState::CHECKED, present),
+            "selected" if tag_name == "option" => self.set_state(ElementState::CHECKED, present),
+            "disabled" => match tag_name.as_str() {
+                "button" | "input" | "select" | "textarea" | "optgroup" | "option" | "fieldset" => {
+                    self.set_state(ElementState::DISABLED, present)
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
     pub fn attributes(&self) -> RefCell<AttributeMap> {
         self.attributes.clone()
     }
@@ -102,15 +177,33 @@ impl Element {
         self.attributes.borrow().contains_key(name)
     }
 
+    /// Get the value of an attribute, including `id` and `class`
+    /// which are stored separately from other attributes
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        match name {
+            "id" => self.id(),
+            "class" => {
+                let class_list = self.class_list.borrow();
+                if class_list.length() > 0 {
+                    Some(class_list.value())
+                } else {
+                    None
+                }
+            }
+            _ => self.attributes.borrow().get(name).cloned(),
+        }
+    }
+
     pub fn class_list(&self) -> RefCell<DOMTokenList> {
         self.class_list.clone()
     }
 
-    pub fn id(&self) -> Option<String> {
-        self.id.borrow().clone()
+    /// Check for a class without cloning the class list
+    pub fn has_class(&self, class: &str) -> bool {
+        self.class_list.borrow().contains(class)
     }
 
-    pub fn handle_on_inserted(&self, document: Rc<Node>) {
-        self.data.handle_on_inserted(document);
+    pub fn id(&self) -> Option<String> {
+        self.id.borrow().clone()
     }
 }
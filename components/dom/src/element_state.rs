@@ -0,0 +1,71 @@
+use std::ops::{BitOr, BitOrAssign};
+
+/// Dynamic state of an element used by the user action and input
+/// pseudo-classes
+/// https://www.w3.org/TR/selectors-4/#useraction-pseudos
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ElementState(u16);
+
+impl ElementState {
+    pub const HOVER: Self = Self(1 << 0);
+    pub const ACTIVE: Self = Self(1 << 1);
+    pub const FOCUS: Self = Self(1 << 2);
+    pub const FOCUS_WITHIN: Self = Self(1 << 3);
+    pub const FOCUS_VISIBLE: Self = Self(1 << 4);
+    pub const CHECKED: Self = Self(1 << 5);
+    pub const DISABLED: Self = Self(1 << 6);
+    pub const VISITED: Self = Self(1 << 7);
+
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Get the state of a pseudo-class name, e.g. `hover` for `:hover`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "hover" => Some(Self::HOVER),
+            "active" => Some(Self::ACTIVE),
+            "focus" => Some(Self::FOCUS),
+            "focus-within" => Some(Self::FOCUS_WITHIN),
+            "focus-visible" => Some(Self::FOCUS_VISIBLE),
+            "checked" => Some(Self::CHECKED),
+            "disabled" => Some(Self::DISABLED),
+            "visited" => Some(Self::VISITED),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn intersects(&self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for ElementState {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ElementState {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.insert(rhs);
+    }
+}
@@ -32,7 +32,7 @@ impl ElementHooks for HTMLAnchorElement {
 }
 
 impl NodeHooks for HTMLAnchorElement {
-    fn on_inserted(&self, document: Rc<Node>) {
+    fn on_inserted(&self, _node: Rc<Node>, document: Rc<Node>) {
         let base = document.as_document().base();
         *self.href.borrow_mut() = URLParser::parse(&self._raw_href.borrow(), base);
     }
@@ -5,6 +5,7 @@ use super::ElementHooks;
 use super::ElementMethods;
 use crate::node::Node;
 use crate::node::NodeHooks;
+use crate::style_sheet_list::{StyleSheetEntry, StyleSheetLocation, StyleSheetOwner};
 use document_loader::LoadRequest;
 use url::Url;
 
@@ -16,12 +17,15 @@ use url::parser::URLParser;
 pub struct HTMLLinkElement {
     href: RefCell<Option<Url>>,
     relationship: RefCell<Option<HTMLLinkRelationship>>,
+    title: RefCell<String>,
     _raw_href: RefCell<String>,
 }
 
 #[derive(Debug)]
 pub enum HTMLLinkRelationship {
     Stylesheet,
+    /// `rel="alternate stylesheet"`, only enabled when its set is selected
+    AlternateStylesheet,
 }
 
 impl HTMLLinkElement {
@@ -29,13 +33,21 @@ impl HTMLLinkElement {
         Self {
             href: RefCell::new(None),
             relationship: RefCell::new(None),
+            title: RefCell::new(String::new()),
             _raw_href: RefCell::new(String::new()),
         }
     }
 
-    pub fn load_stylesheet(&self, url: &Url, document: Rc<Node>) {
+    pub fn load_stylesheet(&self, url: &Url, node: &Rc<Node>, document: Rc<Node>) {
         let cloned_doc = document.clone();
         let cloned_url = url.clone();
+        let stylesheet_url = url.clone();
+        let owner = Rc::downgrade(node);
+        let title = self.title.borrow().clone();
+        let alternate = matches!(
+            *self.relationship.borrow(),
+            Some(HTMLLinkRelationship::AlternateStylesheet)
+        );
 
         log::info!("Loading stylesheet from: {}", url);
 
@@ -43,10 +55,23 @@ impl HTMLLinkElement {
             .on_success(move |bytes| {
                 let css = String::from_utf8(bytes).unwrap();
                 let tokenizer = Tokenizer::new(css.chars());
-                let mut parser = Parser::<Token>::new(tokenizer.run());
+                let (tokens, spans) = tokenizer.run_with_spans();
+                let mut parser = Parser::<Token>::with_spans(tokens, spans);
                 let stylesheet = parser.parse_a_css_stylesheet();
+                for diagnostic in stylesheet.diagnostics() {
+                    log::debug!("{}:{}", stylesheet_url, diagnostic);
+                }
 
-                cloned_doc.as_document().append_stylesheet(stylesheet);
+                // the link might have been removed while the sheet was loading
+                match owner.upgrade() {
+                    Some(node) if node.is_connected() => {
+                        let entry = StyleSheetEntry::new(stylesheet, StyleSheetOwner::node(&node))
+                            .with_title(&title, alternate)
+                            .with_location(StyleSheetLocation::External);
+                        cloned_doc.as_document().add_stylesheet(entry);
+                    }
+                    _ => log::info!("Link removed before loading, ignoring {}", stylesheet_url),
+                }
             })
             .on_error(move |e| log::error!("Unable to load CSS: {} ({})", e, cloned_url));
 
@@ -65,9 +90,19 @@ impl ElementHooks for HTMLLinkElement {
                 *self._raw_href.borrow_mut() = value.to_string();
             }
             "rel" => {
-                if value == "stylesheet" {
-                    *self.relationship.borrow_mut() = Some(HTMLLinkRelationship::Stylesheet);
-                }
+                let keywords = value
+                    .split_ascii_whitespace()
+                    .map(|keyword| keyword.to_ascii_lowercase())
+                    .collect::<Vec<String>>();
+                let has = |keyword: &str| keywords.iter().any(|k| k == keyword);
+                *self.relationship.borrow_mut() = match (has("stylesheet"), has("alternate")) {
+                    (true, false) => Some(HTMLLinkRelationship::Stylesheet),
+                    (true, true) => Some(HTMLLinkRelationship::AlternateStylesheet),
+                    _ => None,
+                };
+            }
+            "title" => {
+                *self.title.borrow_mut() = value.to_string();
             }
             _ => {}
         }
@@ -75,17 +110,32 @@ impl ElementHooks for HTMLLinkElement {
 }
 
 impl NodeHooks for HTMLLinkElement {
-    fn on_inserted(&self, document: Rc<Node>) {
+    fn on_inserted(&self, node: Rc<Node>, document: Rc<Node>) {
+        if !node.is_connected() {
+            return;
+        }
         let href_url = &*self._raw_href.borrow();
         *self.href.borrow_mut() = URLParser::parse(href_url, document.as_document().base());
         match &*self.href.borrow() {
             Some(url) => match *self.relationship.borrow() {
-                Some(HTMLLinkRelationship::Stylesheet) => self.load_stylesheet(url, document),
+                Some(HTMLLinkRelationship::Stylesheet) => {
+                    self.load_stylesheet(url, &node, document)
+                }
+                // an alternate stylesheet without a title is ignored
+                Some(HTMLLinkRelationship::AlternateStylesheet)
+                    if !self.title.borrow().is_empty() =>
+                {
+                    self.load_stylesheet(url, &node, document)
+                }
                 _ => {}
             },
             None => log::info!("Empty or invalid URL, ignoring"),
         }
     }
+
+    fn on_removed(&self, node: Rc<Node>, document: Rc<Node>) {
+        document.as_document().remove_stylesheets_owned_by(&node);
+    }
 }
 
 impl ElementMethods for HTMLLinkElement {
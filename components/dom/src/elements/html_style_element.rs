@@ -0,0 +1,202 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use super::ElementHooks;
+use super::ElementMethods;
+use crate::node::Node;
+use crate::node::NodeHooks;
+use crate::style_sheet_list::{StyleSheetEntry, StyleSheetOwner};
+
+use css::cssom::stylesheet::StyleSheet;
+use css::media_queries::MediaQueryList;
+use css::parser::Parser;
+use css::tokenizer::{token::Token, Tokenizer};
+
+#[derive(Debug)]
+pub struct HTMLStyleElement {
+    /// The child text content the stylesheet was parsed from
+    text: RefCell<String>,
+    media: RefCell<MediaQueryList>,
+    title: RefCell<String>,
+    sheet: RefCell<Option<Rc<RefCell<StyleSheet>>>>,
+    /// The document the stylesheet was added to
+    document: RefCell<Weak<Node>>,
+}
+
+impl HTMLStyleElement {
+    pub fn empty() -> Self {
+        Self {
+            text: RefCell::new(String::new()),
+            media: RefCell::new(MediaQueryList::all()),
+            title: RefCell::new(String::new()),
+            sheet: RefCell::new(None),
+            document: RefCell::new(Weak::new()),
+        }
+    }
+
+    /// The stylesheet associated with the element
+    pub fn sheet(&self) -> Option<Rc<RefCell<StyleSheet>>> {
+        self.sheet.borrow().clone()
+    }
+
+    /// https://html.spec.whatwg.org/multipage/semantics.html#update-a-style-block
+    fn update_style_block(&self, node: &Rc<Node>, document: Rc<Node>) {
+        let text = self.text.borrow().clone();
+        let tokenizer = Tokenizer::new(text.chars());
+        let (tokens, spans) = tokenizer.run_with_spans();
+        let mut parser = Parser::<Token>::with_spans(tokens, spans);
+        let mut stylesheet = parser.parse_a_css_stylesheet();
+        stylesheet.media = self.media.borrow().clone();
+        for diagnostic in stylesheet.diagnostics() {
+            log::debug!("<style>:{}", diagnostic);
+        }
+
+        // the sheet is replaced in place to keep its position in the document
+        let existing = self.sheet();
+        match existing {
+            Some(sheet) => {
+                *sheet.borrow_mut() = stylesheet;
+                document.as_document().mark_style_dirty();
+            }
+            None => {
+                let entry = StyleSheetEntry::new(stylesheet, StyleSheetOwner::node(node))
+                    .with_title(&self.title.borrow(), false);
+                let sheet = document.as_document().add_stylesheet(entry);
+                *self.sheet.borrow_mut() = Some(sheet);
+                *self.document.borrow_mut() = Rc::downgrade(&document);
+            }
+        }
+    }
+}
+
+impl ElementHooks for HTMLStyleElement {
+    fn on_attribute_change(&self, attr: &str, value: &str) {
+        match attr {
+            "media" => {
+                let media = MediaQueryList::parse_str(value);
+                if let Some(sheet) = self.sheet() {
+                    sheet.borrow_mut().media = media.clone();
+                    if let Some(document) = self.document.borrow().upgrade() {
+                        document.as_document().mark_style_dirty();
+                    }
+                }
+                *self.media.borrow_mut() = media;
+            }
+            "title" => *self.title.borrow_mut() = value.to_string(),
+            _ => {}
+        }
+    }
+}
+
+impl NodeHooks for HTMLStyleElement {
+    fn on_inserted(&self, node: Rc<Node>, document: Rc<Node>) {
+        if node.is_connected() {
+            self.update_style_block(&node, document);
+        }
+    }
+
+    fn on_removed(&self, node: Rc<Node>, document: Rc<Node>) {
+        *self.sheet.borrow_mut() = None;
+        *self.document.borrow_mut() = Weak::new();
+        document.as_document().remove_stylesheets_owned_by(&node);
+    }
+
+    fn on_children_changed(&self, node: Rc<Node>) {
+        *self.text.borrow_mut() = node.child_text_content();
+        if !node.is_connected() {
+            return;
+        }
+        if let Some(document) = node.owner_document() {
+            self.update_style_block(&node, document);
+        }
+    }
+}
+
+impl ElementMethods for HTMLStyleElement {
+    fn tag_name(&self) -> String {
+        "style".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_element;
+    use crate::document::Document;
+    use crate::node::NodeData;
+    use crate::text::Text;
+
+    #[test]
+    fn parse_style_block() {
+        let document = Rc::new(Node::new(NodeData::Document(Document::new())));
+        let style = create_element(Rc::downgrade(&document), "style");
+        style.set_attribute("media", "print");
+
+        let text = Rc::new(Node::new(NodeData::Text(Text::new(
+            "p { color: red }".to_string(),
+        ))));
+        text.set_document(Rc::downgrade(&document));
+        Node::append_child(style.clone(), text.clone());
+        assert!(document.as_document().stylesheets().is_empty());
+
+        Node::append_child(document.clone(), style.clone());
+        let stylesheets = document.as_document().stylesheets();
+        assert_eq!(stylesheets.len(), 1);
+        assert_eq!(stylesheets[0].borrow().len(), 1);
+        assert_eq!(
+            stylesheets[0].borrow().media,
+            MediaQueryList::parse_str("print")
+        );
+
+        // children changes re-parse into the same stylesheet
+        text.as_text()
+            .character_data
+            .append_data(" a { color: blue }");
+        Node::children_changed(style.clone());
+        let stylesheets = document.as_document().stylesheets();
+        assert_eq!(stylesheets.len(), 1);
+        assert_eq!(stylesheets[0].borrow().len(), 2);
+
+        // changing the media of the sheet restyles the document
+        document.as_document().clear_style_dirty();
+        style.set_attribute("media", "screen");
+        assert_eq!(
+            stylesheets[0].borrow().media,
+            MediaQueryList::parse_str("screen")
+        );
+        assert!(document.as_document().is_style_dirty());
+
+        Node::detach(text);
+        assert!(document.as_document().stylesheets()[0].borrow().is_empty());
+    }
+
+    #[test]
+    fn style_blocks_in_tree_order() {
+        let document = Rc::new(Node::new(NodeData::Document(Document::new())));
+        let head = create_element(Rc::downgrade(&document), "head");
+        Node::append_child(document.clone(), head.clone());
+
+        let style = |css: &str| {
+            let style = create_element(Rc::downgrade(&document), "style");
+            let text = Rc::new(Node::new(NodeData::Text(Text::new(css.to_string()))));
+            text.set_document(Rc::downgrade(&document));
+            Node::append_child(style.clone(), text);
+            style
+        };
+        let last = style("a { color: red }");
+        let first = style("a { color: blue } p { color: blue }");
+        Node::append_child(head.clone(), last.clone());
+        Node::insert_before(head.clone(), first.clone(), Some(last.clone()));
+
+        let stylesheets = document.as_document().stylesheets();
+        assert_eq!(stylesheets.len(), 2);
+        assert_eq!(stylesheets[0].borrow().len(), 2);
+        assert_eq!(stylesheets[1].borrow().len(), 1);
+
+        // detaching an ancestor removes the sheets of the subtree
+        document.as_document().clear_style_dirty();
+        Node::detach(head);
+        assert!(document.as_document().stylesheets().is_empty());
+        assert!(document.as_document().is_style_dirty());
+    }
+}
@@ -11,6 +11,7 @@ mod html_div_element;
 mod html_head_element;
 mod html_html_element;
 mod html_link_element;
+mod html_style_element;
 mod html_title_element;
 mod html_unknown_element;
 
@@ -20,6 +21,7 @@ pub use html_div_element::*;
 pub use html_head_element::*;
 pub use html_html_element::*;
 pub use html_link_element::*;
+pub use html_style_element::*;
 pub use html_title_element::*;
 pub use html_unknown_element::*;
 
@@ -34,6 +36,7 @@ pub enum ElementData {
     Title(HTMLTitleElement),
     Unknown(HTMLUnknownElement),
     Link(HTMLLinkElement),
+    Style(HTMLStyleElement),
 }
 
 #[enum_dispatch]
@@ -54,7 +57,15 @@ impl ElementData {
         self.on_attribute_change(attr, value);
     }
 
-    pub fn handle_on_inserted(&self, document: Rc<Node>) {
-        self.on_inserted(document);
+    pub fn handle_on_inserted(&self, node: Rc<Node>, document: Rc<Node>) {
+        self.on_inserted(node, document);
+    }
+
+    pub fn handle_on_removed(&self, node: Rc<Node>, document: Rc<Node>) {
+        self.on_removed(node, document);
+    }
+
+    pub fn handle_on_children_changed(&self, node: Rc<Node>) {
+        self.on_children_changed(node);
     }
 }
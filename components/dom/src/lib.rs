@@ -6,7 +6,10 @@ pub mod character_data;
 pub mod comment;
 pub mod document;
 pub mod element;
+pub mod element_state;
 pub mod node;
+pub mod restyle;
+pub mod style_sheet_list;
 pub mod text;
 
 pub mod conversion;
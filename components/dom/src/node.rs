@@ -1,11 +1,15 @@
 use super::comment::Comment;
 use super::document::Document;
+use super::dom_token_list::DOMTokenList;
 use super::element::Element;
+use super::element_state::ElementState;
 use super::elements::ElementData;
 use super::node_list::NodeList;
+use super::restyle::{DirtyFlags, StyleChange};
 use super::text::Text;
 use enum_dispatch::enum_dispatch;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
 use std::ops::Deref;
 use std::rc::{Rc, Weak};
 
@@ -20,6 +24,8 @@ pub struct Node {
     prev_sibling: RefCell<NullableWeakNode>,
     owner_document: RefCell<NullableWeakNode>,
     data: Option<NodeData>,
+    dirty: Cell<DirtyFlags>,
+    style_changes: RefCell<Vec<StyleChange>>,
 }
 
 #[enum_dispatch(NodeHooks)]
@@ -33,7 +39,15 @@ pub enum NodeData {
 #[enum_dispatch]
 pub trait NodeHooks {
     #[allow(unused_variables)]
-    fn on_inserted(&self, document: Rc<Node>) {}
+    fn on_inserted(&self, node: Rc<Node>, document: Rc<Node>) {}
+
+    /// Called when the node or one of its ancestors is detached
+    #[allow(unused_variables)]
+    fn on_removed(&self, node: Rc<Node>, document: Rc<Node>) {}
+
+    /// Called when a child of the node is inserted or removed
+    #[allow(unused_variables)]
+    fn on_children_changed(&self, node: Rc<Node>) {}
 }
 
 impl core::fmt::Debug for Node {
@@ -47,8 +61,16 @@ impl core::fmt::Debug for Node {
 }
 
 impl NodeData {
-    pub fn handle_on_inserted(&self, document: Rc<Node>) {
-        self.on_inserted(document);
+    pub fn handle_on_inserted(&self, node: Rc<Node>, document: Rc<Node>) {
+        self.on_inserted(node, document);
+    }
+
+    pub fn handle_on_removed(&self, node: Rc<Node>, document: Rc<Node>) {
+        self.on_removed(node, document);
+    }
+
+    pub fn handle_on_children_changed(&self, node: Rc<Node>) {
+        self.on_children_changed(node);
     }
 }
 
@@ -79,6 +101,8 @@ impl Node {
             prev_sibling: RefCell::new(None),
             owner_document: RefCell::new(None),
             data: None,
+            dirty: Cell::new(DirtyFlags::empty()),
+            style_changes: RefCell::new(Vec::new()),
         }
     }
 
@@ -131,6 +155,161 @@ impl Node {
         }
     }
 
+    /// Whether the node is in a document tree
+    /// https://dom.spec.whatwg.org/#connected
+    pub fn is_connected(&self) -> bool {
+        match self.parent() {
+            Some(parent) => parent.is_document() || parent.is_connected(),
+            None => self.is_document(),
+        }
+    }
+
+    /// Compare the position of two nodes in tree order
+    /// https://dom.spec.whatwg.org/#concept-tree-order
+    pub fn cmp_tree_order(a: &Node, b: &Node) -> Ordering {
+        a.tree_position().cmp(&b.tree_position())
+    }
+
+    /// Index of each inclusive ancestor among its siblings, from the root
+    fn tree_position(&self) -> Vec<usize> {
+        let mut position = match self.parent() {
+            Some(parent) => parent.tree_position(),
+            None => Vec::new(),
+        };
+        let mut index = 0;
+        let mut sibling = self.prev_sibling();
+        while let Some(node) = sibling {
+            index += 1;
+            sibling = node.prev_sibling();
+        }
+        position.push(index);
+        position
+    }
+
+    pub fn dirty_flags(&self) -> DirtyFlags {
+        self.dirty.get()
+    }
+
+    /// Flag the node and tell its ancestors that a descendant is dirty
+    pub fn mark_dirty(&self, flags: DirtyFlags) {
+        let mut dirty = self.dirty.get();
+        dirty.insert(flags);
+        self.dirty.set(dirty);
+        let mut ancestor = self.parent();
+        while let Some(node) = ancestor {
+            let mut dirty = node.dirty.get();
+            dirty.insert(DirtyFlags::DESCENDANTS);
+            node.dirty.set(dirty);
+            ancestor = node.parent();
+        }
+    }
+
+    /// Clear the flags and the pending changes of the node once it's restyled
+    pub fn clear_dirty(&self) {
+        self.dirty.set(DirtyFlags::empty());
+        self.style_changes.borrow_mut().clear();
+    }
+
+    /// Record a change for the style invalidation
+    pub fn add_style_change(&self, change: StyleChange) {
+        self.style_changes.borrow_mut().push(change);
+        self.mark_dirty(DirtyFlags::CHANGED);
+    }
+
+    pub fn take_style_changes(&self) -> Vec<StyleChange> {
+        self.style_changes.take()
+    }
+
+    /// Set an attribute of an element node, recording the classes,
+    /// IDs or attribute changed for the style invalidation
+    pub fn set_attribute(&self, name: &str, value: &str) {
+        let element = self.as_element();
+        match name {
+            "class" => {
+                let old = element.class_list().borrow().clone();
+                let new = DOMTokenList::from(value);
+                for class in old.iter().filter(|class| !new.contains(class)) {
+                    self.add_style_change(StyleChange::Class(class.clone()));
+                }
+                for class in new.iter().filter(|class| !old.contains(class)) {
+                    self.add_style_change(StyleChange::Class(class.clone()));
+                }
+            }
+            "id" => {
+                let old = element.id();
+                if old.as_deref() != Some(value) {
+                    for id in old.into_iter().chain(Some(value.to_string())) {
+                        self.add_style_change(StyleChange::ID(id));
+                    }
+                }
+            }
+            _ => {
+                if element.get_attribute(name).as_deref() != Some(value) {
+                    self.add_style_change(StyleChange::Attribute(name.to_string()));
+                }
+            }
+        }
+        let state = element.state();
+        element.set_attribute(name, value);
+        if element.state() != state {
+            self.add_style_change(StyleChange::State(state | element.state()));
+        }
+    }
+
+    /// Remove an attribute of an element node, recording the change
+    /// for the style invalidation like `set_attribute`
+    pub fn remove_attribute(&self, name: &str) {
+        let element = self.as_element();
+        match name {
+            "class" => {
+                for class in element.class_list().borrow().iter() {
+                    self.add_style_change(StyleChange::Class(class.clone()));
+                }
+            }
+            "id" => {
+                if let Some(id) = element.id() {
+                    self.add_style_change(StyleChange::ID(id));
+                }
+            }
+            _ => {
+                if element.has_attribute(name) {
+                    self.add_style_change(StyleChange::Attribute(name.to_string()));
+                }
+            }
+        }
+        let state = element.state();
+        element.remove_attribute(name);
+        if element.state() != state {
+            self.add_style_change(StyleChange::State(state | element.state()));
+        }
+    }
+
+    /// Add or remove dynamic states of an element node,
+    /// e.g. when the pointer moves over it
+    pub fn set_state(&self, state: ElementState, value: bool) {
+        let element = self.as_element();
+        let old = element.state();
+        element.set_state(state, value);
+        if element.state() != old {
+            self.add_style_change(StyleChange::State(state));
+        }
+    }
+
+    /// Replace the data of a text node
+    pub fn set_text_data(&self, data: &str) {
+        self.as_text().set_data(data);
+        self.mark_dirty(DirtyFlags::TEXT);
+        if let Some(parent) = self.parent() {
+            parent.add_style_change(StyleChange::ChildList);
+        }
+    }
+
+    /// Mark a node whose children were inserted or removed
+    fn child_list_changed(&self) {
+        self.add_style_change(StyleChange::ChildList);
+        self.mark_dirty(DirtyFlags::CHILDREN);
+    }
+
     /// Descendant text content of the node
     /// https://dom.spec.whatwg.org/#concept-descendant-text-content
     pub fn descendant_text_content(&self) -> String {
@@ -166,13 +345,15 @@ impl Node {
                 .prev_sibling
                 .replace(node.prev_sibling.borrow().clone());
         }
-        if let Some(parent) = node.parent() {
+        let parent = node.parent();
+        if let Some(parent) = &parent {
             let first_child = parent.first_child().unwrap();
             let last_child = parent.last_child().unwrap();
 
             if Rc::ptr_eq(&node, &first_child) {
                 parent.first_child.replace(node.next_sibling());
-            } else if Rc::ptr_eq(&node, &last_child) {
+            }
+            if Rc::ptr_eq(&node, &last_child) {
                 parent.last_child.replace(node.prev_sibling());
             }
         }
@@ -180,6 +361,41 @@ impl Node {
         node.parent_node.replace(None);
         node.prev_sibling.replace(None);
         node.next_sibling.replace(None);
+
+        if let Some(parent) = parent {
+            parent.child_list_changed();
+            Node::children_changed(parent);
+            Node::removed(node);
+        }
+    }
+
+    /// Run the insertion steps for the node and its descendants
+    fn inserted(node: Rc<Node>) {
+        let document = node.owner_document().unwrap();
+        if let Some(data) = &node.data {
+            data.handle_on_inserted(node.clone(), document);
+        }
+        for child in node.child_nodes() {
+            Node::inserted(child);
+        }
+    }
+
+    /// Run the removing steps for the node and its descendants
+    fn removed(node: Rc<Node>) {
+        for child in node.child_nodes() {
+            Node::removed(child);
+        }
+        if let (Some(data), Some(document)) = (&node.data, node.owner_document()) {
+            data.handle_on_removed(node.clone(), document);
+        }
+    }
+
+    /// Run the children changed steps of the node, e.g. after the parser
+    /// finished appending text to it
+    pub fn children_changed(node: Rc<Node>) {
+        if let Some(data) = &node.data {
+            data.handle_on_children_changed(node.clone());
+        }
     }
 
     /// Transfer parent of nodes
@@ -219,10 +435,10 @@ impl Node {
         }
 
         parent.last_child.replace(Some(child.clone()));
-        let document = child.owner_document().clone().unwrap();
-        if let Some(data) = &child.data {
-            data.handle_on_inserted(document);
-        }
+        child.mark_dirty(DirtyFlags::SUBTREE);
+        parent.child_list_changed();
+        Node::inserted(child);
+        Node::children_changed(parent);
     }
 
     /// Insert a child node to a parent node before a reference child node
@@ -258,6 +474,11 @@ impl Node {
             // set inserted child to be new previous sibling of ref child
             ref_child.prev_sibling.replace(Some(Rc::downgrade(&child)));
             child.next_sibling.replace(Some(ref_child.clone()));
+
+            child.mark_dirty(DirtyFlags::SUBTREE);
+            parent.child_list_changed();
+            Node::inserted(child);
+            Node::children_changed(parent);
         } else {
             Node::append_child(parent, child);
         }
@@ -333,6 +554,7 @@ impl Node {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::create_element;
 
     pub fn assert_node_eq(a: NullableNode, b: NullableNode) {
         let result = match (a, b) {
@@ -457,4 +679,89 @@ mod test {
         assert_node_eq(new_parent.first_child(), Some(child.clone()));
         assert_node_eq(child.parent(), Some(new_parent.clone()));
     }
+
+    #[test]
+    fn mark_ancestors_of_dirty_nodes() {
+        let root = Rc::new(Node::empty());
+        let parent = Rc::new(Node::empty());
+        let child = Rc::new(Node::empty());
+        let doc = Rc::new(Node::new(NodeData::Document(Document::new())));
+        for node in [&root, &parent, &child] {
+            node.set_document(Rc::downgrade(&doc));
+        }
+        Node::append_child(root.clone(), parent.clone());
+        root.clear_dirty();
+        parent.clear_dirty();
+
+        Node::append_child(parent.clone(), child.clone());
+        assert!(child.dirty_flags().contains(DirtyFlags::SUBTREE));
+        assert!(parent
+            .dirty_flags()
+            .contains(DirtyFlags::CHILDREN | DirtyFlags::CHANGED | DirtyFlags::DESCENDANTS));
+        assert_eq!(root.dirty_flags(), DirtyFlags::DESCENDANTS);
+        assert_eq!(parent.take_style_changes(), vec![StyleChange::ChildList]);
+        assert!(parent.take_style_changes().is_empty());
+
+        child.clear_dirty();
+        parent.clear_dirty();
+        root.clear_dirty();
+        Node::detach(child.clone());
+        assert!(child.dirty_flags().is_empty());
+        assert!(parent.dirty_flags().contains(DirtyFlags::CHILDREN));
+        assert_eq!(root.dirty_flags(), DirtyFlags::DESCENDANTS);
+    }
+
+    #[test]
+    fn reflect_form_control_attributes_in_state() {
+        let doc = Rc::new(Node::new(NodeData::Document(Document::new())));
+        let input = create_element(Rc::downgrade(&doc), "input");
+        let element = input.as_element();
+
+        input.set_attribute("checked", "");
+        input.set_attribute("disabled", "");
+        assert_eq!(
+            element.state(),
+            ElementState::CHECKED | ElementState::DISABLED
+        );
+
+        input.clear_dirty();
+        input.remove_attribute("checked");
+        assert_eq!(element.state(), ElementState::DISABLED);
+        assert_eq!(
+            input.take_style_changes(),
+            vec![
+                StyleChange::Attribute("checked".to_string()),
+                StyleChange::State(ElementState::CHECKED | ElementState::DISABLED),
+            ]
+        );
+        input.remove_attribute("disabled");
+        assert_eq!(element.state(), ElementState::empty());
+
+        input.clear_dirty();
+        input.set_state(ElementState::HOVER, true);
+        input.set_state(ElementState::HOVER, true);
+        assert_eq!(
+            input.take_style_changes(),
+            vec![StyleChange::State(ElementState::HOVER)]
+        );
+        assert!(input.dirty_flags().contains(DirtyFlags::CHANGED));
+    }
+
+    #[test]
+    fn clear_inline_style_on_style_removal() {
+        let doc = Rc::new(Node::new(NodeData::Document(Document::new())));
+        let div = create_element(Rc::downgrade(&doc), "div");
+        let element = div.as_element();
+
+        div.set_attribute("style", "color: red");
+        assert_eq!(element.style().css_text(), "color: red;");
+        div.clear_dirty();
+        div.remove_attribute("style");
+        assert_eq!(
+            div.take_style_changes(),
+            vec![StyleChange::Attribute("style".to_string())]
+        );
+        assert!(element.style().is_empty());
+        assert!(!element.has_attribute("style"));
+    }
 }
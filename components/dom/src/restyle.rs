@@ -0,0 +1,76 @@
+use super::element_state::ElementState;
+use std::ops::{BitOr, BitOrAssign};
+
+/// What has to be done again for a node since the last restyle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DirtyFlags(u8);
+
+impl DirtyFlags {
+    /// The node has style changes waiting for the invalidation
+    pub const CHANGED: Self = Self(1 << 0);
+    /// The style of the node has to be recomputed
+    pub const STYLE: Self = Self(1 << 1);
+    /// The styles of the node and of all its descendants have to be recomputed
+    pub const SUBTREE: Self = Self(1 << 2);
+    /// Children were inserted or removed
+    pub const CHILDREN: Self = Self(1 << 3);
+    /// The data of the text node changed
+    pub const TEXT: Self = Self(1 << 4);
+    /// A descendant has one of the other flags
+    pub const DESCENDANTS: Self = Self(1 << 5);
+
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn intersects(&self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for DirtyFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for DirtyFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.insert(rhs);
+    }
+}
+
+/// A mutation of a node that may change which selectors match it
+/// or its relatives. The style engine decides which nodes to restyle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StyleChange {
+    /// An attribute other than `class` and `id` was set
+    Attribute(String),
+    /// A class was added or removed
+    Class(String),
+    /// The element lost or gained the ID
+    ID(String),
+    /// The dynamic states of the element changed, e.g. when an attribute
+    /// of a form control was set. Holds the states that may have changed.
+    State(ElementState),
+    /// Children were inserted or removed, or the data of a text child changed
+    ChildList,
+}
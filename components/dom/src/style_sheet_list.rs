@@ -0,0 +1,285 @@
+use super::node::Node;
+use css::cssom::stylesheet::StyleSheet;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::{Rc, Weak};
+
+/// Where a stylesheet comes from
+#[derive(Debug, Clone)]
+pub enum StyleSheetOwner {
+    UserAgent,
+    User,
+    /// A `<link>` or `<style>` element of the document
+    Node(Weak<Node>),
+}
+
+/// Whether a stylesheet is written in the document or loaded from a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleSheetLocation {
+    /// A `<style>` element
+    Embedded,
+    /// A `<link>` element, or a UA or user stylesheet file
+    External,
+}
+
+/// A stylesheet with the information about how it's associated with the document
+/// https://www.w3.org/TR/cssom-1/#css-style-sheets
+#[derive(Debug, Clone)]
+pub struct StyleSheetEntry {
+    sheet: Rc<RefCell<StyleSheet>>,
+    owner: StyleSheetOwner,
+    location: StyleSheetLocation,
+    title: Option<String>,
+    alternate: bool,
+    disabled: bool,
+}
+
+/// The stylesheets of a document, UA sheets first, then user sheets,
+/// then author sheets in the tree order of their owner nodes
+/// https://www.w3.org/TR/cssom-1/#the-stylesheetlist-interface
+#[derive(Debug, Default)]
+pub struct StyleSheetList {
+    entries: Vec<StyleSheetEntry>,
+    /// The style sheet set chosen with `select_style_sheet_set`
+    selected_set: Option<String>,
+}
+
+impl StyleSheetOwner {
+    pub fn node(node: &Rc<Node>) -> Self {
+        StyleSheetOwner::Node(Rc::downgrade(node))
+    }
+
+    fn is_node(&self, node: &Node) -> bool {
+        match self {
+            StyleSheetOwner::Node(owner) => std::ptr::eq(owner.as_ptr(), node),
+            _ => false,
+        }
+    }
+}
+
+impl StyleSheetEntry {
+    pub fn new(stylesheet: StyleSheet, owner: StyleSheetOwner) -> Self {
+        Self {
+            sheet: Rc::new(RefCell::new(stylesheet)),
+            owner,
+            location: StyleSheetLocation::Embedded,
+            title: None,
+            alternate: false,
+            disabled: false,
+        }
+    }
+
+    /// Name the stylesheet set of the sheet, an empty title means no set
+    pub fn with_title(mut self, title: &str, alternate: bool) -> Self {
+        if !title.is_empty() {
+            self.title = Some(title.to_string());
+        }
+        self.alternate = alternate;
+        self
+    }
+
+    pub fn with_location(mut self, location: StyleSheetLocation) -> Self {
+        self.location = location;
+        self
+    }
+
+    pub fn sheet(&self) -> Rc<RefCell<StyleSheet>> {
+        self.sheet.clone()
+    }
+
+    pub fn owner(&self) -> &StyleSheetOwner {
+        &self.owner
+    }
+
+    pub fn location(&self) -> StyleSheetLocation {
+        self.location
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn is_alternate(&self) -> bool {
+        self.alternate
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    fn cmp_order(&self, other: &Self) -> Ordering {
+        let rank = |owner: &StyleSheetOwner| match owner {
+            StyleSheetOwner::UserAgent => 0,
+            StyleSheetOwner::User => 1,
+            StyleSheetOwner::Node(_) => 2,
+        };
+        match (&self.owner, &other.owner) {
+            (StyleSheetOwner::Node(a), StyleSheetOwner::Node(b)) => {
+                match (a.upgrade(), b.upgrade()) {
+                    (Some(a), Some(b)) => Node::cmp_tree_order(&a, &b),
+                    _ => Ordering::Equal,
+                }
+            }
+            (a, b) => rank(a).cmp(&rank(b)),
+        }
+    }
+}
+
+impl StyleSheetList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&StyleSheetEntry> {
+        self.entries.get(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StyleSheetEntry> {
+        self.entries.iter()
+    }
+
+    /// Add a stylesheet at its position in the list, replacing the
+    /// stylesheet of the same owner node, e.g. of a re-inserted `<link>`
+    pub fn add(&mut self, entry: StyleSheetEntry) -> Rc<RefCell<StyleSheet>> {
+        if let StyleSheetOwner::Node(owner) = &entry.owner {
+            self.entries.retain(|existing| match &existing.owner {
+                StyleSheetOwner::Node(existing) => !existing.ptr_eq(owner),
+                _ => true,
+            });
+        }
+        let sheet = entry.sheet();
+        let index = self
+            .entries
+            .iter()
+            .position(|existing| entry.cmp_order(existing) == Ordering::Less)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, entry);
+        sheet
+    }
+
+    /// Remove the stylesheets owned by the node.
+    /// Returns whether any stylesheet was removed.
+    pub fn remove_owned_by(&mut self, node: &Node) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| !entry.owner.is_node(node));
+        self.entries.len() != len
+    }
+
+    pub fn set_disabled(&mut self, index: usize, disabled: bool) -> bool {
+        match self.entries.get_mut(index) {
+            Some(entry) => {
+                entry.disabled = disabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The name of the enabled stylesheet set, the selected one or the
+    /// title of the first non-alternate titled sheet (the preferred set)
+    /// https://html.spec.whatwg.org/multipage/semantics.html#preferred-style-sheet-set-name
+    pub fn current_set(&self) -> Option<&str> {
+        if let Some(name) = &self.selected_set {
+            return Some(name);
+        }
+        self.entries
+            .iter()
+            .find(|entry| entry.title.is_some() && !entry.alternate)
+            .and_then(|entry| entry.title())
+    }
+
+    /// Enable the stylesheets of the named set, disabling the other titled sheets
+    /// https://www.w3.org/TR/cssom-1/#select-a-css-style-sheet-set
+    pub fn select_style_sheet_set(&mut self, name: &str) {
+        self.selected_set = Some(name.to_string());
+    }
+
+    /// Whether the stylesheet takes part in the cascade
+    pub fn is_enabled(&self, entry: &StyleSheetEntry) -> bool {
+        if entry.disabled {
+            return false;
+        }
+        match entry.title() {
+            Some(title) => self.current_set() == Some(title),
+            None => !entry.alternate,
+        }
+    }
+
+    /// The enabled stylesheets in cascade order
+    pub fn enabled(&self) -> Vec<StyleSheetEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| self.is_enabled(entry))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_element;
+    use crate::document::Document;
+    use crate::node::NodeData;
+
+    #[test]
+    fn order_and_enable_stylesheets() {
+        let document = Rc::new(Node::new(NodeData::Document(Document::new())));
+        let head = create_element(Rc::downgrade(&document), "head");
+        let first = create_element(Rc::downgrade(&document), "div");
+        let second = create_element(Rc::downgrade(&document), "div");
+        Node::append_child(document.clone(), head.clone());
+        Node::append_child(head.clone(), first.clone());
+        Node::append_child(head.clone(), second.clone());
+
+        let mut list = StyleSheetList::new();
+        let late = list.add(
+            StyleSheetEntry::new(StyleSheet::new(), StyleSheetOwner::node(&second))
+                .with_title("dark", true),
+        );
+        let early = list.add(
+            StyleSheetEntry::new(StyleSheet::new(), StyleSheetOwner::node(&first))
+                .with_title("light", false),
+        );
+        let ua = list.add(StyleSheetEntry::new(
+            StyleSheet::new(),
+            StyleSheetOwner::UserAgent,
+        ));
+
+        let order = list.iter().map(|entry| entry.sheet()).collect::<Vec<_>>();
+        assert!(Rc::ptr_eq(&order[0], &ua));
+        assert!(Rc::ptr_eq(&order[1], &early));
+        assert!(Rc::ptr_eq(&order[2], &late));
+
+        // the alternate sheet is disabled until its set is selected
+        assert_eq!(list.current_set(), Some("light"));
+        assert_eq!(list.enabled().len(), 2);
+        list.select_style_sheet_set("dark");
+        let enabled = list.enabled();
+        assert_eq!(enabled.len(), 2);
+        assert!(Rc::ptr_eq(&enabled[1].sheet(), &late));
+
+        assert!(list.set_disabled(0, true));
+        assert_eq!(list.enabled().len(), 1);
+
+        // a node owns a single stylesheet
+        let reloaded = list.add(StyleSheetEntry::new(
+            StyleSheet::new(),
+            StyleSheetOwner::node(&first),
+        ));
+        assert_eq!(list.len(), 3);
+        assert!(Rc::ptr_eq(&list.get(1).unwrap().sheet(), &reloaded));
+
+        assert!(list.remove_owned_by(&second));
+        assert!(!list.remove_owned_by(&second));
+        assert_eq!(list.len(), 2);
+    }
+}
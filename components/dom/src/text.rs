@@ -23,4 +23,8 @@ impl Text {
     pub fn get_data(&self) -> String {
         self.character_data.get_data()
     }
+
+    pub fn set_data(&self, data: &str) {
+        self.character_data.set_data(data)
+    }
 }
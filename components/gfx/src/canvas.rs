@@ -219,6 +219,11 @@ impl<'a> GfxPainter for Canvas<'a> {
         self.resize((size.width as u32, size.height as u32));
     }
 
+    fn clear(&mut self) {
+        self.rect_painter.clear();
+        self.text_painter.clear();
+    }
+
     async fn output(&mut self) -> Vec<u8> {
         self.paint();
         self.output().await
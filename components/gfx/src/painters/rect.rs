@@ -19,6 +19,10 @@ impl RectPainter {
         }
     }
 
+    pub fn clear(&mut self) {
+        self.vertex_buffers.clear();
+    }
+
     pub fn vertex_buffers(&self) -> &[VertexBuffers<Vertex, Index>] {
         &self.vertex_buffers
     }
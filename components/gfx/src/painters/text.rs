@@ -20,6 +20,10 @@ impl TextPainter {
         })
     }
 
+    pub fn clear(&mut self) {
+        self.texts.clear();
+    }
+
     pub fn texts(&self) -> &[Text] {
         &self.texts
     }
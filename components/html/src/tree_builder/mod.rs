@@ -345,9 +345,7 @@ impl<T: Tokenizing> TreeBuilder<T> {
         };
         let element_ref = dom::create_element(Rc::downgrade(&self.document), &tag_name);
         for attribute in attributes {
-            element_ref
-                .as_element()
-                .set_attribute(&attribute.name, &attribute.value);
+            element_ref.set_attribute(&attribute.name, &attribute.value);
         }
         element_ref
     }
@@ -1429,7 +1427,7 @@ impl<T: Tokenizing> TreeBuilder<T> {
                 if current_element.has_attribute(&attribute.name) {
                     continue;
                 }
-                current_element.set_attribute(&attribute.name, &attribute.value);
+                current_node.set_attribute(&attribute.name, &attribute.value);
             }
             return;
         }
@@ -1474,9 +1472,8 @@ impl<T: Tokenizing> TreeBuilder<T> {
 
             self.frameset_ok = false;
             let body = self.open_elements.get(1);
-            let body = body.as_element();
             for attribute in token.attributes() {
-                if body.has_attribute(&attribute.name) {
+                if body.as_element().has_attribute(&attribute.name) {
                     continue;
                 }
                 body.set_attribute(&attribute.name, &attribute.value);
@@ -2303,7 +2300,11 @@ impl<T: Tokenizing> TreeBuilder<T> {
         }
 
         if token.is_end_tag() {
-            self.open_elements.pop();
+            if let Some(node) = self.open_elements.pop() {
+                // the text is appended without notifying the element,
+                // let it process the final content, e.g. a <style> block
+                Node::children_changed(node);
+            }
             self.switch_to(self.original_insert_mode.clone().unwrap());
             return;
         }
@@ -2609,6 +2610,7 @@ impl<T: Tokenizing> TreeBuilder<T> {
                 return;
             }
             self.switch_to(InsertMode::AfterAfterBody);
+            return;
         }
 
         if let Token::EOF = token {
@@ -3227,6 +3229,21 @@ mod test {
         );
     }
 
+    #[test]
+    fn handle_html_end_tag_after_body() {
+        // the `<object>` keeps the body out of scope so `</body>` switches
+        // to the after body mode, where `</html>` must not be reprocessed
+        let html = "<object></body></html>";
+        let tokenizer = Tokenizer::new(html.chars());
+        let tree_builder = TreeBuilder::default(tokenizer);
+        let document = tree_builder.run();
+
+        let html = document.first_child().unwrap();
+        let body = html.last_child().unwrap();
+        let object = body.first_child().unwrap();
+        assert_eq!(object.as_element().tag_name(), "object");
+    }
+
     #[test]
     fn handle_parsing_children_correctly() {
         let html = "<div><div></div><div></div><div></div></div>";
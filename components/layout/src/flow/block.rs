@@ -1,7 +1,7 @@
 use crate::{box_model::BoxComponent, formatting_context::LayoutContext, layout_box::LayoutBox};
 use shared::primitive::edge::Edge;
 use std::rc::Rc;
-use style::{property::Property, values::prelude::Position};
+use style::values::prelude::Position;
 
 pub struct BlockFormattingContext {
     layout_context: Rc<LayoutContext>,
@@ -78,21 +78,21 @@ impl BlockFormattingContext {
             _ => return,
         };
 
-        let computed_width = render_node.get_style(&Property::Width);
-        let computed_margin_left = render_node.get_style(&Property::MarginLeft);
-        let computed_margin_right = render_node.get_style(&Property::MarginRight);
-        let computed_border_left = render_node.get_style(&Property::BorderLeftWidth);
-        let computed_border_right = render_node.get_style(&Property::BorderRightWidth);
-        let computed_padding_left = render_node.get_style(&Property::PaddingLeft);
-        let computed_padding_right = render_node.get_style(&Property::PaddingRight);
+        let computed_width = render_node.style.width();
+        let computed_margin_left = render_node.style.margin_left();
+        let computed_margin_right = render_node.style.margin_right();
+        let computed_border_left = render_node.style.border_left_width();
+        let computed_border_right = render_node.style.border_right_width();
+        let computed_padding_left = render_node.style.padding_left();
+        let computed_padding_right = render_node.style.padding_right();
         let containing_width = containing_block.width;
 
         let box_width = computed_margin_left.to_px(containing_width)
-            + computed_border_left.to_px(containing_width)
+            + computed_border_left.to_px()
             + computed_padding_left.to_px(containing_width)
             + computed_width.to_px(containing_width)
             + computed_padding_right.to_px(containing_width)
-            + computed_border_right.to_px(containing_width)
+            + computed_border_right.to_px()
             + computed_margin_right.to_px(containing_width);
 
         let mut used_width = computed_width.to_px(containing_width);
@@ -198,12 +198,12 @@ impl BlockFormattingContext {
         box_model.set(
             BoxComponent::Border,
             Edge::Left,
-            computed_border_left.to_px(containing_width),
+            computed_border_left.to_px(),
         );
         box_model.set(
             BoxComponent::Border,
             Edge::Right,
-            computed_border_right.to_px(containing_width),
+            computed_border_right.to_px(),
         );
     }
 
@@ -214,26 +214,23 @@ impl BlockFormattingContext {
 
         let render_node = layout_node.render_node().unwrap();
         let containing_block = layout_node.containing_block().content_size();
-        let margin_top = render_node
-            .get_style(&Property::MarginTop)
-            .to_px(containing_block.width);
+        let margin_top = render_node.style.margin_top().to_px(containing_block.width);
         let margin_bottom = render_node
-            .get_style(&Property::MarginBottom)
+            .style
+            .margin_bottom()
             .to_px(containing_block.width);
 
         let padding_top = render_node
-            .get_style(&Property::PaddingTop)
+            .style
+            .padding_top()
             .to_px(containing_block.width);
         let padding_bottom = render_node
-            .get_style(&Property::PaddingBottom)
+            .style
+            .padding_bottom()
             .to_px(containing_block.width);
 
-        let border_top = render_node
-            .get_style(&Property::BorderTopWidth)
-            .to_px(containing_block.width);
-        let border_bottom = render_node
-            .get_style(&Property::BorderBottomWidth)
-            .to_px(containing_block.width);
+        let border_top = render_node.style.border_top_width().to_px();
+        let border_bottom = render_node.style.border_bottom_width().to_px();
 
         let mut box_model = layout_node.base.box_model.borrow_mut();
         box_model.set(BoxComponent::Margin, Edge::Top, margin_top);
@@ -255,10 +252,7 @@ impl BlockFormattingContext {
         }
 
         let containing_block = layout_node.containing_block().content_size();
-        let computed_height = layout_node
-            .render_node()
-            .unwrap()
-            .get_style(&Property::Height);
+        let computed_height = layout_node.render_node().unwrap().style.height().clone();
 
         if computed_height.is_auto() {
             self.compute_auto_height(layout_node)
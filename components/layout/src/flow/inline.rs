@@ -4,7 +4,6 @@ use crate::{box_model::BoxComponent, formatting_context::LayoutContext, layout_b
 use dom::node::NodeData;
 use regex::Regex;
 use shared::primitive::edge::Edge;
-use style::property::Property;
 
 use super::line_box::LineBoxBuilder;
 
@@ -91,9 +90,9 @@ impl InlineFormattingContext {
             _ => return,
         };
 
-        let computed_width = render_node.get_style(&Property::Width);
-        let computed_margin_left = render_node.get_style(&Property::MarginLeft);
-        let computed_margin_right = render_node.get_style(&Property::MarginRight);
+        let computed_width = render_node.style.width();
+        let computed_margin_left = render_node.style.margin_left();
+        let computed_margin_right = render_node.style.margin_right();
         let containing_width = containing_block.width;
 
         let mut used_width = computed_width.to_px(containing_width);
@@ -132,25 +131,22 @@ impl InlineFormattingContext {
         let mut box_model = layout_node.base.box_model.borrow_mut();
 
         if let Some(render_node) = render_node {
-            let margin_top = render_node
-                .get_style(&Property::MarginTop)
-                .to_px(containing_block.width);
+            let margin_top = render_node.style.margin_top().to_px(containing_block.width);
             let margin_bottom = render_node
-                .get_style(&Property::MarginBottom)
+                .style
+                .margin_bottom()
                 .to_px(containing_block.width);
 
-            let border_top = render_node
-                .get_style(&Property::BorderTopWidth)
-                .to_px(containing_block.width);
-            let border_bottom = render_node
-                .get_style(&Property::BorderBottomWidth)
-                .to_px(containing_block.width);
+            let border_top = render_node.style.border_top_width().to_px();
+            let border_bottom = render_node.style.border_bottom_width().to_px();
 
             let padding_top = render_node
-                .get_style(&Property::PaddingTop)
+                .style
+                .padding_top()
                 .to_px(containing_block.width);
             let padding_bottom = render_node
-                .get_style(&Property::PaddingBottom)
+                .style
+                .padding_bottom()
                 .to_px(containing_block.width);
 
             box_model.set(BoxComponent::Margin, Edge::Top, margin_top);
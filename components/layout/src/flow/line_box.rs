@@ -1,7 +1,6 @@
 use std::rc::Rc;
 
 use shared::primitive::{Point, Size};
-use style::property::Property;
 
 use crate::{layout_box::LayoutBox, text::TextMeasure};
 
@@ -167,7 +166,7 @@ impl LineBoxBuilder {
 
     pub fn add_text_fragment(&mut self, layout_box: Rc<LayoutBox>, text: String) {
         let render_node = layout_box.render_node().unwrap();
-        let font_size = render_node.get_style(&Property::FontSize).to_absolute_px();
+        let font_size = render_node.style.font_size().to_px();
         let mut text_measurer = TextMeasure::new();
         let text_size = text_measurer.measure(&text, font_size);
         let fragment_width = text_size.width;
@@ -4,11 +4,7 @@ use std::{
 };
 
 use shared::primitive::*;
-use style::{
-    property::Property,
-    value::Value,
-    values::{display::InnerDisplayType, prelude::Display},
-};
+use style::values::{display::InnerDisplayType, prelude::Display};
 
 use crate::{
     flow::{block::BlockFormattingContext, inline::InlineFormattingContext},
@@ -69,9 +65,9 @@ fn get_formatting_context_type(layout_node: Rc<LayoutBox>) -> FormattingContextT
 
     let node = layout_node.render_node().unwrap();
 
-    let display = node.get_style(&Property::Display);
-    let inner_display = match display.inner() {
-        Value::Display(Display::Full(_, inner)) => inner,
+    let display = node.style.display();
+    let inner_display = match display {
+        Display::Full(_, inner) => inner,
         _ => unreachable!(),
     };
 
@@ -6,9 +6,7 @@ use std::{
 
 use shared::primitive::{Point, Rect, Size};
 use style::{
-    property::Property,
     render_tree::RenderNode,
-    value::Value,
     values::{
         display::Display,
         display::{InnerDisplayType, OuterDisplayType},
@@ -86,21 +84,17 @@ impl LayoutBox {
             if render_node.node.is_text() {
                 BoxData::InlineContents(InlineContents::TextRun)
             } else {
-                match render_node.get_style(&Property::Display).inner() {
-                    Value::Display(d) => match d {
-                        Display::Full(outer, inner) => match (outer, inner) {
-                            (OuterDisplayType::Block, InnerDisplayType::Flow) => {
-                                BoxData::block_box()
-                            }
-                            (OuterDisplayType::Inline, InnerDisplayType::Flow)
-                            | (OuterDisplayType::Inline, InnerDisplayType::FlowRoot) => {
-                                BoxData::inline_box()
-                            }
-                            _ => unimplemented!("Unsupport display type: {:#?}", d),
-                        },
+                let d = render_node.style.display();
+                match d {
+                    Display::Full(outer, inner) => match (outer, inner) {
+                        (OuterDisplayType::Block, InnerDisplayType::Flow) => BoxData::block_box(),
+                        (OuterDisplayType::Inline, InnerDisplayType::Flow)
+                        | (OuterDisplayType::Inline, InnerDisplayType::FlowRoot) => {
+                            BoxData::inline_box()
+                        }
                         _ => unimplemented!("Unsupport display type: {:#?}", d),
                     },
-                    _ => unreachable!(),
+                    _ => unimplemented!("Unsupport display type: {:#?}", d),
                 }
             }
         };
@@ -122,7 +116,7 @@ impl LayoutBox {
 
     pub fn is_root_element(&self) -> bool {
         match &self.node {
-            Some(node) => match node.node.as_element_opt() {
+            Some(node) if node.pseudo_element.is_none() => match node.node.as_element_opt() {
                 Some(element) => element.tag_name() == "html",
                 _ => false,
             },
@@ -132,7 +126,7 @@ impl LayoutBox {
 
     pub fn is_body_element(&self) -> bool {
         match &self.node {
-            Some(node) => match node.node.as_element_opt() {
+            Some(node) if node.pseudo_element.is_none() => match node.node.as_element_opt() {
                 Some(element) => element.tag_name() == "body",
                 _ => false,
             },
@@ -225,8 +219,8 @@ impl LayoutBox {
 
     pub fn is_inline_block(&self) -> bool {
         match self.render_node() {
-            Some(node) => match node.get_style(&Property::Display).inner() {
-                Value::Display(Display::Full(_, InnerDisplayType::FlowRoot)) => self.is_inline(),
+            Some(node) => match node.style.display() {
+                Display::Full(_, InnerDisplayType::FlowRoot) => self.is_inline(),
                 _ => false,
             },
             _ => false,
@@ -235,17 +229,15 @@ impl LayoutBox {
 
     pub fn is_positioned(&self, position: Position) -> bool {
         match self.render_node() {
-            Some(node) => match node.get_style(&Property::Position).inner() {
-                Value::Position(pos) => *pos == position,
-                _ => false,
-            },
+            Some(node) => *node.style.position() == position,
             _ => false,
         }
     }
 
     pub fn is_non_replaced(&self) -> bool {
         match &self.render_node() {
-            Some(node) => match node.node.as_element_opt() {
+            // generated content of pseudo-elements is never replaced
+            Some(node) if node.pseudo_element.is_none() => match node.node.as_element_opt() {
                 Some(e) => match e.tag_name().as_str() {
                     "video" | "image" | "img" | "canvas" => false,
                     _ => true,
@@ -347,8 +339,8 @@ impl LayoutBox {
         }
 
         if let Some(render_node) = self.render_node() {
-            let computed_width = render_node.get_style(&Property::Width);
-            let computed_height = render_node.get_style(&Property::Height);
+            let computed_width = render_node.style.width();
+            let computed_height = render_node.style.height();
 
             if !computed_width.is_auto() {
                 let used_width = computed_width.to_px(containing_block.width);
@@ -392,7 +384,7 @@ impl LayoutBox {
         );
 
         let node_info = match &self.render_node() {
-            Some(node) => format!(" {:?}", node.node),
+            Some(node) => format!(" {:?}", node),
             None => String::new(),
         };
 
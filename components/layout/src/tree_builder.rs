@@ -222,4 +222,48 @@ mod tests {
         assert!(root.children()[2].is_block());
         assert!(root.children()[2].is_anonymous());
     }
+
+    #[test]
+    fn test_pseudo_element_boxes() {
+        let document = document();
+        let dom = element(
+            "div",
+            document.clone(),
+            vec![element("p", document.clone(), vec![])],
+        );
+
+        let css = format!(
+            "{}\n{}",
+            SHARED_CSS,
+            r#"
+            div::before { content: "a"; }
+            div::after { content: "b"; display: block; }
+            p::before { content: none; }
+            "#
+        );
+        let root = build_tree(dom, &css);
+
+        // The result box tree should look like this
+        // [Block] - Div
+        //   |- [Block Anonymous]
+        //        |- [Inline] - Div::before
+        //             |- [TextRun] - "a"
+        //   |- [Block] - P
+        //   |- [Block] - Div::after
+        //        |- [TextRun] - "b"
+
+        assert_eq!(root.children().len(), 3);
+
+        let before = root.children()[0].children()[0].clone();
+        assert!(before.is_inline());
+        assert_eq!(before.children().len(), 1);
+        assert!(before.render_node().unwrap().pseudo_element.is_some());
+
+        assert!(root.children()[1].children().is_empty());
+
+        let after = root.children()[2].clone();
+        assert!(after.is_block());
+        assert!(!after.is_anonymous());
+        assert_eq!(after.children().len(), 1);
+    }
 }
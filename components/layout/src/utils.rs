@@ -2,6 +2,7 @@ use std::rc::Rc;
 
 use css::cssom::css_rule::CSSRule;
 use dom::node::Node;
+use style::cascade_layers::UNLAYERED;
 use style::value_processing::{CSSLocation, CascadeOrigin, ContextualRule};
 use test_utils::css::parse_stylesheet;
 
@@ -25,6 +26,7 @@ pub fn build_tree(dom: Rc<Node>, css: &str) -> Rc<LayoutBox> {
                 inner: style,
                 location: CSSLocation::Embedded,
                 origin: CascadeOrigin::User,
+                layer: UNLAYERED,
             },
         })
         .collect::<Vec<ContextualRule>>();
@@ -8,5 +8,7 @@ pub trait GfxPainter {
     fn fill_rrect(&mut self, rect: RRect, color: Color);
     fn fill_text(&mut self, content: String, bounds: Rect, color: Color, size: f32);
     fn resize(&mut self, size: Size);
+    /// Drop everything painted so far
+    fn clear(&mut self);
     async fn output(&mut self) -> Vec<u8>;
 }
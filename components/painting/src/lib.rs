@@ -13,8 +13,8 @@ use shared::{
     color::Color,
     primitive::{Corners, Rect, Size},
 };
-use style::{property::Property, value::Value, values::color::Color as CSSColor};
-use utils::{color_from_value, is_zero, to_radii};
+use style::values::color::Color as CSSColor;
+use utils::{is_zero, to_radii};
 
 pub struct Painter<G: GfxPainter> {
     gfx: G,
@@ -36,6 +36,12 @@ impl<G: GfxPainter> Painter<G> {
         self.canvas_size = size;
     }
 
+    /// Start painting a new frame
+    pub fn clear(&mut self) {
+        self.gfx.clear();
+        self.root_element_use_body_background = false;
+    }
+
     pub async fn output(&mut self) -> Vec<u8> {
         let result = self.gfx.output().await;
         result
@@ -64,8 +70,7 @@ impl<G: GfxPainter> Painter<G> {
                     let mut background_rect =
                         Rect::from((containing_block.absolute_location(), fragment.size.clone()));
                     background_rect.translate(fragment.offset.x, fragment.offset.y);
-                    let background_color =
-                        color_from_value(&render_node.get_style(&Property::BackgroundColor));
+                    let background_color = Color::from(render_node.style.background_color());
                     let corners = self.compute_border_radius_corner(layout_box.clone());
                     self.paint_background(background_rect, background_color, corners);
                 }
@@ -74,8 +79,8 @@ impl<G: GfxPainter> Painter<G> {
                     let mut text_rect =
                         Rect::from((containing_block.absolute_location(), fragment.size.clone()));
                     text_rect.translate(fragment.offset.x, fragment.offset.y);
-                    let text_color = color_from_value(&render_node.get_style(&Property::Color));
-                    let font_size = render_node.get_style(&Property::FontSize).to_absolute_px();
+                    let text_color = Color::from(render_node.style.color());
+                    let font_size = render_node.style.font_size().to_px();
                     self.gfx
                         .fill_text(content.clone(), text_rect, text_color, font_size);
                 }
@@ -91,15 +96,11 @@ impl<G: GfxPainter> Painter<G> {
 
         let render_node = layout_box.render_node().unwrap();
         let mut background_rect = layout_box.padding_box_absolute();
-        let background_color = color_from_value(&render_node.get_style(&Property::BackgroundColor));
+        let background_color = Color::from(render_node.style.background_color());
 
         if layout_box.is_root_element() {
-            self.root_element_use_body_background = {
-                match render_node.get_style(&Property::BackgroundColor).inner() {
-                    Value::Color(CSSColor::Transparent) => true,
-                    _ => false,
-                }
-            };
+            self.root_element_use_body_background =
+                matches!(render_node.style.background_color(), CSSColor::Transparent);
 
             if self.root_element_use_body_background {
                 // Delegate the rendering to the body element
@@ -133,15 +134,15 @@ impl<G: GfxPainter> Painter<G> {
             return None;
         }
         let render_node = layout_box.render_node().unwrap();
-        let border_top_left_radius = render_node.get_style(&Property::BorderTopLeftRadius);
-        let border_bottom_left_radius = render_node.get_style(&Property::BorderBottomLeftRadius);
-        let border_top_right_radius = render_node.get_style(&Property::BorderTopRightRadius);
-        let border_bottom_right_radius = render_node.get_style(&Property::BorderBottomRightRadius);
+        let border_top_left_radius = render_node.style.border_top_left_radius();
+        let border_bottom_left_radius = render_node.style.border_bottom_left_radius();
+        let border_top_right_radius = render_node.style.border_top_right_radius();
+        let border_bottom_right_radius = render_node.style.border_bottom_right_radius();
 
-        let has_no_border_radius = is_zero(border_top_left_radius.inner())
-            && is_zero(border_bottom_left_radius.inner())
-            && is_zero(border_top_right_radius.inner())
-            && is_zero(border_bottom_right_radius.inner());
+        let has_no_border_radius = is_zero(border_top_left_radius)
+            && is_zero(border_bottom_left_radius)
+            && is_zero(border_top_right_radius)
+            && is_zero(border_bottom_right_radius);
 
         if has_no_border_radius {
             return None;
@@ -149,10 +150,10 @@ impl<G: GfxPainter> Painter<G> {
 
         let border_box = layout_box.border_box_absolute();
 
-        let tl = to_radii(border_top_left_radius.inner(), border_box.width);
-        let tr = to_radii(border_top_right_radius.inner(), border_box.width);
-        let bl = to_radii(border_bottom_left_radius.inner(), border_box.width);
-        let br = to_radii(border_bottom_right_radius.inner(), border_box.width);
+        let tl = to_radii(border_top_left_radius, border_box.width);
+        let tr = to_radii(border_top_right_radius, border_box.width);
+        let bl = to_radii(border_bottom_left_radius, border_box.width);
+        let br = to_radii(border_bottom_right_radius, border_box.width);
 
         Some(Corners::new(tl, tr, bl, br))
     }
@@ -0,0 +1,112 @@
+//! Compare matching every rule against every element, as the style tree
+//! builder did before the rule index, with matching the candidate rules
+//! of the rule index. Run with `cargo bench --bench selector_matching`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use css::cssom::css_rule::CSSRule;
+use dom::node::Node;
+use html::tokenizer::Tokenizer;
+use html::tree_builder::TreeBuilder;
+use std::rc::Rc;
+use style::bloom::AncestorFilter;
+use style::cascade_layers::UNLAYERED;
+use style::rule_index::RuleIndex;
+use style::selector_matching::is_match_pseudo_element_selectors;
+use style::value_processing::{CSSLocation, CascadeOrigin, ContextualRule};
+use test_utils::css::parse_stylesheet;
+
+/// Match the rules of every element in tree order, keeping
+/// the ancestor filter up to date like the style tree builder
+fn match_indexed(node: &Rc<Node>, index: &RuleIndex, ancestors: &mut AncestorFilter) {
+    for child in node.child_nodes() {
+        if child.is_element() {
+            black_box(index.matching_rules(&child, ancestors, None).len());
+            ancestors.push(&child);
+            match_indexed(&child, index, ancestors);
+            ancestors.pop(&child);
+        }
+    }
+}
+
+fn collect_elements(node: &Rc<Node>, result: &mut Vec<Rc<Node>>) {
+    for child in node.child_nodes() {
+        if child.is_element() {
+            result.push(child.clone());
+            collect_elements(&child, result);
+        }
+    }
+}
+
+/// The page's own `style.css` is not bundled, so generate a stylesheet in
+/// the same spirit: a rule for every ID and class, and a descendant rule
+/// for every class nested in a parent class.
+fn generate_css(elements: &[Rc<Node>]) -> String {
+    let mut selectors = Vec::new();
+    for node in elements {
+        let element = node.as_element();
+        if let Some(id) = element.id() {
+            selectors.push(format!("#{}", id));
+        }
+        let parent_class = node
+            .parent()
+            .filter(|parent| parent.is_element())
+            .and_then(|parent| parent.as_element().class_list().borrow().item(0));
+        for class in element.class_list().borrow().iter() {
+            selectors.push(format!(".{}", class));
+            if let Some(parent_class) = &parent_class {
+                selectors.push(format!(".{} > .{}", parent_class, class));
+                selectors.push(format!(".{} .{} .{}", parent_class, parent_class, class));
+            }
+        }
+    }
+    selectors.sort();
+    selectors.dedup();
+    selectors
+        .iter()
+        .map(|selector| format!("{} {{ width: 1px; }}\n", selector))
+        .collect()
+}
+
+fn selector_matching_benchmark(c: &mut Criterion) {
+    let html = include_str!("../../html/benches/purecss_gaze.html");
+    let document = TreeBuilder::default(Tokenizer::new(html.chars())).run();
+    let mut elements = Vec::new();
+    collect_elements(&document, &mut elements);
+
+    let stylesheet = parse_stylesheet(&generate_css(&elements));
+    let rules = stylesheet
+        .iter()
+        .map(|rule| match rule {
+            CSSRule::Style(style) => ContextualRule {
+                inner: style,
+                location: CSSLocation::Embedded,
+                origin: CascadeOrigin::Author,
+                layer: UNLAYERED,
+            },
+        })
+        .collect::<Vec<ContextualRule>>();
+
+    c.bench_function("match_purecss_gaze_linear", |b| {
+        b.iter(|| {
+            for element in &elements {
+                let matched = rules
+                    .iter()
+                    .filter(|rule| {
+                        is_match_pseudo_element_selectors(element, &rule.inner.selectors, None)
+                    })
+                    .count();
+                black_box(matched);
+            }
+        })
+    });
+
+    c.bench_function("match_purecss_gaze_indexed", |b| {
+        b.iter(|| {
+            let index = RuleIndex::new(&rules);
+            match_indexed(&document, &index, &mut AncestorFilter::new());
+        })
+    });
+}
+
+criterion_group!(benches, selector_matching_benchmark);
+criterion_main!(benches);
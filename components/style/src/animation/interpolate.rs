@@ -0,0 +1,188 @@
+use crate::value::Value;
+use crate::values::color::Color;
+use crate::values::length::Length;
+use crate::values::percentage::Percentage;
+use crate::values::transform::{Transform, TransformFunction};
+
+fn lerp(from: f32, to: f32, progress: f32) -> f32 {
+    from + (to - from) * progress
+}
+
+/// Interpolate between two computed values. Values that can't be
+/// interpolated flip from one to the other halfway.
+/// https://www.w3.org/TR/css-values-4/#interpolation
+pub fn interpolate(from: &Value, to: &Value, progress: f32) -> Value {
+    let interpolated = match (from, to) {
+        (Value::Length(from), Value::Length(to)) => {
+            interpolate_length(from, to, progress).map(Value::Length)
+        }
+        (Value::Percentage(from), Value::Percentage(to)) => Some(Value::Percentage(Percentage(
+            lerp(*from.0, *to.0, progress).into(),
+        ))),
+        (Value::Color(from), Value::Color(to)) => {
+            interpolate_color(from, to, progress).map(Value::Color)
+        }
+        (Value::Transform(from), Value::Transform(to)) => {
+            interpolate_transform(from, to, progress).map(Value::Transform)
+        }
+        _ => None,
+    };
+
+    interpolated.unwrap_or_else(|| match progress < 0.5 {
+        true => from.clone(),
+        false => to.clone(),
+    })
+}
+
+/// Lengths are interpolated in their unit, or in px if they are
+/// in different absolute units
+fn interpolate_length(from: &Length, to: &Length, progress: f32) -> Option<Length> {
+    if from.unit == to.unit {
+        return Some(Length::new(
+            lerp(*from.value, *to.value, progress),
+            from.unit.clone(),
+        ));
+    }
+    let from = from.absolute_px()?;
+    let to = to.absolute_px()?;
+    Some(Length::new_px(lerp(from, to, progress)))
+}
+
+/// Colors are interpolated with premultiplied alpha
+/// https://www.w3.org/TR/css-color-4/#interpolation-alpha
+fn interpolate_color(from: &Color, to: &Color, progress: f32) -> Option<Color> {
+    let rgba = |color: &Color| match color {
+        Color::Rgba(r, g, b, a) => Some([**r, **g, **b, **a]),
+        Color::Transparent => Some([0., 0., 0., 0.]),
+        Color::CurrentColor | Color::Mix(_) => None,
+    };
+    let from = rgba(from)?;
+    let to = rgba(to)?;
+
+    let alpha = lerp(from[3], to[3], progress).clamp(0., 255.);
+    let channel = |index: usize| {
+        if alpha == 0. {
+            return 0.;
+        }
+        let premultiplied = lerp(from[index] * from[3], to[index] * to[3], progress);
+        (premultiplied / alpha).clamp(0., 255.)
+    };
+    Some(Color::Rgba(
+        channel(0).into(),
+        channel(1).into(),
+        channel(2).into(),
+        alpha.into(),
+    ))
+}
+
+/// Transform lists are interpolated function by function, the shorter
+/// list is padded with the identity functions of the longer one. Lists
+/// whose functions don't match fall back to a discrete flip since there
+/// is no matrix decomposition yet.
+/// https://www.w3.org/TR/css-transforms-1/#interpolation-of-transforms
+fn interpolate_transform(from: &Transform, to: &Transform, progress: f32) -> Option<Transform> {
+    let pad = |transform: &Transform, other: &Transform| {
+        let mut functions = transform.0.clone();
+        functions.extend(
+            other.0[transform.0.len().min(other.0.len())..]
+                .iter()
+                .map(TransformFunction::identity),
+        );
+        functions
+    };
+    let from_functions = pad(from, to);
+    let to_functions = pad(to, from);
+
+    from_functions
+        .iter()
+        .zip(to_functions.iter())
+        .map(|functions| match functions {
+            (TransformFunction::Translate(fx, fy), TransformFunction::Translate(tx, ty)) => {
+                Some(TransformFunction::Translate(
+                    interpolate_length(fx, tx, progress)?,
+                    interpolate_length(fy, ty, progress)?,
+                ))
+            }
+            (TransformFunction::Scale(fx, fy), TransformFunction::Scale(tx, ty)) => {
+                Some(TransformFunction::Scale(
+                    lerp(**fx, **tx, progress).into(),
+                    lerp(**fy, **ty, progress).into(),
+                ))
+            }
+            (TransformFunction::Rotate(from), TransformFunction::Rotate(to)) => Some(
+                TransformFunction::Rotate(lerp(**from, **to, progress).into()),
+            ),
+            _ => None,
+        })
+        .collect::<Option<Vec<TransformFunction>>>()
+        .map(Transform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::values::length::LengthUnit;
+
+    #[test]
+    fn interpolate_values() {
+        let px = |value: f32| Value::Length(Length::new_px(value));
+        assert_eq!(interpolate(&px(0.), &px(100.), 0.25), px(25.));
+        assert_eq!(
+            interpolate(
+                &Value::Length(Length::new(1., LengthUnit::In)),
+                &px(0.),
+                0.5
+            ),
+            px(48.)
+        );
+
+        let em = Value::Length(Length::new(1., LengthUnit::Em));
+        assert_eq!(interpolate(&em, &px(10.), 0.4), em);
+        assert_eq!(interpolate(&em, &px(10.), 0.5), px(10.));
+
+        let red = Value::Color(Color::Rgba(
+            255.0.into(),
+            0.0.into(),
+            0.0.into(),
+            255.0.into(),
+        ));
+        let transparent = Value::Color(Color::Transparent);
+        assert_eq!(
+            interpolate(&red, &transparent, 0.5),
+            Value::Color(Color::Rgba(
+                255.0.into(),
+                0.0.into(),
+                0.0.into(),
+                127.5.into()
+            ))
+        );
+
+        let rotate = Value::Transform(Transform(vec![
+            TransformFunction::Rotate(90.0.into()),
+            TransformFunction::Scale(3.0.into(), 1.0.into()),
+        ]));
+        assert_eq!(
+            interpolate(&Value::Transform(Transform::none()), &rotate, 0.5),
+            Value::Transform(Transform(vec![
+                TransformFunction::Rotate(45.0.into()),
+                TransformFunction::Scale(2.0.into(), 1.0.into()),
+            ]))
+        );
+
+        let shorter = Value::Transform(Transform(vec![TransformFunction::Rotate(10.0.into())]));
+        assert_eq!(
+            interpolate(&shorter, &rotate, 0.5),
+            Value::Transform(Transform(vec![
+                TransformFunction::Rotate(50.0.into()),
+                TransformFunction::Scale(2.0.into(), 1.0.into()),
+            ]))
+        );
+
+        let scale = Value::Transform(Transform(vec![TransformFunction::Scale(
+            2.0.into(),
+            2.0.into(),
+        )]));
+        assert_eq!(interpolate(&shorter, &scale, 0.4), shorter);
+        assert_eq!(interpolate(&shorter, &scale, 0.5), scale);
+    }
+}
@@ -0,0 +1,680 @@
+pub mod interpolate;
+
+use crate::computed_values::ComputedValues;
+use crate::expand::{expand_shorthand, get_expander_shorthand_property};
+use crate::inheritable::INHERITABLES;
+use crate::property::Property;
+use crate::render_tree::RenderNode;
+use crate::value::Value;
+use crate::value_processing::{ImportantProperties, Properties};
+use crate::values::animation::{
+    AnimationDirection, AnimationName, FillMode, List, TimeList, TimingFunctionList,
+};
+use crate::values::timing_function::TimingFunction;
+use css::cssom::keyframes_rule::KeyframesRule;
+use css::selector::structs::PseudoElement;
+use dom::node::Node;
+use interpolate::interpolate;
+use std::collections::{HashMap, HashSet};
+use std::rc::{Rc, Weak};
+use strum::IntoEnumIterator;
+
+/// The element or pseudo-element an animation runs on
+type AnimationTarget = (usize, Option<PseudoElement>);
+
+/// The values of a keyframe at one offset
+#[derive(Debug)]
+struct Keyframe {
+    offset: f32,
+    values: HashMap<Property, Value>,
+}
+
+/// The animation started on a target by an `animation-name` item
+#[derive(Debug)]
+struct RunningAnimation {
+    name: String,
+    start_time: f32,
+}
+
+#[derive(Debug)]
+struct RunningTransition {
+    from: Value,
+    to: Value,
+    start_time: f32,
+    duration: f32,
+    timing_function: TimingFunction,
+}
+
+#[derive(Debug, Default)]
+struct TargetTransitions {
+    /// The computed values of the last restyle, without transitions
+    before_change: Option<ComputedValues>,
+    running: HashMap<Property, RunningTransition>,
+}
+
+/// Run CSS animations and transitions on a virtual timeline. The time
+/// only moves when it is set, so the same time always gives the same styles.
+/// https://www.w3.org/TR/css-animations-1/
+/// https://www.w3.org/TR/css-transitions-1/
+#[derive(Debug, Default)]
+pub struct AnimationEngine {
+    /// The current time of the timeline in milliseconds
+    now: f32,
+    keyframes: HashMap<String, Vec<Keyframe>>,
+    animations: HashMap<AnimationTarget, Vec<RunningAnimation>>,
+    transitions: HashMap<AnimationTarget, TargetTransitions>,
+    /// The targets styled since the restyle began
+    styled: HashSet<AnimationTarget>,
+    /// The targets removed from the render tree since the restyle began
+    dropped: HashSet<AnimationTarget>,
+}
+
+impl AnimationEngine {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn now(&self) -> f32 {
+        self.now
+    }
+
+    pub fn set_time(&mut self, now: f32) {
+        self.now = now;
+    }
+
+    /// Whether an animation or a transition is running, so the styles
+    /// may change when the time changes
+    pub fn is_active(&self) -> bool {
+        self.animations
+            .values()
+            .any(|animations| !animations.is_empty())
+            || self
+                .transitions
+                .values()
+                .any(|transitions| !transitions.running.is_empty())
+    }
+
+    /// Replace the keyframes, when names collide the last rule wins
+    pub fn set_keyframes(&mut self, rules: &[&KeyframesRule]) {
+        self.keyframes.clear();
+        for rule in rules {
+            let mut keyframes = Vec::new();
+            for keyframe in &rule.keyframes {
+                let values = parse_keyframe_values(keyframe);
+                for offset in &keyframe.offsets {
+                    keyframes.push(Keyframe {
+                        offset: *offset,
+                        values: values.clone(),
+                    });
+                }
+            }
+            keyframes.sort_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap());
+            self.keyframes.insert(rule.name.clone(), keyframes);
+        }
+    }
+
+    pub fn begin_restyle(&mut self) {
+        self.styled.clear();
+        self.dropped.clear();
+    }
+
+    /// Drop the state of the targets that were not styled, e.g. removed
+    /// elements or elements that became `display: none`
+    pub fn end_restyle(&mut self) {
+        let styled = &self.styled;
+        self.animations.retain(|target, _| styled.contains(target));
+        self.transitions.retain(|target, _| styled.contains(target));
+    }
+
+    /// Record that the render node of a target was removed by an
+    /// incremental restyle
+    pub fn drop_target(&mut self, node: &Rc<Node>, pseudo_element: Option<PseudoElement>) {
+        self.dropped
+            .insert((Rc::as_ptr(node) as usize, pseudo_element));
+    }
+
+    /// End an incremental restyle, which only styles the invalidated
+    /// elements. Only the dropped targets that were not styled again,
+    /// e.g. moved elements, lose their state.
+    pub fn end_partial_restyle(&mut self) {
+        let (styled, dropped) = (&self.styled, &self.dropped);
+        let keep = |target: &AnimationTarget| styled.contains(target) || !dropped.contains(target);
+        self.animations.retain(|target, _| keep(target));
+        self.transitions.retain(|target, _| keep(target));
+    }
+
+    /// Apply the keyframe animations of a target to its cascaded values,
+    /// except to the properties won by an `!important` declaration.
+    /// Returns the animated properties.
+    pub fn animate(
+        &mut self,
+        node: &Rc<Node>,
+        pseudo_element: Option<PseudoElement>,
+        properties: &mut Properties,
+        important: &ImportantProperties,
+        parent: Option<&Weak<RenderNode>>,
+    ) -> HashSet<Property> {
+        let target = (Rc::as_ptr(node) as usize, pseudo_element);
+        self.styled.insert(target);
+
+        let mut animated = HashSet::new();
+        let names = match base_value(&Property::AnimationName, properties, parent) {
+            Value::AnimationNameList(names) => names.0,
+            _ => Vec::new(),
+        };
+        let list = |property: Property| base_value(&property, properties, parent);
+        let (durations, delays) = (
+            list(Property::AnimationDuration),
+            list(Property::AnimationDelay),
+        );
+        let timing_functions = match list(Property::AnimationTimingFunction) {
+            Value::TimingFunctionList(list) => list,
+            _ => List(Vec::new()),
+        };
+        let iteration_counts = list(Property::AnimationIterationCount);
+        let directions = list(Property::AnimationDirection);
+        let fill_modes = list(Property::AnimationFillMode);
+
+        // animations keep their start time while their name stays
+        // in `animation-name`, new ones start now
+        let previous = self.animations.remove(&target).unwrap_or_default();
+        let mut running = Vec::new();
+        for (index, name) in names.iter().enumerate() {
+            let name = match name {
+                AnimationName::Name(name) => name,
+                AnimationName::None => continue,
+            };
+            let start_time = previous
+                .iter()
+                .find(|animation| &animation.name == name)
+                .map(|animation| animation.start_time)
+                .unwrap_or(self.now);
+            running.push(RunningAnimation {
+                name: name.clone(),
+                start_time,
+            });
+
+            let keyframes = match self.keyframes.get(name) {
+                Some(keyframes) if !keyframes.is_empty() => keyframes,
+                _ => continue,
+            };
+            let duration = match &durations {
+                Value::TimeList(list) => list.get_cycled(index).map(|t| t.ms()),
+                _ => None,
+            }
+            .unwrap_or(0.)
+            .max(0.);
+            let delay = match &delays {
+                Value::TimeList(list) => list.get_cycled(index).map(|t| t.ms()),
+                _ => None,
+            }
+            .unwrap_or(0.);
+            let iterations = match &iteration_counts {
+                Value::IterationCountList(list) => list.get_cycled(index).map(|c| c.as_f32()),
+                _ => None,
+            }
+            .unwrap_or(1.);
+            let direction = match &directions {
+                Value::AnimationDirectionList(list) => list.get_cycled(index).copied(),
+                _ => None,
+            }
+            .unwrap_or(AnimationDirection::Normal);
+            let fill_mode = match &fill_modes {
+                Value::FillModeList(list) => list.get_cycled(index).copied(),
+                _ => None,
+            }
+            .unwrap_or(FillMode::None);
+            let timing_function = timing_function_at(&timing_functions, index);
+
+            let local_time = self.now - start_time - delay;
+            let progress =
+                match iteration_progress(local_time, duration, iterations, direction, fill_mode) {
+                    Some(progress) => progress,
+                    None => continue,
+                };
+
+            // later animations in the list override the earlier ones, and
+            // important declarations override animations
+            // https://www.w3.org/TR/css-cascade-4/#cascade-origin
+            for (property, value) in sample(keyframes, progress, &timing_function, |property| {
+                base_value(property, properties, parent)
+            }) {
+                if important.contains(&property) {
+                    continue;
+                }
+                properties.insert(property.clone(), Some(value));
+                animated.insert(property);
+            }
+        }
+        self.animations.insert(target, running);
+
+        animated
+    }
+
+    /// Start, update or finish the transitions of a target from the changes
+    /// of its computed values since the last restyle. Animated properties
+    /// are not transitioned.
+    pub fn transition(
+        &mut self,
+        node: &Rc<Node>,
+        pseudo_element: Option<PseudoElement>,
+        computed: &mut ComputedValues,
+        animated: &HashSet<Property>,
+    ) {
+        let target = (Rc::as_ptr(node) as usize, pseudo_element);
+        self.styled.insert(target);
+        let now = self.now;
+
+        let transitions = self.transitions.entry(target).or_default();
+        let properties = computed.transition_property().clone();
+        let durations = computed.transition_duration().clone();
+        let delays = computed.transition_delay().clone();
+        let timing_functions = computed.transition_timing_function().clone();
+
+        let before_change = transitions.before_change.replace(computed.clone());
+        for property in Property::iter() {
+            let property = &property;
+            let after_change = computed.get(property);
+            let before_change = before_change.as_ref().map(|values| values.get(property));
+            if animated.contains(property) {
+                transitions.running.remove(property);
+                continue;
+            }
+
+            if let Some(before_change) = before_change {
+                if before_change != after_change {
+                    // the last matching item of `transition-property` wins
+                    let index = properties
+                        .0
+                        .iter()
+                        .rposition(|transition| transition.contains(property));
+                    let time_at = |times: &TimeList, index: usize| {
+                        times.get_cycled(index).map(|t| t.ms()).unwrap_or(0.)
+                    };
+                    let running = index.and_then(|index| {
+                        let duration = time_at(&durations, index).max(0.);
+                        let delay = time_at(&delays, index);
+                        if duration + delay <= 0. || duration == 0. {
+                            return None;
+                        }
+                        // a running transition starts the new one from its current value
+                        let from = match transitions.running.get(property) {
+                            Some(running) => running.value_at(now),
+                            None => before_change,
+                        };
+                        Some(RunningTransition {
+                            from,
+                            to: after_change,
+                            start_time: now + delay,
+                            duration,
+                            timing_function: timing_function_at(&timing_functions, index),
+                        })
+                    });
+                    match running {
+                        Some(running) => transitions.running.insert(property.clone(), running),
+                        None => transitions.running.remove(property),
+                    };
+                }
+            }
+
+            let finished = match transitions.running.get(property) {
+                Some(running) if now < running.start_time + running.duration => {
+                    computed.set(property, &running.value_at(now));
+                    false
+                }
+                Some(_) => true,
+                None => false,
+            };
+            if finished {
+                transitions.running.remove(property);
+            }
+        }
+    }
+}
+
+impl RunningTransition {
+    fn value_at(&self, now: f32) -> Value {
+        let progress = ((now - self.start_time) / self.duration).clamp(0., 1.);
+        interpolate(&self.from, &self.to, self.timing_function.apply(progress))
+    }
+}
+
+fn timing_function_at(functions: &TimingFunctionList, index: usize) -> TimingFunction {
+    functions
+        .get_cycled(index)
+        .cloned()
+        .unwrap_or_else(TimingFunction::ease)
+}
+
+/// The value of a property before animations are applied
+fn base_value(
+    property: &Property,
+    properties: &Properties,
+    parent: Option<&Weak<RenderNode>>,
+) -> Value {
+    let inherited = || {
+        parent
+            .and_then(|parent| parent.upgrade())
+            .map(|parent| parent.style.get(property))
+            .unwrap_or_else(|| Value::initial(property))
+    };
+    match properties.get(property) {
+        Some(Some(Value::Initial)) => Value::initial(property),
+        Some(Some(Value::Inherit)) => inherited(),
+        Some(Some(Value::Unset)) | Some(None) | None if INHERITABLES.contains(property) => {
+            inherited()
+        }
+        Some(Some(Value::Unset)) | Some(None) | None => Value::initial(property),
+        Some(Some(value)) => value.clone(),
+    }
+}
+
+/// Parse the declarations of a keyframe, the animation properties
+/// and `!important` declarations are ignored
+/// https://www.w3.org/TR/css-animations-1/#keyframes
+fn parse_keyframe_values(
+    keyframe: &css::cssom::keyframes_rule::Keyframe,
+) -> HashMap<Property, Value> {
+    let mut values = HashMap::new();
+    for declaration in &keyframe.declarations {
+        if declaration.important
+            || declaration.name.starts_with("animation")
+            || declaration.name.starts_with("transition")
+        {
+            continue;
+        }
+        if get_expander_shorthand_property(&declaration.name).is_some() {
+            let expanded = expand_shorthand(&declaration.name, &declaration.value);
+            for (property, value) in expanded.into_iter().flatten() {
+                if let Some(value) = value {
+                    values.insert(property, value);
+                }
+            }
+        } else if let Some(property) = Property::parse(&declaration.name) {
+            if let Some(value) = Value::parse(&property, &declaration.value) {
+                values.insert(property, value);
+            }
+        }
+    }
+    values
+}
+
+/// Get the directed progress of an animation in its current iteration,
+/// or `None` if the animation has no effect at the time
+/// https://www.w3.org/TR/web-animations-1/#calculating-the-simple-iteration-progress
+fn iteration_progress(
+    local_time: f32,
+    duration: f32,
+    iterations: f32,
+    direction: AnimationDirection,
+    fill_mode: FillMode,
+) -> Option<f32> {
+    let active_duration = match duration == 0. {
+        true => 0.,
+        false => duration * iterations,
+    };
+    let overall_progress = if local_time < 0. {
+        if !fill_mode.fills_backwards() {
+            return None;
+        }
+        0.
+    } else if local_time >= active_duration {
+        if !fill_mode.fills_forwards() {
+            return None;
+        }
+        iterations
+    } else {
+        local_time / duration
+    };
+
+    // a zero duration animation repeating forever ends at the end of
+    // an iteration, played forwards for the alternate directions
+    if overall_progress.is_infinite() {
+        return match direction {
+            AnimationDirection::Reverse => Some(0.),
+            _ => Some(1.),
+        };
+    }
+
+    let mut iteration = overall_progress.floor();
+    let mut progress = overall_progress - iteration;
+    // an animation ending at the end of an iteration stays at its end
+    if progress == 0. && overall_progress > 0. && overall_progress == iterations {
+        progress = 1.;
+        iteration -= 1.;
+    }
+
+    let is_reversed = match direction {
+        AnimationDirection::Normal => false,
+        AnimationDirection::Reverse => true,
+        AnimationDirection::Alternate => iteration % 2. == 1.,
+        AnimationDirection::AlternateReverse => iteration % 2. == 0.,
+    };
+    match is_reversed {
+        true => Some(1. - progress),
+        false => Some(progress),
+    }
+}
+
+/// Get the values of the animated properties at the progress. A property
+/// without a `from` or `to` keyframe uses its value before the animation.
+/// https://www.w3.org/TR/css-animations-1/#keyframes
+fn sample<F>(
+    keyframes: &[Keyframe],
+    progress: f32,
+    timing_function: &TimingFunction,
+    base_value: F,
+) -> Vec<(Property, Value)>
+where
+    F: Fn(&Property) -> Value,
+{
+    let mut properties = Vec::new();
+    for keyframe in keyframes {
+        for property in keyframe.values.keys() {
+            if !properties.contains(property) {
+                properties.push(property.clone());
+            }
+        }
+    }
+
+    properties
+        .into_iter()
+        .map(|property| {
+            let mut frames = keyframes
+                .iter()
+                .filter_map(|keyframe| {
+                    keyframe
+                        .values
+                        .get(&property)
+                        .map(|value| (keyframe.offset, value.clone()))
+                })
+                .collect::<Vec<(f32, Value)>>();
+            if frames.first().map(|(offset, _)| *offset) != Some(0.) {
+                frames.insert(0, (0., base_value(&property)));
+            }
+            if frames.last().map(|(offset, _)| *offset) != Some(1.) {
+                frames.push((1., base_value(&property)));
+            }
+
+            let index = frames
+                .iter()
+                .rposition(|(offset, _)| *offset <= progress)
+                .unwrap_or(0)
+                .min(frames.len() - 2);
+            let (from_offset, from) = &frames[index];
+            let (to_offset, to) = &frames[index + 1];
+            let local_progress = match to_offset > from_offset {
+                true => (progress - from_offset) / (to_offset - from_offset),
+                false => 1.,
+            };
+            let value = interpolate(from, to, timing_function.apply(local_progress));
+            (property, value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cascade_layers::UNLAYERED;
+    use crate::tree_builder::TreeBuilder;
+    use crate::value_processing::{CSSLocation, CascadeOrigin, ContextualRule};
+    use crate::values::color::Color;
+    use crate::values::length::Length;
+    use css::cssom::css_rule::CSSRule;
+    use css::cssom::stylesheet::StyleSheet;
+    use dom::element_state::ElementState;
+    use shared::primitive::Size;
+    use test_utils::css::parse_stylesheet;
+    use test_utils::dom_creator::*;
+
+    fn style_at(
+        node: &Rc<Node>,
+        rules: &[ContextualRule],
+        engine: &mut AnimationEngine,
+        time: f32,
+        property: Property,
+    ) -> Value {
+        engine.set_time(time);
+        let tree = TreeBuilder::build_with_animations(node.clone(), rules, Size::default(), engine);
+        tree.root.unwrap().style.get(&property)
+    }
+
+    fn contextual_rules(stylesheet: &StyleSheet) -> Vec<ContextualRule<'_>> {
+        stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn progress_of_zero_duration_animations() {
+        let progress = |iterations: f32, direction: AnimationDirection| {
+            iteration_progress(10., 0., iterations, direction, FillMode::Forwards)
+        };
+        assert_eq!(
+            progress(f32::INFINITY, AnimationDirection::Normal),
+            Some(1.)
+        );
+        assert_eq!(
+            progress(f32::INFINITY, AnimationDirection::Reverse),
+            Some(0.)
+        );
+        assert_eq!(
+            progress(f32::INFINITY, AnimationDirection::AlternateReverse),
+            Some(1.)
+        );
+        assert_eq!(progress(2., AnimationDirection::Alternate), Some(0.));
+        assert_eq!(
+            iteration_progress(
+                10.,
+                0.,
+                f32::INFINITY,
+                AnimationDirection::Normal,
+                FillMode::None
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn run_animations_and_transitions() {
+        let document = document();
+        let dom_tree = element("div#box", document.clone(), vec![]);
+        let css = r#"
+        #box {
+            width: 0px;
+            color: black;
+            transition: color 100ms linear;
+            animation: grow 1s linear 500ms 2 alternate forwards;
+        }
+        #box:hover { color: white; }
+        @keyframes grow {
+            50% { width: 100px; }
+            to { width: 50px; }
+        }
+        "#;
+        let stylesheet = parse_stylesheet(css);
+        let rules = contextual_rules(&stylesheet);
+
+        let mut engine = AnimationEngine::new();
+        engine.set_keyframes(&stylesheet.keyframes.iter().collect::<Vec<_>>());
+
+        let px = |value: f32| Value::Length(Length::new_px(value));
+        let width_at = |engine: &mut AnimationEngine, time: f32| {
+            style_at(&dom_tree, &rules, engine, time, Property::Width)
+        };
+        // the animation starts with the first restyle and is delayed
+        assert_eq!(width_at(&mut engine, 0.), px(0.));
+        assert_eq!(width_at(&mut engine, 750.), px(50.));
+        assert_eq!(width_at(&mut engine, 1250.), px(75.));
+        // the second iteration is reversed
+        assert_eq!(width_at(&mut engine, 1750.), px(75.));
+        // filled forwards with the end of the last iteration
+        assert_eq!(width_at(&mut engine, 3000.), px(0.));
+
+        let color_at = |engine: &mut AnimationEngine, time: f32| {
+            style_at(&dom_tree, &rules, engine, time, Property::Color)
+        };
+        dom_tree.set_state(ElementState::HOVER, true);
+        assert_eq!(color_at(&mut engine, 3000.), Value::Color(Color::black()));
+        assert_eq!(
+            color_at(&mut engine, 3050.),
+            Value::Color(Color::Rgba(
+                127.5.into(),
+                127.5.into(),
+                127.5.into(),
+                255.0.into()
+            ))
+        );
+        assert!(engine.is_active());
+        assert_eq!(
+            color_at(&mut engine, 3100.),
+            Value::Color(Color::Rgba(
+                255.0.into(),
+                255.0.into(),
+                255.0.into(),
+                255.0.into()
+            ))
+        );
+    }
+
+    #[test]
+    fn keep_important_declarations_over_animations() {
+        let document = document();
+        let dom_tree = element("div#box", document.clone(), vec![]);
+        let css = r#"
+        #box {
+            width: 10px !important;
+            height: 0px;
+            animation: grow 1s linear;
+        }
+        @keyframes grow {
+            to { width: 100px; height: 100px; }
+        }
+        "#;
+        let stylesheet = parse_stylesheet(css);
+        let rules = contextual_rules(&stylesheet);
+
+        let mut engine = AnimationEngine::new();
+        engine.set_keyframes(&stylesheet.keyframes.iter().collect::<Vec<_>>());
+
+        let px = |value: f32| Value::Length(Length::new_px(value));
+        style_at(&dom_tree, &rules, &mut engine, 0., Property::Width);
+        assert_eq!(
+            style_at(&dom_tree, &rules, &mut engine, 500., Property::Width),
+            px(10.)
+        );
+        assert_eq!(
+            style_at(&dom_tree, &rules, &mut engine, 500., Property::Height),
+            px(50.)
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use dom::node::Node;
+
+/// Number of bits of a hash used for one key of the filter
+const KEY_BITS: u32 = 12;
+const KEY_MASK: u32 = (1 << KEY_BITS) - 1;
+const ARRAY_SIZE: usize = 1 << KEY_BITS;
+
+/// A counting Bloom filter, entries can be removed as long as
+/// they were inserted before. Each hash is split into two keys.
+/// https://en.wikipedia.org/wiki/Counting_Bloom_filter
+pub struct CountingBloomFilter {
+    counters: Box<[u8; ARRAY_SIZE]>,
+}
+
+/// The tags, IDs and classes of the ancestors of the element being styled,
+/// used to reject selectors requiring an ancestor that doesn't exist
+pub struct AncestorFilter {
+    filter: CountingBloomFilter,
+}
+
+/// The kinds of ancestor selectors tracked, hashed apart from each other
+#[derive(Clone, Copy)]
+pub enum AncestorKey<'a> {
+    Tag(&'a str),
+    ID(&'a str),
+    Class(&'a str),
+}
+
+impl CountingBloomFilter {
+    pub fn new() -> Self {
+        Self {
+            counters: Box::new([0; ARRAY_SIZE]),
+        }
+    }
+
+    fn keys(hash: u32) -> [usize; 2] {
+        [
+            (hash & KEY_MASK) as usize,
+            ((hash >> KEY_BITS) & KEY_MASK) as usize,
+        ]
+    }
+
+    pub fn insert_hash(&mut self, hash: u32) {
+        for key in Self::keys(hash).iter() {
+            let counter = &mut self.counters[*key];
+            *counter = counter.saturating_add(1);
+        }
+    }
+
+    pub fn remove_hash(&mut self, hash: u32) {
+        for key in Self::keys(hash).iter() {
+            let counter = &mut self.counters[*key];
+            // a saturated counter doesn't know its count anymore
+            if *counter != u8::MAX {
+                *counter -= 1;
+            }
+        }
+    }
+
+    /// `false` if the hash was definitely not inserted
+    pub fn might_contain_hash(&self, hash: u32) -> bool {
+        Self::keys(hash).iter().all(|key| self.counters[*key] != 0)
+    }
+}
+
+impl Default for CountingBloomFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> AncestorKey<'a> {
+    /// FNV-1a hash of the key, salted with its kind
+    pub fn hash(&self) -> u32 {
+        let (salt, value) = match self {
+            AncestorKey::Tag(value) => (b't', value),
+            AncestorKey::ID(value) => (b'#', value),
+            AncestorKey::Class(value) => (b'.', value),
+        };
+        std::iter::once(salt)
+            .chain(value.bytes())
+            .fold(0x811c9dc5, |hash: u32, byte| {
+                (hash ^ byte as u32).wrapping_mul(0x01000193)
+            })
+    }
+}
+
+impl AncestorFilter {
+    pub fn new() -> Self {
+        Self {
+            filter: CountingBloomFilter::new(),
+        }
+    }
+
+    fn element_hashes(node: &Node) -> Vec<u32> {
+        let element = node.as_element();
+        let mut hashes = vec![AncestorKey::Tag(&element.tag_name()).hash()];
+        if let Some(id) = element.id() {
+            hashes.push(AncestorKey::ID(&id).hash());
+        }
+        for class in element.class_list().borrow().iter() {
+            hashes.push(AncestorKey::Class(class).hash());
+        }
+        hashes
+    }
+
+    /// Add an element before styling its descendants
+    pub fn push(&mut self, node: &Node) {
+        for hash in Self::element_hashes(node) {
+            self.filter.insert_hash(hash);
+        }
+    }
+
+    /// Remove an element pushed before, once its descendants are styled
+    pub fn pop(&mut self, node: &Node) {
+        for hash in Self::element_hashes(node) {
+            self.filter.remove_hash(hash);
+        }
+    }
+
+    pub fn might_contain_hash(&self, hash: u32) -> bool {
+        self.filter.might_contain_hash(hash)
+    }
+}
+
+impl Default for AncestorFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_and_remove_hashes() {
+        let mut filter = CountingBloomFilter::new();
+        let div = AncestorKey::Tag("div").hash();
+        let main = AncestorKey::ID("main").hash();
+
+        filter.insert_hash(div);
+        filter.insert_hash(div);
+        filter.insert_hash(main);
+        assert!(filter.might_contain_hash(div));
+        assert!(filter.might_contain_hash(main));
+        assert!(!filter.might_contain_hash(AncestorKey::Class("main").hash()));
+
+        filter.remove_hash(div);
+        assert!(filter.might_contain_hash(div));
+        filter.remove_hash(div);
+        filter.remove_hash(main);
+        assert!(!filter.might_contain_hash(div));
+        assert!(!filter.might_contain_hash(main));
+    }
+}
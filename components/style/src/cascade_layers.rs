@@ -0,0 +1,122 @@
+use css::cssom::layer::{LayerName, LayerSegment};
+use std::collections::HashMap;
+
+/// The rank of the rules that are not in a layer, which win
+/// over the layered rules with normal declarations
+pub const UNLAYERED: usize = usize::MAX;
+
+/// A segment of a layer name, with the anonymous layers told apart by
+/// the position of their stylesheet since each stylesheet numbers its own
+type Segment = (LayerSegment, Option<usize>);
+
+struct Layer {
+    segment: Segment,
+    nested: Vec<Layer>,
+}
+
+/// The order of the cascade layers of a document. A layer is ordered
+/// when it's first declared, after the layers declared before it at the
+/// same level, and the rules directly in it come after its nested layers.
+/// https://www.w3.org/TR/css-cascade-5/#layer-ordering
+pub struct LayerOrder {
+    ranks: HashMap<Vec<Segment>, usize>,
+}
+
+impl LayerOrder {
+    /// Order the layers from their names, with the position of the
+    /// stylesheet declaring them, in the order they are declared
+    pub fn new<'a>(names: impl IntoIterator<Item = (usize, &'a LayerName)>) -> Self {
+        let mut layers: Vec<Layer> = Vec::new();
+        for (stylesheet, name) in names {
+            let mut level = &mut layers;
+            for segment in segments(stylesheet, name) {
+                let index = match level.iter().position(|layer| layer.segment == segment) {
+                    Some(index) => index,
+                    None => {
+                        level.push(Layer {
+                            segment,
+                            nested: Vec::new(),
+                        });
+                        level.len() - 1
+                    }
+                };
+                level = &mut level[index].nested;
+            }
+        }
+
+        fn rank(layers: &[Layer], parent: &[Segment], ranks: &mut HashMap<Vec<Segment>, usize>) {
+            for layer in layers {
+                let mut name = parent.to_vec();
+                name.push(layer.segment.clone());
+                rank(&layer.nested, &name, ranks);
+                ranks.insert(name, ranks.len());
+            }
+        }
+        let mut ranks = HashMap::new();
+        rank(&layers, &[], &mut ranks);
+        Self { ranks }
+    }
+
+    /// The rank of a layer of a stylesheet, a rule in a layer
+    /// with a higher rank wins
+    pub fn rank(&self, stylesheet: usize, name: &LayerName) -> usize {
+        self.ranks
+            .get(&segments(stylesheet, name))
+            .copied()
+            .unwrap_or(UNLAYERED)
+    }
+}
+
+fn segments(stylesheet: usize, name: &LayerName) -> Vec<Segment> {
+    name.0
+        .iter()
+        .map(|segment| match segment {
+            LayerSegment::Named(_) => (segment.clone(), None),
+            LayerSegment::Anonymous(_) => (segment.clone(), Some(stylesheet)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_nested_layers() {
+        let name = |names: &[&str]| {
+            LayerName(
+                names
+                    .iter()
+                    .map(|name| LayerSegment::Named(name.to_string()))
+                    .collect(),
+            )
+        };
+        let names = [
+            name(&["base"]),
+            name(&["theme", "dark"]),
+            name(&["base", "reset"]),
+            name(&["theme"]),
+            name(&["base"]),
+        ];
+        let order = LayerOrder::new(names.iter().map(|name| (0, name)));
+
+        assert_eq!(order.rank(0, &name(&["base", "reset"])), 0);
+        assert_eq!(order.rank(0, &name(&["base"])), 1);
+        assert_eq!(order.rank(0, &name(&["theme", "dark"])), 2);
+        assert_eq!(order.rank(0, &name(&["theme"])), 3);
+        assert_eq!(order.rank(0, &LayerName::default()), UNLAYERED);
+    }
+
+    #[test]
+    fn order_anonymous_layers_of_each_stylesheet() {
+        let base = LayerName(vec![LayerSegment::Named("base".to_string())]);
+        let anonymous = LayerName::anonymous(0);
+        let order = LayerOrder::new([(0, &anonymous), (1, &base), (1, &anonymous), (2, &base)]);
+
+        assert_eq!(order.rank(0, &anonymous), 0);
+        assert_eq!(order.rank(1, &base), 1);
+        assert_eq!(order.rank(2, &base), 1);
+        assert_eq!(order.rank(1, &anonymous), 2);
+        assert_eq!(order.rank(2, &anonymous), UNLAYERED);
+    }
+}
@@ -0,0 +1,317 @@
+use crate::property::Property;
+use crate::value::Value;
+use crate::value_processing::{StyleCache, ValueRef};
+use crate::values::prelude::*;
+use std::rc::Rc;
+
+/// A type that the computed values of a property are stored as,
+/// converted from and to the generic `Value`
+pub trait ComputedValue: Sized {
+    fn from_value(value: &Value) -> Option<Self>;
+
+    fn to_value(&self) -> Value;
+}
+
+/// Implement `ComputedValue` for a type stored in one variant of `Value`
+macro_rules! computed_value {
+    ($($type:ty => $variant:ident,)*) => {
+        $(
+            impl ComputedValue for $type {
+                fn from_value(value: &Value) -> Option<Self> {
+                    match value {
+                        Value::$variant(value) => Some(value.clone()),
+                        _ => None,
+                    }
+                }
+
+                fn to_value(&self) -> Value {
+                    Value::$variant(self.clone())
+                }
+            }
+        )*
+    };
+}
+
+computed_value! {
+    Color => Color,
+    Length => Length,
+    Direction => Direction,
+    WritingMode => WritingMode,
+    Display => Display,
+    BorderStyle => BorderStyle,
+    BorderRadius => BorderRadius,
+    Position => Position,
+    Float => Float,
+    Content => Content,
+    CounterList => CounterList,
+    AnimationNameList => AnimationNameList,
+    TimeList => TimeList,
+    TimingFunctionList => TimingFunctionList,
+    IterationCountList => IterationCountList,
+    AnimationDirectionList => AnimationDirectionList,
+    FillModeList => FillModeList,
+    TransitionPropertyList => TransitionPropertyList,
+    Transform => Transform,
+}
+
+impl ComputedValue for LengthPercentageOrAuto {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Length(length) => Some(Self::Length(length.clone())),
+            Value::Percentage(percentage) => Some(Self::Percentage(percentage.clone())),
+            Value::Calc(calc) => Some(Self::Calc(calc.clone())),
+            Value::Auto => Some(Self::Auto),
+            _ => None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            Self::Length(length) => Value::Length(length.clone()),
+            Self::Percentage(percentage) => Value::Percentage(percentage.clone()),
+            Self::Calc(calc) => Value::Calc(calc.clone()),
+            Self::Auto => Value::Auto,
+        }
+    }
+}
+
+impl ComputedValue for LineWidth {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::BorderWidth(keyword) => Some(Self::Keyword(keyword.clone())),
+            Value::Length(length) => Some(Self::Length(length.clone())),
+            _ => None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            Self::Keyword(keyword) => Value::BorderWidth(keyword.clone()),
+            Self::Length(length) => Value::Length(length.clone()),
+        }
+    }
+}
+
+/// Convert the computed value of a property to the type it's stored as.
+/// A value of another type, which computing never produces, falls back
+/// to the initial value.
+fn resolve<T: ComputedValue>(property: Property, value: &Value) -> T {
+    T::from_value(value)
+        .or_else(|| T::from_value(&Value::initial(&property)))
+        .expect("the initial value of a property has its computed type")
+}
+
+/// Declare the computed value groups with a typed field and an accessor
+/// for each property. Inherited properties are in `InheritedValues` and
+/// the rest in `ResetValues`.
+macro_rules! computed_values {
+    (
+        inherited { $($inherited:ident: $inherited_property:ident: $inherited_type:ty,)* }
+        reset { $($reset:ident: $reset_property:ident: $reset_type:ty,)* }
+    ) => {
+        /// Computed values of the inherited properties
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct InheritedValues {
+            $(pub $inherited: $inherited_type,)*
+        }
+
+        /// Computed values of the properties that are not inherited
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct ResetValues {
+            $(pub $reset: $reset_type,)*
+        }
+
+        impl InheritedValues {
+            pub const PROPERTIES: &'static [Property] = &[$(Property::$inherited_property,)*];
+
+            pub fn from_fn<F: FnMut(Property) -> ValueRef>(mut value: F) -> Self {
+                Self {
+                    $($inherited: resolve(
+                        Property::$inherited_property,
+                        &value(Property::$inherited_property),
+                    ),)*
+                }
+            }
+        }
+
+        impl ResetValues {
+            pub const PROPERTIES: &'static [Property] = &[$(Property::$reset_property,)*];
+
+            pub fn from_fn<F: FnMut(Property) -> ValueRef>(mut value: F) -> Self {
+                Self {
+                    $($reset: resolve(
+                        Property::$reset_property,
+                        &value(Property::$reset_property),
+                    ),)*
+                }
+            }
+        }
+
+        impl ComputedValues {
+            $(
+                pub fn $inherited(&self) -> &$inherited_type {
+                    &self.inherited.$inherited
+                }
+            )*
+            $(
+                pub fn $reset(&self) -> &$reset_type {
+                    &self.reset.$reset
+                }
+            )*
+
+            /// The computed value of a property as a generic `Value`
+            pub fn get(&self, property: &Property) -> Value {
+                match property {
+                    $(Property::$inherited_property => self.inherited.$inherited.to_value(),)*
+                    $(Property::$reset_property => self.reset.$reset.to_value(),)*
+                }
+            }
+
+            /// Replace the value of a property, copying its group
+            /// if it's shared with another element. A value of another
+            /// type than the property's is ignored.
+            pub fn set(&mut self, property: &Property, value: &Value) {
+                match property {
+                    $(Property::$inherited_property => {
+                        if let Some(value) = ComputedValue::from_value(value) {
+                            Rc::make_mut(&mut self.inherited).$inherited = value;
+                        }
+                    })*
+                    $(Property::$reset_property => {
+                        if let Some(value) = ComputedValue::from_value(value) {
+                            Rc::make_mut(&mut self.reset).$reset = value;
+                        }
+                    })*
+                }
+            }
+        }
+    };
+}
+
+computed_values! {
+    inherited {
+        color: Color: Color,
+        font_size: FontSize: Length,
+        direction: Direction: Direction,
+        writing_mode: WritingMode: WritingMode,
+    }
+    reset {
+        background_color: BackgroundColor: Color,
+        display: Display: Display,
+        width: Width: LengthPercentageOrAuto,
+        height: Height: LengthPercentageOrAuto,
+        margin_top: MarginTop: LengthPercentageOrAuto,
+        margin_right: MarginRight: LengthPercentageOrAuto,
+        margin_bottom: MarginBottom: LengthPercentageOrAuto,
+        margin_left: MarginLeft: LengthPercentageOrAuto,
+        padding_top: PaddingTop: LengthPercentageOrAuto,
+        padding_right: PaddingRight: LengthPercentageOrAuto,
+        padding_bottom: PaddingBottom: LengthPercentageOrAuto,
+        padding_left: PaddingLeft: LengthPercentageOrAuto,
+        border_top_width: BorderTopWidth: LineWidth,
+        border_right_width: BorderRightWidth: LineWidth,
+        border_bottom_width: BorderBottomWidth: LineWidth,
+        border_left_width: BorderLeftWidth: LineWidth,
+        border_bottom_style: BorderBottomStyle: BorderStyle,
+        border_left_style: BorderLeftStyle: BorderStyle,
+        border_right_style: BorderRightStyle: BorderStyle,
+        border_top_style: BorderTopStyle: BorderStyle,
+        border_top_color: BorderTopColor: Color,
+        border_right_color: BorderRightColor: Color,
+        border_bottom_color: BorderBottomColor: Color,
+        border_left_color: BorderLeftColor: Color,
+        border_top_left_radius: BorderTopLeftRadius: BorderRadius,
+        border_top_right_radius: BorderTopRightRadius: BorderRadius,
+        border_bottom_left_radius: BorderBottomLeftRadius: BorderRadius,
+        border_bottom_right_radius: BorderBottomRightRadius: BorderRadius,
+        position: Position: Position,
+        float: Float: Float,
+        left: Left: LengthPercentageOrAuto,
+        right: Right: LengthPercentageOrAuto,
+        top: Top: LengthPercentageOrAuto,
+        bottom: Bottom: LengthPercentageOrAuto,
+        content: Content: Content,
+        counter_reset: CounterReset: CounterList,
+        counter_increment: CounterIncrement: CounterList,
+        animation_name: AnimationName: AnimationNameList,
+        animation_duration: AnimationDuration: TimeList,
+        animation_timing_function: AnimationTimingFunction: TimingFunctionList,
+        animation_delay: AnimationDelay: TimeList,
+        animation_iteration_count: AnimationIterationCount: IterationCountList,
+        animation_direction: AnimationDirection: AnimationDirectionList,
+        animation_fill_mode: AnimationFillMode: FillModeList,
+        transition_property: TransitionProperty: TransitionPropertyList,
+        transition_duration: TransitionDuration: TimeList,
+        transition_timing_function: TransitionTimingFunction: TimingFunctionList,
+        transition_delay: TransitionDelay: TimeList,
+        transform: Transform: Transform,
+    }
+}
+
+/// The computed values of an element. Each group is shared with
+/// the parent or with the initial values when it's unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedValues {
+    pub inherited: Rc<InheritedValues>,
+    pub reset: Rc<ResetValues>,
+}
+
+impl ComputedValues {
+    /// The initial values of every property, with `currentColor`
+    /// computed to the initial `color`
+    pub fn initial(cache: &mut StyleCache) -> Self {
+        let color = match Value::initial(&Property::Color) {
+            Value::Color(color) => color,
+            _ => Color::black(),
+        };
+        let mut initial = |property: Property| match Value::initial(&property) {
+            Value::Color(value) => cache.get(&Value::Color(value.resolve(&color))),
+            value => cache.get(&value),
+        };
+        Self {
+            inherited: Rc::new(InheritedValues::from_fn(&mut initial)),
+            reset: Rc::new(ResetValues::from_fn(&mut initial)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inheritable::INHERITABLES;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn group_every_property() {
+        for property in Property::iter() {
+            assert_eq!(
+                InheritedValues::PROPERTIES.contains(&property),
+                INHERITABLES.contains(&property)
+            );
+            assert_ne!(
+                InheritedValues::PROPERTIES.contains(&property),
+                ResetValues::PROPERTIES.contains(&property)
+            );
+        }
+
+        let mut cache = StyleCache::new();
+        let mut values = ComputedValues::initial(&mut cache);
+        let shared = values.clone();
+        let width = Value::Length(Length::new_px(1.));
+        values.set(&Property::Width, &width);
+        assert!(Rc::ptr_eq(&values.inherited, &shared.inherited));
+        assert!(!Rc::ptr_eq(&values.reset, &shared.reset));
+        assert_eq!(
+            values.width(),
+            &LengthPercentageOrAuto::Length(Length::new_px(1.))
+        );
+
+        // a value of another type is ignored
+        values.set(&Property::Width, &Value::Color(Color::black()));
+        assert_eq!(values.get(&Property::Width), width);
+        assert_eq!(
+            values.get(&Property::BorderTopWidth),
+            Value::BorderWidth(BorderWidth::Medium)
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use crate::property::Property;
+use crate::value::Value;
+use crate::value_processing::ComputeContext;
+use crate::value_processing::ValueRef;
+use crate::values::calc::{Calc, CalcNode};
+use crate::values::length::Length;
+
+use super::length::length_context;
+
+/// Compute a math function by resolving relative lengths
+/// and simplifying the expression. Percentages are kept because they
+/// can only be resolved during layout.
+pub fn compute_calc(property: &Property, calc: &Calc, context: &mut ComputeContext) -> ValueRef {
+    let length_context = length_context(property, context);
+    let font_size = length_context.font_size;
+    let simplified = calc.simplify(&|length: &Length| Some(length.resolve(&length_context)));
+
+    let value = match simplified {
+        CalcNode::Length(length) => Value::Length(length),
+        CalcNode::Percentage(percentage) if *property == Property::FontSize => {
+            Value::Length(Length::new_px(percentage.to_px(font_size)))
+        }
+        CalcNode::Percentage(percentage) => Value::Percentage(percentage),
+        // the parser only accepts length-percentages, a number is
+        // taken as px rather than failing the whole style
+        CalcNode::Number(number) => Value::Length(Length::new_px(*number)),
+        node if *property == Property::FontSize => {
+            Value::Length(Length::new_px(Calc(node).to_px(font_size)))
+        }
+        node => Value::Calc(Calc(node)),
+    };
+    context.style_cache.get(&value)
+}
@@ -4,17 +4,41 @@ use crate::value_processing::ComputeContext;
 use crate::value_processing::ValueRef;
 use crate::values::color::Color;
 
-pub fn compute_color(value: &Value, context: &mut ComputeContext) -> ValueRef {
+/// Resolve `currentColor` in a color value. It's the color of
+/// the parent for `color` and the color of the element otherwise.
+/// https://www.w3.org/TR/css-color-4/#resolving-other-colors
+pub fn compute_color(property: &Property, value: &Value, context: &mut ComputeContext) -> ValueRef {
     match value {
-        Value::Color(Color::CurrentColor) => {
-            if let Some(parent) = &context.parent {
-                if let Some(p) = parent.upgrade() {
-                    return p.get_style(&Property::Color);
-                }
-            }
-            let value = Value::initial(&Property::Color);
+        Value::Color(color) if color.uses_current_color() => {
+            let current_color = match property {
+                Property::Color => parent_color(context),
+                _ => current_color(context),
+            };
+            let value = Value::Color(color.resolve(&current_color));
             context.style_cache.get(&value)
         }
         _ => context.style_cache.get(value),
     }
 }
+
+/// The computed `color` of the parent, or the initial color for the root
+fn parent_color(context: &ComputeContext) -> Color {
+    let parent = context.parent.as_ref().and_then(|parent| parent.upgrade());
+    match parent {
+        Some(parent) => parent.style.color().clone(),
+        None => match Value::initial(&Property::Color) {
+            Value::Color(color) => color,
+            _ => Color::black(),
+        },
+    }
+}
+
+/// The computed `color` of the element, which is inherited when
+/// the element doesn't declare it
+fn current_color(context: &ComputeContext) -> Color {
+    let parent_color = parent_color(context);
+    match context.properties.get(&Property::Color) {
+        Some(Value::Color(color)) => color.resolve(&parent_color),
+        _ => parent_color,
+    }
+}
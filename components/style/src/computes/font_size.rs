@@ -1,59 +1,28 @@
+use super::calc::compute_calc;
+use super::length::length_context;
 use crate::property::Property;
 use crate::value::Value;
 use crate::value_processing::ComputeContext;
 use crate::value_processing::ValueRef;
 use crate::values::length::Length;
-use crate::values::length::LengthUnit;
 
-const BASE_FONT_SIZE: f32 = 16.; // 16px
+pub const BASE_FONT_SIZE: f32 = 16.; // 16px
 
 pub fn compute_font_size(value: &Value, context: &mut ComputeContext) -> ValueRef {
-    match value {
-        Value::Percentage(percentage) => {
-            let parent_font_size = context
-                .parent
-                .as_ref()
-                .map(|parent| {
-                    if let Some(p) = parent.upgrade() {
-                        return Some(p.get_style(&Property::FontSize).to_absolute_px());
-                    }
-                    None
-                })
-                .unwrap_or_default();
-            let value = parent_font_size
-                .map(|font_size| Value::Length(Length::new_px(percentage.to_px(font_size))))
-                // TODO: investigate what will happen if we set percentage in font-size for
-                // HTML element
-                .unwrap_or(Value::Length(Length::new_px(
-                    percentage.to_px(BASE_FONT_SIZE),
-                )));
-            context.style_cache.get(&value)
-        }
-        Value::Length(Length {
-            value: _,
-            unit: LengthUnit::Px,
-        }) => context.style_cache.get(value),
-        Value::Length(Length {
-            value,
-            unit: LengthUnit::Em,
-        }) => {
-            let parent_font_size = context
-                .parent
-                .as_ref()
-                .map(|parent| {
-                    if let Some(p) = parent.upgrade() {
-                        return Some(p.get_style(&Property::FontSize).to_absolute_px());
-                    }
-                    None
-                })
-                .unwrap_or_default();
-            let value = parent_font_size
-                .map(|font_size| Value::Length(Length::new_px(value.0 * font_size)))
-                // TODO: investigate what will happen if we set percentage in font-size for
-                // HTML element
-                .unwrap_or(Value::Length(Length::new_px(value.0 * BASE_FONT_SIZE)));
-            context.style_cache.get(&value)
-        }
-        _ => unreachable!("Invalid value provided for font-size"),
+    if let Value::Calc(calc) = value {
+        return compute_calc(&Property::FontSize, calc, context);
     }
+
+    // relative lengths and percentages refer to the parent font size,
+    // which is the initial font size for the root element
+    let length_context = length_context(&Property::FontSize, context);
+    let font_size = match value {
+        Value::Percentage(percentage) => percentage.to_px(length_context.font_size),
+        Value::Length(length) => length.resolve(&length_context),
+        // TODO: support the font size keywords, `auto` is inherited until then
+        _ => length_context.font_size,
+    };
+    context
+        .style_cache
+        .get(&Value::Length(Length::new_px(font_size)))
 }
@@ -0,0 +1,121 @@
+use crate::property::Property;
+use crate::value::Value;
+use crate::value_processing::ComputeContext;
+use crate::value_processing::ValueRef;
+use crate::values::border_radius::BorderRadius;
+use crate::values::length::{Length, LengthContext};
+use crate::values::length_percentage::LengthPercentage;
+use crate::values::transform::{Transform, TransformFunction};
+use crate::values::writing_mode::WritingMode;
+
+use super::font_size::{compute_font_size, BASE_FONT_SIZE};
+
+/// Resolve the lengths of a value to px. Percentages are kept because
+/// they can only be resolved during layout.
+/// https://www.w3.org/TR/css-values-4/#lengths
+pub fn compute_length(
+    property: &Property,
+    value: &Value,
+    context: &mut ComputeContext,
+) -> ValueRef {
+    let length_context = length_context(property, context);
+    let resolve = |length: &Length| Length::new_px(length.resolve(&length_context));
+    let resolve_percentage = |length: &LengthPercentage| match length {
+        LengthPercentage::Length(length) => LengthPercentage::Length(resolve(length)),
+        percentage => percentage.clone(),
+    };
+
+    let value = match value {
+        Value::Length(length) => Value::Length(resolve(length)),
+        Value::BorderRadius(BorderRadius(horizontal, vertical)) => Value::BorderRadius(
+            BorderRadius(resolve_percentage(horizontal), resolve_percentage(vertical)),
+        ),
+        Value::Transform(Transform(functions)) => Value::Transform(Transform(
+            functions
+                .iter()
+                .map(|function| match function {
+                    TransformFunction::Translate(x, y) => {
+                        TransformFunction::Translate(resolve(x), resolve(y))
+                    }
+                    function => function.clone(),
+                })
+                .collect(),
+        )),
+        value => value.clone(),
+    };
+    context.style_cache.get(&value)
+}
+
+/// The sizes that the relative lengths of a property are resolved against
+pub fn length_context(property: &Property, context: &ComputeContext) -> LengthContext {
+    match property {
+        // font-relative lengths in font-size refer to the parent font size
+        Property::FontSize => LengthContext {
+            font_size: parent_font_size(context),
+            ..context.lengths.clone()
+        },
+        _ => context.lengths.clone(),
+    }
+}
+
+/// The sizes that the relative lengths of the element are resolved
+/// against, computed once before the values of the element
+pub fn element_length_context(context: &mut ComputeContext) -> LengthContext {
+    context.lengths = LengthContext {
+        font_size: parent_font_size(context),
+        root_font_size: root_font_size(context),
+        viewport: context.viewport.clone(),
+        vertical: writing_mode(context).is_vertical(),
+    };
+    let font_size = match context.properties.get(&Property::FontSize).cloned() {
+        Some(font_size) => compute_font_size(&font_size, context).to_absolute_px(),
+        None => context.lengths.font_size,
+    };
+    LengthContext {
+        font_size,
+        ..context.lengths.clone()
+    }
+}
+
+/// The writing mode of the element, which is inherited when
+/// the element doesn't declare it
+fn writing_mode(context: &ComputeContext) -> WritingMode {
+    let parent = context.parent.as_ref().and_then(|parent| parent.upgrade());
+    let writing_mode = match context.properties.get(&Property::WritingMode) {
+        Some(writing_mode) => writing_mode.clone(),
+        None => match parent {
+            Some(parent) => Value::WritingMode(parent.style.writing_mode().clone()),
+            None => Value::initial(&Property::WritingMode),
+        },
+    };
+    match writing_mode {
+        Value::WritingMode(writing_mode) => writing_mode,
+        _ => WritingMode::HorizontalTb,
+    }
+}
+
+fn parent_font_size(context: &ComputeContext) -> f32 {
+    context
+        .parent
+        .as_ref()
+        .and_then(|parent| parent.upgrade())
+        .map(|parent| parent.style.font_size().to_px())
+        .unwrap_or(BASE_FONT_SIZE)
+}
+
+/// The font size of the root element, which is the initial
+/// font size when computing the root element itself
+fn root_font_size(context: &ComputeContext) -> f32 {
+    let mut root = match context.parent.as_ref().and_then(|parent| parent.upgrade()) {
+        Some(parent) => parent,
+        None => return BASE_FONT_SIZE,
+    };
+    while let Some(parent) = root
+        .parent_render_node
+        .as_ref()
+        .and_then(|parent| parent.upgrade())
+    {
+        root = parent;
+    }
+    root.style.font_size().to_px()
+}
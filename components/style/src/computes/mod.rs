@@ -1,2 +1,4 @@
+pub mod calc;
 pub mod color;
 pub mod font_size;
+pub mod length;
@@ -0,0 +1,420 @@
+use crate::expand::expand_shorthand;
+use crate::logical::expand_logical_shorthand;
+use crate::property::Property;
+use crate::value::Value;
+use crate::value_processing::Properties;
+use crate::values::unparsed::UnparsedValue;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Computed custom properties of an element, stored as token streams
+/// https://www.w3.org/TR/css-variables-1/#defining-variables
+pub type CustomProperties = HashMap<String, Vec<ComponentValue>>;
+
+/// Check if a property name is a custom property name (`--*`)
+pub fn is_custom_property(name: &str) -> bool {
+    name.starts_with("--")
+}
+
+/// Check if the component values contain any `var()` reference
+pub fn has_references(values: &[ComponentValue]) -> bool {
+    values.iter().any(|value| match value {
+        ComponentValue::Function(function) => {
+            function.name.eq_ignore_ascii_case("var") || has_references(&function.value)
+        }
+        ComponentValue::SimpleBlock(block) => has_references(&block.value),
+        _ => false,
+    })
+}
+
+/// Trim leading and trailing whitespace tokens
+pub fn trim_whitespace(values: &[ComponentValue]) -> &[ComponentValue] {
+    let is_whitespace =
+        |value: &ComponentValue| matches!(value, ComponentValue::PerservedToken(Token::Whitespace));
+    let start = values
+        .iter()
+        .position(|value| !is_whitespace(value))
+        .unwrap_or(values.len());
+    let end = values
+        .iter()
+        .rposition(|value| !is_whitespace(value))
+        .map(|index| index + 1)
+        .unwrap_or(start);
+    &values[start..end]
+}
+
+/// Split the arguments of a `var()` function into the referenced
+/// custom property name and the optional fallback
+fn parse_var_function(arguments: &[ComponentValue]) -> Option<(String, Option<&[ComponentValue]>)> {
+    let arguments = trim_whitespace(arguments);
+    let name = match arguments.first() {
+        Some(ComponentValue::PerservedToken(Token::Ident(name))) if is_custom_property(name) => {
+            name.clone()
+        }
+        _ => return None,
+    };
+    let rest = trim_whitespace(&arguments[1..]);
+    match rest.first() {
+        None => Some((name, None)),
+        Some(ComponentValue::PerservedToken(Token::Comma)) => {
+            Some((name, Some(trim_whitespace(&rest[1..]))))
+        }
+        _ => None,
+    }
+}
+
+/// Collect the names of every custom property referenced by the values,
+/// including the ones referenced in fallbacks
+fn collect_references(values: &[ComponentValue], result: &mut Vec<String>) {
+    for value in values {
+        match value {
+            ComponentValue::Function(function) => {
+                if function.name.eq_ignore_ascii_case("var") {
+                    if let Some((name, _)) = parse_var_function(&function.value) {
+                        result.push(name);
+                    }
+                }
+                collect_references(&function.value, result);
+            }
+            ComponentValue::SimpleBlock(block) => collect_references(&block.value, result),
+            _ => {}
+        }
+    }
+}
+
+/// Replace every `var()` in the values with the value of the referenced
+/// custom property, or its fallback if the custom property is not defined.
+///
+/// Returns `None` if the values are invalid at computed-value time.
+/// https://www.w3.org/TR/css-variables-1/#substitute-a-var
+pub fn substitute_references<F>(
+    values: &[ComponentValue],
+    lookup: &mut F,
+) -> Option<Vec<ComponentValue>>
+where
+    F: FnMut(&str) -> Option<Vec<ComponentValue>>,
+{
+    let mut result = Vec::new();
+    for value in values {
+        match value {
+            ComponentValue::Function(function) if function.name.eq_ignore_ascii_case("var") => {
+                let (name, fallback) = parse_var_function(&function.value)?;
+                match lookup(&name) {
+                    Some(tokens) => result.extend(tokens),
+                    None => {
+                        let fallback = fallback?;
+                        result.extend(substitute_references(fallback, lookup)?);
+                    }
+                }
+            }
+            ComponentValue::Function(function) => {
+                let mut function = function.clone();
+                function.value = substitute_references(&function.value, lookup)?;
+                result.push(ComponentValue::Function(function));
+            }
+            ComponentValue::SimpleBlock(block) => {
+                let mut block = block.clone();
+                block.value = substitute_references(&block.value, lookup)?;
+                result.push(ComponentValue::SimpleBlock(block));
+            }
+            value => result.push(value.clone()),
+        }
+    }
+    Some(result)
+}
+
+/// Find custom properties that are part of a dependency cycle
+/// using Tarjan's strongly connected components algorithm.
+fn find_cyclic_properties(graph: &HashMap<&str, Vec<String>>) -> HashSet<String> {
+    struct State<'a> {
+        graph: &'a HashMap<&'a str, Vec<String>>,
+        index: usize,
+        indices: HashMap<&'a str, usize>,
+        low_links: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: HashSet<&'a str>,
+        cyclic: HashSet<String>,
+    }
+
+    fn connect<'a>(state: &mut State<'a>, node: &'a str) {
+        state.indices.insert(node, state.index);
+        state.low_links.insert(node, state.index);
+        state.index += 1;
+        state.stack.push(node);
+        state.on_stack.insert(node);
+
+        let graph = state.graph;
+        for dependency in &graph[node] {
+            let dependency = match graph.get_key_value(dependency.as_str()) {
+                Some((key, _)) => *key,
+                // references to undeclared properties are resolved from the parent
+                None => continue,
+            };
+            if !state.indices.contains_key(dependency) {
+                connect(state, dependency);
+                let low_link = state.low_links[node].min(state.low_links[dependency]);
+                state.low_links.insert(node, low_link);
+            } else if state.on_stack.contains(dependency) {
+                let low_link = state.low_links[node].min(state.indices[dependency]);
+                state.low_links.insert(node, low_link);
+            }
+        }
+
+        if state.low_links[node] == state.indices[node] {
+            let mut component = Vec::new();
+            while let Some(member) = state.stack.pop() {
+                state.on_stack.remove(member);
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            let is_self_referencing = graph[node].iter().any(|dep| dep == node);
+            if component.len() > 1 || is_self_referencing {
+                state
+                    .cyclic
+                    .extend(component.into_iter().map(|member| member.to_string()));
+            }
+        }
+    }
+
+    let mut state = State {
+        graph,
+        index: 0,
+        indices: HashMap::new(),
+        low_links: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        cyclic: HashSet::new(),
+    };
+
+    for node in graph.keys() {
+        if !state.indices.contains_key(node) {
+            connect(&mut state, node);
+        }
+    }
+
+    state.cyclic
+}
+
+/// Compute the custom properties of an element from the cascaded custom
+/// property values and the computed custom properties of the parent.
+///
+/// Custom properties are inherited, so the parent map is shared when the
+/// element does not declare any custom property.
+pub fn compute_custom_properties(
+    declared: HashMap<String, Vec<ComponentValue>>,
+    parent: Option<&Rc<CustomProperties>>,
+) -> Rc<CustomProperties> {
+    if declared.is_empty() {
+        return match parent {
+            Some(parent) => parent.clone(),
+            None => Rc::new(CustomProperties::new()),
+        };
+    }
+
+    let inherited = |name: &str| parent.and_then(|parent| parent.get(name).cloned());
+
+    // Explicit defaulting
+    // https://www.w3.org/TR/css-variables-1/#defining-variables
+    let declared = declared
+        .into_iter()
+        .map(|(name, value)| {
+            let keyword = match trim_whitespace(&value) {
+                [ComponentValue::PerservedToken(Token::Ident(keyword))] => Some(keyword.clone()),
+                _ => None,
+            };
+            match keyword {
+                Some(k) if k.eq_ignore_ascii_case("initial") => (name, None),
+                Some(k) if k.eq_ignore_ascii_case("inherit") || k.eq_ignore_ascii_case("unset") => {
+                    let value = inherited(&name);
+                    (name, value)
+                }
+                _ => (name, Some(value)),
+            }
+        })
+        .collect::<HashMap<String, Option<Vec<ComponentValue>>>>();
+
+    let graph = declared
+        .iter()
+        .map(|(name, value)| {
+            let mut references = Vec::new();
+            if let Some(value) = value {
+                collect_references(value, &mut references);
+            }
+            (name.as_str(), references)
+        })
+        .collect::<HashMap<&str, Vec<String>>>();
+    let cyclic = find_cyclic_properties(&graph);
+
+    fn resolve(
+        name: &str,
+        declared: &HashMap<String, Option<Vec<ComponentValue>>>,
+        cyclic: &HashSet<String>,
+        parent: Option<&Rc<CustomProperties>>,
+        resolved: &mut HashMap<String, Option<Vec<ComponentValue>>>,
+    ) -> Option<Vec<ComponentValue>> {
+        if let Some(value) = resolved.get(name) {
+            return value.clone();
+        }
+        let value = match declared.get(name) {
+            Some(_) if cyclic.contains(name) => None,
+            Some(Some(value)) if has_references(value) => {
+                substitute_references(value, &mut |reference| {
+                    resolve(reference, declared, cyclic, parent, resolved)
+                })
+            }
+            Some(value) => value.clone(),
+            None => return parent.and_then(|parent| parent.get(name).cloned()),
+        };
+        resolved.insert(name.to_string(), value.clone());
+        value
+    }
+
+    let mut resolved = HashMap::new();
+    let mut result = match parent {
+        Some(parent) => (**parent).clone(),
+        None => CustomProperties::new(),
+    };
+
+    for name in declared.keys() {
+        match resolve(name, &declared, &cyclic, parent, &mut resolved) {
+            Some(value) => result.insert(name.clone(), value),
+            // invalid at computed-value time, fall back to the guaranteed-invalid value
+            None => result.remove(name),
+        };
+    }
+
+    Rc::new(result)
+}
+
+/// Resolve an unparsed value of a property by substituting its `var()`
+/// references and parsing the result.
+///
+/// Returns `None` if the value is invalid at computed-value time.
+pub fn resolve_unparsed_value(
+    property: &Property,
+    value: &UnparsedValue,
+    custom_properties: &CustomProperties,
+) -> Option<Value> {
+    let tokens = substitute_references(&value.tokens, &mut |name| {
+        custom_properties.get(name).cloned()
+    })?;
+    let tokens = trim_whitespace(&tokens);
+
+    if tokens.is_empty() {
+        return None;
+    }
+
+    match (&value.shorthand, value.logical_longhand) {
+        (Some(shorthand), Some(logical_longhand)) => expand_logical_shorthand(shorthand, tokens)?
+            .into_iter()
+            .find(|(longhand, _)| *longhand == logical_longhand)
+            .map(|(_, value)| value),
+        (Some(shorthand), None) => expand_shorthand(shorthand, tokens)?
+            .into_iter()
+            .find(|(longhand, _)| longhand == property)
+            .and_then(|(_, value)| value),
+        (None, _) => Value::parse(property, tokens),
+    }
+}
+
+/// Substitute `var()` references in all of the cascaded values.
+///
+/// A value that is invalid at computed-value time behaves like `unset`.
+/// https://www.w3.org/TR/css-variables-1/#invalid-at-computed-value-time
+pub fn substitute_properties(
+    properties: Properties,
+    custom_properties: &CustomProperties,
+) -> Properties {
+    properties
+        .into_iter()
+        .map(|(property, value)| match value {
+            Some(Value::Unparsed(unparsed)) => {
+                let value = resolve_unparsed_value(&property, &unparsed, custom_properties)
+                    .unwrap_or(Value::Unset);
+                (property, Some(value))
+            }
+            value => (property, value),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::values::color::Color;
+    use css::parser::Parser;
+    use css::tokenizer::Tokenizer;
+
+    fn tokens(css: &str) -> Vec<ComponentValue> {
+        // trailing whitespace so the last token is not cut at the end of input
+        let css = format!("{} ", css);
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        trim_whitespace(&parser.parse_a_list_of_component_values()).to_vec()
+    }
+
+    #[test]
+    fn substitute_with_fallback() {
+        let mut custom = CustomProperties::new();
+        custom.insert("--main".to_string(), tokens("red"));
+
+        let value = UnparsedValue::new(tokens("var(--main)"), None);
+        assert_eq!(
+            resolve_unparsed_value(&Property::Color, &value, &custom),
+            Some(Value::Color(Color::Rgba(
+                255.0.into(),
+                0.0.into(),
+                0.0.into(),
+                255.0.into()
+            )))
+        );
+
+        let value = UnparsedValue::new(tokens("var(--missing, var(--main))"), None);
+        assert!(resolve_unparsed_value(&Property::Color, &value, &custom).is_some());
+
+        let value = UnparsedValue::new(tokens("var(--missing)"), None);
+        assert_eq!(
+            resolve_unparsed_value(&Property::Color, &value, &custom),
+            None
+        );
+    }
+
+    #[test]
+    fn substitute_in_shorthand() {
+        let mut custom = CustomProperties::new();
+        custom.insert("--gap".to_string(), tokens("10px 20px"));
+
+        let value = UnparsedValue::new(tokens("var(--gap)"), Some("margin".to_string()));
+        assert_eq!(
+            resolve_unparsed_value(&Property::MarginLeft, &value, &custom),
+            Value::parse(&Property::MarginLeft, &tokens("20px"))
+        );
+    }
+
+    #[test]
+    fn compute_with_cycles() {
+        let mut declared = HashMap::new();
+        declared.insert("--a".to_string(), tokens("var(--b, 1px)"));
+        declared.insert("--b".to_string(), tokens("var(--a, 2px)"));
+        declared.insert("--c".to_string(), tokens("var(--a, 3px)"));
+        declared.insert("--d".to_string(), tokens("var(--d)"));
+        declared.insert("--e".to_string(), tokens("var(--inherited)"));
+
+        let mut parent = CustomProperties::new();
+        parent.insert("--inherited".to_string(), tokens("4px"));
+        parent.insert("--a".to_string(), tokens("5px"));
+
+        let computed = compute_custom_properties(declared, Some(&Rc::new(parent)));
+
+        assert_eq!(computed.get("--a"), None);
+        assert_eq!(computed.get("--b"), None);
+        assert_eq!(computed.get("--c"), Some(&tokens("3px")));
+        assert_eq!(computed.get("--d"), None);
+        assert_eq!(computed.get("--e"), Some(&tokens("4px")));
+        assert_eq!(computed.get("--inherited"), Some(&tokens("4px")));
+    }
+}
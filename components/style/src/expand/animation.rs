@@ -0,0 +1,118 @@
+use super::ExpandOutput;
+use crate::property::Property;
+use crate::value::Value;
+use crate::values::animation::*;
+use crate::values::time::Time;
+use crate::values::timing_function::TimingFunction;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// Split the whitespace separated values of a shorthand into its
+/// comma separated layers
+pub fn split_layers(values: &[&[ComponentValue]]) -> Vec<Vec<ComponentValue>> {
+    let mut layers = vec![Vec::new()];
+    for value in values.iter().flat_map(|values| values.iter()) {
+        match value {
+            ComponentValue::PerservedToken(Token::Comma) => layers.push(Vec::new()),
+            ComponentValue::PerservedToken(Token::Whitespace) => {}
+            value => layers.last_mut().unwrap().push(value.clone()),
+        }
+    }
+    layers
+}
+
+/// Set the value at the first empty slot
+fn set_once<T>(slot: &mut Option<T>, value: Option<T>) -> bool {
+    match (slot.is_none(), value) {
+        (true, Some(value)) => {
+            *slot = Some(value);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// https://www.w3.org/TR/css-animations-1/#animation
+pub fn expand_animation(values: &[&[ComponentValue]]) -> ExpandOutput {
+    let mut names = Vec::new();
+    let mut durations = Vec::new();
+    let mut timing_functions = Vec::new();
+    let mut delays = Vec::new();
+    let mut iteration_counts = Vec::new();
+    let mut directions = Vec::new();
+    let mut fill_modes = Vec::new();
+
+    for layer in split_layers(values) {
+        let mut name = None;
+        let mut duration = None;
+        let mut timing_function = None;
+        let mut delay = None;
+        let mut iteration_count = None;
+        let mut direction = None;
+        let mut fill_mode = None;
+        let mut play_state = None;
+
+        for value in layer.chunks(1) {
+            if let Some(time) = Time::parse(value) {
+                if !set_once(&mut duration, Some(time.clone())) && !set_once(&mut delay, Some(time))
+                {
+                    return None;
+                }
+                continue;
+            }
+            let is_play_state = matches!(
+                value,
+                [ComponentValue::PerservedToken(Token::Ident(ident))]
+                    if ident.eq_ignore_ascii_case("running") || ident.eq_ignore_ascii_case("paused")
+            );
+            let is_parsed = set_once(&mut timing_function, TimingFunction::parse(value))
+                || set_once(&mut iteration_count, IterationCount::parse_item(value))
+                || set_once(&mut direction, AnimationDirection::parse_item(value))
+                || set_once(&mut fill_mode, FillMode::parse_item(value))
+                || (is_play_state && set_once(&mut play_state, Some(())))
+                || set_once(&mut name, AnimationName::parse_item(value));
+            if !is_parsed {
+                return None;
+            }
+        }
+
+        names.push(name.unwrap_or(AnimationName::None));
+        durations.push(duration.unwrap_or_else(Time::zero));
+        timing_functions.push(timing_function.unwrap_or_else(TimingFunction::ease));
+        delays.push(delay.unwrap_or_else(Time::zero));
+        iteration_counts.push(iteration_count.unwrap_or(IterationCount::Number(1.0.into())));
+        directions.push(direction.unwrap_or(AnimationDirection::Normal));
+        fill_modes.push(fill_mode.unwrap_or(FillMode::None));
+    }
+
+    Some(vec![
+        (
+            Property::AnimationName,
+            Some(Value::AnimationNameList(List(names))),
+        ),
+        (
+            Property::AnimationDuration,
+            Some(Value::TimeList(List(durations))),
+        ),
+        (
+            Property::AnimationTimingFunction,
+            Some(Value::TimingFunctionList(List(timing_functions))),
+        ),
+        (
+            Property::AnimationDelay,
+            Some(Value::TimeList(List(delays))),
+        ),
+        (
+            Property::AnimationIterationCount,
+            Some(Value::IterationCountList(List(iteration_counts))),
+        ),
+        (
+            Property::AnimationDirection,
+            Some(Value::AnimationDirectionList(List(directions))),
+        ),
+        (
+            Property::AnimationFillMode,
+            Some(Value::FillModeList(List(fill_modes))),
+        ),
+    ])
+}
@@ -0,0 +1,28 @@
+use super::ExpandOutput;
+use crate::property::Property;
+use crate::value::Value;
+use css::parser::structs::ComponentValue;
+
+/// https://www.w3.org/TR/css-logical-1/#propdef-inset
+pub fn expand_inset(values: &[&[ComponentValue]]) -> ExpandOutput {
+    let values = values
+        .iter()
+        .map(|value| Value::parse(&Property::Top, value))
+        .collect::<Option<Vec<Value>>>()?;
+
+    // omitted sides are copied from the opposite side
+    let (top, right, bottom, left) = match &values[..] {
+        [all] => (all, all, all, all),
+        [y, x] => (y, x, y, x),
+        [top, x, bottom] => (top, x, bottom, x),
+        [top, right, bottom, left] => (top, right, bottom, left),
+        _ => return None,
+    };
+
+    Some(vec![
+        (Property::Top, Some(top.clone())),
+        (Property::Right, Some(right.clone())),
+        (Property::Bottom, Some(bottom.clone())),
+        (Property::Left, Some(left.clone())),
+    ])
+}
@@ -1,23 +1,135 @@
 use crate::property::Property;
 use crate::value::Value;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
 
 pub type ExpandOutput = Option<Vec<(Property, Option<Value>)>>;
 
+pub type Expander = dyn Fn(&[&[ComponentValue]]) -> ExpandOutput;
+
+mod animation;
 mod border;
 mod border_color;
 mod border_radius;
 mod border_style;
 mod border_width;
+mod inset;
 mod margin;
 mod padding;
+mod transition;
 
 pub(crate) mod prelude {
+    pub use super::animation::expand_animation;
     pub use super::border::expand_border;
     pub use super::border_color::expand_border_color;
     pub use super::border_radius::expand_border_radius;
     pub use super::border_style::expand_border_style;
     pub use super::border_width::expand_border_width;
+    pub use super::inset::expand_inset;
     pub use super::margin::expand_margin;
     pub use super::padding::expand_padding;
-    pub use super::ExpandOutput;
+    pub use super::transition::expand_transition;
+}
+
+use prelude::*;
+
+/// Get a short-hand property expander
+pub fn get_expander_shorthand_property(property: &str) -> Option<&Expander> {
+    match property {
+        "margin" => Some(&expand_margin),
+        "padding" => Some(&expand_padding),
+        "border" => Some(&expand_border),
+        "border-style" => Some(&expand_border_style),
+        "border-width" => Some(&expand_border_width),
+        "border-color" => Some(&expand_border_color),
+        "border-radius" => Some(&expand_border_radius),
+        "inset" => Some(&expand_inset),
+        "animation" => Some(&expand_animation),
+        "transition" => Some(&expand_transition),
+        _ => None,
+    }
+}
+
+/// Get the long-hand properties that a short-hand property sets
+pub fn get_shorthand_longhands(property: &str) -> Option<Vec<Property>> {
+    let longhands = match property {
+        "margin" => vec![
+            Property::MarginTop,
+            Property::MarginRight,
+            Property::MarginBottom,
+            Property::MarginLeft,
+        ],
+        "padding" => vec![
+            Property::PaddingTop,
+            Property::PaddingRight,
+            Property::PaddingBottom,
+            Property::PaddingLeft,
+        ],
+        "border-style" => vec![
+            Property::BorderTopStyle,
+            Property::BorderRightStyle,
+            Property::BorderBottomStyle,
+            Property::BorderLeftStyle,
+        ],
+        "border-width" => vec![
+            Property::BorderTopWidth,
+            Property::BorderRightWidth,
+            Property::BorderBottomWidth,
+            Property::BorderLeftWidth,
+        ],
+        "border-color" => vec![
+            Property::BorderTopColor,
+            Property::BorderRightColor,
+            Property::BorderBottomColor,
+            Property::BorderLeftColor,
+        ],
+        "border-radius" => vec![
+            Property::BorderTopLeftRadius,
+            Property::BorderTopRightRadius,
+            Property::BorderBottomLeftRadius,
+            Property::BorderBottomRightRadius,
+        ],
+        "inset" => vec![
+            Property::Top,
+            Property::Right,
+            Property::Bottom,
+            Property::Left,
+        ],
+        "animation" => vec![
+            Property::AnimationName,
+            Property::AnimationDuration,
+            Property::AnimationTimingFunction,
+            Property::AnimationDelay,
+            Property::AnimationIterationCount,
+            Property::AnimationDirection,
+            Property::AnimationFillMode,
+        ],
+        "transition" => vec![
+            Property::TransitionProperty,
+            Property::TransitionDuration,
+            Property::TransitionTimingFunction,
+            Property::TransitionDelay,
+        ],
+        "border" => ["border-style", "border-width", "border-color"]
+            .iter()
+            .filter_map(|shorthand| get_shorthand_longhands(shorthand))
+            .flatten()
+            .collect(),
+        _ => return None,
+    };
+    Some(longhands)
+}
+
+/// Expand a short-hand property from its whitespace separated values
+pub fn expand_shorthand(property: &str, values: &[ComponentValue]) -> ExpandOutput {
+    let expand = get_expander_shorthand_property(property)?;
+    expand(&split_values(values))
+}
+
+/// Split the value of a short-hand property on whitespace
+pub(crate) fn split_values(values: &[ComponentValue]) -> Vec<&[ComponentValue]> {
+    values
+        .split(|val| matches!(val, ComponentValue::PerservedToken(Token::Whitespace)))
+        .filter(|tokens| !tokens.is_empty())
+        .collect()
 }
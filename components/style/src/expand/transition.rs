@@ -0,0 +1,69 @@
+use super::animation::split_layers;
+use super::ExpandOutput;
+use crate::property::Property;
+use crate::value::Value;
+use crate::values::animation::*;
+use crate::values::time::Time;
+use crate::values::timing_function::TimingFunction;
+use css::parser::structs::ComponentValue;
+
+/// https://www.w3.org/TR/css-transitions-1/#transition-shorthand-property
+pub fn expand_transition(values: &[&[ComponentValue]]) -> ExpandOutput {
+    let mut properties = Vec::new();
+    let mut durations = Vec::new();
+    let mut timing_functions = Vec::new();
+    let mut delays = Vec::new();
+
+    for layer in split_layers(values) {
+        let mut property = None;
+        let mut duration = None;
+        let mut timing_function = None;
+        let mut delay = None;
+
+        for value in layer.chunks(1) {
+            if let Some(time) = Time::parse(value) {
+                match (&duration, &delay) {
+                    (None, _) => duration = Some(time),
+                    (Some(_), None) => delay = Some(time),
+                    _ => return None,
+                }
+            } else if let Some(function) = TimingFunction::parse(value) {
+                match timing_function {
+                    None => timing_function = Some(function),
+                    Some(_) => return None,
+                }
+            } else if let Some(name) = TransitionProperty::parse_item(value) {
+                match property {
+                    None => property = Some(name),
+                    Some(_) => return None,
+                }
+            } else {
+                return None;
+            }
+        }
+
+        properties.push(property.unwrap_or(TransitionProperty::All));
+        durations.push(duration.unwrap_or_else(Time::zero));
+        timing_functions.push(timing_function.unwrap_or_else(TimingFunction::ease));
+        delays.push(delay.unwrap_or_else(Time::zero));
+    }
+
+    Some(vec![
+        (
+            Property::TransitionProperty,
+            Some(Value::TransitionPropertyList(List(properties))),
+        ),
+        (
+            Property::TransitionDuration,
+            Some(Value::TimeList(List(durations))),
+        ),
+        (
+            Property::TransitionTimingFunction,
+            Some(Value::TimingFunctionList(List(timing_functions))),
+        ),
+        (
+            Property::TransitionDelay,
+            Some(Value::TimeList(List(delays))),
+        ),
+    ])
+}
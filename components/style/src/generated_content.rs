@@ -0,0 +1,92 @@
+use crate::render_tree::RenderNode;
+use crate::values::content::ContentItem;
+use dom::element::Element;
+
+/// The quotes used for `open-quote` and `close-quote`, by nesting level.
+/// https://www.w3.org/TR/css-content-3/#quotes
+const DEFAULT_QUOTES: [(&str, &str); 2] = [("\u{201C}", "\u{201D}"), ("\u{2018}", "\u{2019}")];
+
+/// Counters and quote nesting level tracked in tree order
+/// while building the render tree
+/// https://www.w3.org/TR/css-lists-3/#creating-a-counter
+#[derive(Debug, Default)]
+pub struct GeneratedContent {
+    /// The counter instances in scope, innermost last
+    counters: Vec<(String, i32)>,
+    quote_depth: usize,
+}
+
+impl GeneratedContent {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Start the scope of the counters created by the children of a node
+    pub fn enter_scope(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Drop the counters created since the scope started
+    pub fn leave_scope(&mut self, scope: usize) {
+        self.counters.truncate(scope);
+    }
+
+    /// Apply `counter-reset` then `counter-increment` of a render node
+    pub fn update_counters(&mut self, render_node: &RenderNode) {
+        for (name, value) in &render_node.style.counter_reset().0 {
+            self.counters.push((name.clone(), value.unwrap_or(0)));
+        }
+
+        for (name, value) in &render_node.style.counter_increment().0 {
+            let increment = value.unwrap_or(1);
+            match self.counters.iter_mut().rev().find(|(n, _)| n == name) {
+                Some((_, counter)) => *counter = counter.saturating_add(increment),
+                // incrementing a counter that is not in scope instantiates it
+                None => self.counters.push((name.clone(), increment)),
+            }
+        }
+    }
+
+    /// Get the value of the innermost counter with the name
+    pub fn counter(&self, name: &str) -> i32 {
+        self.counters
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| *value)
+            .unwrap_or(0)
+    }
+
+    /// Generate the text of the `content` items for an element
+    pub fn generate_text(&mut self, items: &[ContentItem], element: &Element) -> String {
+        let mut text = String::new();
+        for item in items {
+            match item {
+                ContentItem::String(value) => text.push_str(value),
+                ContentItem::Attr(name) => {
+                    text.push_str(&element.get_attribute(name).unwrap_or_default())
+                }
+                ContentItem::Counter(name, style) => {
+                    text.push_str(&style.format(self.counter(name)))
+                }
+                ContentItem::OpenQuote => {
+                    text.push_str(quote_at(self.quote_depth).0);
+                    self.quote_depth += 1;
+                }
+                ContentItem::CloseQuote => {
+                    if self.quote_depth > 0 {
+                        self.quote_depth -= 1;
+                        text.push_str(quote_at(self.quote_depth).1);
+                    }
+                }
+                ContentItem::NoOpenQuote => self.quote_depth += 1,
+                ContentItem::NoCloseQuote => self.quote_depth = self.quote_depth.saturating_sub(1),
+            }
+        }
+        text
+    }
+}
+
+fn quote_at(depth: usize) -> (&'static str, &'static str) {
+    DEFAULT_QUOTES[depth.min(DEFAULT_QUOTES.len() - 1)]
+}
@@ -6,6 +6,8 @@ lazy_static! {
         let mut set = HashSet::new();
         set.insert(Property::Color);
         set.insert(Property::FontSize);
+        set.insert(Property::Direction);
+        set.insert(Property::WritingMode);
         set
     };
 }
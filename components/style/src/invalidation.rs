@@ -0,0 +1,351 @@
+use crate::selector_matching::get_element_state;
+use css::cssom::style_rule::StyleRule;
+use css::selector::structs::*;
+use dom::element_state::ElementState;
+use dom::node::Node;
+use dom::restyle::{DirtyFlags, StyleChange};
+use std::collections::HashMap;
+use std::ops::{BitOr, BitOrAssign};
+use std::rc::Rc;
+
+/// The elements whose style may depend on a class, ID, attribute
+/// or state of an element, relative to that element
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Scope(u8);
+
+impl Scope {
+    /// The element itself
+    pub const SELF: Self = Self(1 << 0);
+    /// The element and its descendants
+    pub const DESCENDANTS: Self = Self(1 << 1);
+    /// The following siblings of the element and their descendants
+    pub const SIBLINGS: Self = Self(1 << 2);
+    /// The ancestors of the element and the siblings of the element
+    /// and of its ancestors, for `:has()`
+    pub const ANCESTORS: Self = Self(1 << 3);
+    /// Every element of the document
+    pub const DOCUMENT: Self = Self(1 << 4);
+
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitOr for Scope {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Scope {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.insert(rhs);
+    }
+}
+
+/// The selector dependencies of the style rules: which elements have to
+/// be restyled when a class, ID, attribute or state of an element changes,
+/// or when its children are inserted or removed
+#[derive(Debug, Default)]
+pub struct InvalidationMap {
+    classes: HashMap<String, Scope>,
+    ids: HashMap<String, Scope>,
+    attributes: HashMap<String, Scope>,
+    states: HashMap<ElementState, Scope>,
+    child_list: Scope,
+    /// Whether a rule declares `counter-reset` or `counter-increment`.
+    /// Counters depend on every element before in tree order so the
+    /// whole render tree is built again instead.
+    pub uses_counters: bool,
+    /// Whether a rule declares `content`, which may read any attribute
+    uses_content: bool,
+}
+
+impl InvalidationMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_rule(&mut self, rule: &StyleRule) {
+        for selector in &rule.selectors {
+            self.add_selector(selector, Scope::SELF);
+        }
+        let declares = |property: &str| !rule.declarations.get_property_value(property).is_empty();
+        self.uses_counters |= declares("counter-reset") || declares("counter-increment");
+        self.uses_content |= declares("content");
+    }
+
+    /// Add the dependencies of a selector whose subject is in the scope
+    fn add_selector(&mut self, selector: &Selector, subject: Scope) {
+        let mut scope = subject;
+        // from right to left, the combinator of a compound is on its right
+        for (sequence, combinator) in selector.values().iter().rev() {
+            match combinator {
+                Some(Combinator::Descendant) | Some(Combinator::Child) => {
+                    scope |= Scope::DESCENDANTS
+                }
+                Some(Combinator::NextSibling) | Some(Combinator::SubsequentSibling) => {
+                    scope |= Scope::SIBLINGS;
+                    self.child_list |= Scope::DESCENDANTS;
+                }
+                None => {}
+            }
+            self.add_compound(sequence, scope);
+        }
+    }
+
+    fn add_compound(&mut self, sequence: &SimpleSelectorSequence, scope: Scope) {
+        for simple in sequence.values() {
+            match (simple.selector_type(), simple.value()) {
+                (SimpleSelectorType::Class, Some(class)) => {
+                    *self.classes.entry(class.to_string()).or_default() |= scope
+                }
+                (SimpleSelectorType::ID, Some(id)) => {
+                    *self.ids.entry(id.to_string()).or_default() |= scope
+                }
+                (SimpleSelectorType::Attribute(attribute), _) => {
+                    *self.attributes.entry(attribute.name.clone()).or_default() |= scope
+                }
+                (SimpleSelectorType::PseudoClass(pseudo_class), _) => {
+                    self.add_pseudo_class(pseudo_class, scope)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn add_pseudo_class(&mut self, pseudo_class: &PseudoClass, scope: Scope) {
+        match pseudo_class {
+            PseudoClass::Is(selectors)
+            | PseudoClass::Where(selectors)
+            | PseudoClass::Not(selectors) => {
+                for selector in selectors {
+                    self.add_selector(selector, scope);
+                }
+            }
+            PseudoClass::NthChild(_, selectors) | PseudoClass::NthLastChild(_, selectors) => {
+                for selector in selectors {
+                    self.add_selector(selector, scope | Scope::SIBLINGS);
+                }
+                self.child_list |= Scope::DESCENDANTS;
+            }
+            PseudoClass::Has(relatives) => {
+                // only the subject is cheap to find from the changed element
+                let subject = scope == Scope::SELF;
+                let has_scope = match subject {
+                    true => Scope::ANCESTORS,
+                    false => Scope::DOCUMENT,
+                };
+                for relative in relatives {
+                    self.add_selector(&relative.selector, has_scope);
+                }
+                self.child_list |= match subject {
+                    true => Scope::SELF | Scope::ANCESTORS,
+                    false => Scope::DOCUMENT,
+                };
+            }
+            PseudoClass::Empty => self.child_list |= scope,
+            PseudoClass::FirstChild
+            | PseudoClass::LastChild
+            | PseudoClass::OnlyChild
+            | PseudoClass::FirstOfType
+            | PseudoClass::LastOfType
+            | PseudoClass::OnlyOfType
+            | PseudoClass::NthOfType(_)
+            | PseudoClass::NthLastOfType(_) => self.child_list |= Scope::DESCENDANTS,
+            _ => {
+                if let Some(state) = get_element_state(pseudo_class) {
+                    *self.states.entry(state).or_default() |= scope;
+                }
+            }
+        }
+    }
+
+    /// The elements to restyle, relative to the changed element
+    pub fn scope_of(&self, change: &StyleChange) -> Scope {
+        let get = |map: &HashMap<String, Scope>, name: &str| map.get(name).copied();
+        match change {
+            StyleChange::Class(class) => get(&self.classes, class).unwrap_or_default(),
+            StyleChange::ID(id) => get(&self.ids, id).unwrap_or_default(),
+            StyleChange::Attribute(name) => {
+                let mut scope = get(&self.attributes, name).unwrap_or_default();
+                if name == "style" || self.uses_content {
+                    scope |= Scope::SELF;
+                }
+                scope
+            }
+            StyleChange::State(changed) => self
+                .states
+                .iter()
+                .filter(|(state, _)| changed.intersects(**state))
+                .fold(Scope::empty(), |result, (_, scope)| result | *scope),
+            StyleChange::ChildList => self.child_list,
+        }
+    }
+
+    /// Turn the pending style changes of the nodes into the dirty flags
+    /// of the elements to restyle
+    pub fn invalidate(&self, node: &Rc<Node>) {
+        let flags = node.dirty_flags();
+        if flags.contains(DirtyFlags::CHANGED) {
+            let scope = node
+                .take_style_changes()
+                .iter()
+                .fold(Scope::empty(), |scope, change| {
+                    scope | self.scope_of(change)
+                });
+            mark_scope(node, scope);
+        }
+        if flags.contains(DirtyFlags::DESCENDANTS) {
+            for child in node.child_nodes().into_iter() {
+                self.invalidate(&child);
+            }
+        }
+    }
+}
+
+fn mark_scope(node: &Rc<Node>, scope: Scope) {
+    if scope.contains(Scope::DOCUMENT) {
+        let mut root = node.clone();
+        while let Some(parent) = root.parent() {
+            root = parent;
+        }
+        root.mark_dirty(DirtyFlags::SUBTREE);
+        return;
+    }
+
+    if scope.contains(Scope::DESCENDANTS) {
+        node.mark_dirty(DirtyFlags::SUBTREE);
+    } else if scope.contains(Scope::SELF) {
+        node.mark_dirty(DirtyFlags::STYLE);
+    }
+    if scope.contains(Scope::SIBLINGS) {
+        let mut sibling = node.next_sibling();
+        while let Some(current) = sibling {
+            current.mark_dirty(DirtyFlags::SUBTREE);
+            sibling = current.next_sibling();
+        }
+    }
+    if scope.contains(Scope::ANCESTORS) {
+        let mut ancestor = Some(node.clone());
+        while let Some(current) = ancestor {
+            if !Rc::ptr_eq(&current, node) {
+                current.mark_dirty(DirtyFlags::STYLE);
+            }
+            if let Some(parent) = current.parent() {
+                for sibling in parent.child_nodes().into_iter() {
+                    if !Rc::ptr_eq(&sibling, &current) {
+                        sibling.mark_dirty(DirtyFlags::STYLE);
+                    }
+                }
+            }
+            ancestor = current.parent();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use css::cssom::css_rule::CSSRule;
+    use test_utils::css::parse_stylesheet;
+    use test_utils::dom_creator::*;
+
+    #[test]
+    fn collect_selector_dependencies() {
+        let css = r#"
+        .a { color: red; }
+        .b p { color: red; }
+        .c + p { color: red; }
+        div:has(> .d) { color: red; }
+        :is(.e) .f { color: red; }
+        a[href] { color: red; }
+        input:checked { color: red; }
+        "#;
+        let stylesheet = parse_stylesheet(css);
+        let mut map = InvalidationMap::new();
+        for rule in stylesheet.iter() {
+            match rule {
+                CSSRule::Style(style) => map.add_rule(style),
+            }
+        }
+
+        let scope = |change: StyleChange| map.scope_of(&change);
+        let class = |name: &str| scope(StyleChange::Class(name.to_string()));
+        assert_eq!(class("a"), Scope::SELF);
+        assert_eq!(class("b"), Scope::SELF | Scope::DESCENDANTS);
+        assert_eq!(class("c"), Scope::SELF | Scope::SIBLINGS);
+        assert_eq!(class("d"), Scope::ANCESTORS);
+        assert_eq!(class("e"), Scope::SELF | Scope::DESCENDANTS);
+        assert_eq!(class("f"), Scope::SELF);
+        assert_eq!(class("g"), Scope::empty());
+        assert_eq!(scope(StyleChange::ID("a".to_string())), Scope::empty());
+        assert_eq!(
+            scope(StyleChange::Attribute("href".to_string())),
+            Scope::SELF
+        );
+        assert_eq!(
+            scope(StyleChange::Attribute("style".to_string())),
+            Scope::SELF
+        );
+        assert_eq!(
+            scope(StyleChange::State(ElementState::CHECKED)),
+            Scope::SELF
+        );
+        assert_eq!(
+            scope(StyleChange::ChildList),
+            Scope::SELF | Scope::DESCENDANTS | Scope::ANCESTORS
+        );
+        assert!(!map.uses_counters);
+    }
+
+    #[test]
+    fn mark_elements_in_scope() {
+        let document = document();
+        let dom_tree = element(
+            "div",
+            document.clone(),
+            vec![
+                element("p.first", document.clone(), vec![]),
+                element("p.second", document.clone(), vec![]),
+                element("p.third", document.clone(), vec![]),
+            ],
+        );
+        let children = dom_tree.child_nodes().into_iter().collect::<Vec<_>>();
+        dom_tree.clear_dirty();
+        for child in &children {
+            child.clear_dirty();
+        }
+
+        let css = ".second + p { color: red; } .third { color: red; }";
+        let stylesheet = parse_stylesheet(css);
+        let mut map = InvalidationMap::new();
+        for rule in stylesheet.iter() {
+            match rule {
+                CSSRule::Style(style) => map.add_rule(style),
+            }
+        }
+
+        children[1].add_style_change(StyleChange::Class("second".to_string()));
+        map.invalidate(&dom_tree);
+        assert!(children[0].dirty_flags().is_empty());
+        assert!(children[1].dirty_flags().contains(DirtyFlags::STYLE));
+        assert!(children[2].dirty_flags().contains(DirtyFlags::SUBTREE));
+        assert!(dom_tree.dirty_flags().contains(DirtyFlags::DESCENDANTS));
+        assert!(children[1].take_style_changes().is_empty());
+    }
+}
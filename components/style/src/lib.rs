@@ -1,10 +1,21 @@
+pub mod animation;
+pub mod bloom;
+pub mod cascade_layers;
+pub mod computed_values;
 pub mod computes;
+pub mod custom_properties;
 pub mod expand;
+pub mod generated_content;
 pub mod inheritable;
+pub mod invalidation;
+pub mod logical;
 pub mod property;
 pub mod render_tree;
+pub mod rule_index;
 pub mod selector_matching;
+pub mod style_sharing;
 pub mod tree_builder;
+pub mod validation;
 pub mod value;
 pub mod value_processing;
 pub mod values;
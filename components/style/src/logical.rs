@@ -0,0 +1,413 @@
+use crate::expand::prelude::expand_border;
+use crate::expand::split_values;
+use crate::property::Property;
+use crate::value::Value;
+use crate::values::direction::Direction;
+use crate::values::writing_mode::WritingMode;
+use css::parser::structs::ComponentValue;
+
+/// A side of a box relative to the writing mode
+/// https://www.w3.org/TR/css-writing-modes-4/#logical-directions
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum LogicalSide {
+    BlockStart,
+    BlockEnd,
+    InlineStart,
+    InlineEnd,
+}
+
+/// A dimension of a box relative to the writing mode
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum LogicalAxis {
+    Block,
+    Inline,
+}
+
+/// A flow-relative property that sets the physical property of
+/// the side or dimension it maps to
+/// https://www.w3.org/TR/css-logical-1/
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum LogicalProperty {
+    Margin(LogicalSide),
+    Padding(LogicalSide),
+    Inset(LogicalSide),
+    BorderWidth(LogicalSide),
+    BorderStyle(LogicalSide),
+    BorderColor(LogicalSide),
+    Size(LogicalAxis),
+}
+
+/// The constructor of a long-hand property for a side
+type SideLonghand = fn(LogicalSide) -> LogicalProperty;
+
+/// The long-hand properties set by a flow-relative border shorthand,
+/// with the physical property whose value is the same on every side
+const BORDER_LONGHANDS: [(SideLonghand, Property); 3] = [
+    (LogicalProperty::BorderWidth, Property::BorderTopWidth),
+    (LogicalProperty::BorderStyle, Property::BorderTopStyle),
+    (LogicalProperty::BorderColor, Property::BorderTopColor),
+];
+
+/// The physical sides in top, right, bottom, left order
+const TOP: usize = 0;
+const RIGHT: usize = 1;
+const BOTTOM: usize = 2;
+const LEFT: usize = 3;
+
+impl LogicalSide {
+    fn parse(side: &str) -> Option<Self> {
+        match side {
+            "block-start" => Some(LogicalSide::BlockStart),
+            "block-end" => Some(LogicalSide::BlockEnd),
+            "inline-start" => Some(LogicalSide::InlineStart),
+            "inline-end" => Some(LogicalSide::InlineEnd),
+            _ => None,
+        }
+    }
+
+    /// The start and end sides of an axis
+    fn of_axis(axis: LogicalAxis) -> [LogicalSide; 2] {
+        match axis {
+            LogicalAxis::Block => [LogicalSide::BlockStart, LogicalSide::BlockEnd],
+            LogicalAxis::Inline => [LogicalSide::InlineStart, LogicalSide::InlineEnd],
+        }
+    }
+
+    /// The index of the physical side in top, right, bottom, left order
+    /// https://www.w3.org/TR/css-writing-modes-4/#logical-to-physical
+    fn physical_index(self, writing_mode: &WritingMode, direction: &Direction) -> usize {
+        let (block_start, inline_start) = match writing_mode {
+            WritingMode::HorizontalTb => (TOP, LEFT),
+            WritingMode::VerticalRl | WritingMode::SidewaysRl => (RIGHT, TOP),
+            WritingMode::VerticalLr => (LEFT, TOP),
+            WritingMode::SidewaysLr => (LEFT, BOTTOM),
+        };
+        let inline_start = match direction {
+            Direction::Ltr => inline_start,
+            Direction::Rtl => (inline_start + 2) % 4,
+        };
+        match self {
+            LogicalSide::BlockStart => block_start,
+            LogicalSide::BlockEnd => (block_start + 2) % 4,
+            LogicalSide::InlineStart => inline_start,
+            LogicalSide::InlineEnd => (inline_start + 2) % 4,
+        }
+    }
+}
+
+impl LogicalAxis {
+    fn parse(axis: &str) -> Option<Self> {
+        match axis {
+            "block" => Some(LogicalAxis::Block),
+            "inline" => Some(LogicalAxis::Inline),
+            _ => None,
+        }
+    }
+}
+
+impl LogicalProperty {
+    pub fn parse(property: &str) -> Option<Self> {
+        if let Some(axis) = property.strip_suffix("-size") {
+            return LogicalAxis::parse(axis).map(LogicalProperty::Size);
+        }
+        if let Some(side) = property.strip_prefix("margin-") {
+            return LogicalSide::parse(side).map(LogicalProperty::Margin);
+        }
+        if let Some(side) = property.strip_prefix("padding-") {
+            return LogicalSide::parse(side).map(LogicalProperty::Padding);
+        }
+        if let Some(side) = property.strip_prefix("inset-") {
+            return LogicalSide::parse(side).map(LogicalProperty::Inset);
+        }
+        let (side, component) = property.strip_prefix("border-")?.rsplit_once('-')?;
+        let side = LogicalSide::parse(side)?;
+        match component {
+            "width" => Some(LogicalProperty::BorderWidth(side)),
+            "style" => Some(LogicalProperty::BorderStyle(side)),
+            "color" => Some(LogicalProperty::BorderColor(side)),
+            _ => None,
+        }
+    }
+
+    /// The physical property that the property maps to
+    pub fn to_physical(self, writing_mode: &WritingMode, direction: &Direction) -> Property {
+        let sides = match self {
+            LogicalProperty::Size(axis) => {
+                let is_width = (axis == LogicalAxis::Inline) != writing_mode.is_vertical();
+                return match is_width {
+                    true => Property::Width,
+                    false => Property::Height,
+                };
+            }
+            LogicalProperty::Margin(_) => [
+                Property::MarginTop,
+                Property::MarginRight,
+                Property::MarginBottom,
+                Property::MarginLeft,
+            ],
+            LogicalProperty::Padding(_) => [
+                Property::PaddingTop,
+                Property::PaddingRight,
+                Property::PaddingBottom,
+                Property::PaddingLeft,
+            ],
+            LogicalProperty::Inset(_) => [
+                Property::Top,
+                Property::Right,
+                Property::Bottom,
+                Property::Left,
+            ],
+            LogicalProperty::BorderWidth(_) => [
+                Property::BorderTopWidth,
+                Property::BorderRightWidth,
+                Property::BorderBottomWidth,
+                Property::BorderLeftWidth,
+            ],
+            LogicalProperty::BorderStyle(_) => [
+                Property::BorderTopStyle,
+                Property::BorderRightStyle,
+                Property::BorderBottomStyle,
+                Property::BorderLeftStyle,
+            ],
+            LogicalProperty::BorderColor(_) => [
+                Property::BorderTopColor,
+                Property::BorderRightColor,
+                Property::BorderBottomColor,
+                Property::BorderLeftColor,
+            ],
+        };
+        let [top, right, bottom, left] = sides;
+        match self.side().physical_index(writing_mode, direction) {
+            TOP => top,
+            RIGHT => right,
+            BOTTOM => bottom,
+            _ => left,
+        }
+    }
+
+    fn side(self) -> LogicalSide {
+        match self {
+            LogicalProperty::Margin(side)
+            | LogicalProperty::Padding(side)
+            | LogicalProperty::Inset(side)
+            | LogicalProperty::BorderWidth(side)
+            | LogicalProperty::BorderStyle(side)
+            | LogicalProperty::BorderColor(side) => side,
+            LogicalProperty::Size(LogicalAxis::Block) => LogicalSide::BlockStart,
+            LogicalProperty::Size(LogicalAxis::Inline) => LogicalSide::InlineStart,
+        }
+    }
+
+    /// Parse the value of the property, which has the same
+    /// syntax as the physical properties it maps to
+    pub fn parse_value(self, values: &[ComponentValue]) -> Option<Value> {
+        let physical = self.to_physical(&WritingMode::HorizontalTb, &Direction::Ltr);
+        Value::parse(&physical, values)
+    }
+}
+
+/// Expand a flow-relative short-hand property, e.g. `margin-block`
+/// sets `margin-block-start` and `margin-block-end`
+pub fn expand_logical_shorthand(
+    property: &str,
+    values: &[ComponentValue],
+) -> Option<Vec<(LogicalProperty, Value)>> {
+    let values = split_values(values);
+
+    // border-block, border-inline-start...
+    if let Some(sides) = border_shorthand_sides(property) {
+        // the physical expansion of `border` sets the same values on every side
+        let expanded = expand_border(&values)?;
+        return Some(
+            BORDER_LONGHANDS
+                .iter()
+                .flat_map(|(longhand, physical)| {
+                    // the omitted components are reset to their initial values
+                    let value = expanded
+                        .iter()
+                        .find(|(property, _)| property == physical)
+                        .and_then(|(_, value)| value.clone())
+                        .unwrap_or_else(|| Value::initial(physical));
+                    sides
+                        .iter()
+                        .map(move |side| (longhand(*side), value.clone()))
+                })
+                .collect(),
+        );
+    }
+
+    // margin-block, padding-inline, border-block-width...
+    let (axis, longhand) = axis_shorthand(property)?;
+    let [start, end] = LogicalSide::of_axis(axis).map(longhand);
+
+    // the end side is the same as the start side when it's omitted
+    let (start_value, end_value) = match values[..] {
+        [value] => (start.parse_value(value)?, end.parse_value(value)?),
+        [start_value, end_value] => (start.parse_value(start_value)?, end.parse_value(end_value)?),
+        _ => return None,
+    };
+    Some(vec![(start, start_value), (end, end_value)])
+}
+
+/// The long-hand properties set by a flow-relative short-hand property
+pub fn get_logical_shorthand_longhands(property: &str) -> Option<Vec<LogicalProperty>> {
+    if let Some(sides) = border_shorthand_sides(property) {
+        return Some(
+            BORDER_LONGHANDS
+                .iter()
+                .flat_map(|(longhand, _)| sides.iter().map(move |side| longhand(*side)))
+                .collect(),
+        );
+    }
+    let (axis, longhand) = axis_shorthand(property)?;
+    Some(LogicalSide::of_axis(axis).map(longhand).to_vec())
+}
+
+/// The sides set by `border-block`, `border-inline` or a
+/// flow-relative side shorthand like `border-block-start`
+fn border_shorthand_sides(property: &str) -> Option<Vec<LogicalSide>> {
+    let sides = property.strip_prefix("border-")?;
+    match LogicalAxis::parse(sides) {
+        Some(axis) => Some(LogicalSide::of_axis(axis).to_vec()),
+        None => LogicalSide::parse(sides).map(|side| vec![side]),
+    }
+}
+
+/// The axis and the long-hand properties of a shorthand that
+/// sets both sides of an axis, e.g. `padding-inline`
+fn axis_shorthand(property: &str) -> Option<(LogicalAxis, SideLonghand)> {
+    let (axis, longhand): (&str, SideLonghand) = match property.split_once('-')? {
+        ("margin", axis) => (axis, LogicalProperty::Margin),
+        ("padding", axis) => (axis, LogicalProperty::Padding),
+        ("inset", axis) => (axis, LogicalProperty::Inset),
+        ("border", rest) => match rest.split_once('-')? {
+            (axis, "width") => (axis, LogicalProperty::BorderWidth),
+            (axis, "style") => (axis, LogicalProperty::BorderStyle),
+            (axis, "color") => (axis, LogicalProperty::BorderColor),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some((LogicalAxis::parse(axis)?, longhand))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::values::border_style::BorderStyle;
+    use crate::values::border_width::BorderWidth;
+    use crate::values::color::Color;
+    use crate::values::length::Length;
+    use css::parser::Parser;
+    use css::tokenizer::token::Token;
+    use css::tokenizer::Tokenizer;
+
+    fn expand(property: &str, css: &str) -> Option<Vec<(LogicalProperty, Value)>> {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        expand_logical_shorthand(property, &parser.parse_a_list_of_component_values())
+    }
+
+    #[test]
+    fn map_to_physical_properties() {
+        let margin = |side| LogicalProperty::Margin(side);
+        let horizontal = &WritingMode::HorizontalTb;
+        let vertical = &WritingMode::VerticalRl;
+
+        let property = margin(LogicalSide::InlineStart);
+        assert_eq!(
+            property.to_physical(horizontal, &Direction::Ltr),
+            Property::MarginLeft
+        );
+        assert_eq!(
+            property.to_physical(horizontal, &Direction::Rtl),
+            Property::MarginRight
+        );
+        assert_eq!(
+            property.to_physical(vertical, &Direction::Ltr),
+            Property::MarginTop
+        );
+
+        let property = LogicalProperty::parse("border-block-end-color").unwrap();
+        assert_eq!(
+            property.to_physical(horizontal, &Direction::Rtl),
+            Property::BorderBottomColor
+        );
+        assert_eq!(
+            property.to_physical(vertical, &Direction::Ltr),
+            Property::BorderLeftColor
+        );
+        assert_eq!(
+            property.to_physical(&WritingMode::VerticalLr, &Direction::Ltr),
+            Property::BorderRightColor
+        );
+
+        let property = LogicalProperty::parse("inline-size").unwrap();
+        assert_eq!(
+            property.to_physical(horizontal, &Direction::Ltr),
+            Property::Width
+        );
+        assert_eq!(
+            property.to_physical(vertical, &Direction::Ltr),
+            Property::Height
+        );
+        assert_eq!(LogicalProperty::parse("margin-block"), None);
+        assert_eq!(LogicalProperty::parse("border-block-start"), None);
+    }
+
+    #[test]
+    fn expand_logical_shorthands() {
+        let px = |value| Value::Length(Length::new_px(value));
+        assert_eq!(
+            expand("margin-inline", "1px 2px"),
+            Some(vec![
+                (LogicalProperty::Margin(LogicalSide::InlineStart), px(1.0)),
+                (LogicalProperty::Margin(LogicalSide::InlineEnd), px(2.0)),
+            ])
+        );
+        assert_eq!(
+            expand("border-block-width", "3px"),
+            Some(vec![
+                (
+                    LogicalProperty::BorderWidth(LogicalSide::BlockStart),
+                    px(3.0)
+                ),
+                (LogicalProperty::BorderWidth(LogicalSide::BlockEnd), px(3.0)),
+            ])
+        );
+        let current_color = Value::Color(Color::CurrentColor);
+        assert_eq!(
+            expand("border-inline-start", "1px solid"),
+            Some(vec![
+                (
+                    LogicalProperty::BorderWidth(LogicalSide::InlineStart),
+                    px(1.0)
+                ),
+                (
+                    LogicalProperty::BorderStyle(LogicalSide::InlineStart),
+                    Value::BorderStyle(BorderStyle::Solid)
+                ),
+                (
+                    LogicalProperty::BorderColor(LogicalSide::InlineStart),
+                    current_color.clone()
+                ),
+            ])
+        );
+        // the omitted width and color are reset on both sides
+        let border = expand("border-block", "dashed").unwrap();
+        assert_eq!(border.len(), 6);
+        for side in [LogicalSide::BlockStart, LogicalSide::BlockEnd] {
+            assert!(border.contains(&(
+                LogicalProperty::BorderWidth(side),
+                Value::BorderWidth(BorderWidth::Medium)
+            )));
+            assert!(border.contains(&(
+                LogicalProperty::BorderStyle(side),
+                Value::BorderStyle(BorderStyle::Dashed)
+            )));
+            assert!(border.contains(&(LogicalProperty::BorderColor(side), current_color.clone())));
+        }
+        assert_eq!(expand("padding-block", "1px 2px 3px"), None);
+        assert_eq!(expand("margin-side", "1px"), None);
+    }
+}
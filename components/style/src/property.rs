@@ -39,7 +39,23 @@ pub enum Property {
     Top,
     Bottom,
     Direction,
+    WritingMode,
     FontSize,
+    Content,
+    CounterReset,
+    CounterIncrement,
+    AnimationName,
+    AnimationDuration,
+    AnimationTimingFunction,
+    AnimationDelay,
+    AnimationIterationCount,
+    AnimationDirection,
+    AnimationFillMode,
+    TransitionProperty,
+    TransitionDuration,
+    TransitionTimingFunction,
+    TransitionDelay,
+    Transform,
 }
 
 impl Property {
@@ -65,11 +81,27 @@ impl Property {
             "top" => Some(Property::Top),
             "bottom" => Some(Property::Bottom),
             "direction" => Some(Property::Direction),
+            "writing-mode" => Some(Property::WritingMode),
             "border-top-left-radius" => Some(Property::BorderTopLeftRadius),
             "border-top-right-radius" => Some(Property::BorderTopRightRadius),
             "border-bottom-left-radius" => Some(Property::BorderBottomLeftRadius),
             "border-bottom-right-radius" => Some(Property::BorderBottomRightRadius),
             "font-size" => Some(Property::FontSize),
+            "content" => Some(Property::Content),
+            "counter-reset" => Some(Property::CounterReset),
+            "counter-increment" => Some(Property::CounterIncrement),
+            "animation-name" => Some(Property::AnimationName),
+            "animation-duration" => Some(Property::AnimationDuration),
+            "animation-timing-function" => Some(Property::AnimationTimingFunction),
+            "animation-delay" => Some(Property::AnimationDelay),
+            "animation-iteration-count" => Some(Property::AnimationIterationCount),
+            "animation-direction" => Some(Property::AnimationDirection),
+            "animation-fill-mode" => Some(Property::AnimationFillMode),
+            "transition-property" => Some(Property::TransitionProperty),
+            "transition-duration" => Some(Property::TransitionDuration),
+            "transition-timing-function" => Some(Property::TransitionTimingFunction),
+            "transition-delay" => Some(Property::TransitionDelay),
+            "transform" => Some(Property::Transform),
             _ => None,
         }
     }
@@ -1,11 +1,12 @@
-use crate::{property::Property, value_processing::StyleCache};
+use crate::{
+    computed_values::ComputedValues, custom_properties::CustomProperties,
+    value_processing::StyleCache,
+};
 
-use super::inheritable::INHERITABLES;
-use super::value_processing::ValueRef;
+use css::selector::structs::PseudoElement;
 use dom::node::Node;
 use std::{
     cell::RefCell,
-    collections::HashMap,
     fmt::Debug,
     rc::{Rc, Weak},
 };
@@ -20,39 +21,28 @@ pub struct RenderTree {
 
 /// A style node in the style tree
 pub struct RenderNode {
-    /// A reference to the DOM node that uses this style
+    /// A reference to the DOM node that uses this style. For a
+    /// pseudo-element, this is the originating element.
     pub node: Rc<Node>,
-    /// A property HashMap containing computed styles
-    pub properties: HashMap<Property, ValueRef>,
+    /// The pseudo-element this style node is generated for
+    pub pseudo_element: Option<PseudoElement>,
+    /// The computed values of every property
+    pub style: ComputedValues,
+    /// Computed custom properties, shared with the parent when unchanged
+    pub custom_properties: Rc<CustomProperties>,
     /// Child style nodes
     pub children: RefCell<Vec<Rc<RenderNode>>>,
     /// Parent reference for inheritance
     pub parent_render_node: Option<Weak<RenderNode>>,
 }
 
-impl RenderNode {
-    /// Get style value of a property
-    /// Ensure that the value return is a shared computed value
-    pub fn get_style(&self, property: &Property) -> ValueRef {
-        if let Some(value) = self.properties.get(property) {
-            return value.clone();
-        }
-
-        if INHERITABLES.contains(property) {
-            if let Some(parent) = &self.parent_render_node {
-                if let Some(p) = parent.upgrade() {
-                    return p.get_style(&property);
-                }
-            }
-        }
-
-        panic!("Oops, we should not reach here");
-    }
-}
-
 impl Debug for RenderNode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.node)
+        match &self.pseudo_element {
+            Some(PseudoElement::Before) => write!(f, "{:?}::before", self.node),
+            Some(PseudoElement::After) => write!(f, "{:?}::after", self.node),
+            None => write!(f, "{:?}", self.node),
+        }
     }
 }
 
@@ -61,7 +51,7 @@ impl RenderTree {
         let mut result = String::new();
 
         fn print_node(result: &mut String, node: Rc<RenderNode>) {
-            result.push_str(&format!("{:?}\n", node.node));
+            result.push_str(&format!("{:?}\n", node));
 
             for child in node.children.borrow().iter() {
                 print_node(result, child.clone());
@@ -0,0 +1,236 @@
+use crate::bloom::AncestorFilter;
+use crate::invalidation::InvalidationMap;
+use crate::selector_matching::CompiledSelector;
+use crate::style_sharing::needs_revalidation;
+use crate::value_processing::ContextualRule;
+use css::selector::structs::{PseudoElement, Specificity};
+use dom::node::Node;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A selector of a rule, stored in the bucket of its right-most compound
+#[derive(Debug)]
+struct IndexedSelector {
+    rule: usize,
+    selector: CompiledSelector,
+    specificity: Specificity,
+}
+
+/// The selectors of the style rules hashed by their right-most ID, class
+/// or type selector. The rules are only referred to by their position so
+/// the index can be kept for as long as the rules don't change.
+#[derive(Debug, Default)]
+pub struct SelectorIndex {
+    rule_count: usize,
+    by_id: HashMap<String, Vec<IndexedSelector>>,
+    by_class: HashMap<String, Vec<IndexedSelector>>,
+    by_tag: HashMap<String, Vec<IndexedSelector>>,
+    universal: Vec<IndexedSelector>,
+    /// Selectors that must match two elements the same way for them to share styles
+    revalidation: Vec<CompiledSelector>,
+    invalidation: InvalidationMap,
+}
+
+impl SelectorIndex {
+    pub fn new(rules: &[ContextualRule]) -> Self {
+        let mut index = Self {
+            rule_count: rules.len(),
+            ..Default::default()
+        };
+        for (rule_index, rule) in rules.iter().enumerate() {
+            let inner = rule.inner;
+            index.invalidation.add_rule(inner);
+            for selector in &inner.selectors {
+                if needs_revalidation(selector) {
+                    index.revalidation.push(CompiledSelector::compile(selector));
+                }
+                index.insert(IndexedSelector {
+                    rule: rule_index,
+                    selector: CompiledSelector::compile(selector),
+                    specificity: selector.specificity(),
+                });
+            }
+        }
+        index
+    }
+
+    fn insert(&mut self, indexed: IndexedSelector) {
+        let bucket = match indexed.selector.subject() {
+            Some(subject) => {
+                if let Some(id) = subject.ids.first() {
+                    self.by_id.entry(id.clone()).or_default()
+                } else if let Some(class) = subject.classes.first() {
+                    self.by_class.entry(class.clone()).or_default()
+                } else if let Some(tag) = &subject.tag {
+                    self.by_tag.entry(tag.clone()).or_default()
+                } else {
+                    &mut self.universal
+                }
+            }
+            None => return,
+        };
+        bucket.push(indexed);
+    }
+}
+
+/// Style rules hashed by the right-most ID, class or type selector so that
+/// only the rules that can possibly match an element are tested against it
+#[derive(Debug)]
+pub struct RuleIndex<'a, 'b> {
+    rules: &'b [ContextualRule<'a>],
+    selectors: Rc<SelectorIndex>,
+}
+
+impl<'a, 'b> RuleIndex<'a, 'b> {
+    pub fn new(rules: &'b [ContextualRule<'a>]) -> Self {
+        Self::with_selectors(rules, Rc::new(SelectorIndex::new(rules)))
+    }
+
+    /// Index the rules with the selectors indexed before for the same rules
+    pub fn with_selectors(rules: &'b [ContextualRule<'a>], selectors: Rc<SelectorIndex>) -> Self {
+        debug_assert_eq!(rules.len(), selectors.rule_count);
+        Self { rules, selectors }
+    }
+
+    /// The indexed selectors, to reuse while the rules don't change
+    pub fn selectors(&self) -> Rc<SelectorIndex> {
+        self.selectors.clone()
+    }
+
+    /// The rules matching the element, or one of its pseudo-elements,
+    /// in the order they were given, with the specificity of their most
+    /// specific selector that matches
+    pub fn matching_rules(
+        &self,
+        node: &Rc<Node>,
+        ancestors: &AncestorFilter,
+        pseudo_element: Option<&PseudoElement>,
+    ) -> Vec<(&'b ContextualRule<'a>, Specificity)> {
+        let element = node.as_element();
+        let selectors = &self.selectors;
+        let mut candidates = Vec::new();
+        if let Some(id) = element.id() {
+            candidates.extend(selectors.by_id.get(id.as_str()));
+        }
+        for class in element.class_list().borrow().iter() {
+            candidates.extend(selectors.by_class.get(class.as_str()));
+        }
+        candidates.extend(selectors.by_tag.get(element.tag_name().as_str()));
+        candidates.push(&selectors.universal);
+
+        let mut matched = candidates
+            .into_iter()
+            .flatten()
+            .filter(|indexed| {
+                indexed.selector.may_match(ancestors)
+                    && indexed.selector.matches(node, pseudo_element)
+            })
+            .map(|indexed| (indexed.rule, &indexed.specificity))
+            .collect::<Vec<(usize, &Specificity)>>();
+        // a rule with several matching selectors is only applied once
+        matched.sort_unstable_by(|(a, a_specificity), (b, b_specificity)| {
+            a.cmp(b).then(b_specificity.cmp(a_specificity))
+        });
+        matched.dedup_by_key(|(index, _)| *index);
+        matched
+            .into_iter()
+            .map(|(index, specificity)| (&self.rules[index], specificity.clone()))
+            .collect()
+    }
+
+    /// The elements to restyle when an element changes
+    pub fn invalidation(&self) -> &InvalidationMap {
+        &self.selectors.invalidation
+    }
+
+    /// Whether each revalidation selector matches the element
+    /// or the pseudo-element it targets
+    pub fn revalidate(&self, node: &Rc<Node>) -> Vec<bool> {
+        self.selectors
+            .revalidation
+            .iter()
+            .map(|selector| selector.matches(node, selector.pseudo_element()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cascade_layers::UNLAYERED;
+    use crate::selector_matching::is_match_pseudo_element_selectors;
+    use crate::value_processing::{CSSLocation, CascadeOrigin};
+    use css::cssom::css_rule::CSSRule;
+    use test_utils::css::parse_stylesheet;
+    use test_utils::dom_creator::*;
+
+    #[test]
+    fn match_same_rules_as_linear_scan() {
+        let document = document();
+        let dom_tree = element(
+            "div#main.card",
+            document.clone(),
+            vec![
+                element("p.title.big", document.clone(), vec![]),
+                element("span", document.clone(), vec![]),
+                element("p", document.clone(), vec![]),
+            ],
+        );
+        let css = r#"
+        * { color: red; }
+        #main p { color: blue; }
+        #other p, div.missing p, section > span { color: blue; }
+        .card > .title, .big { display: block; }
+        span, p { display: inline; }
+        p + span, .card span { color: white; }
+        p:first-child::before { content: "a"; }
+        #other, .missing, a { color: black; }
+        :not(.title) { width: 1px; }
+        "#;
+        let stylesheet = parse_stylesheet(css);
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+        let index = RuleIndex::new(&rules);
+
+        let empty = AncestorFilter::new();
+        let mut ancestors = AncestorFilter::new();
+        ancestors.push(&dom_tree);
+        let mut elements = vec![(dom_tree.clone(), &empty)];
+        elements.extend(
+            dom_tree
+                .child_nodes()
+                .into_iter()
+                .map(|child| (child, &ancestors)),
+        );
+        for (node, ancestors) in elements {
+            for pseudo in [None, Some(PseudoElement::Before)] {
+                let expected = rules
+                    .iter()
+                    .filter(|rule| {
+                        is_match_pseudo_element_selectors(
+                            &node,
+                            &rule.inner.selectors,
+                            pseudo.as_ref(),
+                        )
+                    })
+                    .map(|rule| rule.inner.selector_text())
+                    .collect::<Vec<String>>();
+                let actual = index
+                    .matching_rules(&node, ancestors, pseudo.as_ref())
+                    .iter()
+                    .map(|(rule, _)| rule.inner.selector_text())
+                    .collect::<Vec<String>>();
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+}
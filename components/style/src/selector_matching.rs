@@ -1,7 +1,9 @@
 use std::rc::Rc;
 
+use crate::bloom::{AncestorFilter, AncestorKey};
 use css::selector::structs::*;
-use dom::{element::Element, node::Node};
+use dom::element_state::ElementState;
+use dom::node::{Node, NodeData};
 
 fn get_parent(el: &Rc<Node>) -> Option<Rc<Node>> {
     let parent = el.parent();
@@ -14,7 +16,33 @@ fn get_parent(el: &Rc<Node>) -> Option<Rc<Node>> {
 }
 
 fn get_prev_sibling(el: &Rc<Node>) -> Option<Rc<Node>> {
-    el.prev_sibling()
+    let mut sibling = el.prev_sibling();
+    while let Some(s) = sibling {
+        if s.is_element() {
+            return Some(s);
+        }
+        sibling = s.prev_sibling();
+    }
+    None
+}
+
+/// Get the elements that can be on the left side of a combinator
+/// when the element is on the right side
+fn get_combinator_candidates(element: &Rc<Node>, combinator: &Combinator) -> Vec<Rc<Node>> {
+    let next: fn(&Rc<Node>) -> Option<Rc<Node>> = match combinator {
+        Combinator::Child | Combinator::Descendant => get_parent,
+        Combinator::NextSibling | Combinator::SubsequentSibling => get_prev_sibling,
+    };
+    let mut candidates = Vec::new();
+    let mut current = next(element);
+    while let Some(candidate) = current {
+        current = next(&candidate);
+        candidates.push(candidate);
+        if let Combinator::Child | Combinator::NextSibling = combinator {
+            break;
+        }
+    }
+    candidates
 }
 
 pub fn is_match_selectors(element: &Rc<Node>, selectors: &Vec<Selector>) -> bool {
@@ -24,72 +52,99 @@ pub fn is_match_selectors(element: &Rc<Node>, selectors: &Vec<Selector>) -> bool
 }
 
 pub fn is_match_selector(element: Rc<Node>, selector: &Selector) -> bool {
-    let mut current_element = Some(element);
-    for (selector_seq, combinator) in selector.values().iter().rev() {
-        if let Some(el) = current_element.clone() {
-            match combinator {
-                Some(Combinator::Child) => {
-                    let parent = get_parent(&el);
-                    if let Some(p) = &parent {
-                        if !is_match_simple_selector_seq(p, selector_seq) {
-                            return false;
-                        }
-                    }
-                    current_element = parent;
-                }
-                Some(Combinator::Descendant) => loop {
-                    let parent = get_parent(&el);
-                    if let Some(p) = &parent {
-                        if is_match_simple_selector_seq(p, selector_seq) {
-                            current_element = parent;
-                            break;
-                        }
-                    } else {
-                        return false;
-                    }
-                },
-                Some(Combinator::NextSibling) => {
-                    let sibling = get_prev_sibling(&el);
-                    if let Some(sibling) = &sibling {
-                        if !is_match_simple_selector_seq(sibling, selector_seq) {
-                            return false;
-                        }
-                    }
-                    current_element = sibling;
-                }
-                Some(Combinator::SubsequentSibling) => loop {
-                    let sibling = get_prev_sibling(&el);
-                    if let Some(s) = &sibling {
-                        if is_match_simple_selector_seq(s, selector_seq) {
-                            current_element = sibling;
-                            break;
-                        }
-                    } else {
-                        return false;
-                    }
-                },
-                None => {
-                    if !is_match_simple_selector_seq(&el, selector_seq) {
-                        return false;
-                    }
+    selector.pseudo_element().is_none()
+        && is_match_selector_parts(&element, selector.values(), None)
+}
+
+/// Check if any of the selectors targets the pseudo-element of an element.
+/// A selector with a pseudo-element only matches that pseudo-element.
+pub fn is_match_pseudo_element_selectors(
+    element: &Rc<Node>,
+    selectors: &[Selector],
+    pseudo_element: Option<&PseudoElement>,
+) -> bool {
+    selectors.iter().any(|selector| {
+        selector.pseudo_element() == pseudo_element
+            && is_match_selector_parts(element, selector.values(), None)
+    })
+}
+
+/// Match the compound selectors from right to left, backtracking
+/// through every candidate of a combinator. When an anchor is provided,
+/// the left-most compound selector must be related to it with the combinator.
+fn is_match_selector_parts(
+    element: &Rc<Node>,
+    parts: &[(SimpleSelectorSequence, Option<Combinator>)],
+    anchor: Option<(&Rc<Node>, &Combinator)>,
+) -> bool {
+    let (selector_seq, rest) = match parts.split_last() {
+        Some(((selector_seq, _), rest)) => (selector_seq, rest),
+        None => return false,
+    };
+
+    if !is_match_simple_selector_seq(element, selector_seq) {
+        return false;
+    }
+
+    match rest.last() {
+        Some((_, Some(combinator))) => get_combinator_candidates(element, combinator)
+            .iter()
+            .any(|candidate| is_match_selector_parts(candidate, rest, anchor)),
+        Some((_, None)) => false,
+        None => match anchor {
+            Some((anchor, combinator)) => get_combinator_candidates(element, combinator)
+                .iter()
+                .any(|candidate| Rc::ptr_eq(candidate, anchor)),
+            None => true,
+        },
+    }
+}
+
+/// Match a relative selector of `:has()` against the elements
+/// that can be reached from the anchor element
+fn is_match_relative_selector(anchor: &Rc<Node>, relative: &RelativeSelector) -> bool {
+    fn collect_descendants(node: &Rc<Node>, result: &mut Vec<Rc<Node>>) {
+        for child in node.child_nodes().into_iter() {
+            if child.is_element() {
+                result.push(child.clone());
+                collect_descendants(&child, result);
+            }
+        }
+    }
+
+    let mut scope = Vec::new();
+    match relative.combinator {
+        Combinator::Child | Combinator::Descendant => collect_descendants(anchor, &mut scope),
+        Combinator::NextSibling | Combinator::SubsequentSibling => {
+            let mut sibling = anchor.next_sibling();
+            while let Some(s) = sibling {
+                if s.is_element() {
+                    scope.push(s.clone());
+                    collect_descendants(&s, &mut scope);
                 }
+                sibling = s.next_sibling();
             }
-        } else {
-            return false;
         }
     }
-    true
+
+    scope.iter().any(|element| {
+        is_match_selector_parts(
+            element,
+            relative.selector.values(),
+            Some((anchor, &relative.combinator)),
+        )
+    })
 }
 
 fn is_match_simple_selector_seq(element: &Rc<Node>, sequence: &SimpleSelectorSequence) -> bool {
-    let element = element.as_element();
     sequence
         .values()
         .iter()
         .all(|selector| is_match_simple_selector(element, selector))
 }
 
-fn is_match_simple_selector(element: &Element, selector: &SimpleSelector) -> bool {
+fn is_match_simple_selector(node: &Rc<Node>, selector: &SimpleSelector) -> bool {
+    let element = node.as_element();
     match selector.selector_type() {
         SimpleSelectorType::Universal => true,
         SimpleSelectorType::Type => {
@@ -110,7 +165,293 @@ fn is_match_simple_selector(element: &Element, selector: &SimpleSelector) -> boo
             }
             false
         }
-        _ => false,
+        SimpleSelectorType::Attribute(attribute) => element
+            .get_attribute(&attribute.name.to_ascii_lowercase())
+            .map(|value| attribute.matches(&value))
+            .unwrap_or(false),
+        SimpleSelectorType::PseudoClass(pseudo_class) => is_match_pseudo_class(node, pseudo_class),
+        // checked against the pseudo-element being styled by the caller
+        SimpleSelectorType::PseudoElement(_) => true,
+    }
+}
+
+/// Get the element siblings of an element, including itself, in tree order
+fn get_element_siblings(element: &Rc<Node>) -> Vec<Rc<Node>> {
+    match element.parent() {
+        Some(parent) => parent
+            .child_nodes()
+            .into_iter()
+            .filter(|child| child.is_element())
+            .collect(),
+        None => vec![element.clone()],
+    }
+}
+
+/// Get the 1-based index of an element among the siblings matching the filter,
+/// counting from the end if `from_end` is set
+fn get_element_index<F>(element: &Rc<Node>, from_end: bool, filter: F) -> i32
+where
+    F: Fn(&Rc<Node>) -> bool,
+{
+    let siblings = get_element_siblings(element);
+    let mut matched = siblings.iter().filter(|sibling| filter(sibling));
+    let position = if from_end {
+        matched
+            .rev()
+            .position(|sibling| Rc::ptr_eq(sibling, element))
+    } else {
+        matched.position(|sibling| Rc::ptr_eq(sibling, element))
+    };
+    position.map(|index| index as i32 + 1).unwrap_or(0)
+}
+
+fn is_same_type(a: &Rc<Node>, b: &Rc<Node>) -> bool {
+    a.as_element().tag_name() == b.as_element().tag_name()
+}
+
+fn is_match_pseudo_class(element: &Rc<Node>, pseudo_class: &PseudoClass) -> bool {
+    match pseudo_class {
+        PseudoClass::Root => element
+            .parent()
+            .map(|parent| parent.is_document())
+            .unwrap_or(false),
+        PseudoClass::Empty => element
+            .child_nodes()
+            .into_iter()
+            .all(|child| match child.data() {
+                Some(NodeData::Element(_)) => false,
+                Some(NodeData::Text(text)) => text.get_data().is_empty(),
+                _ => true,
+            }),
+        PseudoClass::FirstChild => get_element_index(element, false, |_| true) == 1,
+        PseudoClass::LastChild => get_element_index(element, true, |_| true) == 1,
+        PseudoClass::OnlyChild => get_element_siblings(element).len() == 1,
+        PseudoClass::FirstOfType => {
+            get_element_index(element, false, |sibling| is_same_type(sibling, element)) == 1
+        }
+        PseudoClass::LastOfType => {
+            get_element_index(element, true, |sibling| is_same_type(sibling, element)) == 1
+        }
+        PseudoClass::OnlyOfType => {
+            get_element_siblings(element)
+                .iter()
+                .filter(|sibling| is_same_type(sibling, element))
+                .count()
+                == 1
+        }
+        PseudoClass::NthChild(an_plus_b, selectors)
+        | PseudoClass::NthLastChild(an_plus_b, selectors) => {
+            if !selectors.is_empty() && !is_match_selectors(element, selectors) {
+                return false;
+            }
+            let from_end = matches!(pseudo_class, PseudoClass::NthLastChild(..));
+            let index = get_element_index(element, from_end, |sibling| {
+                selectors.is_empty() || is_match_selectors(sibling, selectors)
+            });
+            an_plus_b.matches(index)
+        }
+        PseudoClass::NthOfType(an_plus_b) | PseudoClass::NthLastOfType(an_plus_b) => {
+            let from_end = matches!(pseudo_class, PseudoClass::NthLastOfType(_));
+            let index =
+                get_element_index(element, from_end, |sibling| is_same_type(sibling, element));
+            an_plus_b.matches(index)
+        }
+        PseudoClass::Is(selectors) | PseudoClass::Where(selectors) => {
+            is_match_selectors(element, selectors)
+        }
+        PseudoClass::Not(selectors) => !is_match_selectors(element, selectors),
+        PseudoClass::Has(selectors) => selectors
+            .iter()
+            .any(|relative| is_match_relative_selector(element, relative)),
+        PseudoClass::FocusWithin => has_focus_within(element),
+        PseudoClass::Hover
+        | PseudoClass::Active
+        | PseudoClass::Focus
+        | PseudoClass::FocusVisible
+        | PseudoClass::Checked
+        | PseudoClass::Disabled
+        | PseudoClass::Visited => match get_element_state(pseudo_class) {
+            Some(state) => element.as_element().state().contains(state),
+            None => false,
+        },
+    }
+}
+
+/// Get the element state that a dynamic pseudo-class matches
+pub fn get_element_state(pseudo_class: &PseudoClass) -> Option<ElementState> {
+    match pseudo_class {
+        PseudoClass::Hover => Some(ElementState::HOVER),
+        PseudoClass::Active => Some(ElementState::ACTIVE),
+        PseudoClass::Focus => Some(ElementState::FOCUS),
+        PseudoClass::FocusWithin => Some(ElementState::FOCUS_WITHIN),
+        PseudoClass::FocusVisible => Some(ElementState::FOCUS_VISIBLE),
+        PseudoClass::Checked => Some(ElementState::CHECKED),
+        PseudoClass::Disabled => Some(ElementState::DISABLED),
+        PseudoClass::Visited => Some(ElementState::VISITED),
+        _ => None,
+    }
+}
+
+/// An element matches `:focus-within` if it or one of its descendants is focused
+fn has_focus_within(element: &Rc<Node>) -> bool {
+    if let Some(el) = element.as_element_opt() {
+        if el
+            .state()
+            .intersects(ElementState::FOCUS | ElementState::FOCUS_WITHIN)
+        {
+            return true;
+        }
+    }
+    element
+        .child_nodes()
+        .into_iter()
+        .filter(|child| child.is_element())
+        .any(|child| has_focus_within(&child))
+}
+
+/// A selector compiled for matching from right to left, with the type,
+/// ID and class selectors of each compound pulled out for cheap rejection
+#[derive(Debug)]
+pub struct CompiledSelector {
+    /// The compound selectors from right to left, each with the
+    /// combinator relating it to the next compound on its left
+    compounds: Vec<(CompiledCompound, Option<Combinator>)>,
+    pseudo_element: Option<PseudoElement>,
+    /// Hashes of the tags, IDs and classes some ancestor must have
+    ancestor_hashes: Vec<u32>,
+}
+
+#[derive(Debug, Default)]
+pub struct CompiledCompound {
+    pub tag: Option<String>,
+    pub ids: Vec<String>,
+    pub classes: Vec<String>,
+    /// Attribute selectors, pseudo-classes and the rest
+    others: Vec<SimpleSelector>,
+}
+
+impl CompiledSelector {
+    pub fn compile(selector: &Selector) -> Self {
+        let parts = selector.values();
+        let compounds = parts
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, (sequence, _))| {
+                let combinator = match index {
+                    0 => None,
+                    _ => parts[index - 1].1.clone(),
+                };
+                (CompiledCompound::compile(sequence), combinator)
+            })
+            .collect::<Vec<(CompiledCompound, Option<Combinator>)>>();
+
+        // a compound on the left of a descendant or child combinator
+        // matches an ancestor, one on the left of a sibling combinator
+        // matches a sibling of the subject or of an ancestor
+        let mut ancestor_hashes = Vec::new();
+        for index in 1..compounds.len() {
+            if let Some(Combinator::Descendant) | Some(Combinator::Child) = compounds[index - 1].1 {
+                ancestor_hashes.extend(compounds[index].0.hashes());
+            }
+        }
+
+        Self {
+            compounds,
+            pseudo_element: selector.pseudo_element().copied(),
+            ancestor_hashes,
+        }
+    }
+
+    /// `false` if the ancestors required by the selector can't all exist
+    pub fn may_match(&self, ancestors: &AncestorFilter) -> bool {
+        self.ancestor_hashes
+            .iter()
+            .all(|hash| ancestors.might_contain_hash(*hash))
+    }
+
+    pub fn pseudo_element(&self) -> Option<&PseudoElement> {
+        self.pseudo_element.as_ref()
+    }
+
+    /// The right-most compound selector, the one the subject must match
+    pub fn subject(&self) -> Option<&CompiledCompound> {
+        self.compounds.first().map(|(compound, _)| compound)
+    }
+
+    pub fn matches(&self, element: &Rc<Node>, pseudo_element: Option<&PseudoElement>) -> bool {
+        self.pseudo_element.as_ref() == pseudo_element && self.matches_from(element, 0)
+    }
+
+    fn matches_from(&self, element: &Rc<Node>, index: usize) -> bool {
+        let (compound, combinator) = match self.compounds.get(index) {
+            Some(compound) => compound,
+            None => return false,
+        };
+        if !compound.matches(element) {
+            return false;
+        }
+        if index + 1 == self.compounds.len() {
+            return true;
+        }
+        match combinator {
+            Some(combinator) => get_combinator_candidates(element, combinator)
+                .iter()
+                .any(|candidate| self.matches_from(candidate, index + 1)),
+            None => false,
+        }
+    }
+}
+
+impl CompiledCompound {
+    fn compile(sequence: &SimpleSelectorSequence) -> Self {
+        let mut compound = Self::default();
+        for selector in sequence.values() {
+            match (selector.selector_type(), selector.value()) {
+                (SimpleSelectorType::Universal, _) => {}
+                (SimpleSelectorType::Type, Some(tag)) if compound.tag.is_none() => {
+                    compound.tag = Some(tag.to_string())
+                }
+                (SimpleSelectorType::ID, Some(id)) => compound.ids.push(id.to_string()),
+                (SimpleSelectorType::Class, Some(class)) => {
+                    compound.classes.push(class.to_string())
+                }
+                _ => compound.others.push(selector.clone()),
+            }
+        }
+        compound
+    }
+
+    fn hashes(&self) -> Vec<u32> {
+        let tag = self.tag.as_deref().map(AncestorKey::Tag);
+        let ids = self.ids.iter().map(|id| AncestorKey::ID(id));
+        let classes = self.classes.iter().map(|class| AncestorKey::Class(class));
+        tag.into_iter()
+            .chain(ids)
+            .chain(classes)
+            .map(|key| key.hash())
+            .collect()
+    }
+
+    fn matches(&self, node: &Rc<Node>) -> bool {
+        let element = node.as_element();
+        if !self.ids.is_empty() {
+            let id = element.id();
+            if !self.ids.iter().all(|value| id.as_ref() == Some(value)) {
+                return false;
+            }
+        }
+        if !self.classes.iter().all(|class| element.has_class(class)) {
+            return false;
+        }
+        if let Some(tag) = &self.tag {
+            if element.tag_name() != *tag {
+                return false;
+            }
+        }
+        self.others
+            .iter()
+            .all(|selector| is_match_simple_selector(node, selector))
     }
 }
 
@@ -119,11 +460,12 @@ mod tests {
     use super::*;
     use css::cssom::css_rule::CSSRule;
     use css::parser::Parser;
+    use css::selector::parse_selector_str;
     use css::tokenizer::token::Token;
     use css::tokenizer::Tokenizer;
     use dom::create_element;
     use dom::node::Node;
-    use test_utils::dom_creator::document;
+    use test_utils::dom_creator::{document, element, text};
 
     #[test]
     fn match_simple_type() {
@@ -148,7 +490,7 @@ mod tests {
     #[test]
     fn match_simple_id() {
         let element_node = create_element(Rc::downgrade(&document()), "h1");
-        element_node.as_element().set_attribute("id", "button");
+        element_node.set_attribute("id", "button");
         let css = "h1#button { color: red; }";
 
         let tokenizer = Tokenizer::new(css.chars());
@@ -286,4 +628,201 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn match_attribute() {
+        let element = create_element(Rc::downgrade(&document()), "a");
+        element.set_attribute("href", "HTTPS://example.com/logo.png");
+        element.set_attribute("lang", "en-US");
+        element.set_attribute("class", "nav main");
+
+        let css = r#"
+        [href] {}
+        a[href^="https" i] {}
+        [href$=".png"] {}
+        [href*=example] {}
+        [lang|=en] {}
+        [class~=main] {}
+        [lang=en-us i] {}
+        [href^="https"] {}
+        [lang|=US] {}
+        [class~="nav main"] {}
+        [target] {}
+        "#;
+
+        let tokenizer = Tokenizer::new(css.chars());
+        let tokens = tokenizer.run();
+        let mut parser = Parser::<Token>::new(tokens);
+        let stylesheet = parser.parse_a_css_stylesheet();
+
+        let results = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => is_match_selectors(&element, &style.selectors),
+            })
+            .collect::<Vec<bool>>();
+
+        assert_eq!(
+            results,
+            vec![true, true, true, true, true, true, true, false, false, false, false]
+        );
+    }
+
+    #[test]
+    fn match_structural_pseudo_classes() {
+        let doc = document();
+        let list = element(
+            "ul",
+            doc.clone(),
+            vec![
+                element("li#a.item", doc.clone(), vec![]),
+                element("p#b", doc.clone(), vec![]),
+                element("li#c", doc.clone(), vec![text("Hello", doc.clone())]),
+                element("li#d.item", doc.clone(), vec![]),
+                element("li#e.item", doc.clone(), vec![]),
+            ],
+        );
+        Node::append_child(doc.clone(), list.clone());
+
+        let matched = |selector: &str| {
+            let selector = parse_selector_str(selector).unwrap();
+            let mut ids = Vec::new();
+            if is_match_selector(list.clone(), &selector) {
+                ids.push("ul".to_string());
+            }
+            for child in list.child_nodes().into_iter() {
+                if is_match_selector(child.clone(), &selector) {
+                    ids.push(child.as_element().id().unwrap());
+                }
+            }
+            ids.join(",")
+        };
+
+        assert_eq!(matched(":root"), "ul");
+        assert_eq!(matched("li:empty"), "a,d,e");
+        assert_eq!(matched("li:first-child"), "a");
+        assert_eq!(matched(":last-child"), "ul,e");
+        assert_eq!(matched("p:only-of-type"), "b");
+        assert_eq!(matched(":only-child"), "ul");
+        assert_eq!(matched("li:first-of-type"), "a");
+        assert_eq!(matched("li:last-of-type"), "e");
+        assert_eq!(matched("li:nth-child(odd)"), "a,c,e");
+        assert_eq!(matched("li:nth-child(-n+3)"), "a,c");
+        assert_eq!(matched(":nth-last-child(2)"), "d");
+        assert_eq!(matched("li:nth-of-type(2n)"), "c,e");
+        assert_eq!(matched("li:nth-last-of-type(1)"), "e");
+        assert_eq!(matched(":nth-child(2 of .item)"), "d");
+        assert_eq!(matched(":nth-last-child(even of li)"), "a,d");
+        assert_eq!(parse_selector_str("li:hovered"), None);
+    }
+
+    #[test]
+    fn match_combinators_with_backtracking() {
+        let doc = document();
+        let target = element("span#target", doc.clone(), vec![]);
+        let _tree = element(
+            "div.outer",
+            doc.clone(),
+            vec![element(
+                "section",
+                doc.clone(),
+                vec![
+                    element("h1", doc.clone(), vec![]),
+                    text("text between siblings", doc.clone()),
+                    element("p", doc.clone(), vec![]),
+                    element("div.inner", doc.clone(), vec![target.clone()]),
+                ],
+            )],
+        );
+
+        let matched = |selector: &str| {
+            is_match_selector(target.clone(), &parse_selector_str(selector).unwrap())
+        };
+
+        assert!(matched("div.outer span"));
+        assert!(matched("div > span"));
+        assert!(matched(".outer section > div span"));
+        assert!(matched("h1 + p + div > span"));
+        assert!(matched("h1 ~ div span"));
+        assert!(!matched("p ~ h1 span"));
+        assert!(!matched("section > span"));
+        assert!(!matched("article span"));
+    }
+
+    #[test]
+    fn match_logical_pseudo_classes() {
+        let doc = document();
+        let section = element(
+            "section#s",
+            doc.clone(),
+            vec![
+                element("h1#title", doc.clone(), vec![]),
+                element(
+                    "div#d.content",
+                    doc.clone(),
+                    vec![element("img#i", doc.clone(), vec![])],
+                ),
+            ],
+        );
+        let _root = element(
+            "main",
+            doc.clone(),
+            vec![section.clone(), element("p#after", doc.clone(), vec![])],
+        );
+
+        let matched = |selector: &str| {
+            is_match_selector(section.clone(), &parse_selector_str(selector).unwrap())
+        };
+
+        assert!(matched("section:is(#x, #s)"));
+        assert!(matched(":where(main) > :is(section)"));
+        assert!(!matched("section:is(.a, .b)"));
+        assert!(matched("section:not(.a, div)"));
+        assert!(!matched("section:not(#s)"));
+        assert!(matched("section:has(img)"));
+        assert!(matched("section:has(> h1)"));
+        assert!(!matched("section:has(> img)"));
+        assert!(matched("section:has(> .content > img)"));
+        assert!(matched("section:has(h1 + div img)"));
+        assert!(!matched("section:has(main img)"));
+        assert!(matched("section:has(+ p)"));
+        assert!(matched("section:has(~ p, > nothing)"));
+        assert!(!matched("section:has(+ div)"));
+        assert!(matched("main:has(> section:has(img)) > section"));
+    }
+
+    #[test]
+    fn match_element_states() {
+        let doc = document();
+        let button = element("button#btn", doc.clone(), vec![]);
+        let input = element("input#check", doc.clone(), vec![]);
+        let form = element(
+            "form#f",
+            doc.clone(),
+            vec![
+                element("div", doc.clone(), vec![button.clone()]),
+                input.clone(),
+            ],
+        );
+
+        let matched = |element: &Rc<Node>, selector: &str| {
+            is_match_selector(element.clone(), &parse_selector_str(selector).unwrap())
+        };
+
+        assert!(!matched(&button, "#btn:hover"));
+        button.set_state(ElementState::HOVER | ElementState::FOCUS, true);
+        assert!(matched(&button, "#btn:hover:focus"));
+        assert!(!matched(&button, "#btn:active"));
+        assert!(matched(&form, "form:focus-within"));
+        assert!(!matched(&input, ":focus-within"));
+
+        button.set_state(ElementState::FOCUS, false);
+        assert!(!matched(&form, "form:focus-within"));
+        assert!(matched(&button, "button:hover"));
+
+        input.set_attribute("checked", "");
+        input.set_attribute("disabled", "");
+        assert!(matched(&input, "input:checked:disabled"));
+        assert!(!matched(&input, "input:visited"));
+    }
 }
@@ -0,0 +1,210 @@
+use crate::computed_values::ComputedValues;
+use crate::custom_properties::CustomProperties;
+use crate::render_tree::RenderNode;
+use crate::rule_index::RuleIndex;
+use crate::selector_matching::get_element_state;
+use crate::values::animation::AnimationName;
+use css::selector::structs::*;
+use dom::node::Node;
+use std::collections::{HashMap, VecDeque};
+use std::rc::{Rc, Weak};
+
+/// Number of recently styled elements kept as candidates
+const CACHE_SIZE: usize = 16;
+
+/// The computed style of an element, as it was before transitions
+pub struct SharedStyle {
+    pub values: ComputedValues,
+    pub custom_properties: Rc<CustomProperties>,
+    /// Elements with the same style ID have the same computed style
+    /// because they matched the same rules under the same ancestors
+    pub style: usize,
+}
+
+struct Candidate {
+    node: Rc<Node>,
+    parent_style: Option<usize>,
+    /// Whether each revalidation selector of the rules matches the element
+    revalidation: Vec<bool>,
+    style: SharedStyle,
+}
+
+/// Recently styled elements whose computed style can be reused by a
+/// sibling or a cousin that would match the same rules, skipping the
+/// cascade and the computation of its values
+pub struct StyleSharingCache {
+    candidates: VecDeque<Candidate>,
+    /// The style ID of each render node, by address
+    styles: HashMap<usize, usize>,
+    next_style: usize,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl StyleSharingCache {
+    pub fn new() -> Self {
+        Self {
+            candidates: VecDeque::with_capacity(CACHE_SIZE),
+            styles: HashMap::new(),
+            next_style: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn parent_style(&self, parent: Option<&Weak<RenderNode>>) -> Option<usize> {
+        parent.and_then(|parent| self.styles.get(&(parent.as_ptr() as usize)).copied())
+    }
+
+    /// Find a candidate whose style the element can share
+    pub fn lookup(
+        &mut self,
+        node: &Rc<Node>,
+        parent: Option<&Weak<RenderNode>>,
+        rules: &RuleIndex,
+    ) -> Option<SharedStyle> {
+        let parent_style = self.parent_style(parent);
+        let mut revalidation = None;
+        let position = self.candidates.iter().position(|candidate| {
+            candidate.parent_style == parent_style
+                && is_similar(&candidate.node, node)
+                && *revalidation.get_or_insert_with(|| rules.revalidate(node))
+                    == candidate.revalidation
+        });
+
+        let candidate = match position {
+            Some(position) => self.candidates.remove(position)?,
+            None => {
+                self.misses += 1;
+                return None;
+            }
+        };
+        self.hits += 1;
+        let shared = SharedStyle {
+            values: candidate.style.values.clone(),
+            custom_properties: candidate.style.custom_properties.clone(),
+            style: candidate.style.style,
+        };
+        self.candidates.push_front(candidate);
+        Some(shared)
+    }
+
+    /// Keep the style of an element that missed the cache as a candidate.
+    /// Returns the new style ID.
+    pub fn insert(
+        &mut self,
+        node: &Rc<Node>,
+        parent: Option<&Weak<RenderNode>>,
+        rules: &RuleIndex,
+        values: &ComputedValues,
+        custom_properties: &Rc<CustomProperties>,
+    ) -> usize {
+        let style = self.unique_style();
+        // animations depend on the element they run on
+        if has_animations(values) {
+            return style;
+        }
+
+        if self.candidates.len() == CACHE_SIZE {
+            self.candidates.pop_back();
+        }
+        self.candidates.push_front(Candidate {
+            node: node.clone(),
+            parent_style: self.parent_style(parent),
+            revalidation: rules.revalidate(node),
+            style: SharedStyle {
+                values: values.clone(),
+                custom_properties: custom_properties.clone(),
+                style,
+            },
+        });
+        style
+    }
+
+    /// A new style ID, for an element that shares its style with no other
+    pub fn unique_style(&mut self) -> usize {
+        let style = self.next_style;
+        self.next_style += 1;
+        style
+    }
+
+    /// Record the style ID of a render node, so its children
+    /// can share styles with the children of nodes with the same style
+    pub fn set_style(&mut self, render_node: &Rc<RenderNode>, style: usize) {
+        self.styles.insert(Rc::as_ptr(render_node) as usize, style);
+    }
+}
+
+impl Default for StyleSharingCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether the elements match the same selectors made of types,
+/// IDs, classes and states, and have the same inline style
+fn is_similar(a: &Node, b: &Node) -> bool {
+    let (a, b) = (a.as_element(), b.as_element());
+    a.tag_name() == b.tag_name()
+        && a.id() == b.id()
+        && a.state() == b.state()
+        && a.class_list()
+            .borrow()
+            .iter()
+            .eq(b.class_list().borrow().iter())
+        && *a.style() == *b.style()
+}
+
+fn has_animations(values: &ComputedValues) -> bool {
+    values
+        .animation_name()
+        .0
+        .iter()
+        .any(|name| !matches!(name, AnimationName::None))
+}
+
+/// Whether matching the selector depends on more than the type, ID,
+/// classes and state of the element and its ancestors. Such selectors,
+/// e.g. attribute selectors and structural pseudo-classes, are matched
+/// against both elements before sharing a style.
+pub fn needs_revalidation(selector: &Selector) -> bool {
+    selector.values().iter().any(|(sequence, combinator)| {
+        matches!(
+            combinator,
+            Some(Combinator::NextSibling) | Some(Combinator::SubsequentSibling)
+        ) || sequence
+            .values()
+            .iter()
+            .any(|simple| match simple.selector_type() {
+                SimpleSelectorType::Attribute(_) => true,
+                SimpleSelectorType::PseudoClass(pseudo_class) => match pseudo_class {
+                    PseudoClass::Is(selectors)
+                    | PseudoClass::Where(selectors)
+                    | PseudoClass::Not(selectors) => selectors.iter().any(needs_revalidation),
+                    // depends on the descendants of the element
+                    PseudoClass::FocusWithin => true,
+                    _ => get_element_state(pseudo_class).is_none(),
+                },
+                _ => false,
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use css::selector::parse_selector_str;
+
+    #[test]
+    fn selectors_needing_revalidation() {
+        let needs = |selector: &str| {
+            needs_revalidation(&parse_selector_str(selector).expect("Invalid selector"))
+        };
+        assert!(!needs("ul > li.item"));
+        assert!(!needs("a:hover span:not(.big)"));
+        assert!(needs("li:first-child span"));
+        assert!(needs("input[type=text]"));
+        assert!(needs("h1 + p"));
+        assert!(needs(":is(p, :has(> img))"));
+    }
+}
@@ -1,88 +1,579 @@
+use crate::animation::AnimationEngine;
+use crate::computed_values::{ComputedValues, InheritedValues, ResetValues};
+use crate::computes::length::element_length_context;
+use crate::custom_properties::{
+    compute_custom_properties, substitute_properties, CustomProperties,
+};
+use crate::generated_content::GeneratedContent;
 use crate::property::Property;
 use crate::value::Value;
-use crate::value_processing::{compute, ComputeContext, Properties, StyleCache};
+use crate::value_processing::{
+    compute, ComputeContext, DeclaredCustomProperties, Properties, StyleCache,
+};
+use crate::values::content::Content;
 use crate::values::display::{Display, DisplayBox};
-use dom::node::Node;
+use crate::values::length::LengthContext;
+use css::selector::structs::PseudoElement;
+use dom::node::{Node, NodeData};
+use dom::restyle::DirtyFlags;
+use dom::text::Text;
+use shared::primitive::Size;
 use strum::IntoEnumIterator;
 
+use super::bloom::AncestorFilter;
 use super::inheritable::INHERITABLES;
 use super::render_tree::{RenderNode, RenderTree};
-use super::value_processing::{apply_styles, ContextualRule, ValueRef};
-use std::collections::HashMap;
+use super::rule_index::RuleIndex;
+use super::style_sharing::StyleSharingCache;
+use super::value_processing::{apply_styles, ContextualRule};
+use std::collections::{HashMap, HashSet};
 use std::rc::{Rc, Weak};
 
 pub struct TreeBuilder;
 
+/// The state threaded through the render tree while it's built
+struct BuildContext<'r, 'a, 'b> {
+    rules: &'r RuleIndex<'a, 'b>,
+    ancestors: AncestorFilter,
+    cache: StyleCache,
+    generated: GeneratedContent,
+    animations: &'r mut AnimationEngine,
+    sharing: StyleSharingCache,
+    viewport: Size,
+}
+
 impl TreeBuilder {
     pub fn build(node: Rc<Node>, rules: &[ContextualRule]) -> RenderTree {
-        let mut style_cache = StyleCache::new();
-        let render_root = if node.is_document() {
-            // the first child is HTML tag
-            node.first_child()
-        } else {
-            Some(node)
+        Self::build_with_animations(node, rules, Size::default(), &mut AnimationEngine::new())
+    }
+
+    /// Build the render tree for a viewport with the animations and
+    /// transitions at the current time of the animation engine
+    pub fn build_with_animations(
+        node: Rc<Node>,
+        rules: &[ContextualRule],
+        viewport: Size,
+        animations: &mut AnimationEngine,
+    ) -> RenderTree {
+        Self::build_with_index(node, &RuleIndex::new(rules), viewport, animations)
+    }
+
+    /// Build the render tree with rules already hashed into an index
+    pub fn build_with_index(
+        node: Rc<Node>,
+        rules: &RuleIndex,
+        viewport: Size,
+        animations: &mut AnimationEngine,
+    ) -> RenderTree {
+        node.clear_dirty();
+        let render_root = render_root(&node);
+        let mut context = BuildContext {
+            rules,
+            ancestors: ancestor_filter(render_root.as_ref()),
+            cache: StyleCache::new(),
+            generated: GeneratedContent::new(),
+            animations,
+            sharing: StyleSharingCache::new(),
+            viewport,
+        };
+        context.animations.begin_restyle();
+        let root = render_root.and_then(|node| build_from_node(node, None, &mut context));
+        context.animations.end_restyle();
+
+        log::debug!(
+            "Style sharing: {} hits, {} misses",
+            context.sharing.hits,
+            context.sharing.misses
+        );
+
+        RenderTree {
+            root,
+            style_cache: context.cache,
+        }
+    }
+
+    /// Restyle the elements invalidated by the DOM mutations since the
+    /// render tree was built and keep the render nodes of the others.
+    /// Returns the nodes whose boxes have to be laid out again.
+    pub fn restyle(
+        tree: &mut RenderTree,
+        node: Rc<Node>,
+        rules: &RuleIndex,
+        viewport: Size,
+        animations: &mut AnimationEngine,
+    ) -> Vec<Rc<Node>> {
+        let render_root = render_root(&node);
+        let old_root = tree.root.clone().filter(|old_root| {
+            render_root
+                .as_ref()
+                .is_some_and(|root| Rc::ptr_eq(&old_root.node, root))
+        });
+        let old_root = match old_root {
+            Some(old_root) if !rules.invalidation().uses_counters => old_root,
+            _ => {
+                *tree = Self::build_with_index(node, rules, viewport, animations);
+                return render_root.into_iter().collect();
+            }
         };
 
-        let root = match render_root {
-            Some(node) => build_from_node(node, rules, None, &mut style_cache),
-            None => None,
+        rules.invalidation().invalidate(&node);
+        let restyle_all = node.dirty_flags().contains(DirtyFlags::SUBTREE);
+
+        let mut context = BuildContext {
+            rules,
+            ancestors: ancestor_filter(render_root.as_ref()),
+            cache: std::mem::replace(&mut tree.style_cache, StyleCache::new()),
+            generated: GeneratedContent::new(),
+            animations,
+            sharing: StyleSharingCache::new(),
+            viewport,
         };
+        let mut relayout = Vec::new();
+        context.animations.begin_restyle();
+        tree.root = render_root.and_then(|node| {
+            let old = Some(&old_root);
+            restyle_node(node, old, None, restyle_all, &mut context, &mut relayout)
+        });
+        context.animations.end_partial_restyle();
+        node.clear_dirty();
+        tree.style_cache = context.cache;
+        relayout
+    }
+}
+
+/// Record the animation targets of a render node and its descendants
+/// that are no longer in the render tree
+fn drop_render_node(render_node: &RenderNode, animations: &mut AnimationEngine) {
+    animations.drop_target(&render_node.node, render_node.pseudo_element);
+    for child in render_node.children.borrow().iter() {
+        drop_render_node(child, animations);
+    }
+}
+
+/// The node at the root of the render tree
+fn render_root(node: &Rc<Node>) -> Option<Rc<Node>> {
+    if node.is_document() {
+        // the first child is HTML tag
+        node.first_child()
+    } else {
+        Some(node.clone())
+    }
+}
 
-        RenderTree { root, style_cache }
+/// The filter holding the ancestors of the first styled element
+fn ancestor_filter(node: Option<&Rc<Node>>) -> AncestorFilter {
+    let mut ancestors = AncestorFilter::new();
+    let mut ancestor = node.and_then(|node| node.parent());
+    while let Some(node) = ancestor {
+        if node.is_element() {
+            ancestors.push(&node);
+        }
+        ancestor = node.parent();
+    }
+    ancestors
+}
+
+/// Cascade and compute the style of an element, or share the
+/// style of a similar element styled before
+fn style_element(
+    node: &Rc<Node>,
+    parent: Option<&Weak<RenderNode>>,
+    context: &mut BuildContext,
+) -> Option<(ComputedValues, Rc<CustomProperties>, usize)> {
+    let rules = context.rules;
+    if let Some(shared) = context.sharing.lookup(node, parent, rules) {
+        let mut computed = shared.values;
+        let animated = HashSet::new();
+        context
+            .animations
+            .transition(node, None, &mut computed, &animated);
+        return Some((computed, shared.custom_properties, shared.style));
     }
+
+    let (properties, custom_properties, important) =
+        apply_styles(node, rules, &context.ancestors, None, parent);
+    let (mut properties, custom_properties) =
+        resolve_custom_properties(properties, custom_properties, parent)?;
+
+    let animated = context
+        .animations
+        .animate(node, None, &mut properties, &important, parent);
+    let mut computed = compute_styles(
+        properties,
+        parent.cloned(),
+        &mut context.cache,
+        &context.viewport,
+    );
+    // the candidate keeps the style before transitions, which depend on the element
+    let style = context
+        .sharing
+        .insert(node, parent, rules, &computed, &custom_properties);
+    context
+        .animations
+        .transition(node, None, &mut computed, &animated);
+    Some((computed, custom_properties, style))
 }
 
 fn build_from_node(
     node: Rc<Node>,
-    rules: &[ContextualRule],
     parent: Option<Weak<RenderNode>>,
-    cache: &mut StyleCache,
+    context: &mut BuildContext,
 ) -> Option<Rc<RenderNode>> {
-    let properties = if node.is_text() {
-        HashMap::new()
-    } else {
-        apply_styles(&node, &rules)
+    node.clear_dirty();
+    if !node.is_element() {
+        let (properties, custom_properties) =
+            resolve_custom_properties(HashMap::new(), HashMap::new(), parent.as_ref())?;
+        return Some(Rc::new(RenderNode {
+            node,
+            pseudo_element: None,
+            style: compute_styles(
+                properties,
+                parent.clone(),
+                &mut context.cache,
+                &context.viewport,
+            ),
+            custom_properties,
+            parent_render_node: parent,
+            children: Default::default(),
+        }));
+    }
+
+    let (style, custom_properties, style_id) = style_element(&node, parent.as_ref(), context)?;
+    let render_node = Rc::new(RenderNode {
+        node: node.clone(),
+        pseudo_element: None,
+        style,
+        custom_properties,
+        parent_render_node: parent,
+        children: Default::default(),
+    });
+    context.sharing.set_style(&render_node, style_id);
+
+    context.generated.update_counters(&render_node);
+    let scope = context.generated.enter_scope();
+
+    let mut children = Vec::new();
+    children.extend(build_pseudo_element(
+        &render_node,
+        PseudoElement::Before,
+        context,
+    ));
+    // the element is an ancestor of its children but not of its pseudo-elements
+    context.ancestors.push(&node);
+    for child in node.child_nodes().into_iter() {
+        // this is fine because we clone the node when iterate
+        children.extend(build_from_node(
+            child,
+            Some(Rc::downgrade(&render_node)),
+            context,
+        ));
+    }
+    context.ancestors.pop(&node);
+    children.extend(build_pseudo_element(
+        &render_node,
+        PseudoElement::After,
+        context,
+    ));
+    render_node.children.replace(children);
+
+    context.generated.leave_scope(scope);
+
+    Some(render_node)
+}
+
+/// Restyle the dirty nodes of a subtree, or all of them if `restyle`
+/// is set, reusing the old render nodes whose style didn't change
+fn restyle_node(
+    node: Rc<Node>,
+    old: Option<&Rc<RenderNode>>,
+    parent: Option<Weak<RenderNode>>,
+    restyle: bool,
+    context: &mut BuildContext,
+    relayout: &mut Vec<Rc<Node>>,
+) -> Option<Rc<RenderNode>> {
+    let old = match old {
+        Some(old) => old,
+        // inserted, or not rendered before because of `display: none`
+        None => {
+            let render_node = build_from_node(node.clone(), parent, context);
+            if render_node.is_some() {
+                relayout.push(node);
+            }
+            return render_node;
+        }
+    };
+    let flags = node.dirty_flags();
+    node.clear_dirty();
+    // a render node can't be moved under a new parent, which
+    // would be dropped with the old render node still pointing to it
+    let same_parent = match (&parent, &old.parent_render_node) {
+        (Some(parent), Some(old_parent)) => parent.ptr_eq(old_parent),
+        (None, None) => true,
+        _ => false,
     };
 
-    // Filter display none from render tree
-    if let Some(display_value) = properties.get(&Property::Display) {
-        if let Some(value) = display_value {
-            if let Value::Display(Display::Box(DisplayBox::None)) = value {
+    if !node.is_element() {
+        if flags.contains(DirtyFlags::TEXT) {
+            relayout.push(node.clone());
+        }
+        return match same_parent {
+            true => Some(old.clone()),
+            false => build_from_node(node, parent, context),
+        };
+    }
+
+    let restyle_self = restyle || flags.intersects(DirtyFlags::STYLE | DirtyFlags::SUBTREE);
+    let (style, custom_properties, style_id) = if restyle_self {
+        match style_element(&node, parent.as_ref(), context) {
+            Some(style) => style,
+            None => {
+                drop_render_node(old, context.animations);
+                relayout.push(node);
                 return None;
             }
         }
+    } else {
+        let style_id = context.sharing.unique_style();
+        (old.style.clone(), old.custom_properties.clone(), style_id)
+    };
+    let changed = style != old.style || custom_properties != old.custom_properties;
+    if changed {
+        relayout.push(node.clone());
+    }
+
+    let reuse = same_parent && !changed;
+    if reuse && !restyle_self && !flags.intersects(DirtyFlags::DESCENDANTS | DirtyFlags::CHILDREN) {
+        return Some(old.clone());
+    }
+    if flags.contains(DirtyFlags::CHILDREN) {
+        relayout.push(node.clone());
+    }
+
+    let render_node = match reuse {
+        true => old.clone(),
+        false => Rc::new(RenderNode {
+            node: node.clone(),
+            pseudo_element: None,
+            style,
+            custom_properties,
+            parent_render_node: parent,
+            children: Default::default(),
+        }),
+    };
+    context.sharing.set_style(&render_node, style_id);
+
+    let old_children = old.children.borrow().clone();
+    let old_pseudo_element = |pseudo_element: PseudoElement| {
+        old_children
+            .iter()
+            .find(|child| child.pseudo_element == Some(pseudo_element))
+    };
+    let mut old_by_node = old_children
+        .iter()
+        .filter(|child| child.pseudo_element.is_none())
+        .map(|child| (Rc::as_ptr(&child.node), child))
+        .collect::<HashMap<*const Node, &Rc<RenderNode>>>();
+    // the children inherit from the new style
+    let restyle_children = restyle || flags.contains(DirtyFlags::SUBTREE) || changed;
+    let restyle_pseudo_elements = restyle_self || !reuse;
+
+    let mut children = Vec::new();
+    children.extend(restyle_pseudo_element(
+        &render_node,
+        PseudoElement::Before,
+        old_pseudo_element(PseudoElement::Before),
+        restyle_pseudo_elements,
+        context,
+        relayout,
+    ));
+    context.ancestors.push(&node);
+    for child in node.child_nodes().into_iter() {
+        let old_child = old_by_node.remove(&Rc::as_ptr(&child));
+        children.extend(restyle_node(
+            child,
+            old_child,
+            Some(Rc::downgrade(&render_node)),
+            restyle_children,
+            context,
+            relayout,
+        ));
+    }
+    context.ancestors.pop(&node);
+    // the old children that are left were removed from the element
+    for removed in old_by_node.values() {
+        drop_render_node(removed, context.animations);
     }
+    children.extend(restyle_pseudo_element(
+        &render_node,
+        PseudoElement::After,
+        old_pseudo_element(PseudoElement::After),
+        restyle_pseudo_elements,
+        context,
+        relayout,
+    ));
+    render_node.children.replace(children);
+
+    Some(render_node)
+}
+
+/// Build a pseudo-element again if its originating element was restyled
+fn restyle_pseudo_element(
+    originating: &Rc<RenderNode>,
+    pseudo_element: PseudoElement,
+    old: Option<&Rc<RenderNode>>,
+    restyle: bool,
+    context: &mut BuildContext,
+    relayout: &mut Vec<Rc<Node>>,
+) -> Option<Rc<RenderNode>> {
+    if !restyle {
+        return old.cloned();
+    }
+    let render_node = build_pseudo_element(originating, pseudo_element, context);
+    if let (Some(old), None) = (old, &render_node) {
+        drop_render_node(old, context.animations);
+    }
+    let generated_text = |render_node: &RenderNode| {
+        let children = render_node.children.borrow();
+        children.first().map(|text| text.node.as_text().get_data())
+    };
+    let unchanged = match (old, &render_node) {
+        (Some(old), Some(new)) => {
+            old.style == new.style && generated_text(old) == generated_text(new)
+        }
+        (None, None) => true,
+        _ => false,
+    };
+    if !unchanged {
+        relayout.push(originating.node.clone());
+    }
+    render_node
+}
+
+/// Build the render node of a `::before` or `::after` pseudo-element,
+/// only generated when a matching rule sets `content` to a non-`none` value
+/// https://www.w3.org/TR/css-pseudo-4/#generated-content
+fn build_pseudo_element(
+    originating: &Rc<RenderNode>,
+    pseudo_element: PseudoElement,
+    context: &mut BuildContext,
+) -> Option<Rc<RenderNode>> {
+    let parent = Some(Rc::downgrade(originating));
+    let (properties, custom_properties, important) = apply_styles(
+        &originating.node,
+        context.rules,
+        &context.ancestors,
+        Some(&pseudo_element),
+        parent.as_ref(),
+    );
+    if properties.is_empty() {
+        return None;
+    }
+
+    let (mut properties, custom_properties) =
+        resolve_custom_properties(properties, custom_properties, parent.as_ref())?;
+
+    let node = &originating.node;
+    let cache = &mut context.cache;
+    let animations = &mut context.animations;
+    let animated = animations.animate(
+        node,
+        Some(pseudo_element),
+        &mut properties,
+        &important,
+        parent.as_ref(),
+    );
+    let mut computed = compute_styles(properties, parent.clone(), cache, &context.viewport);
+    animations.transition(node, Some(pseudo_element), &mut computed, &animated);
 
     let render_node = Rc::new(RenderNode {
-        node: node.clone(),
-        properties: compute_styles(properties, parent.clone(), cache),
+        node: originating.node.clone(),
+        pseudo_element: Some(pseudo_element),
+        style: computed,
+        custom_properties,
         parent_render_node: parent,
         children: Default::default(),
     });
 
-    render_node.children.replace(
-        node.child_nodes()
-            .into_iter() // this is fine because we clone the node when iterate
-            .filter_map(|child| {
-                build_from_node(child, &rules, Some(Rc::downgrade(&render_node)), cache)
-            })
-            .collect(),
-    );
+    // `normal` computes to `none` for ::before and ::after
+    let items = match render_node.style.content() {
+        Content::Items(items) => items.clone(),
+        _ => return None,
+    };
+
+    context.generated.update_counters(&render_node);
+    let text = context
+        .generated
+        .generate_text(&items, originating.node.as_element());
+
+    let text_node = Rc::new(Node::new(NodeData::Text(Text::new(text))));
+    let text_parent = Some(Rc::downgrade(&render_node));
+    let text_render_node = Rc::new(RenderNode {
+        node: text_node,
+        pseudo_element: None,
+        style: compute_styles(
+            HashMap::new(),
+            text_parent.clone(),
+            &mut context.cache,
+            &context.viewport,
+        ),
+        custom_properties: render_node.custom_properties.clone(),
+        parent_render_node: text_parent,
+        children: Default::default(),
+    });
+    render_node.children.replace(vec![text_render_node]);
 
     Some(render_node)
 }
 
+/// Compute the custom properties and substitute them in the cascaded values.
+/// Returns `None` if the node is not rendered because of `display: none`.
+fn resolve_custom_properties(
+    properties: Properties,
+    custom_properties: DeclaredCustomProperties,
+    parent: Option<&Weak<RenderNode>>,
+) -> Option<(Properties, Rc<CustomProperties>)> {
+    let parent_custom_properties = parent
+        .and_then(|parent| parent.upgrade())
+        .map(|parent| parent.custom_properties.clone());
+    let custom_properties =
+        compute_custom_properties(custom_properties, parent_custom_properties.as_ref());
+    let properties = substitute_properties(properties, &custom_properties);
+
+    // Filter display none from render tree
+    if let Some(Some(Value::Display(Display::Box(DisplayBox::None)))) =
+        properties.get(&Property::Display)
+    {
+        return None;
+    }
+
+    Some((properties, custom_properties))
+}
+
 fn compute_styles(
     properties: Properties,
     parent: Option<Weak<RenderNode>>,
     cache: &mut StyleCache,
-) -> HashMap<Property, ValueRef> {
+    viewport: &Size,
+) -> ComputedValues {
+    let parent_style = parent
+        .as_ref()
+        .and_then(|parent| parent.upgrade())
+        .map(|parent| parent.style.clone());
+    let initial = cache.initial_values();
+
+    // a group without declared values is shared with the parent or
+    // with the initial values instead of being computed again
+    let declares = |inherited: bool| {
+        properties.iter().any(|(property, value)| {
+            value.is_some() && INHERITABLES.contains(property) == inherited
+        })
+    };
+    let (declares_inherited, declares_reset) = (declares(true), declares(false));
+
     // get inherit value for a property
     let inherit = |property: Property| {
-        if let Some(parent) = &parent {
-            if let Some(p) = parent.upgrade() {
-                return (property.clone(), (**p.get_style(&property)).clone());
-            }
+        if let Some(style) = &parent_style {
+            return (property.clone(), style.get(&property));
         }
         // if there's no parent
         // we will use the initial value for that property
@@ -91,6 +582,10 @@ fn compute_styles(
 
     // Step 3
     let specified_values = Property::iter()
+        .filter(|property| match INHERITABLES.contains(property) {
+            true => declares_inherited,
+            false => declares_reset,
+        })
         .map(|property| {
             if let Some(value) = properties.get(&property) {
                 if let Some(v) = value {
@@ -129,49 +624,48 @@ fn compute_styles(
         parent,
         properties: temp_specified,
         style_cache: cache,
+        viewport: viewport.clone(),
+        lengths: LengthContext::default(),
+    };
+    context.lengths = element_length_context(&mut context);
+    let mut compute_value = |property: Property| match specified_values.get(&property) {
+        Some(value) => compute(&property, value, &mut context),
+        None => compute(&property, &Value::initial(&property), &mut context),
     };
-    let computed_values = specified_values
-        .into_iter()
-        .map(|(property, value)| {
-            // some properties requires layout to compute
-            let is_not_compute = match property {
-                Property::Width
-                | Property::Height
-                | Property::MarginTop
-                | Property::MarginRight
-                | Property::MarginBottom
-                | Property::MarginLeft
-                | Property::PaddingTop
-                | Property::PaddingRight
-                | Property::PaddingBottom
-                | Property::PaddingLeft
-                | Property::Top
-                | Property::Right
-                | Property::Bottom
-                | Property::Left => true,
-                _ => false,
-            };
-            let computed_value = if is_not_compute {
-                context.style_cache.get(&value)
-            } else {
-                compute(&property, &value, &mut context)
-            };
-            return (property.clone(), computed_value);
-        })
-        .collect::<HashMap<Property, ValueRef>>();
 
-    computed_values
+    let inherited = match parent_style {
+        Some(parent_style) if !declares_inherited => parent_style.inherited,
+        Some(parent_style) => {
+            let inherited = InheritedValues::from_fn(&mut compute_value);
+            if inherited == *parent_style.inherited {
+                parent_style.inherited
+            } else {
+                Rc::new(inherited)
+            }
+        }
+        None if declares_inherited => Rc::new(InheritedValues::from_fn(&mut compute_value)),
+        None => initial.inherited.clone(),
+    };
+    // the initial border colors are `currentColor` so the initial
+    // reset values only apply to the elements with the initial color
+    let reset = match declares_reset || inherited.color != initial.inherited.color {
+        true => Rc::new(ResetValues::from_fn(&mut compute_value)),
+        false => initial.reset,
+    };
+    ComputedValues { inherited, reset }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cascade_layers::{LayerOrder, UNLAYERED};
     use crate::value_processing::{CSSLocation, CascadeOrigin};
     use crate::values::border_style::BorderStyle;
     use crate::values::border_width::BorderWidth;
     use crate::values::color::Color;
     use crate::values::display::Display;
     use crate::values::length::{Length, LengthUnit};
+    use crate::values::length_percentage::LengthPercentageOrAuto;
     use crate::values::number::Number;
     use css::cssom::css_rule::CSSRule;
     use std::rc::Rc;
@@ -179,31 +673,510 @@ mod tests {
     use test_utils::dom_creator::*;
 
     #[test]
-    fn build_tree_simple() {
+    fn build_tree_simple() {
+        let document = document();
+        let dom_tree = element(
+            "div#parent",
+            document.clone(),
+            vec![element(
+                "div#child",
+                document.clone(),
+                vec![text("Hello", document.clone())],
+            )],
+        );
+
+        let css = r#"
+        #parent {
+            background-color: white;
+        }
+        #parent #child {
+            color: rgba(255, 255, 255, 255);
+        }
+        #child {
+            display: block;
+        }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
+
+        let render_tree_inner = render_tree.root.expect("No root node");
+        let parent_styles = &render_tree_inner.style;
+        assert_eq!(
+            parent_styles.get(&Property::BackgroundColor),
+            Value::Color(Color::Rgba(
+                255.0.into(),
+                255.0.into(),
+                255.0.into(),
+                255.0.into()
+            ))
+        );
+
+        let child_inner = render_tree_inner.children.borrow()[0].clone();
+        let child_styles = &child_inner.style;
+        assert_eq!(
+            child_styles.get(&Property::Color),
+            Value::Color(Color::Rgba(
+                255.0.into(),
+                255.0.into(),
+                255.0.into(),
+                255.0.into()
+            ))
+        );
+        assert_eq!(
+            child_styles.get(&Property::Display),
+            Value::Display(Display::new_block())
+        );
+    }
+
+    #[test]
+    fn inline_style_attribute() {
+        let document = document();
+        let dom_tree = element("div#parent", document.clone(), vec![]);
+        dom_tree.set_attribute("style", "display: inline; color: black");
+
+        let css = r#"
+        div#parent {
+            display: block;
+            color: white;
+        }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
+        let styles = &render_tree.root.expect("No root node").style;
+
+        assert_eq!(
+            styles.get(&Property::Display),
+            Value::Display(Display::new_inline())
+        );
+        assert_eq!(styles.get(&Property::Color), Value::Color(Color::black()));
+    }
+
+    #[test]
+    fn resolve_current_color() {
+        let document = document();
+        let dom_tree = element(
+            "div#parent",
+            document.clone(),
+            vec![
+                element("p#inherit", document.clone(), vec![]),
+                element("p#own", document.clone(), vec![]),
+            ],
+        );
+
+        let css = r#"
+        #parent { color: rgb(0 0 255); }
+        p { border-color: currentColor; }
+        #inherit { color: currentColor; }
+        #own {
+            color: red;
+            background-color: color-mix(in srgb, currentColor 50%, transparent);
+        }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
+        let root = render_tree.root.expect("No root node");
+        let children = root.children.borrow();
+        let rgba = |r: f32, g: f32, b: f32, a: f32| {
+            Value::Color(Color::Rgba(r.into(), g.into(), b.into(), a.into()))
+        };
+
+        let inherit = &children[0].style;
+        assert_eq!(inherit.get(&Property::Color), rgba(0., 0., 255., 255.));
+        assert_eq!(
+            inherit.get(&Property::BorderTopColor),
+            rgba(0., 0., 255., 255.)
+        );
+        let own = &children[1].style;
+        assert_eq!(own.get(&Property::BorderTopColor), rgba(255., 0., 0., 255.));
+        assert_eq!(
+            own.get(&Property::BackgroundColor),
+            rgba(255., 0., 0., 127.5)
+        );
+    }
+
+    #[test]
+    fn compute_initial_border_colors() {
+        let document = document();
+        let dom_tree = element(
+            "div",
+            document.clone(),
+            vec![
+                element(
+                    "div#red",
+                    document.clone(),
+                    vec![element("span", document.clone(), vec![])],
+                ),
+                element("div", document.clone(), vec![]),
+            ],
+        );
+
+        let stylesheet = parse_stylesheet("#red { color: red; }");
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let render_tree = TreeBuilder::build(dom_tree, &rules);
+        let root = render_tree.root.expect("No root node");
+        let children = root.children.borrow();
+        let red = children[0].clone();
+        let span = red.children.borrow()[0].clone();
+        let rgb = |r: f32, g: f32, b: f32| Color::Rgba(r.into(), g.into(), b.into(), 255.0.into());
+        assert_eq!(red.style.border_top_color(), &rgb(255., 0., 0.));
+        assert_eq!(span.style.border_top_color(), &rgb(255., 0., 0.));
+        assert_eq!(children[1].style.border_top_color(), &rgb(0., 0., 0.));
+        assert!(Rc::ptr_eq(&children[1].style.reset, &root.style.reset));
+    }
+
+    #[test]
+    fn share_styles_between_similar_elements() {
+        let document = document();
+        let item = |children| element("li.item", document.clone(), children);
+        let list = |items| element("ul", document.clone(), items);
+        let dom_tree = element(
+            "div",
+            document.clone(),
+            vec![
+                list(vec![item(vec![]), item(vec![]), item(vec![])]),
+                list(vec![item(vec![]), item(vec![])]),
+                element("ul#last", document.clone(), vec![item(vec![])]),
+            ],
+        );
+
+        let css = r#"
+        li.item { display: block; color: white; }
+        li:first-child { color: black; }
+        #last { color: red; }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+        let index = RuleIndex::new(&rules);
+        let mut animations = AnimationEngine::new();
+        let mut context = BuildContext {
+            rules: &index,
+            ancestors: AncestorFilter::new(),
+            cache: StyleCache::new(),
+            generated: GeneratedContent::new(),
+            animations: &mut animations,
+            sharing: StyleSharingCache::new(),
+            viewport: Size::default(),
+        };
+        let root = build_from_node(dom_tree, None, &mut context).expect("No root node");
+
+        // the second list and its items share the styles of the first list.
+        // The first item of a list matches another rule than its siblings
+        // and the items of the last list have another parent style.
+        assert_eq!(context.sharing.hits, 4);
+        assert_eq!(context.sharing.misses, 6);
+
+        let colors = root
+            .children
+            .borrow()
+            .iter()
+            .map(|list| {
+                list.children
+                    .borrow()
+                    .iter()
+                    .map(|item| item.style.color().clone())
+                    .collect::<Vec<Color>>()
+            })
+            .collect::<Vec<Vec<Color>>>();
+        let white = Color::Rgba(255.0.into(), 255.0.into(), 255.0.into(), 255.0.into());
+        let black = Color::black();
+        assert_eq!(
+            colors,
+            vec![
+                vec![black.clone(), white.clone(), white.clone()],
+                vec![black.clone(), white],
+                vec![black],
+            ]
+        );
+    }
+
+    #[test]
+    fn share_unchanged_value_groups() {
+        let document = document();
+        let dom_tree = element(
+            "div#parent",
+            document.clone(),
+            vec![
+                element("span", document.clone(), vec![]),
+                element("p", document.clone(), vec![]),
+            ],
+        );
+
+        let css = r#"
+        #parent { color: black; }
+        p { color: black; display: block; }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let root = TreeBuilder::build(dom_tree, &rules)
+            .root
+            .expect("No root node");
+        let children = root.children.borrow();
+        let (span, p) = (&children[0].style, &children[1].style);
+
+        // the span declares nothing and the p the same color as its parent
+        assert!(Rc::ptr_eq(&span.inherited, &root.style.inherited));
+        assert!(Rc::ptr_eq(&p.inherited, &root.style.inherited));
+        assert!(Rc::ptr_eq(&span.reset, &root.style.reset));
+        assert!(!Rc::ptr_eq(&p.reset, &root.style.reset));
+        assert_eq!(p.display(), &Display::new_block());
+    }
+
+    #[test]
+    fn restyle_invalidated_elements() {
+        let document = document();
+        let dom_tree = element(
+            "div#parent",
+            document.clone(),
+            vec![
+                element("p#first", document.clone(), vec![]),
+                element("p#second", document.clone(), vec![]),
+            ],
+        );
+
+        let css = r#"
+        p { display: block; }
+        .big { width: 10px; }
+        .big + p { height: 10px; }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+        let index = RuleIndex::new(&rules);
+        let mut animations = AnimationEngine::new();
+        let mut tree = TreeBuilder::build_with_index(
+            dom_tree.clone(),
+            &index,
+            Size::default(),
+            &mut animations,
+        );
+        let old_root = tree.root.clone().expect("No root node");
+        let old_children = old_root.children.borrow().clone();
+
+        let (first, second) = (old_children[0].node.clone(), old_children[1].node.clone());
+        first.set_attribute("class", "big");
+        assert!(dom_tree.dirty_flags().contains(DirtyFlags::DESCENDANTS));
+        let relayout = TreeBuilder::restyle(
+            &mut tree,
+            dom_tree.clone(),
+            &index,
+            Size::default(),
+            &mut animations,
+        );
+
+        assert_eq!(relayout.len(), 2);
+        assert!(Rc::ptr_eq(&relayout[0], &first));
+        assert!(Rc::ptr_eq(&relayout[1], &second));
+        let root = tree.root.clone().expect("No root node");
+        assert!(Rc::ptr_eq(&root, &old_root));
+        let children = root.children.borrow().clone();
+        assert!(!Rc::ptr_eq(&children[0], &old_children[0]));
+        assert_eq!(
+            children[0].style.width(),
+            &LengthPercentageOrAuto::Length(Length::new_px(10.))
+        );
+        assert_eq!(
+            children[1].style.height(),
+            &LengthPercentageOrAuto::Length(Length::new_px(10.))
+        );
+        assert!(dom_tree.dirty_flags().is_empty());
+
+        // nothing depends on the ID
+        first.set_attribute("id", "other");
+        let relayout = TreeBuilder::restyle(
+            &mut tree,
+            dom_tree.clone(),
+            &index,
+            Size::default(),
+            &mut animations,
+        );
+        assert!(relayout.is_empty());
+        assert!(Rc::ptr_eq(&root.children.borrow()[0], &children[0]));
+
+        Node::append_child(dom_tree.clone(), element("p", document.clone(), vec![]));
+        let relayout = TreeBuilder::restyle(
+            &mut tree,
+            dom_tree.clone(),
+            &index,
+            Size::default(),
+            &mut animations,
+        );
+        assert_eq!(relayout.len(), 2);
+        assert!(Rc::ptr_eq(&relayout[0], &dom_tree));
+        assert_eq!(root.children.borrow().len(), 3);
+        assert!(Rc::ptr_eq(&root.children.borrow()[1], &children[1]));
+    }
+
+    #[test]
+    fn prune_animations_of_removed_elements() {
+        let document = document();
+        let dom_tree = element(
+            "div#parent",
+            document.clone(),
+            vec![
+                element("p#first", document.clone(), vec![]),
+                element("p#second", document.clone(), vec![]),
+            ],
+        );
+
+        let css = r#"
+        p { display: block; animation: grow 1s; }
+        @keyframes grow { to { width: 10px; } }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+        let index = RuleIndex::new(&rules);
+        let mut animations = AnimationEngine::new();
+        animations.set_keyframes(&stylesheet.keyframes.iter().collect::<Vec<_>>());
+        let mut tree = TreeBuilder::build_with_index(
+            dom_tree.clone(),
+            &index,
+            Size::default(),
+            &mut animations,
+        );
+        assert!(animations.is_active());
+
+        let first = dom_tree.first_child().expect("No first child");
+        let second = dom_tree.last_child().expect("No second child");
+        Node::detach(first);
+        TreeBuilder::restyle(
+            &mut tree,
+            dom_tree.clone(),
+            &index,
+            Size::default(),
+            &mut animations,
+        );
+        // the animation of the element left untouched keeps running
+        assert!(animations.is_active());
+
+        Node::detach(second);
+        TreeBuilder::restyle(
+            &mut tree,
+            dom_tree.clone(),
+            &index,
+            Size::default(),
+            &mut animations,
+        );
+        assert!(!animations.is_active());
+    }
+
+    #[test]
+    fn cascade_layers_specificity_and_order() {
         let document = document();
-        let dom_tree = element(
-            "div#parent",
-            document.clone(),
-            vec![element(
-                "div#child",
-                document.clone(),
-                vec![text("Hello", document.clone())],
-            )],
-        );
+        let dom_tree = element("p#text.big", document.clone(), vec![]);
 
         let css = r#"
-        #parent {
-            background-color: white;
-        }
-        #parent #child {
-            color: rgba(255, 255, 255, 255);
-        }
-        #child {
-            display: block;
-        }
+        @layer base, theme;
+        @layer theme { p { width: 1px; } p { margin-left: 2px !important; } }
+        @layer base { #text { width: 2px; margin-left: 1px !important; } }
+        #missing, p { height: 1px; }
+        .big { height: 2px; }
+        p { margin-top: 1px; }
+        p { margin-top: 2px; }
         "#;
 
         let stylesheet = parse_stylesheet(css);
+        let layers = LayerOrder::new(stylesheet.layers.iter().map(|layer| (0, &layer.name)));
 
         let rules = stylesheet
             .iter()
@@ -212,39 +1185,19 @@ mod tests {
                     inner: style,
                     location: CSSLocation::Embedded,
                     origin: CascadeOrigin::User,
+                    layer: layers.rank(0, &style.layer),
                 },
             })
             .collect::<Vec<ContextualRule>>();
 
-        let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
-
-        let render_tree_inner = render_tree.root.expect("No root node");
-        let parent_styles = &render_tree_inner.properties;
-        assert_eq!(
-            parent_styles.get(&Property::BackgroundColor),
-            Some(&ValueRef(Rc::new(Value::Color(Color::Rgba(
-                255.0.into(),
-                255.0.into(),
-                255.0.into(),
-                255.0.into()
-            )))))
-        );
-
-        let child_inner = render_tree_inner.children.borrow()[0].clone();
-        let child_styles = &child_inner.properties;
-        assert_eq!(
-            child_styles.get(&Property::Color),
-            Some(&ValueRef(Rc::new(Value::Color(Color::Rgba(
-                255.0.into(),
-                255.0.into(),
-                255.0.into(),
-                255.0.into()
-            )))))
-        );
-        assert_eq!(
-            child_styles.get(&Property::Display),
-            Some(&ValueRef(Rc::new(Value::Display(Display::new_block()))))
-        );
+        let root = TreeBuilder::build(dom_tree, &rules)
+            .root
+            .expect("No root node");
+        let px = |value: f32| LengthPercentageOrAuto::Length(Length::new_px(value));
+        assert_eq!(root.style.width(), &px(1.));
+        assert_eq!(root.style.margin_left(), &px(1.));
+        assert_eq!(root.style.height(), &px(2.));
+        assert_eq!(root.style.margin_top(), &px(2.));
     }
 
     #[test]
@@ -267,6 +1220,7 @@ mod tests {
                     inner: style,
                     location: CSSLocation::Embedded,
                     origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
                 },
             })
             .collect::<Vec<ContextualRule>>();
@@ -274,34 +1228,34 @@ mod tests {
         let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
 
         let render_tree_inner = render_tree.root.expect("No root node");
-        let parent_styles = &render_tree_inner.properties;
+        let parent_styles = &render_tree_inner.style;
         assert_eq!(
             parent_styles.get(&Property::MarginTop),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(20.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::MarginRight),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(20.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::MarginBottom),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(20.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::MarginLeft),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(20.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
     }
 
@@ -324,6 +1278,7 @@ mod tests {
                     inner: style,
                     location: CSSLocation::Embedded,
                     origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
                 },
             })
             .collect::<Vec<ContextualRule>>();
@@ -331,54 +1286,54 @@ mod tests {
         let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
 
         let render_tree_inner = render_tree.root.expect("No root node");
-        let parent_styles = &render_tree_inner.properties;
+        let parent_styles = &render_tree_inner.style;
         assert_eq!(
             parent_styles.get(&Property::BorderTopColor),
-            Some(&ValueRef(Rc::new(Value::Color(Color::black()))))
+            Value::Color(Color::black())
         );
         assert_eq!(
             parent_styles.get(&Property::BorderRightColor),
-            Some(&ValueRef(Rc::new(Value::Color(Color::black()))))
+            Value::Color(Color::black())
         );
         assert_eq!(
             parent_styles.get(&Property::BorderBottomColor),
-            Some(&ValueRef(Rc::new(Value::Color(Color::black()))))
+            Value::Color(Color::black())
         );
         assert_eq!(
             parent_styles.get(&Property::BorderLeftColor),
-            Some(&ValueRef(Rc::new(Value::Color(Color::black()))))
+            Value::Color(Color::black())
         );
         assert_eq!(
             parent_styles.get(&Property::BorderTopWidth),
-            Some(&ValueRef(Rc::new(Value::BorderWidth(BorderWidth::Medium))))
+            Value::BorderWidth(BorderWidth::Medium)
         );
         assert_eq!(
             parent_styles.get(&Property::BorderRightWidth),
-            Some(&ValueRef(Rc::new(Value::BorderWidth(BorderWidth::Medium))))
+            Value::BorderWidth(BorderWidth::Medium)
         );
         assert_eq!(
             parent_styles.get(&Property::BorderBottomWidth),
-            Some(&ValueRef(Rc::new(Value::BorderWidth(BorderWidth::Medium))))
+            Value::BorderWidth(BorderWidth::Medium)
         );
         assert_eq!(
             parent_styles.get(&Property::BorderLeftWidth),
-            Some(&ValueRef(Rc::new(Value::BorderWidth(BorderWidth::Medium))))
+            Value::BorderWidth(BorderWidth::Medium)
         );
         assert_eq!(
             parent_styles.get(&Property::BorderTopStyle),
-            Some(&ValueRef(Rc::new(Value::BorderStyle(BorderStyle::None))))
+            Value::BorderStyle(BorderStyle::None)
         );
         assert_eq!(
             parent_styles.get(&Property::BorderRightStyle),
-            Some(&ValueRef(Rc::new(Value::BorderStyle(BorderStyle::None))))
+            Value::BorderStyle(BorderStyle::None)
         );
         assert_eq!(
             parent_styles.get(&Property::BorderBottomStyle),
-            Some(&ValueRef(Rc::new(Value::BorderStyle(BorderStyle::None))))
+            Value::BorderStyle(BorderStyle::None)
         );
         assert_eq!(
             parent_styles.get(&Property::BorderLeftStyle),
-            Some(&ValueRef(Rc::new(Value::BorderStyle(BorderStyle::None))))
+            Value::BorderStyle(BorderStyle::None)
         );
     }
 
@@ -401,6 +1356,7 @@ mod tests {
                     inner: style,
                     location: CSSLocation::Embedded,
                     origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
                 },
             })
             .collect::<Vec<ContextualRule>>();
@@ -408,34 +1364,34 @@ mod tests {
         let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
 
         let render_tree_inner = render_tree.root.expect("No root node");
-        let parent_styles = &render_tree_inner.properties;
+        let parent_styles = &render_tree_inner.style;
         assert_eq!(
             parent_styles.get(&Property::PaddingTop),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(20.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::PaddingRight),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(10.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::PaddingBottom),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(20.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::PaddingLeft),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(0.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
     }
 
@@ -466,6 +1422,7 @@ mod tests {
                     inner: style,
                     location: CSSLocation::Embedded,
                     origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
                 },
             })
             .collect::<Vec<ContextualRule>>();
@@ -473,23 +1430,23 @@ mod tests {
         let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
 
         let render_tree_inner = render_tree.root.expect("No root node");
-        let parent_styles = &render_tree_inner.properties;
+        let parent_styles = &render_tree_inner.style;
         assert_eq!(
             parent_styles.get(&Property::Color),
-            Some(&ValueRef(Rc::new(Value::Color(Color::Rgba(
+            Value::Color(Color::Rgba(
                 Number(255.0),
                 Number(0.0),
                 Number(0.0),
                 Number(255.0),
-            )))))
+            ))
         );
 
         let child_inner = render_tree_inner.children.borrow()[0].clone();
-        let child_styles = &child_inner.properties;
+        let child_styles = &child_inner.style;
 
         assert_eq!(
             child_styles.get(&Property::Color),
-            Some(&ValueRef(Rc::new(Value::Color(Color::black()))))
+            Value::Color(Color::black())
         );
     }
 
@@ -512,6 +1469,7 @@ mod tests {
                     inner: style,
                     location: CSSLocation::Embedded,
                     origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
                 },
             })
             .collect::<Vec<ContextualRule>>();
@@ -519,34 +1477,34 @@ mod tests {
         let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
 
         let render_tree_inner = render_tree.root.expect("No root node");
-        let parent_styles = &render_tree_inner.properties;
+        let parent_styles = &render_tree_inner.style;
         assert_eq!(
             parent_styles.get(&Property::MarginTop),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(20.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::MarginRight),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(10.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::MarginBottom),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(20.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::MarginLeft),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(10.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
     }
 
@@ -569,6 +1527,7 @@ mod tests {
                     inner: style,
                     location: CSSLocation::Embedded,
                     origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
                 },
             })
             .collect::<Vec<ContextualRule>>();
@@ -576,66 +1535,427 @@ mod tests {
         let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
 
         let render_tree_inner = render_tree.root.expect("No root node");
-        let parent_styles = &render_tree_inner.properties;
+        let parent_styles = &render_tree_inner.style;
         assert_eq!(
             parent_styles.get(&Property::BorderTopColor),
-            Some(&ValueRef(Rc::new(Value::Color(Color::black()))))
+            Value::Color(Color::black())
         );
         assert_eq!(
             parent_styles.get(&Property::BorderRightColor),
-            Some(&ValueRef(Rc::new(Value::Color(Color::black()))))
+            Value::Color(Color::black())
         );
         assert_eq!(
             parent_styles.get(&Property::BorderBottomColor),
-            Some(&ValueRef(Rc::new(Value::Color(Color::black()))))
+            Value::Color(Color::black())
         );
         assert_eq!(
             parent_styles.get(&Property::BorderLeftColor),
-            Some(&ValueRef(Rc::new(Value::Color(Color::black()))))
+            Value::Color(Color::black())
         );
         assert_eq!(
             parent_styles.get(&Property::BorderTopWidth),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(2.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::BorderRightWidth),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(2.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::BorderBottomWidth),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(2.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::BorderLeftWidth),
-            Some(&ValueRef(Rc::new(Value::Length(Length {
+            Value::Length(Length {
                 value: Number(2.0),
                 unit: LengthUnit::Px
-            }))))
+            })
         );
         assert_eq!(
             parent_styles.get(&Property::BorderTopStyle),
-            Some(&ValueRef(Rc::new(Value::BorderStyle(BorderStyle::Dotted))))
+            Value::BorderStyle(BorderStyle::Dotted)
         );
         assert_eq!(
             parent_styles.get(&Property::BorderRightStyle),
-            Some(&ValueRef(Rc::new(Value::BorderStyle(BorderStyle::Dotted))))
+            Value::BorderStyle(BorderStyle::Dotted)
         );
         assert_eq!(
             parent_styles.get(&Property::BorderBottomStyle),
-            Some(&ValueRef(Rc::new(Value::BorderStyle(BorderStyle::Dotted))))
+            Value::BorderStyle(BorderStyle::Dotted)
         );
         assert_eq!(
             parent_styles.get(&Property::BorderLeftStyle),
-            Some(&ValueRef(Rc::new(Value::BorderStyle(BorderStyle::Dotted))))
+            Value::BorderStyle(BorderStyle::Dotted)
+        );
+    }
+
+    #[test]
+    fn custom_properties() {
+        let document = document();
+        let dom_tree = element(
+            "div#parent",
+            document.clone(),
+            vec![element("div#child", document.clone(), vec![])],
+        );
+
+        let css = r#"
+        #parent {
+            --main-color: red;
+            --gap: 10px 20px;
+            color: var(--main-color);
+            background-color: var(--missing, var(--main-color));
+        }
+        #parent #child {
+            --loop: var(--loop);
+            margin: var(--gap);
+            background-color: var(--loop);
+        }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
+
+        let red = Value::Color(Color::Rgba(
+            Number(255.0),
+            Number(0.0),
+            Number(0.0),
+            Number(255.0),
+        ));
+        let render_tree_inner = render_tree.root.expect("No root node");
+        let parent_styles = &render_tree_inner.style;
+        assert_eq!(parent_styles.get(&Property::Color), red.clone());
+        assert_eq!(parent_styles.get(&Property::BackgroundColor), red);
+
+        let child_inner = render_tree_inner.children.borrow()[0].clone();
+        let child_styles = &child_inner.style;
+        assert_eq!(
+            child_styles.get(&Property::MarginTop),
+            Value::Length(Length::new_px(10.0))
+        );
+        assert_eq!(
+            child_styles.get(&Property::MarginLeft),
+            Value::Length(Length::new_px(20.0))
+        );
+        // invalid at computed-value time behaves like unset
+        assert_eq!(
+            child_styles.get(&Property::BackgroundColor),
+            Value::Color(Color::transparent())
+        );
+        assert!(child_inner.custom_properties.contains_key("--main-color"));
+        assert!(!child_inner.custom_properties.contains_key("--loop"));
+    }
+
+    #[test]
+    fn compute_math_functions() {
+        let dom_tree = element("div#parent", document(), vec![]);
+
+        let css = r#"
+        #parent {
+            font-size: calc(10px + 50%);
+            width: calc(2em + 10px);
+            height: calc(100% - 1em);
+            margin: min(1in, 10%) max(1em, 5px);
+        }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
+
+        let render_tree_inner = render_tree.root.expect("No root node");
+        assert_eq!(render_tree_inner.style.font_size(), &Length::new_px(18.0));
+        assert_eq!(
+            render_tree_inner.style.width(),
+            &LengthPercentageOrAuto::Length(Length::new_px(46.0))
+        );
+        assert_eq!(render_tree_inner.style.height().to_px(100.0), 82.0);
+        assert_eq!(render_tree_inner.style.margin_top().to_px(100.0), 10.0);
+        assert_eq!(render_tree_inner.style.margin_top().to_px(2000.0), 96.0);
+        assert_eq!(
+            render_tree_inner.style.margin_left(),
+            &LengthPercentageOrAuto::Length(Length::new_px(18.0))
+        );
+    }
+
+    #[test]
+    fn compute_relative_lengths() {
+        let document = document();
+        let dom_tree = element(
+            "div#root",
+            document.clone(),
+            vec![element("p#child", document.clone(), vec![])],
+        );
+
+        let css = r#"
+        #root { font-size: 1.25REM; width: 50vw; height: 10dvh; }
+        #child {
+            font-size: 2em;
+            width: calc(10vmin + 1rem);
+            margin: 1ch 1lh 1cm 4Q;
+        }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let viewport = Size::new(800.0, 600.0);
+        let render_tree = TreeBuilder::build_with_animations(
+            dom_tree.clone(),
+            &rules,
+            viewport,
+            &mut AnimationEngine::new(),
+        );
+        let px = |value: f32| Value::Length(Length::new_px(value));
+
+        let root = render_tree.root.expect("No root node");
+        assert_eq!(root.style.get(&Property::FontSize), px(20.0));
+        assert_eq!(root.style.get(&Property::Width), px(400.0));
+        assert_eq!(root.style.get(&Property::Height), px(60.0));
+
+        let children = root.children.borrow();
+        let child = &children[0].style;
+        assert_eq!(child.get(&Property::FontSize), px(40.0));
+        assert_eq!(child.get(&Property::Width), px(80.0));
+        assert_eq!(child.get(&Property::MarginTop), px(20.0));
+        assert_eq!(child.get(&Property::MarginRight), px(48.0));
+        assert_eq!(child.margin_bottom().to_px(0.0), 96.0 / 2.54);
+        assert_eq!(child.margin_left().to_px(0.0), 96.0 / 25.4);
+    }
+
+    #[test]
+    fn map_logical_properties() {
+        let document = document();
+        let dom_tree = element(
+            "div#root",
+            document.clone(),
+            vec![
+                element("p#rtl", document.clone(), vec![]),
+                element("p#vertical", document.clone(), vec![]),
+            ],
+        );
+
+        let css = r#"
+        #root {
+            direction: rtl;
+            margin-left: 1px;
+            margin-inline-start: 2px;
+            margin-right: 3px;
+            inset: 1px 2px 3px;
+        }
+        p { margin-inline: 4px 5px; padding-block-start: 6px; inline-size: 7px; }
+        #rtl { margin-right: 8px; }
+        #vertical { writing-mode: vertical-rl; direction: ltr; }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
+        let px = |value: f32| Value::Length(Length::new_px(value));
+
+        // the last declaration wins between a logical and a physical property
+        let root = render_tree.root.expect("No root node");
+        assert_eq!(root.style.get(&Property::MarginLeft), px(1.0));
+        assert_eq!(root.style.get(&Property::MarginRight), px(3.0));
+        assert_eq!(root.style.get(&Property::Left), px(2.0));
+
+        let children = root.children.borrow();
+        let rtl = &children[0].style;
+        assert_eq!(rtl.get(&Property::MarginLeft), px(5.0));
+        assert_eq!(rtl.get(&Property::MarginRight), px(8.0));
+        assert_eq!(rtl.get(&Property::PaddingTop), px(6.0));
+        assert_eq!(rtl.get(&Property::Width), px(7.0));
+
+        let vertical = &children[1].style;
+        assert_eq!(vertical.get(&Property::MarginTop), px(4.0));
+        assert_eq!(vertical.get(&Property::MarginBottom), px(5.0));
+        assert_eq!(vertical.get(&Property::PaddingRight), px(6.0));
+        assert_eq!(vertical.get(&Property::Height), px(7.0));
+        assert_eq!(vertical.get(&Property::MarginLeft), px(0.0));
+    }
+
+    #[test]
+    fn map_logical_properties_with_references() {
+        let document = document();
+        let dom_tree = element(
+            "div#root",
+            document.clone(),
+            vec![element("p", document.clone(), vec![])],
+        );
+
+        let css = r#"
+        #root { --x: 1px 2px; --b: 3px solid red; --mode: vertical-rl; }
+        p {
+            writing-mode: var(--mode);
+            margin-inline: var(--x);
+            border-block: var(--b);
+        }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
+        let px = |value: f32| Value::Length(Length::new_px(value));
+
+        // the references in writing-mode are substituted before the
+        // flow-relative properties are mapped with it
+        let root = render_tree.root.expect("No root node");
+        let p = &root.children.borrow()[0].style;
+        assert_eq!(p.get(&Property::MarginTop), px(1.0));
+        assert_eq!(p.get(&Property::MarginBottom), px(2.0));
+        assert_eq!(p.get(&Property::MarginLeft), px(0.0));
+        assert_eq!(p.get(&Property::BorderRightWidth), px(3.0));
+        assert_eq!(p.get(&Property::BorderLeftWidth), px(3.0));
+        assert_eq!(
+            p.get(&Property::BorderLeftStyle),
+            Value::BorderStyle(BorderStyle::Solid)
+        );
+        assert_eq!(
+            p.get(&Property::BorderTopStyle),
+            Value::BorderStyle(BorderStyle::None)
+        );
+    }
+
+    #[test]
+    fn generated_content() {
+        let document = document();
+        let dom_tree = element(
+            "ol",
+            document.clone(),
+            vec![
+                element("li#a", document.clone(), vec![]),
+                element("li#b", document.clone(), vec![]),
+                element("li#c", document.clone(), vec![]),
+            ],
+        );
+
+        let css = r#"
+        ol { counter-reset: item 5; }
+        li { counter-increment: item; }
+        li::before { content: counter(item, lower-roman) ". " attr(id); }
+        li:after { content: open-quote; }
+        #c::after { content: close-quote "!"; }
+        #b::before { content: none; }
+        "#;
+
+        let stylesheet = parse_stylesheet(css);
+
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style,
+                    location: CSSLocation::Embedded,
+                    origin: CascadeOrigin::User,
+                    layer: UNLAYERED,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let render_tree = TreeBuilder::build(dom_tree.clone(), &rules);
+        let root = render_tree.root.expect("No root node");
+
+        let generated_text = |node: &Rc<RenderNode>| {
+            let children = node.children.borrow();
+            match children[0].node.data() {
+                Some(NodeData::Text(text)) => text.get_data(),
+                _ => panic!("Expected generated text"),
+            }
+        };
+        let items = root.children.borrow();
+        let pseudo_elements = |index: usize| {
+            items[index]
+                .children
+                .borrow()
+                .iter()
+                .map(|child| (child.pseudo_element, generated_text(child)))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            pseudo_elements(0),
+            vec![
+                (Some(PseudoElement::Before), "vi. a".to_string()),
+                (Some(PseudoElement::After), "\u{201C}".to_string()),
+            ]
+        );
+        assert_eq!(
+            pseudo_elements(1),
+            vec![(Some(PseudoElement::After), "\u{2018}".to_string())]
+        );
+        assert_eq!(
+            pseudo_elements(2),
+            vec![
+                (Some(PseudoElement::Before), "viii. c".to_string()),
+                (Some(PseudoElement::After), "\u{2019}!".to_string()),
+            ]
         );
     }
 }
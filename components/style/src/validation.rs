@@ -0,0 +1,157 @@
+use crate::custom_properties::{has_references, is_custom_property};
+use crate::expand::{expand_shorthand, get_expander_shorthand_property};
+use crate::logical::{expand_logical_shorthand, get_logical_shorthand_longhands, LogicalProperty};
+use crate::property::Property;
+use crate::value::Value;
+use css::cssom::css_rule::CSSRule;
+use css::cssom::stylesheet::StyleSheet;
+use css::diagnostics::{Diagnostic, DiagnosticKind};
+use css::parser::structs::Declaration;
+use css::serializer::ToCss;
+
+/// Check the declarations of a stylesheet against the supported
+/// properties and values, and record a diagnostic for each
+/// declaration that would be ignored by the cascade. A stylesheet
+/// is only validated once.
+pub fn validate_stylesheet(stylesheet: &mut StyleSheet) {
+    if stylesheet.is_validated() {
+        return;
+    }
+    let mut diagnostics = Vec::new();
+
+    let style_declarations = stylesheet.iter().flat_map(|rule| {
+        let CSSRule::Style(rule) = rule;
+        rule.declarations.iter()
+    });
+    let keyframe_declarations = stylesheet
+        .keyframes
+        .iter()
+        .flat_map(|rule| rule.keyframes.iter())
+        .flat_map(|keyframe| keyframe.declarations.iter());
+
+    for declaration in style_declarations.chain(keyframe_declarations) {
+        if let Some(kind) = validate_declaration(declaration) {
+            diagnostics.push(Diagnostic::new(
+                kind,
+                declaration.location.unwrap_or_default(),
+            ));
+        }
+    }
+
+    stylesheet.add_diagnostics(diagnostics);
+    stylesheet.mark_validated();
+}
+
+/// Whether the property of a declaration is supported and its value
+/// is valid for it, e.g. to check a value set through the CSSOM
+pub fn is_valid_declaration(declaration: &Declaration) -> bool {
+    validate_declaration(declaration).is_none()
+}
+
+fn validate_declaration(declaration: &Declaration) -> Option<DiagnosticKind> {
+    let name = &declaration.name;
+
+    // values with var() can only be checked after substitution
+    if is_custom_property(name) || has_references(&declaration.value) {
+        return None;
+    }
+
+    let is_valid = if get_expander_shorthand_property(name).is_some() {
+        expand_shorthand(name, &declaration.value).is_some()
+    } else if let Some(property) = LogicalProperty::parse(name) {
+        property.parse_value(&declaration.value).is_some()
+    } else if get_logical_shorthand_longhands(name).is_some() {
+        expand_logical_shorthand(name, &declaration.value).is_some()
+    } else {
+        match Property::parse(name) {
+            Some(property) => Value::parse(&property, &declaration.value).is_some(),
+            None => return Some(DiagnosticKind::UnknownProperty(name.clone())),
+        }
+    };
+
+    if is_valid {
+        return None;
+    }
+
+    let value = declaration
+        .value
+        .iter()
+        .map(|value| value.to_css_string())
+        .collect();
+    Some(DiagnosticKind::InvalidValue {
+        property: name.clone(),
+        value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use css::cssom::css_style_declaration::CSSStyleDeclaration;
+    use css::parser::Parser;
+    use css::tokenizer::token::Token;
+    use css::tokenizer::Tokenizer;
+
+    #[test]
+    fn validate_declarations() {
+        let css = "a {\n  colour: red;\n  color: 12px;\n  margin: 0 auto;\n  width: var(--w);\n}\np { padding: x }";
+        let tokenizer = Tokenizer::new(css.chars());
+        let (tokens, spans) = tokenizer.run_with_spans();
+        let mut parser = Parser::<Token>::with_spans(tokens, spans);
+        let mut stylesheet = parser.parse_a_css_stylesheet();
+        validate_stylesheet(&mut stylesheet);
+        // validating again doesn't duplicate the diagnostics
+        validate_stylesheet(&mut stylesheet);
+
+        assert_eq!(
+            stylesheet
+                .diagnostics()
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "2:3: unknown property `colour`",
+                "3:3: invalid value `12px` for property `color`",
+                "7:5: invalid value `x` for property `padding`",
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_logical_properties() {
+        let css = "a {\n  margin-inline: 1px 2px;\n  padding-block-start: 3px;\n  border-block: 1px solid red;\n  margin-block: x;\n  inset-block-middle: 0;\n}";
+        let tokenizer = Tokenizer::new(css.chars());
+        let (tokens, spans) = tokenizer.run_with_spans();
+        let mut parser = Parser::<Token>::with_spans(tokens, spans);
+        let mut stylesheet = parser.parse_a_css_stylesheet();
+        validate_stylesheet(&mut stylesheet);
+
+        assert_eq!(
+            stylesheet
+                .diagnostics()
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "5:3: invalid value `x` for property `margin-block`",
+                "6:3: unknown property `inset-block-middle`",
+            ]
+        );
+    }
+
+    #[test]
+    fn set_only_valid_properties() {
+        let mut style = CSSStyleDeclaration::default();
+        assert!(!style.set_property("colour", "red", "", is_valid_declaration));
+        assert!(!style.set_property("color", "12px", "", is_valid_declaration));
+        assert!(!style.set_property("margin", "0 x", "", is_valid_declaration));
+        assert!(style.set_property("color", "red", "", is_valid_declaration));
+        assert!(style.set_property("margin", "0 auto", "", is_valid_declaration));
+        assert!(style.set_property("--gap", "anything", "", is_valid_declaration));
+        assert!(style.set_property("width", "var(--w)", "", is_valid_declaration));
+        assert_eq!(
+            style.css_text(),
+            "color: red; margin: 0 auto; --gap: anything; width: var(--w);"
+        );
+    }
+}
@@ -2,7 +2,12 @@ use css::{parser::structs::ComponentValue, tokenizer::token::Token};
 
 use crate::property::Property;
 
+use super::values::animation::{
+    AnimationDirection, AnimationName, FillMode, IterationCount, List, TransitionProperty,
+};
 use super::values::prelude::*;
+use super::values::time::Time;
+use super::values::timing_function::TimingFunction;
 
 macro_rules! parse_value {
     (Auto; $tokens:ident) => {{
@@ -61,7 +66,20 @@ pub enum Value {
     Float(Float),
     Position(Position),
     Direction(Direction),
+    WritingMode(WritingMode),
     BorderRadius(BorderRadius),
+    Content(Content),
+    CounterList(CounterList),
+    Calc(Calc),
+    TimeList(TimeList),
+    TimingFunctionList(TimingFunctionList),
+    AnimationNameList(AnimationNameList),
+    IterationCountList(IterationCountList),
+    AnimationDirectionList(AnimationDirectionList),
+    FillModeList(FillModeList),
+    TransitionPropertyList(TransitionPropertyList),
+    Transform(Transform),
+    Unparsed(UnparsedValue),
     Auto,
     Inherit,
     Initial,
@@ -84,43 +102,43 @@ impl Value {
                 tokens
             ),
             Property::Width => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset;
                 tokens
             ),
             Property::Height => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset;
                 tokens
             ),
             Property::MarginTop => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset;
                 tokens
             ),
             Property::MarginRight => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset;
                 tokens
             ),
             Property::MarginBottom => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset;
                 tokens
             ),
             Property::MarginLeft => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset;
                 tokens
             ),
             Property::PaddingTop => parse_value!(
-                Length | Percentage | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Inherit | Initial | Unset;
                 tokens
             ),
             Property::PaddingRight => parse_value!(
-                Length | Percentage | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Inherit | Initial | Unset;
                 tokens
             ),
             Property::PaddingBottom => parse_value!(
-                Length | Percentage | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Inherit | Initial | Unset;
                 tokens
             ),
             Property::PaddingLeft => parse_value!(
-                Length | Percentage | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Inherit | Initial | Unset;
                 tokens
             ),
             Property::BorderTopStyle => parse_value!(
@@ -180,25 +198,29 @@ impl Value {
                 tokens
             ),
             Property::Top => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset;
                 tokens
             ),
             Property::Right => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset;
                 tokens
             ),
             Property::Bottom => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset;
                 tokens
             ),
             Property::Left => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset;
                 tokens
             ),
             Property::Direction => parse_value!(
                 Direction | Inherit | Initial | Unset;
                 tokens
             ),
+            Property::WritingMode => parse_value!(
+                WritingMode | Inherit | Initial | Unset;
+                tokens
+            ),
             Property::BorderTopLeftRadius => parse_value!(
                 BorderRadius | Inherit | Initial | Unset;
                 tokens
@@ -216,7 +238,67 @@ impl Value {
                 tokens
             ),
             Property::FontSize => parse_value!(
-                Length | Percentage | Auto | Inherit | Initial | Unset;
+                Length | Percentage | Calc | Auto | Inherit | Initial | Unset;
+                tokens
+            ),
+            Property::Content => parse_value!(
+                Inherit | Initial | Unset | Content;
+                tokens
+            ),
+            Property::CounterReset => parse_value!(
+                Inherit | Initial | Unset | CounterList;
+                tokens
+            ),
+            Property::CounterIncrement => parse_value!(
+                Inherit | Initial | Unset | CounterList;
+                tokens
+            ),
+            Property::AnimationName => parse_value!(
+                Inherit | Initial | Unset | AnimationNameList;
+                tokens
+            ),
+            Property::AnimationDuration => parse_value!(
+                TimeList | Inherit | Initial | Unset;
+                tokens
+            ),
+            Property::AnimationTimingFunction => parse_value!(
+                Inherit | Initial | Unset | TimingFunctionList;
+                tokens
+            ),
+            Property::AnimationDelay => parse_value!(
+                TimeList | Inherit | Initial | Unset;
+                tokens
+            ),
+            Property::AnimationIterationCount => parse_value!(
+                Inherit | Initial | Unset | IterationCountList;
+                tokens
+            ),
+            Property::AnimationDirection => parse_value!(
+                Inherit | Initial | Unset | AnimationDirectionList;
+                tokens
+            ),
+            Property::AnimationFillMode => parse_value!(
+                Inherit | Initial | Unset | FillModeList;
+                tokens
+            ),
+            Property::TransitionProperty => parse_value!(
+                Inherit | Initial | Unset | TransitionPropertyList;
+                tokens
+            ),
+            Property::TransitionDuration => parse_value!(
+                TimeList | Inherit | Initial | Unset;
+                tokens
+            ),
+            Property::TransitionTimingFunction => parse_value!(
+                Inherit | Initial | Unset | TimingFunctionList;
+                tokens
+            ),
+            Property::TransitionDelay => parse_value!(
+                TimeList | Inherit | Initial | Unset;
+                tokens
+            ),
+            Property::Transform => parse_value!(
+                Inherit | Initial | Unset | Transform;
                 tokens
             ),
         }
@@ -245,10 +327,10 @@ impl Value {
             Property::BorderRightWidth => Value::BorderWidth(BorderWidth::Medium),
             Property::BorderBottomWidth => Value::BorderWidth(BorderWidth::Medium),
             Property::BorderLeftWidth => Value::BorderWidth(BorderWidth::Medium),
-            Property::BorderTopColor => Value::Color(Color::black()),
-            Property::BorderRightColor => Value::Color(Color::black()),
-            Property::BorderBottomColor => Value::Color(Color::black()),
-            Property::BorderLeftColor => Value::Color(Color::black()),
+            Property::BorderTopColor => Value::Color(Color::CurrentColor),
+            Property::BorderRightColor => Value::Color(Color::CurrentColor),
+            Property::BorderBottomColor => Value::Color(Color::CurrentColor),
+            Property::BorderLeftColor => Value::Color(Color::CurrentColor),
             Property::Float => Value::Float(Float::None),
             Property::Position => Value::Position(Position::Static),
             Property::Left => Value::Auto,
@@ -256,12 +338,38 @@ impl Value {
             Property::Bottom => Value::Auto,
             Property::Top => Value::Auto,
             Property::Direction => Value::Direction(Direction::Ltr),
+            Property::WritingMode => Value::WritingMode(WritingMode::HorizontalTb),
             Property::BorderTopLeftRadius => Value::BorderRadius(BorderRadius::zero()),
             Property::BorderTopRightRadius => Value::BorderRadius(BorderRadius::zero()),
             Property::BorderBottomLeftRadius => Value::BorderRadius(BorderRadius::zero()),
             Property::BorderBottomRightRadius => Value::BorderRadius(BorderRadius::zero()),
             // TODO: replace with `medium` when we support absolute size
             Property::FontSize => Value::Length(Length::new_px(16.)),
+            Property::Content => Value::Content(Content::Normal),
+            Property::CounterReset => Value::CounterList(CounterList::none()),
+            Property::CounterIncrement => Value::CounterList(CounterList::none()),
+            Property::AnimationName => Value::AnimationNameList(List::single(AnimationName::None)),
+            Property::AnimationDuration => Value::TimeList(List::single(Time::zero())),
+            Property::AnimationTimingFunction => {
+                Value::TimingFunctionList(List::single(TimingFunction::ease()))
+            }
+            Property::AnimationDelay => Value::TimeList(List::single(Time::zero())),
+            Property::AnimationIterationCount => {
+                Value::IterationCountList(List::single(IterationCount::Number(1.0.into())))
+            }
+            Property::AnimationDirection => {
+                Value::AnimationDirectionList(List::single(AnimationDirection::Normal))
+            }
+            Property::AnimationFillMode => Value::FillModeList(List::single(FillMode::None)),
+            Property::TransitionProperty => {
+                Value::TransitionPropertyList(List::single(TransitionProperty::All))
+            }
+            Property::TransitionDuration => Value::TimeList(List::single(Time::zero())),
+            Property::TransitionTimingFunction => {
+                Value::TimingFunctionList(List::single(TimingFunction::ease()))
+            }
+            Property::TransitionDelay => Value::TimeList(List::single(Time::zero())),
+            Property::Transform => Value::Transform(Transform::none()),
         }
     }
 }
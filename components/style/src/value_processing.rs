@@ -1,31 +1,56 @@
+use crate::computed_values::ComputedValues;
 use crate::property::Property;
 use crate::render_tree::RenderNode;
 use crate::value::Value;
 
-use super::selector_matching::is_match_selectors;
+use super::bloom::AncestorFilter;
+use super::cascade_layers::UNLAYERED;
+use super::rule_index::RuleIndex;
 use css::cssom::style_rule::StyleRule;
 use css::parser::structs::ComponentValue;
 use css::parser::structs::Declaration;
-use css::selector::structs::Specificity;
-use css::tokenizer::token::Token;
+use css::selector::structs::{PseudoElement, Specificity};
 use dom::node::Node;
+use shared::primitive::Size;
 use std::borrow::Borrow;
+use std::cell::Cell;
 use std::cmp::{Ord, Ordering};
 use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
 use std::rc::Rc;
 use std::rc::Weak;
 
-use super::expand::prelude::*;
+use super::custom_properties::{
+    compute_custom_properties, has_references, is_custom_property, resolve_unparsed_value,
+};
+use super::expand::{expand_shorthand, get_expander_shorthand_property, get_shorthand_longhands};
+use super::logical::{expand_logical_shorthand, get_logical_shorthand_longhands, LogicalProperty};
+use super::values::direction::Direction;
+use super::values::length::LengthContext;
+use super::values::unparsed::UnparsedValue;
+use super::values::writing_mode::WritingMode;
 
 // computes
+use super::computes::calc::compute_calc;
 use super::computes::color::compute_color;
 use super::computes::font_size::compute_font_size;
+use super::computes::length::compute_length;
 
 type DeclaredValuesMap = HashMap<Property, Vec<PropertyDeclaration>>;
 
+type DeclaredCustomValuesMap = HashMap<String, Vec<PropertyDeclaration>>;
+
+type DeclaredLogicalValuesMap = HashMap<LogicalProperty, Vec<PropertyDeclaration>>;
+
+/// Cascaded values of the custom properties declared on a node
+pub type DeclaredCustomProperties = HashMap<String, Vec<ComponentValue>>;
+
 pub type Properties = HashMap<Property, Option<Value>>;
 
+/// The properties won by an `!important` declaration, which animations
+/// don't override
+pub type ImportantProperties = HashSet<Property>;
+
 /// CSS property declaration for cascading
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct PropertyDeclaration {
@@ -33,7 +58,11 @@ pub struct PropertyDeclaration {
     pub important: bool,
     pub origin: CascadeOrigin,
     pub location: CSSLocation,
+    /// The rank of the cascade layer
+    pub layer: usize,
     pub specificity: Specificity,
+    /// The position of the declaration among the declarations for the element
+    pub order: usize,
 }
 
 /// Location of the CSS applied
@@ -56,12 +85,22 @@ pub enum CascadeOrigin {
     UserAgent,
 }
 
+/// The cascade context shared by a block of declarations
+struct DeclarationSource {
+    origin: CascadeOrigin,
+    location: CSSLocation,
+    layer: usize,
+    specificity: Specificity,
+}
+
 /// Style rule with context for cascading
 #[derive(Debug)]
 pub struct ContextualRule<'a> {
     pub inner: &'a StyleRule,
     pub origin: CascadeOrigin,
     pub location: CSSLocation,
+    /// The rank of the cascade layer of the rule, see `LayerOrder`
+    pub layer: usize,
 }
 
 /// Context for computing values
@@ -69,21 +108,41 @@ pub struct ComputeContext<'a> {
     pub parent: Option<Weak<RenderNode>>,
     pub properties: HashMap<Property, Value>,
     pub style_cache: &'a mut StyleCache,
+    /// The size of the viewport that viewport units refer to
+    pub viewport: Size,
+    /// The sizes that the relative lengths of the element refer to
+    pub lengths: LengthContext,
 }
 
 #[derive(Debug)]
-pub struct StyleCache(HashSet<ValueRef>);
+pub struct StyleCache {
+    values: HashSet<ValueRef>,
+    /// The initial values, shared by the elements that don't change them
+    initial: Option<ComputedValues>,
+}
 
 impl StyleCache {
     pub fn new() -> Self {
-        Self(HashSet::new())
+        Self {
+            values: HashSet::new(),
+            initial: None,
+        }
     }
 
     pub fn get(&mut self, value: &Value) -> ValueRef {
-        if !self.0.contains(value) {
-            self.0.insert(ValueRef::new(value.clone()));
+        if !self.values.contains(value) {
+            self.values.insert(ValueRef::new(value.clone()));
         }
-        self.0.get(value).unwrap().clone()
+        self.values.get(value).unwrap().clone()
+    }
+
+    pub fn initial_values(&mut self) -> ComputedValues {
+        if let Some(initial) = &self.initial {
+            return initial.clone();
+        }
+        let initial = ComputedValues::initial(self);
+        self.initial = Some(initial.clone());
+        initial
     }
 }
 
@@ -117,6 +176,7 @@ impl ValueRef {
         match self.borrow() {
             Value::Length(l) => l.to_px(),
             Value::Percentage(p) => p.to_px(relative_to),
+            Value::Calc(c) => c.to_px(relative_to),
             _ => 0.0,
         }
     }
@@ -149,26 +209,75 @@ impl Deref for ValueRef {
     }
 }
 
-/// Apply a list of style rules for a node
-pub fn apply_styles(node: &Rc<Node>, rules: &[ContextualRule]) -> Properties {
+/// Apply a list of style rules for a node, or for one of its
+/// pseudo-elements when `pseudo_element` is provided
+///
+/// Returns the cascaded values of the properties and the cascaded
+/// values of the custom properties. The parent is used to map the
+/// flow-relative properties when the element inherits its writing mode.
+pub fn apply_styles(
+    node: &Rc<Node>,
+    rules: &RuleIndex,
+    ancestors: &AncestorFilter,
+    pseudo_element: Option<&PseudoElement>,
+    parent: Option<&Weak<RenderNode>>,
+) -> (Properties, DeclaredCustomProperties, ImportantProperties) {
     // https://www.w3.org/TR/css3-cascade/#value-stages
     // Step 1
-    let mut declared_values = collect_declared_values(&node, rules);
+    let (mut declared_values, mut declared_custom_values, declared_logical_values) =
+        collect_declared_values(&node, rules, ancestors, pseudo_element);
+
+    let cascade_custom_values = declared_custom_values
+        .iter_mut()
+        .filter_map(|(name, values)| match cascade(values) {
+            Some(Value::Unparsed(value)) => Some((name.clone(), value.tokens)),
+            _ => None,
+        })
+        .collect::<DeclaredCustomProperties>();
+
+    // flow-relative properties set the physical property they map to,
+    // and the declarations of both are cascaded together
+    // https://www.w3.org/TR/css-logical-1/#box
+    let (writing_mode, direction) =
+        cascaded_writing_mode(&mut declared_values, &cascade_custom_values, parent);
+    for (property, declarations) in declared_logical_values {
+        declared_values
+            .entry(property.to_physical(&writing_mode, &direction))
+            .or_default()
+            .extend(declarations);
+    }
 
     // Step 2
+    let mut important = ImportantProperties::new();
     let cascade_values = declared_values
         .iter_mut()
-        .map(|(property, values)| (property.clone(), cascade(values)))
+        .map(|(property, values)| {
+            let value = cascade(values);
+            if values
+                .last()
+                .is_some_and(|declaration| declaration.important)
+            {
+                important.insert(property.clone());
+            }
+            (property.clone(), value)
+        })
         .collect::<Properties>();
 
-    cascade_values
+    (cascade_values, cascade_custom_values, important)
 }
 
 /// Resolve specified values to computed values
 pub fn compute(property: &Property, value: &Value, context: &mut ComputeContext) -> ValueRef {
-    match property {
-        Property::Color => compute_color(value, context),
-        Property::FontSize => compute_font_size(value, context),
+    if let Value::Calc(calc) = value {
+        return compute_calc(property, calc, context);
+    }
+
+    match (property, value) {
+        (Property::FontSize, _) => compute_font_size(value, context),
+        (_, Value::Color(_)) => compute_color(property, value, context),
+        (_, Value::Length(_) | Value::BorderRadius(_) | Value::Transform(_)) => {
+            compute_length(property, value, context)
+        }
         _ => context.style_cache.get(value),
     }
 }
@@ -184,68 +293,185 @@ fn cascade(declared_values: &mut Vec<PropertyDeclaration>) -> Option<Value> {
     }
 }
 
-/// Get a short-hand property expander
-fn get_expander_shorthand_property(
-    property: &str,
-) -> Option<&dyn Fn(&[&[ComponentValue]]) -> ExpandOutput> {
-    match property {
-        "margin" => Some(&expand_margin),
-        "padding" => Some(&expand_padding),
-        "border" => Some(&expand_border),
-        "border-style" => Some(&expand_border_style),
-        "border-width" => Some(&expand_border_width),
-        "border-color" => Some(&expand_border_color),
-        "border-radius" => Some(&expand_border_radius),
-        _ => None,
-    }
+/// The writing mode and direction of an element from their cascaded
+/// values or from the parent when they are inherited
+fn cascaded_writing_mode(
+    declared_values: &mut DeclaredValuesMap,
+    custom_values: &DeclaredCustomProperties,
+    parent: Option<&Weak<RenderNode>>,
+) -> (WritingMode, Direction) {
+    let parent = parent.and_then(|parent| parent.upgrade());
+    // the custom properties are only computed here for the references
+    // in these two properties, which are needed to map the others
+    let mut custom_properties = None;
+    let mut cascaded = |property: Property| {
+        let value = match declared_values.get_mut(&property).and_then(cascade) {
+            Some(Value::Unparsed(value)) => {
+                let custom_properties = custom_properties.get_or_insert_with(|| {
+                    let parent = parent.as_ref().map(|parent| &parent.custom_properties);
+                    compute_custom_properties(custom_values.clone(), parent)
+                });
+                resolve_unparsed_value(&property, &value, custom_properties)
+            }
+            value => value,
+        };
+        match value {
+            Some(Value::Initial) => Value::initial(&property),
+            Some(Value::Inherit | Value::Unset) | None => match &parent {
+                Some(parent) => parent.style.get(&property),
+                None => Value::initial(&property),
+            },
+            Some(value) => value,
+        }
+    };
+    let writing_mode = match cascaded(Property::WritingMode) {
+        Value::WritingMode(writing_mode) => writing_mode,
+        _ => WritingMode::HorizontalTb,
+    };
+    let direction = match cascaded(Property::Direction) {
+        Value::Direction(direction) => direction,
+        _ => Direction::Ltr,
+    };
+    (writing_mode, direction)
 }
 
 /// Collect declared values for each property
 /// found in each style rule
-fn collect_declared_values(node: &Rc<Node>, rules: &[ContextualRule]) -> DeclaredValuesMap {
+fn collect_declared_values(
+    node: &Rc<Node>,
+    rules: &RuleIndex,
+    ancestors: &AncestorFilter,
+    pseudo_element: Option<&PseudoElement>,
+) -> (
+    DeclaredValuesMap,
+    DeclaredCustomValuesMap,
+    DeclaredLogicalValuesMap,
+) {
     let mut result: DeclaredValuesMap = HashMap::new();
+    let mut custom_result: DeclaredCustomValuesMap = HashMap::new();
+    let mut logical_result: DeclaredLogicalValuesMap = HashMap::new();
 
     if !node.is_element() {
-        return result;
+        return (result, custom_result, logical_result);
     }
 
-    let matched_rules = rules
-        .iter()
-        .filter(|rule| is_match_selectors(node, &rule.inner.selectors))
-        .collect::<Vec<&ContextualRule>>();
+    let matched_rules = rules.matching_rules(node, ancestors, pseudo_element);
 
-    let mut insert_declaration =
-        |value: Value, property: Property, rule: &ContextualRule, declaration: &Declaration| {
-            let declaration = PropertyDeclaration {
-                value,
-                important: declaration.important,
+    // the inline style of an element applies after its matched rules
+    let inline_style = match pseudo_element {
+        None => Some(node.as_element().style()),
+        Some(_) => None,
+    };
+    let mut blocks = matched_rules
+        .iter()
+        .map(|(rule, specificity)| {
+            let source = DeclarationSource {
                 origin: rule.origin.clone(),
                 location: rule.location.clone(),
-                specificity: rule.inner.specificity(),
+                layer: rule.layer,
+                specificity: specificity.clone(),
             };
-            if result.contains_key(&property) {
-                result.get_mut(&property).unwrap().push(declaration);
-            } else {
-                result.insert(property, vec![declaration]);
+            (source, &rule.inner.declarations[..])
+        })
+        .collect::<Vec<(DeclarationSource, &[Declaration])>>();
+    if let Some(style) = &inline_style {
+        let source = DeclarationSource {
+            origin: CascadeOrigin::Author,
+            location: CSSLocation::Inline,
+            layer: UNLAYERED,
+            specificity: Specificity::new(0, 0, 0),
+        };
+        blocks.push((source, &style[..]));
+    }
+
+    // later declarations win over earlier ones that are otherwise equal
+    let order = Cell::new(0);
+    let to_property_declaration =
+        |value: Value, source: &DeclarationSource, declaration: &Declaration| {
+            order.set(order.get() + 1);
+            PropertyDeclaration {
+                value,
+                important: declaration.important,
+                origin: source.origin.clone(),
+                location: source.location.clone(),
+                layer: source.layer,
+                specificity: source.specificity.clone(),
+                order: order.get(),
             }
         };
 
-    for rule in matched_rules {
-        for declaration in &rule.inner.declarations {
-            if let Some(expand) = get_expander_shorthand_property(&declaration.name) {
+    let mut insert_declaration = |value: Value,
+                                  property: Property,
+                                  source: &DeclarationSource,
+                                  declaration: &Declaration| {
+        let declaration = to_property_declaration(value, source, declaration);
+        if result.contains_key(&property) {
+            result.get_mut(&property).unwrap().push(declaration);
+        } else {
+            result.insert(property, vec![declaration]);
+        }
+    };
+    let mut insert_logical_declaration =
+        |value: Value, property: LogicalProperty, source, declaration| {
+            let declaration = to_property_declaration(value, source, declaration);
+            logical_result
+                .entry(property)
+                .or_default()
+                .push(declaration);
+        };
+
+    for (source, declarations) in &blocks {
+        for declaration in declarations.iter() {
+            if is_custom_property(&declaration.name) {
+                // process custom property, the value is kept as tokens
+                let value = Value::Unparsed(UnparsedValue::new(declaration.value.clone(), None));
+                custom_result
+                    .entry(declaration.name.clone())
+                    .or_default()
+                    .push(to_property_declaration(value, source, declaration));
+            } else if has_references(&declaration.value) {
+                // the value can only be parsed after var() substitution
+                let longhands = match get_shorthand_longhands(&declaration.name) {
+                    Some(longhands) => longhands,
+                    None => Property::parse(&declaration.name).into_iter().collect(),
+                };
+                let shorthand = get_expander_shorthand_property(&declaration.name)
+                    .map(|_| declaration.name.clone());
+                for property in longhands {
+                    let value = UnparsedValue::new(declaration.value.clone(), shorthand.clone());
+                    insert_declaration(Value::Unparsed(value), property, source, declaration);
+                }
+                if let Some(property) = LogicalProperty::parse(&declaration.name) {
+                    let value = UnparsedValue::new(declaration.value.clone(), None);
+                    insert_logical_declaration(
+                        Value::Unparsed(value),
+                        property,
+                        source,
+                        declaration,
+                    );
+                } else if let Some(longhands) = get_logical_shorthand_longhands(&declaration.name) {
+                    // the shorthand is expanded after the substitution, once
+                    // its longhands are mapped to physical properties
+                    for property in longhands {
+                        let value = UnparsedValue::new(
+                            declaration.value.clone(),
+                            Some(declaration.name.clone()),
+                        )
+                        .with_logical_longhand(property);
+                        insert_logical_declaration(
+                            Value::Unparsed(value),
+                            property,
+                            source,
+                            declaration,
+                        );
+                    }
+                }
+            } else if get_expander_shorthand_property(&declaration.name).is_some() {
                 // process short hand property
-                let tokens = declaration
-                    .value
-                    .split(|val| match val {
-                        ComponentValue::PerservedToken(Token::Whitespace) => true,
-                        _ => false,
-                    })
-                    .collect::<Vec<&[ComponentValue]>>();
-
-                if let Some(values) = expand(&tokens) {
+                if let Some(values) = expand_shorthand(&declaration.name, &declaration.value) {
                     for (property, value) in values {
                         if let Some(v) = value {
-                            insert_declaration(v, property, rule, declaration);
+                            insert_declaration(v, property, source, declaration);
                         }
                     }
                 }
@@ -257,33 +483,45 @@ fn collect_declared_values(node: &Rc<Node>, rules: &[ContextualRule]) -> Declare
                     let value = Value::parse(&property, values);
 
                     if let Some(value) = value {
-                        insert_declaration(value, property, rule, declaration);
+                        insert_declaration(value, property, source, declaration);
+                    }
+                } else if let Some(property) = LogicalProperty::parse(&declaration.name) {
+                    // process flow-relative long hand property
+                    if let Some(value) = property.parse_value(&declaration.value) {
+                        insert_logical_declaration(value, property, source, declaration);
+                    }
+                } else if let Some(values) =
+                    expand_logical_shorthand(&declaration.name, &declaration.value)
+                {
+                    // process flow-relative short hand property
+                    for (property, value) in values {
+                        insert_logical_declaration(value, property, source, declaration);
                     }
                 }
             }
         }
     }
 
-    result
+    (result, custom_result, logical_result)
 }
 
 /// The implementation for ordering for cascade sort
 ///
 /// These are the steps to compare the order:
-/// 1. Comparing the location of the property declaration (Inline, Embedded, etc.)
-/// 2. If step 1 result in equal ordering compare the cascade origin
-/// 3. If step 2 result in equal ordering compare the specificity
+/// 1. Comparing the cascade origin and importance
+/// 2. If step 1 result in equal ordering compare the location (Inline wins)
+/// 3. If step 2 result in equal ordering compare the cascade layers
+/// 4. If step 3 result in equal ordering compare the specificity
+/// 5. If step 4 result in equal ordering compare the order of appearance
+///
+/// https://www.w3.org/TR/css-cascade-5/#cascade-sort
 impl Ord for PropertyDeclaration {
     fn cmp(&self, other: &Self) -> Ordering {
-        match cmp_location(self, other) {
-            Ordering::Greater => Ordering::Greater,
-            Ordering::Less => Ordering::Less,
-            Ordering::Equal => match cmp_cascade_origin(self, other) {
-                Ordering::Greater => Ordering::Greater,
-                Ordering::Less => Ordering::Less,
-                Ordering::Equal => self.specificity.cmp(&other.specificity),
-            },
-        }
+        cmp_cascade_origin(self, other)
+            .then_with(|| cmp_location(self, other))
+            .then_with(|| cmp_layer(self, other))
+            .then_with(|| self.specificity.cmp(&other.specificity))
+            .then_with(|| self.order.cmp(&other.order))
     }
 }
 
@@ -293,15 +531,20 @@ impl PartialOrd for PropertyDeclaration {
     }
 }
 
+/// Embedded and external stylesheets are ordered by their
+/// appearance, only the inline style comes after them
 fn cmp_location(a: &PropertyDeclaration, b: &PropertyDeclaration) -> Ordering {
-    match (&a.location, &b.location) {
-        (CSSLocation::Inline, CSSLocation::Embedded)
-        | (CSSLocation::Inline, CSSLocation::External)
-        | (CSSLocation::Embedded, CSSLocation::External) => Ordering::Greater,
-        (CSSLocation::Inline, CSSLocation::Inline)
-        | (CSSLocation::Embedded, CSSLocation::Embedded)
-        | (CSSLocation::External, CSSLocation::External) => Ordering::Equal,
-        _ => Ordering::Less,
+    let is_inline = |declaration: &PropertyDeclaration| declaration.location == CSSLocation::Inline;
+    is_inline(a).cmp(&is_inline(b))
+}
+
+/// Later layers win for normal declarations and earlier
+/// layers win for important declarations
+/// https://www.w3.org/TR/css-cascade-5/#cascade-layering
+fn cmp_layer(a: &PropertyDeclaration, b: &PropertyDeclaration) -> Ordering {
+    match a.important {
+        true => b.layer.cmp(&a.layer),
+        false => a.layer.cmp(&b.layer),
     }
 }
 
@@ -364,7 +607,9 @@ mod tests {
             origin: CascadeOrigin::User,
             important: false,
             value: Value::Color(Color::black()),
+            layer: UNLAYERED,
             specificity: Specificity::new(1, 0, 1),
+            order: 1,
         };
 
         let b = PropertyDeclaration {
@@ -372,7 +617,9 @@ mod tests {
             origin: CascadeOrigin::User,
             important: false,
             value: Value::Color(Color::black()),
+            layer: UNLAYERED,
             specificity: Specificity::new(1, 0, 1),
+            order: 2,
         };
 
         let c = PropertyDeclaration {
@@ -380,7 +627,9 @@ mod tests {
             origin: CascadeOrigin::User,
             important: true,
             value: Value::Color(Color::black()),
+            layer: UNLAYERED,
             specificity: Specificity::new(1, 0, 1),
+            order: 3,
         };
 
         let mut declared = vec![a.clone(), b.clone(), c.clone()];
@@ -419,7 +668,9 @@ mod tests {
             origin: CascadeOrigin::User,
             important: false,
             value: Value::Color(Color::black()),
+            layer: UNLAYERED,
             specificity: Specificity::new(0, 0, 0),
+            order: 1,
         };
 
         let b = PropertyDeclaration {
@@ -427,7 +678,9 @@ mod tests {
             origin: CascadeOrigin::User,
             important: false,
             value: Value::Color(Color::transparent()),
+            layer: UNLAYERED,
             specificity: Specificity::new(0, 0, 1),
+            order: 2,
         };
 
         let mut declared = vec![b.clone(), a.clone()];
@@ -435,4 +688,61 @@ mod tests {
         let win = cascade(&mut declared);
         assert_eq!(win, Some(b.value));
     }
+
+    #[test]
+    fn cascade_layers_and_order() {
+        let declaration =
+            |important: bool, layer: usize, specificity: Specificity, order| PropertyDeclaration {
+                location: CSSLocation::Embedded,
+                origin: CascadeOrigin::Author,
+                important,
+                value: Value::Auto,
+                layer,
+                specificity,
+                order,
+            };
+
+        // a later layer wins over specificity
+        let a = declaration(false, 0, Specificity::new(1, 0, 0), 1);
+        let b = declaration(false, 1, Specificity::new(0, 0, 1), 2);
+        assert!(b > a);
+        // an earlier layer wins for important declarations
+        let a = declaration(true, 0, Specificity::new(0, 0, 1), 1);
+        let b = declaration(true, UNLAYERED, Specificity::new(0, 0, 1), 2);
+        assert!(a > b);
+        // the last declaration wins when everything else is equal
+        let a = declaration(false, 0, Specificity::new(0, 1, 0), 2);
+        let b = declaration(false, 0, Specificity::new(0, 1, 0), 1);
+        assert!(a > b);
+    }
+
+    #[test]
+    fn cascade_origins_and_importance() {
+        let declaration =
+            |origin: CascadeOrigin, location: CSSLocation, important: bool| PropertyDeclaration {
+                location,
+                origin,
+                important,
+                value: Value::Auto,
+                layer: UNLAYERED,
+                specificity: Specificity::new(0, 0, 1),
+                order: 0,
+            };
+        let ua =
+            |important| declaration(CascadeOrigin::UserAgent, CSSLocation::External, important);
+        let user = |important| declaration(CascadeOrigin::User, CSSLocation::External, important);
+        let author =
+            |important| declaration(CascadeOrigin::Author, CSSLocation::External, important);
+        let inline = |important| declaration(CascadeOrigin::Author, CSSLocation::Inline, important);
+
+        assert!(author(false) > user(false));
+        assert!(user(false) > ua(false));
+        assert!(ua(true) > user(true));
+        assert!(user(true) > author(true));
+        assert!(author(true) > inline(false));
+        // the style attribute only wins within the author origin
+        assert!(inline(false) > author(false));
+        assert!(user(true) > inline(true));
+        assert!(ua(true) > inline(true));
+    }
 }
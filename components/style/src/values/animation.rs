@@ -0,0 +1,215 @@
+use super::number::Number;
+use super::time::Time;
+use super::timing_function::TimingFunction;
+use crate::expand::get_shorthand_longhands;
+use crate::property::Property;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// A single item of a comma separated list value
+pub trait ListItem: Sized {
+    fn parse_item(values: &[ComponentValue]) -> Option<Self>;
+}
+
+/// A comma separated list, used by the `animation-*` and `transition-*`
+/// properties. The items are matched by index across the properties.
+/// https://www.w3.org/TR/css-animations-1/#animation-name
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct List<T>(pub Vec<T>);
+
+pub type TimeList = List<Time>;
+pub type TimingFunctionList = List<TimingFunction>;
+pub type AnimationNameList = List<AnimationName>;
+pub type IterationCountList = List<IterationCount>;
+pub type AnimationDirectionList = List<AnimationDirection>;
+pub type FillModeList = List<FillMode>;
+pub type TransitionPropertyList = List<TransitionProperty>;
+
+impl<T: ListItem> List<T> {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        let items = values
+            .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+            .map(|item| T::parse_item(trim_whitespace(item)))
+            .collect::<Option<Vec<T>>>()?;
+        Some(Self(items))
+    }
+}
+
+impl<T> List<T> {
+    pub fn single(item: T) -> Self {
+        Self(vec![item])
+    }
+
+    /// Get the item at the index, repeating the list if it is too short
+    pub fn get_cycled(&self, index: usize) -> Option<&T> {
+        match self.0.is_empty() {
+            true => None,
+            false => self.0.get(index % self.0.len()),
+        }
+    }
+}
+
+fn trim_whitespace(mut values: &[ComponentValue]) -> &[ComponentValue] {
+    while let [ComponentValue::PerservedToken(Token::Whitespace), rest @ ..] = values {
+        values = rest;
+    }
+    while let [rest @ .., ComponentValue::PerservedToken(Token::Whitespace)] = values {
+        values = rest;
+    }
+    values
+}
+
+fn parse_ident(values: &[ComponentValue]) -> Option<String> {
+    match values {
+        [ComponentValue::PerservedToken(Token::Ident(ident))] => Some(ident.to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+impl ListItem for Time {
+    fn parse_item(values: &[ComponentValue]) -> Option<Self> {
+        Time::parse(values)
+    }
+}
+
+impl ListItem for TimingFunction {
+    fn parse_item(values: &[ComponentValue]) -> Option<Self> {
+        TimingFunction::parse(values)
+    }
+}
+
+/// https://www.w3.org/TR/css-animations-1/#animation-name
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum AnimationName {
+    None,
+    Name(String),
+}
+
+impl ListItem for AnimationName {
+    fn parse_item(values: &[ComponentValue]) -> Option<Self> {
+        match values {
+            [ComponentValue::PerservedToken(Token::Ident(name))] => {
+                match name.eq_ignore_ascii_case("none") {
+                    true => Some(Self::None),
+                    false => Some(Self::Name(name.clone())),
+                }
+            }
+            [ComponentValue::PerservedToken(Token::Str(name))] => Some(Self::Name(name.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-animations-1/#animation-iteration-count
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum IterationCount {
+    Infinite,
+    Number(Number),
+}
+
+impl IterationCount {
+    pub fn as_f32(&self) -> f32 {
+        match self {
+            IterationCount::Infinite => f32::INFINITY,
+            IterationCount::Number(count) => **count,
+        }
+    }
+}
+
+impl ListItem for IterationCount {
+    fn parse_item(values: &[ComponentValue]) -> Option<Self> {
+        match values {
+            [ComponentValue::PerservedToken(Token::Number { value, .. })] if *value >= 0. => {
+                Some(Self::Number((*value).into()))
+            }
+            values if parse_ident(values).as_deref() == Some("infinite") => Some(Self::Infinite),
+            _ => None,
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-animations-1/#animation-direction
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum AnimationDirection {
+    Normal,
+    Reverse,
+    Alternate,
+    AlternateReverse,
+}
+
+impl ListItem for AnimationDirection {
+    fn parse_item(values: &[ComponentValue]) -> Option<Self> {
+        match parse_ident(values)?.as_str() {
+            "normal" => Some(Self::Normal),
+            "reverse" => Some(Self::Reverse),
+            "alternate" => Some(Self::Alternate),
+            "alternate-reverse" => Some(Self::AlternateReverse),
+            _ => None,
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-animations-1/#animation-fill-mode
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum FillMode {
+    None,
+    Forwards,
+    Backwards,
+    Both,
+}
+
+impl FillMode {
+    pub fn fills_forwards(&self) -> bool {
+        matches!(self, FillMode::Forwards | FillMode::Both)
+    }
+
+    pub fn fills_backwards(&self) -> bool {
+        matches!(self, FillMode::Backwards | FillMode::Both)
+    }
+}
+
+impl ListItem for FillMode {
+    fn parse_item(values: &[ComponentValue]) -> Option<Self> {
+        match parse_ident(values)?.as_str() {
+            "none" => Some(Self::None),
+            "forwards" => Some(Self::Forwards),
+            "backwards" => Some(Self::Backwards),
+            "both" => Some(Self::Both),
+            _ => None,
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-transitions-1/#transition-property-property
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum TransitionProperty {
+    All,
+    None,
+    /// The longhands of the property, empty if the property is unknown
+    Properties(Vec<Property>),
+}
+
+impl TransitionProperty {
+    pub fn contains(&self, property: &Property) -> bool {
+        match self {
+            TransitionProperty::All => true,
+            TransitionProperty::None => false,
+            TransitionProperty::Properties(properties) => properties.contains(property),
+        }
+    }
+}
+
+impl ListItem for TransitionProperty {
+    fn parse_item(values: &[ComponentValue]) -> Option<Self> {
+        let name = parse_ident(values)?;
+        let property = match name.as_str() {
+            "all" => Self::All,
+            "none" => Self::None,
+            name => match get_shorthand_longhands(name) {
+                Some(longhands) => Self::Properties(longhands),
+                None => Self::Properties(Property::parse(name).into_iter().collect()),
+            },
+        };
+        Some(property)
+    }
+}
@@ -1,3 +1,4 @@
+use super::length::Length;
 use css::parser::structs::ComponentValue;
 use css::tokenizer::token::Token;
 
@@ -21,3 +22,21 @@ impl BorderWidth {
         }
     }
 }
+
+/// The computed value of the border widths
+/// https://www.w3.org/TR/css-backgrounds-3/#typedef-line-width
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub enum LineWidth {
+    Keyword(BorderWidth),
+    Length(Length),
+}
+
+impl LineWidth {
+    /// The width in px. The keywords are not resolved yet and are 0.
+    pub fn to_px(&self) -> f32 {
+        match self {
+            LineWidth::Length(l) => l.to_px(),
+            LineWidth::Keyword(_) => 0.0,
+        }
+    }
+}
@@ -0,0 +1,531 @@
+use super::length::Length;
+use super::number::Number;
+use super::percentage::Percentage;
+use css::parser::structs::{ComponentValue, Function};
+use css::tokenizer::token::Token;
+
+/// A node of a math expression tree
+/// https://www.w3.org/TR/css-values-4/#calc-internal
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CalcNode {
+    Number(Number),
+    Length(Length),
+    Percentage(Percentage),
+    Sum(Vec<CalcNode>),
+    Product(Vec<CalcNode>),
+    Negate(Box<CalcNode>),
+    Invert(Box<CalcNode>),
+    Min(Vec<CalcNode>),
+    Max(Vec<CalcNode>),
+    Clamp(Box<CalcNode>, Box<CalcNode>, Box<CalcNode>),
+}
+
+/// Resolved type of a math expression
+/// https://www.w3.org/TR/css-values-4/#calc-type-checking
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CalcType {
+    Number,
+    LengthPercentage,
+}
+
+/// A math function (`calc()`, `min()`, `max()` or `clamp()`)
+/// that resolves to a `<length-percentage>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Calc(pub CalcNode);
+
+impl Calc {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.first() {
+            Some(ComponentValue::Function(function)) => {
+                let node = parse_math_function(function)?;
+                match node.calc_type() {
+                    Some(CalcType::LengthPercentage) => Some(Calc(node)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Simplify the expression after resolving lengths with the
+    /// provided function. Lengths that can not be resolved are kept.
+    pub fn simplify<F>(&self, resolve_length: &F) -> CalcNode
+    where
+        F: Fn(&Length) -> Option<f32>,
+    {
+        self.0.simplify(resolve_length)
+    }
+
+    /// Resolve the expression to px, percentages are resolved
+    /// against the containing size
+    pub fn to_px(&self, containing: f32) -> f32 {
+        self.0.evaluate(containing)
+    }
+}
+
+impl CalcNode {
+    fn calc_type(&self) -> Option<CalcType> {
+        match self {
+            CalcNode::Number(_) => Some(CalcType::Number),
+            CalcNode::Length(_) | CalcNode::Percentage(_) => Some(CalcType::LengthPercentage),
+            CalcNode::Sum(children) | CalcNode::Min(children) | CalcNode::Max(children) => {
+                let first = children.first()?.calc_type()?;
+                for child in children.iter().skip(1) {
+                    if child.calc_type()? != first {
+                        return None;
+                    }
+                }
+                Some(first)
+            }
+            CalcNode::Product(children) => {
+                let mut result = CalcType::Number;
+                for child in children {
+                    match (child.calc_type()?, result) {
+                        (CalcType::Number, _) => {}
+                        (CalcType::LengthPercentage, CalcType::Number) => {
+                            result = CalcType::LengthPercentage
+                        }
+                        // multiplying two lengths is not a valid length
+                        _ => return None,
+                    }
+                }
+                Some(result)
+            }
+            CalcNode::Negate(child) => child.calc_type(),
+            CalcNode::Invert(child) => match child.calc_type()? {
+                CalcType::Number => Some(CalcType::Number),
+                // dividing by a length is not supported
+                CalcType::LengthPercentage => None,
+            },
+            CalcNode::Clamp(min, value, max) => {
+                let value_type = value.calc_type()?;
+                if min.calc_type()? != value_type || max.calc_type()? != value_type {
+                    return None;
+                }
+                Some(value_type)
+            }
+        }
+    }
+
+    /// Simplify a calculation tree
+    /// https://www.w3.org/TR/css-values-4/#calc-simplification
+    pub fn simplify<F>(&self, resolve_length: &F) -> CalcNode
+    where
+        F: Fn(&Length) -> Option<f32>,
+    {
+        match self {
+            CalcNode::Number(_) | CalcNode::Percentage(_) => self.clone(),
+            CalcNode::Length(length) => match resolve_length(length) {
+                Some(px) => CalcNode::Length(Length::new_px(px)),
+                None => self.clone(),
+            },
+            CalcNode::Sum(children) => {
+                let mut number = None;
+                let mut px = None;
+                let mut percentage = None;
+                let mut others = Vec::new();
+
+                let mut terms = Vec::new();
+                for child in children {
+                    match child.simplify(resolve_length) {
+                        CalcNode::Sum(nested) => terms.extend(nested),
+                        child => terms.push(child),
+                    }
+                }
+
+                for term in terms {
+                    match term.as_px() {
+                        Some(value) => px = Some(px.unwrap_or(0.0) + value),
+                        None => match term {
+                            CalcNode::Number(n) => number = Some(number.unwrap_or(0.0) + *n),
+                            CalcNode::Percentage(p) => {
+                                percentage = Some(percentage.unwrap_or(0.0) + *p.0)
+                            }
+                            term => others.push(term),
+                        },
+                    }
+                }
+
+                let mut result = Vec::new();
+                if let Some(n) = number {
+                    result.push(CalcNode::Number(n.into()));
+                }
+                if let Some(px) = px {
+                    result.push(CalcNode::Length(Length::new_px(px)));
+                }
+                if let Some(p) = percentage {
+                    result.push(CalcNode::Percentage(Percentage(p.into())));
+                }
+                result.extend(others);
+
+                if result.len() == 1 {
+                    result.remove(0)
+                } else {
+                    CalcNode::Sum(result)
+                }
+            }
+            CalcNode::Product(children) => {
+                let mut scale = 1.0;
+                let mut others = Vec::new();
+                for child in children {
+                    match child.simplify(resolve_length) {
+                        CalcNode::Number(n) => scale *= *n,
+                        CalcNode::Product(nested) => others.extend(nested),
+                        child => others.push(child),
+                    }
+                }
+
+                match others.len() {
+                    0 => CalcNode::Number(scale.into()),
+                    1 => others.remove(0).scale(scale),
+                    _ => {
+                        others.insert(0, CalcNode::Number(scale.into()));
+                        CalcNode::Product(others)
+                    }
+                }
+            }
+            CalcNode::Negate(child) => child.simplify(resolve_length).scale(-1.0),
+            CalcNode::Invert(child) => match child.simplify(resolve_length) {
+                CalcNode::Number(n) if *n != 0.0 => CalcNode::Number((1.0 / *n).into()),
+                child => CalcNode::Invert(Box::new(child)),
+            },
+            CalcNode::Min(children) | CalcNode::Max(children) => {
+                let is_min = matches!(self, CalcNode::Min(_));
+                let children = children
+                    .iter()
+                    .map(|child| child.simplify(resolve_length))
+                    .collect::<Vec<CalcNode>>();
+
+                match CalcNode::resolve_comparison(&children, is_min) {
+                    Some(node) => node,
+                    None if is_min => CalcNode::Min(children),
+                    None => CalcNode::Max(children),
+                }
+            }
+            CalcNode::Clamp(min, value, max) => {
+                let min = min.simplify(resolve_length);
+                let value = value.simplify(resolve_length);
+                let max = max.simplify(resolve_length);
+
+                let lower = CalcNode::resolve_comparison(&[value.clone(), max.clone()], true);
+                match lower
+                    .and_then(|lower| CalcNode::resolve_comparison(&[min.clone(), lower], false))
+                {
+                    Some(node) => node,
+                    None => CalcNode::Clamp(Box::new(min), Box::new(value), Box::new(max)),
+                }
+            }
+        }
+    }
+
+    /// Multiply a simplified node by a number
+    fn scale(self, factor: f32) -> CalcNode {
+        if factor == 1.0 {
+            return self;
+        }
+        match self {
+            CalcNode::Number(n) => CalcNode::Number((*n * factor).into()),
+            CalcNode::Percentage(p) => CalcNode::Percentage(Percentage((*p.0 * factor).into())),
+            CalcNode::Length(length) => {
+                CalcNode::Length(Length::new(*length.value * factor, length.unit))
+            }
+            CalcNode::Sum(children) => CalcNode::Sum(
+                children
+                    .into_iter()
+                    .map(|child| child.scale(factor))
+                    .collect(),
+            ),
+            node => CalcNode::Product(vec![CalcNode::Number(factor.into()), node]),
+        }
+    }
+
+    fn as_px(&self) -> Option<f32> {
+        match self {
+            CalcNode::Length(length) => length.absolute_px(),
+            _ => None,
+        }
+    }
+
+    /// Resolve `min()` or `max()` when every argument has the same unit
+    fn resolve_comparison(children: &[CalcNode], is_min: bool) -> Option<CalcNode> {
+        let pick = |a: f32, b: f32| if is_min { a.min(b) } else { a.max(b) };
+        let first = children.first()?;
+
+        if let Some(first_px) = first.as_px() {
+            let mut result = first_px;
+            for child in children.iter().skip(1) {
+                result = pick(result, child.as_px()?);
+            }
+            return Some(CalcNode::Length(Length::new_px(result)));
+        }
+
+        match first {
+            CalcNode::Number(first) => {
+                let mut result = **first;
+                for child in children.iter().skip(1) {
+                    match child {
+                        CalcNode::Number(n) => result = pick(result, **n),
+                        _ => return None,
+                    }
+                }
+                Some(CalcNode::Number(result.into()))
+            }
+            CalcNode::Percentage(first) => {
+                let mut result = *first.0;
+                for child in children.iter().skip(1) {
+                    match child {
+                        CalcNode::Percentage(p) => result = pick(result, *p.0),
+                        _ => return None,
+                    }
+                }
+                Some(CalcNode::Percentage(Percentage(result.into())))
+            }
+            _ => None,
+        }
+    }
+
+    fn evaluate(&self, containing: f32) -> f32 {
+        match self {
+            CalcNode::Number(n) => **n,
+            // relative lengths are resolved at computed-value time
+            CalcNode::Length(length) => length.absolute_px().unwrap_or(0.0),
+            CalcNode::Percentage(p) => p.to_px(containing),
+            CalcNode::Sum(children) => children
+                .iter()
+                .map(|child| child.evaluate(containing))
+                .sum(),
+            CalcNode::Product(children) => children
+                .iter()
+                .map(|child| child.evaluate(containing))
+                .product(),
+            CalcNode::Negate(child) => -child.evaluate(containing),
+            CalcNode::Invert(child) => 1.0 / child.evaluate(containing),
+            CalcNode::Min(children) => children
+                .iter()
+                .map(|child| child.evaluate(containing))
+                .fold(f32::INFINITY, f32::min),
+            CalcNode::Max(children) => children
+                .iter()
+                .map(|child| child.evaluate(containing))
+                .fold(f32::NEG_INFINITY, f32::max),
+            CalcNode::Clamp(min, value, max) => {
+                let value = value.evaluate(containing).min(max.evaluate(containing));
+                min.evaluate(containing).max(value)
+            }
+        }
+    }
+}
+
+fn is_whitespace(value: Option<&ComponentValue>) -> bool {
+    matches!(
+        value,
+        Some(ComponentValue::PerservedToken(Token::Whitespace))
+    )
+}
+
+fn trim_whitespace(values: &[ComponentValue]) -> &[ComponentValue] {
+    let start = values
+        .iter()
+        .position(|value| !is_whitespace(Some(value)))
+        .unwrap_or(values.len());
+    let end = values
+        .iter()
+        .rposition(|value| !is_whitespace(Some(value)))
+        .map(|index| index + 1)
+        .unwrap_or(start);
+    &values[start..end]
+}
+
+fn parse_math_function(function: &Function) -> Option<CalcNode> {
+    let arguments = function
+        .value
+        .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+        .map(|argument| parse_sum(trim_whitespace(argument)))
+        .collect::<Option<Vec<CalcNode>>>()?;
+
+    match function.name.to_ascii_lowercase().as_str() {
+        "calc" if arguments.len() == 1 => arguments.into_iter().next(),
+        "min" => Some(CalcNode::Min(arguments)),
+        "max" => Some(CalcNode::Max(arguments)),
+        "clamp" if arguments.len() == 3 => {
+            let mut arguments = arguments.into_iter().map(Box::new);
+            Some(CalcNode::Clamp(
+                arguments.next()?,
+                arguments.next()?,
+                arguments.next()?,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Parse `<calc-sum> = <calc-product> [ [ '+' | '-' ] <calc-product> ]*`
+fn parse_sum(values: &[ComponentValue]) -> Option<CalcNode> {
+    let mut terms = Vec::new();
+    let mut start = 0;
+    let mut negate = false;
+
+    let mut push_term = |values: &[ComponentValue], negate: bool| -> Option<()> {
+        let term = parse_product(trim_whitespace(values))?;
+        terms.push(if negate {
+            CalcNode::Negate(Box::new(term))
+        } else {
+            term
+        });
+        Some(())
+    };
+
+    for (index, value) in values.iter().enumerate() {
+        if let ComponentValue::PerservedToken(Token::Delim(op @ ('+' | '-'))) = value {
+            // the operators must be surrounded by whitespace
+            let before = index.checked_sub(1).and_then(|before| values.get(before));
+            if !is_whitespace(before) || !is_whitespace(values.get(index + 1)) {
+                return None;
+            }
+            push_term(&values[start..index], negate)?;
+            negate = *op == '-';
+            start = index + 1;
+        }
+    }
+    push_term(&values[start..], negate)?;
+
+    if terms.len() == 1 {
+        terms.pop()
+    } else {
+        Some(CalcNode::Sum(terms))
+    }
+}
+
+/// Parse `<calc-product> = <calc-value> [ [ '*' | '/' ] <calc-value> ]*`
+fn parse_product(values: &[ComponentValue]) -> Option<CalcNode> {
+    let mut factors = Vec::new();
+    let mut invert = false;
+    let mut expect_value = true;
+
+    for value in values.iter().filter(|value| !is_whitespace(Some(value))) {
+        if expect_value {
+            let node = parse_calc_value(value)?;
+            factors.push(if invert {
+                CalcNode::Invert(Box::new(node))
+            } else {
+                node
+            });
+            expect_value = false;
+        } else {
+            invert = match value {
+                ComponentValue::PerservedToken(Token::Delim('*')) => false,
+                ComponentValue::PerservedToken(Token::Delim('/')) => true,
+                _ => return None,
+            };
+            expect_value = true;
+        }
+    }
+
+    if expect_value {
+        return None;
+    }
+
+    if factors.len() == 1 {
+        factors.pop()
+    } else {
+        Some(CalcNode::Product(factors))
+    }
+}
+
+/// Parse `<calc-value> = <number> | <dimension> | <percentage> | ( <calc-sum> )`
+fn parse_calc_value(value: &ComponentValue) -> Option<CalcNode> {
+    match value {
+        ComponentValue::PerservedToken(Token::Number { value, .. }) => {
+            Some(CalcNode::Number((*value).into()))
+        }
+        ComponentValue::PerservedToken(Token::Percentage(value)) => {
+            Some(CalcNode::Percentage(Percentage((*value).into())))
+        }
+        ComponentValue::PerservedToken(Token::Dimension { .. }) => {
+            Length::parse(std::slice::from_ref(value)).map(CalcNode::Length)
+        }
+        ComponentValue::SimpleBlock(block) if block.token == Token::ParentheseOpen => {
+            parse_sum(trim_whitespace(&block.value))
+        }
+        ComponentValue::Function(function) => parse_math_function(function),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::values::length::LengthUnit;
+    use css::parser::Parser;
+    use css::tokenizer::Tokenizer;
+
+    fn parse(css: &str) -> Option<Calc> {
+        let css = format!("{} ", css);
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        Calc::parse(trim_whitespace(&parser.parse_a_list_of_component_values()))
+    }
+
+    fn absolute(length: &Length) -> Option<f32> {
+        length.absolute_px()
+    }
+
+    #[test]
+    fn parse_and_simplify_calc() {
+        let calc = parse("calc(1in + 2 * (10px - 4px) / 2)").unwrap();
+        assert_eq!(
+            calc.simplify(&absolute),
+            CalcNode::Length(Length::new_px(102.0))
+        );
+
+        let calc = parse("calc(100% - 2 * 10px)").unwrap();
+        assert_eq!(
+            calc.simplify(&absolute),
+            CalcNode::Sum(vec![
+                CalcNode::Length(Length::new_px(-20.0)),
+                CalcNode::Percentage(Percentage(100.0.into())),
+            ])
+        );
+        assert_eq!(calc.to_px(200.0), 180.0);
+    }
+
+    #[test]
+    fn parse_comparison_functions() {
+        let calc = parse("min(10px, 2em, 5px)").unwrap();
+        assert_eq!(
+            calc.simplify(&absolute),
+            CalcNode::Min(vec![
+                CalcNode::Length(Length::new_px(10.0)),
+                CalcNode::Length(Length::new(2.0, LengthUnit::Em)),
+                CalcNode::Length(Length::new_px(5.0)),
+            ])
+        );
+        assert_eq!(
+            calc.simplify(&|length: &Length| match length.unit {
+                LengthUnit::Em => Some(*length.value * 16.0),
+                _ => length.absolute_px(),
+            }),
+            CalcNode::Length(Length::new_px(5.0))
+        );
+
+        let calc = parse("max(50%, 100px)").unwrap();
+        assert_eq!(calc.to_px(100.0), 100.0);
+        assert_eq!(calc.to_px(400.0), 200.0);
+
+        let calc = parse("clamp(10px, 50%, 100px)").unwrap();
+        assert_eq!(calc.to_px(10.0), 10.0);
+        assert_eq!(calc.to_px(100.0), 50.0);
+        assert_eq!(calc.to_px(1000.0), 100.0);
+    }
+
+    #[test]
+    fn parse_invalid_calc() {
+        assert_eq!(parse("calc(10px+5px)"), None);
+        assert_eq!(parse("calc(10px * 5px)"), None);
+        assert_eq!(parse("calc(10px / 5px)"), None);
+        assert_eq!(parse("calc(10px + 5)"), None);
+        assert_eq!(parse("calc(2 * 3)"), None);
+        assert_eq!(parse("clamp(1px, 2px)"), None);
+        assert_eq!(parse("calc(10px *)"), None);
+    }
+}
@@ -1,247 +1,471 @@
+use super::color_space::{interpolate, ColorSpace, HueInterpolation};
 use super::number::Number;
 use css::parser::structs::ComponentValue;
 use css::parser::structs::Function;
 use css::tokenizer::token::Token;
 
+/// A color, the color functions are converted to sRGB when parsed
+/// https://www.w3.org/TR/css-color-4/
 #[derive(Debug, Clone, PartialEq, Hash)]
 pub enum Color {
     CurrentColor,
+    /// The red, green, blue and alpha channels, from 0 to 255
     Rgba(Number, Number, Number, Number),
     Transparent,
+    /// A `color-mix()` of colors that depend on `currentColor`,
+    /// mixed when the value is computed
+    Mix(Box<ColorMix>),
+}
+
+/// https://www.w3.org/TR/css-color-5/#color-mix
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct ColorMix {
+    pub space: ColorSpace,
+    pub hue_interpolation: HueInterpolation,
+    pub first: Color,
+    pub second: Color,
+    /// The weight of the second color, from 0 to 1
+    pub progress: Number,
+    /// Applied to the mixed alpha when the percentages sum to less than 100%
+    pub alpha_multiplier: Number,
 }
 
 impl Eq for Color {}
 
-macro_rules! match_keyword {
-    ($keyword:ident, { $($match_key:expr => $value:expr),* }) => {
-        match $keyword {
-            $(n if n.trim().eq_ignore_ascii_case($match_key) => Some($value)),*,
-            _ => None
-        }
-    };
+impl Eq for ColorMix {}
+
+/// An argument of a color function
+#[derive(Debug, Clone, Copy)]
+enum Channel {
+    Number(f32),
+    Percentage(f32),
+    /// An angle in degrees
+    Angle(f32),
+    None,
 }
 
+/// The arguments of a color function, before and after the `/`
+#[derive(Clone)]
+struct ColorArguments {
+    channels: Vec<Channel>,
+    alpha: Option<Channel>,
+    /// Whether the arguments are separated with commas
+    legacy: bool,
+}
+
+/// Convert a computed color, in which `currentColor` is already resolved.
+/// An unresolved `currentColor` falls back to opaque black.
 impl From<&Color> for shared::color::Color {
     fn from(color: &Color) -> Self {
-        let default_color = shared::color::Color {
-            r: 0,
-            g: 0,
-            b: 0,
-            a: 0,
-        };
         match color {
-            Color::Rgba(r, g, b, a) => {
-                let alpha: u8 = a.as_u8();
-                shared::color::Color {
-                    r: r.as_u8(),
-                    g: g.as_u8(),
-                    b: b.as_u8(),
-                    a: alpha,
-                }
-            }
-            _ => default_color,
+            Color::Rgba(r, g, b, a) => shared::color::Color {
+                r: r.round() as u8,
+                g: g.round() as u8,
+                b: b.round() as u8,
+                a: a.round() as u8,
+            },
+            Color::Transparent => shared::color::Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            },
+            Color::CurrentColor | Color::Mix(_) => Self::from(&color.resolve(&Color::black())),
         }
     }
 }
 
 impl Color {
     pub fn parse(values: &[ComponentValue]) -> Option<Self> {
-        match values.iter().next() {
-            Some(ComponentValue::Function(function)) => match function.name.as_ref() {
-                "rgba" => Color::parse_rgba_function(function, true),
-                "rgb" => Color::parse_rgba_function(function, false),
-                _ => None,
-            },
-            Some(ComponentValue::PerservedToken(Token::Ident(keyword))) => {
-                Color::parse_color_keyword(&keyword)
+        let values = values
+            .iter()
+            .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+            .collect::<Vec<&ComponentValue>>();
+        match values[..] {
+            [ComponentValue::Function(function)] => Color::parse_function(function),
+            [ComponentValue::PerservedToken(Token::Ident(keyword))] => {
+                Color::parse_color_keyword(keyword)
             }
-            Some(ComponentValue::PerservedToken(Token::Hash(data, _))) => Color::parse_hex(&data),
+            [ComponentValue::PerservedToken(Token::Hash(data, _))] => Color::parse_hex(data),
             _ => None,
         }
     }
 
+    fn parse_function(function: &Function) -> Option<Self> {
+        match function.name.to_ascii_lowercase().as_str() {
+            "rgb" | "rgba" => Color::parse_rgb_function(&parse_arguments(&function.value)?),
+            "hsl" | "hsla" => Color::parse_hsl_function(&parse_arguments(&function.value)?),
+            "hwb" => {
+                Color::parse_hue_function(ColorSpace::Hwb, &parse_arguments(&function.value)?, 100.)
+            }
+            "lab" => Color::parse_lab_function(
+                ColorSpace::Lab,
+                &parse_arguments(&function.value)?,
+                [100., 125.],
+            ),
+            "oklab" => Color::parse_lab_function(
+                ColorSpace::Oklab,
+                &parse_arguments(&function.value)?,
+                [1., 0.4],
+            ),
+            "lch" => Color::parse_lch_function(
+                ColorSpace::Lch,
+                &parse_arguments(&function.value)?,
+                [100., 150.],
+            ),
+            "oklch" => Color::parse_lch_function(
+                ColorSpace::Oklch,
+                &parse_arguments(&function.value)?,
+                [1., 0.4],
+            ),
+            "color" => Color::parse_color_function(&function.value),
+            "color-mix" => Color::parse_color_mix(&function.value),
+            _ => None,
+        }
+    }
+
+    /// Parse 3, 4, 6 and 8 digit hex colors, the last digits are the alpha
     fn parse_hex(hex: &str) -> Option<Self> {
-        let mut chars = hex.chars();
+        if !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            return None;
+        }
+        let digit = |index: usize| u32::from_str_radix(&hex[index..index + 1], 16).ok();
+        let pair = |index: usize| u32::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok();
+
+        let channels = match hex.len() {
+            3 | 4 => (0..hex.len())
+                .map(|index| digit(index).map(|value| value * 0x11))
+                .collect::<Option<Vec<u32>>>()?,
+            6 | 8 => (0..hex.len() / 2).map(pair).collect::<Option<Vec<u32>>>()?,
+            _ => return None,
+        };
+        let alpha = channels.get(3).copied().unwrap_or(255);
+        Some(Color::Rgba(
+            channels[0].into(),
+            channels[1].into(),
+            channels[2].into(),
+            alpha.into(),
+        ))
+    }
 
-        fn parse_digit(chars: &mut std::str::Chars) -> Option<u32> {
-            if let Some(ch) = chars.next() {
-                return ch.to_digit(16);
+    /// https://www.w3.org/TR/css-color-4/#named-colors
+    fn parse_color_keyword(keyword: &str) -> Option<Self> {
+        let keyword = keyword.trim().to_ascii_lowercase();
+        match keyword.as_str() {
+            "currentcolor" => return Some(Color::CurrentColor),
+            "transparent" => return Some(Color::Transparent),
+            _ => {}
+        }
+        let index = NAMED_COLORS
+            .binary_search_by(|(name, _)| name.cmp(&keyword.as_str()))
+            .ok()?;
+        let rgb = NAMED_COLORS[index].1;
+        Some(Color::Rgba(
+            (rgb >> 16).into(),
+            (rgb >> 8 & 0xff).into(),
+            (rgb & 0xff).into(),
+            255.0.into(),
+        ))
+    }
+
+    /// `rgb()` and `rgba()`, the legacy syntax can't mix numbers
+    /// and percentages or have `none`
+    /// https://www.w3.org/TR/css-color-4/#rgb-functions
+    fn parse_rgb_function(arguments: &ColorArguments) -> Option<Self> {
+        if arguments.legacy {
+            let same_type = matches!(
+                arguments.channels[..],
+                [Channel::Number(_), Channel::Number(_), Channel::Number(_)]
+                    | [
+                        Channel::Percentage(_),
+                        Channel::Percentage(_),
+                        Channel::Percentage(_)
+                    ]
+            );
+            if !same_type {
+                return None;
             }
-            None
         }
+        let channel = |index: usize| match arguments.channels.get(index)? {
+            Channel::Number(value) => Some(value.clamp(0., 255.)),
+            Channel::Percentage(value) => Some((value * 2.55).clamp(0., 255.)),
+            Channel::None => Some(0.),
+            Channel::Angle(_) => None,
+        };
+        if arguments.channels.len() != 3 {
+            return None;
+        }
+        Some(Color::Rgba(
+            channel(0)?.into(),
+            channel(1)?.into(),
+            channel(2)?.into(),
+            (arguments.alpha()? * 255.).into(),
+        ))
+    }
 
-        fn parse_pair_digit(chars: &mut std::str::Chars) -> Option<u32> {
-            let pair: String = chars.take(2).collect();
+    /// `hsl()` and `hsla()`, the saturation and lightness are
+    /// percentages in the legacy syntax
+    /// https://www.w3.org/TR/css-color-4/#the-hsl-notation
+    fn parse_hsl_function(arguments: &ColorArguments) -> Option<Self> {
+        if arguments.legacy
+            && !matches!(
+                arguments.channels[..],
+                [
+                    Channel::Number(_) | Channel::Angle(_),
+                    Channel::Percentage(_),
+                    Channel::Percentage(_)
+                ]
+            )
+        {
+            return None;
+        }
+        let arguments = ColorArguments {
+            legacy: false,
+            ..arguments.clone()
+        };
+        Color::parse_hue_function(ColorSpace::Hsl, &arguments, 100.)
+    }
 
-            if pair.len() == 2 {
-                return u32::from_str_radix(&pair, 16).ok();
-            }
+    /// `hsl()` and `hwb()` in the modern syntax, a hue then two
+    /// numbers or percentages
+    /// https://www.w3.org/TR/css-color-4/#the-hwb-notation
+    fn parse_hue_function(
+        space: ColorSpace,
+        arguments: &ColorArguments,
+        percentage: f32,
+    ) -> Option<Self> {
+        if arguments.legacy {
+            return None;
+        }
+        match arguments.channels[..] {
+            [hue, first, second] => Some(Color::from_space(
+                space,
+                [
+                    hue.hue()?,
+                    first.value(percentage)?.max(0.),
+                    second.value(percentage)?.max(0.),
+                ],
+                arguments.alpha()?,
+            )),
+            _ => None,
+        }
+    }
 
-            None
-        }
-
-        if hex.len() == 3 {
-            let r = match parse_digit(&mut chars) {
-                Some(d) => d * 0x11,
-                _ => return None,
-            };
-            let g = match parse_digit(&mut chars) {
-                Some(d) => d * 0x11,
-                _ => return None,
-            };
-            let b = match parse_digit(&mut chars) {
-                Some(d) => d * 0x11,
-                _ => return None,
-            };
-            Some(Color::Rgba(r.into(), g.into(), b.into(), 255.0.into()))
-        } else if hex.len() == 6 {
-            let r = match parse_pair_digit(&mut chars) {
-                Some(d) => d,
-                _ => return None,
-            };
-            let g = match parse_pair_digit(&mut chars) {
-                Some(d) => d,
-                _ => return None,
-            };
-            let b = match parse_pair_digit(&mut chars) {
-                Some(d) => d,
-                _ => return None,
-            };
-            Some(Color::Rgba(r.into(), g.into(), b.into(), 255.0.into()))
-        } else {
-            None
+    /// `lab()` and `oklab()`, with the references of 100% of the
+    /// lightness and of the a and b axes
+    /// https://www.w3.org/TR/css-color-4/#specifying-lab-lch
+    fn parse_lab_function(
+        space: ColorSpace,
+        arguments: &ColorArguments,
+        [lightness, axis]: [f32; 2],
+    ) -> Option<Self> {
+        if arguments.legacy {
+            return None;
+        }
+        match arguments.channels[..] {
+            [l, a, b] => Some(Color::from_space(
+                space,
+                [
+                    l.value(lightness)?.clamp(0., lightness),
+                    a.value(axis)?,
+                    b.value(axis)?,
+                ],
+                arguments.alpha()?,
+            )),
+            _ => None,
         }
     }
 
-    fn parse_color_keyword(keyword: &str) -> Option<Self> {
-        match_keyword!(keyword, {
-            "currentColor" => Color::CurrentColor,
-            "transparent" => Color::Transparent,
-            "black" => Color::black(),
-            "silver" => Color::Rgba(
-                192.0.into(),
-                192.0.into(),
-                192.0.into(),
-                255.0.into(),
-            ),
-            "gray" => Color::Rgba(
-                128.0.into(),
-                128.0.into(),
-                128.0.into(),
-                255.0.into(),
-            ),
-            "white" => Color::Rgba(
-                255.0.into(),
-                255.0.into(),
-                255.0.into(),
-                255.0.into(),
-            ),
-            "maroon" => Color::Rgba(
-                128.0.into(),
-                0.0.into(),
-                0.0.into(),
-                255.0.into(),
-            ),
-            "red" => Color::Rgba(
-                255.0.into(),
-                0.0.into(),
-                0.0.into(),
-                255.0.into(),
-            ),
-            "purple" => Color::Rgba(
-                128.0.into(),
-                0.0.into(),
-                128.0.into(),
-                255.0.into(),
-            ),
-            "fuchsia" => Color::Rgba(
-                255.0.into(),
-                0.0.into(),
-                255.0.into(),
-                255.0.into(),
-            ),
-            "green" => Color::Rgba(
-                0.0.into(),
-                128.0.into(),
-                0.0.into(),
-                255.0.into(),
-            ),
-            "lime" => Color::Rgba(
-                0.0.into(),
-                255.0.into(),
-                0.0.into(),
-                255.0.into(),
-            ),
-            "olive" => Color::Rgba(
-                128.0.into(),
-                128.0.into(),
-                0.0.into(),
-                255.0.into(),
-            ),
-            "yellow" => Color::Rgba(
-                255.0.into(),
-                255.0.into(),
-                0.0.into(),
-                255.0.into(),
-            ),
-            "navy" => Color::Rgba(
-                0.0.into(),
-                0.0.into(),
-                128.0.into(),
-                255.0.into(),
-            ),
-            "blue" => Color::Rgba(
-                0.0.into(),
-                0.0.into(),
-                255.0.into(),
-                255.0.into(),
-            ),
-            "teal" => Color::Rgba(
-                0.0.into(),
-                128.0.into(),
-                128.0.into(),
-                255.0.into(),
-            ),
-            "aqua" => Color::Rgba(
-                0.0.into(),
-                255.0.into(),
-                255.0.into(),
-                255.0.into(),
-            )
-        })
+    /// `lch()` and `oklch()`, with the references of 100% of the
+    /// lightness and of the chroma
+    fn parse_lch_function(
+        space: ColorSpace,
+        arguments: &ColorArguments,
+        [lightness, chroma]: [f32; 2],
+    ) -> Option<Self> {
+        if arguments.legacy {
+            return None;
+        }
+        match arguments.channels[..] {
+            [l, c, h] => Some(Color::from_space(
+                space,
+                [
+                    l.value(lightness)?.clamp(0., lightness),
+                    c.value(chroma)?.max(0.),
+                    h.hue()?,
+                ],
+                arguments.alpha()?,
+            )),
+            _ => None,
+        }
+    }
+
+    /// `color()` with a predefined color space
+    /// https://www.w3.org/TR/css-color-4/#color-function
+    fn parse_color_function(values: &[ComponentValue]) -> Option<Self> {
+        let start = values.iter().position(|value| {
+            !matches!(value, ComponentValue::PerservedToken(Token::Whitespace))
+        })?;
+        let space = match &values[start] {
+            ComponentValue::PerservedToken(Token::Ident(name)) => {
+                ColorSpace::parse_predefined(name)?
+            }
+            _ => return None,
+        };
+        let arguments = parse_arguments(&values[start + 1..])?;
+        if arguments.legacy {
+            return None;
+        }
+        match arguments.channels[..] {
+            [first, second, third] => Some(Color::from_space(
+                space,
+                [first.value(1.)?, second.value(1.)?, third.value(1.)?],
+                arguments.alpha()?,
+            )),
+            _ => None,
+        }
     }
 
-    fn parse_rgba_function(function: &Function, with_alpha: bool) -> Option<Self> {
-        let mut rgba: [f32; 4] = if !with_alpha {
-            [0.0, 0.0, 0.0, 255.0]
-        } else {
-            [0.0, 0.0, 0.0, 0.0]
+    /// `color-mix(in <space> [<hue> hue]?, <color> <percentage>?, <color> <percentage>?)`
+    /// https://www.w3.org/TR/css-color-5/#color-mix
+    fn parse_color_mix(values: &[ComponentValue]) -> Option<Self> {
+        let arguments = values
+            .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+            .map(|argument| {
+                argument
+                    .iter()
+                    .filter(|value| {
+                        !matches!(value, ComponentValue::PerservedToken(Token::Whitespace))
+                    })
+                    .cloned()
+                    .collect::<Vec<ComponentValue>>()
+            })
+            .collect::<Vec<_>>();
+        let (interpolation, first, second) = match &arguments[..] {
+            [interpolation, first, second] => (interpolation, first, second),
+            _ => return None,
         };
 
-        let mut index = 0;
-        let max_length = if !with_alpha { 3 } else { 4 };
+        let idents = interpolation
+            .iter()
+            .map(|value| match value {
+                ComponentValue::PerservedToken(Token::Ident(ident)) => {
+                    Some(ident.to_ascii_lowercase())
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<String>>>()?;
+        let (space, hue_interpolation) = match &idents[..] {
+            [keyword, space] if keyword == "in" => {
+                (ColorSpace::parse(space)?, HueInterpolation::Shorter)
+            }
+            [keyword, space, method, hue] if keyword == "in" && hue == "hue" => {
+                let space = ColorSpace::parse(space)?;
+                space.hue_index()?;
+                let method = match method.as_str() {
+                    "shorter" => HueInterpolation::Shorter,
+                    "longer" => HueInterpolation::Longer,
+                    "increasing" => HueInterpolation::Increasing,
+                    "decreasing" => HueInterpolation::Decreasing,
+                    _ => return None,
+                };
+                (space, method)
+            }
+            _ => return None,
+        };
 
-        for value in &function.value {
-            match value {
-                ComponentValue::PerservedToken(Token::Number { value, .. }) => {
-                    if index == max_length {
+        // a color with an optional percentage before or after it
+        let mix_argument = |values: &[ComponentValue]| -> Option<(Color, Option<f32>)> {
+            match values {
+                [ComponentValue::PerservedToken(Token::Percentage(percentage)), color]
+                | [color, ComponentValue::PerservedToken(Token::Percentage(percentage))] => {
+                    if !(0. ..=100.).contains(percentage) {
                         return None;
                     }
-                    rgba[index] = *value;
-                    index += 1;
+                    Some((
+                        Color::parse(std::slice::from_ref(color))?,
+                        Some(*percentage),
+                    ))
                 }
-                ComponentValue::PerservedToken(Token::Whitespace) => {}
-                ComponentValue::PerservedToken(Token::Comma) => {}
-                _ => return None, // invalid character
+                [color] => Some((Color::parse(std::slice::from_ref(color))?, None)),
+                _ => None,
             }
+        };
+        let (first, first_percentage) = mix_argument(first)?;
+        let (second, second_percentage) = mix_argument(second)?;
+
+        let (first_percentage, second_percentage) = match (first_percentage, second_percentage) {
+            (None, None) => (50., 50.),
+            (Some(first), None) => (first, 100. - first),
+            (None, Some(second)) => (100. - second, second),
+            (Some(first), Some(second)) => (first, second),
+        };
+        let sum = first_percentage + second_percentage;
+        if sum == 0. {
+            return None;
         }
 
-        Some(Color::Rgba(
-            rgba[0].into(),
-            rgba[1].into(),
-            rgba[2].into(),
-            rgba[3].into(),
-        ))
+        let mix = ColorMix {
+            space,
+            hue_interpolation,
+            first,
+            second,
+            progress: (second_percentage / sum).into(),
+            alpha_multiplier: (sum.min(100.) / 100.).into(),
+        };
+        match mix.uses_current_color() {
+            true => Some(Color::Mix(Box::new(mix))),
+            false => mix.mix(),
+        }
+    }
+
+    /// An sRGB color from coordinates in a color space, clipped to the sRGB gamut
+    fn from_space(space: ColorSpace, coords: [f32; 3], alpha: f32) -> Self {
+        let rgb = space.to_srgb(coords.map(f64::from));
+        let channel = |value: f64| (value.clamp(0., 1.) * 255.) as f32;
+        Color::Rgba(
+            channel(rgb[0]).into(),
+            channel(rgb[1]).into(),
+            channel(rgb[2]).into(),
+            (alpha.clamp(0., 1.) * 255.).into(),
+        )
+    }
+
+    /// The coordinates of an sRGB color in a color space and its alpha, from 0 to 1
+    fn to_space(&self, space: ColorSpace) -> Option<([f64; 3], f64)> {
+        match self {
+            Color::Rgba(r, g, b, a) => {
+                let rgb = [**r, **g, **b].map(|value| f64::from(value) / 255.);
+                Some((space.srgb_to_coords(rgb), f64::from(**a) / 255.))
+            }
+            Color::Transparent => Some((space.srgb_to_coords([0., 0., 0.]), 0.)),
+            Color::CurrentColor | Color::Mix(_) => None,
+        }
+    }
+
+    /// Whether the color depends on the value of the `color` property
+    pub fn uses_current_color(&self) -> bool {
+        match self {
+            Color::CurrentColor => true,
+            Color::Mix(mix) => mix.uses_current_color(),
+            _ => false,
+        }
+    }
+
+    /// Replace `currentColor` with the current color
+    pub fn resolve(&self, current_color: &Color) -> Color {
+        match self {
+            Color::CurrentColor => current_color.clone(),
+            Color::Mix(mix) => {
+                let mix = ColorMix {
+                    first: mix.first.resolve(current_color),
+                    second: mix.second.resolve(current_color),
+                    ..(**mix).clone()
+                };
+                mix.mix().unwrap_or_else(Color::transparent)
+            }
+            _ => self.clone(),
+        }
     }
 
     pub fn transparent() -> Self {
@@ -252,3 +476,383 @@ impl Color {
         Color::Rgba(0.0.into(), 0.0.into(), 0.0.into(), 255.0.into())
     }
 }
+
+impl ColorMix {
+    fn uses_current_color(&self) -> bool {
+        self.first.uses_current_color() || self.second.uses_current_color()
+    }
+
+    /// Mix the colors, they must not depend on `currentColor`
+    fn mix(&self) -> Option<Color> {
+        let (coords, alpha) = interpolate(
+            self.space,
+            self.hue_interpolation,
+            self.first.to_space(self.space)?,
+            self.second.to_space(self.space)?,
+            f64::from(*self.progress),
+        );
+        let coords = coords.map(|value| value as f32);
+        let alpha = alpha as f32 * *self.alpha_multiplier;
+        Some(Color::from_space(self.space, coords, alpha))
+    }
+}
+
+impl Channel {
+    /// The value of a channel, `reference` is the value of 100%
+    fn value(&self, reference: f32) -> Option<f32> {
+        match self {
+            Channel::Number(value) => Some(*value),
+            Channel::Percentage(value) => Some(value / 100. * reference),
+            Channel::None => Some(0.),
+            Channel::Angle(_) => None,
+        }
+    }
+
+    /// A hue in degrees
+    fn hue(&self) -> Option<f32> {
+        match self {
+            Channel::Number(value) | Channel::Angle(value) => Some(*value),
+            Channel::None => Some(0.),
+            Channel::Percentage(_) => None,
+        }
+    }
+}
+
+impl ColorArguments {
+    /// The alpha from 0 to 1, opaque when it's omitted
+    fn alpha(&self) -> Option<f32> {
+        match self.alpha {
+            Some(alpha) => Some(alpha.value(1.)?.clamp(0., 1.)),
+            None => Some(1.),
+        }
+    }
+}
+
+/// Parse the arguments of a color function, either three channels
+/// separated with commas and an optional alpha, or three channels
+/// separated with spaces and an optional alpha after a `/`
+/// https://www.w3.org/TR/css-color-4/#color-syntax-legacy
+fn parse_arguments(values: &[ComponentValue]) -> Option<ColorArguments> {
+    let mut channels = Vec::new();
+    let mut alpha = None;
+    let mut commas = 0;
+    let mut after_slash = false;
+
+    for value in values {
+        let channel = match value {
+            ComponentValue::PerservedToken(Token::Whitespace) => continue,
+            ComponentValue::PerservedToken(Token::Comma) => {
+                commas += 1;
+                if commas != channels.len() || after_slash {
+                    return None;
+                }
+                continue;
+            }
+            ComponentValue::PerservedToken(Token::Delim('/')) => {
+                if after_slash || channels.len() != 3 {
+                    return None;
+                }
+                after_slash = true;
+                continue;
+            }
+            ComponentValue::PerservedToken(Token::Number { value, .. }) => Channel::Number(*value),
+            ComponentValue::PerservedToken(Token::Percentage(value)) => Channel::Percentage(*value),
+            ComponentValue::PerservedToken(Token::Dimension { value, unit, .. }) => {
+                match unit.to_ascii_lowercase().as_str() {
+                    "deg" => Channel::Angle(*value),
+                    "grad" => Channel::Angle(value * 0.9),
+                    "rad" => Channel::Angle(value.to_degrees()),
+                    "turn" => Channel::Angle(value * 360.),
+                    _ => return None,
+                }
+            }
+            ComponentValue::PerservedToken(Token::Ident(ident))
+                if ident.eq_ignore_ascii_case("none") =>
+            {
+                Channel::None
+            }
+            _ => return None,
+        };
+        match after_slash {
+            true if alpha.is_none() => alpha = Some(channel),
+            true => return None,
+            false => channels.push(channel),
+        }
+    }
+
+    if after_slash && alpha.is_none() {
+        return None;
+    }
+    let legacy = commas > 0;
+    if legacy {
+        // the alpha is the fourth argument
+        if commas + 1 != channels.len() || channels.len() > 4 || after_slash {
+            return None;
+        }
+        if channels
+            .iter()
+            .any(|channel| matches!(channel, Channel::None))
+        {
+            return None;
+        }
+        if channels.len() == 4 {
+            alpha = channels.pop();
+        }
+    }
+    if channels.len() != 3 {
+        return None;
+    }
+    Some(ColorArguments {
+        channels,
+        alpha,
+        legacy,
+    })
+}
+
+/// The named colors, sorted by name
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use css::parser::Parser;
+    use css::tokenizer::Tokenizer;
+
+    fn parse_value_str(css: &str) -> Vec<ComponentValue> {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        parser.parse_a_list_of_component_values()
+    }
+
+    /// The 8-bit channels of a parsed color
+    fn rgba(css: &str) -> Option<[u8; 4]> {
+        let color = Color::parse(&parse_value_str(css))?;
+        let color = shared::color::Color::from(&color);
+        Some([color.r, color.g, color.b, color.a])
+    }
+
+    #[test]
+    fn parse_colors() {
+        assert_eq!(rgba("RebeccaPurple"), Some([102, 51, 153, 255]));
+        assert_eq!(rgba("#f008"), Some([255, 0, 0, 136]));
+        assert_eq!(rgba("#00ff0080"), Some([0, 255, 0, 128]));
+        assert_eq!(rgba("#00ff0"), None);
+        assert_eq!(rgba("rgba(255, 0, 0, 0.5)"), Some([255, 0, 0, 128]));
+        assert_eq!(rgba("rgb(100% 50% none / 25%)"), Some([255, 128, 0, 64]));
+        assert_eq!(rgba("rgb(255, 50%, 0)"), None);
+        assert_eq!(rgba("rgb(255, 0, none)"), None);
+        assert_eq!(rgba("hsl(120deg 100% 25%)"), Some([0, 128, 0, 255]));
+        assert_eq!(
+            rgba("hsla(0.5turn, 100%, 50%, 1)"),
+            Some([0, 255, 255, 255])
+        );
+        assert_eq!(rgba("hwb(0 0% 0%)"), Some([255, 0, 0, 255]));
+        assert_eq!(rgba("hwb(0 60% 60%)"), Some([128, 128, 128, 255]));
+        assert_eq!(rgba("lab(100 0 0)"), Some([255, 255, 255, 255]));
+        assert_eq!(rgba("lab(54.29 80.8 69.89)"), Some([255, 0, 0, 255]));
+        assert_eq!(rgba("lch(54.29 106.84 40.85)"), Some([255, 0, 0, 255]));
+        assert_eq!(rgba("oklab(100% 0 0)"), Some([255, 255, 255, 255]));
+        assert_eq!(rgba("oklch(0.628 0.2577 29.23)"), Some([255, 0, 0, 255]));
+        assert_eq!(rgba("color(srgb 1 0.5 0)"), Some([255, 128, 0, 255]));
+        assert_eq!(rgba("color(display-p3 0 1 0)"), Some([0, 255, 0, 255]));
+        assert_eq!(
+            rgba("color(xyz 0.9505 1 1.089)"),
+            Some([255, 255, 255, 255])
+        );
+        assert_eq!(rgba("color(rec2020 0 0 0 / 0.5)"), Some([0, 0, 0, 128]));
+        assert_eq!(rgba("color(unknown 0 0 0)"), None);
+    }
+
+    #[test]
+    fn parse_color_mix() {
+        assert_eq!(
+            rgba("color-mix(in srgb, red, blue)"),
+            Some([128, 0, 128, 255])
+        );
+        assert_eq!(
+            rgba("color-mix(in srgb, red 75%, blue)"),
+            Some([191, 0, 64, 255])
+        );
+        // the percentages sum to less than 100%
+        assert_eq!(
+            rgba("color-mix(in srgb, white 25%, black 25%)"),
+            Some([128, 128, 128, 128])
+        );
+        // the missing hue of white takes the hue of red
+        assert_eq!(
+            rgba("color-mix(in hsl, white, red)"),
+            Some([223, 159, 159, 255])
+        );
+        assert_eq!(
+            rgba("color-mix(in hsl longer hue, hsl(0 100% 50%), hsl(90 100% 50%))"),
+            rgba("hsl(225 100% 50%)")
+        );
+        assert_eq!(rgba("color-mix(in srgb longer hue, red, blue)"), None);
+        assert_eq!(rgba("color-mix(in srgb, red 0%, blue 0%)"), None);
+
+        let color = Color::parse(&parse_value_str("color-mix(in srgb, currentColor, white)"))
+            .expect("Invalid color");
+        assert!(color.uses_current_color());
+        let resolved = color.resolve(&Color::black());
+        assert_eq!(
+            shared::color::Color::from(&resolved).r,
+            rgba("color-mix(in srgb, black, white)").unwrap()[0]
+        );
+        // an unresolved color converts as if the current color were black
+        let channels = |color: &Color| {
+            let color = shared::color::Color::from(color);
+            [color.r, color.g, color.b, color.a]
+        };
+        assert_eq!(channels(&color), channels(&resolved));
+        assert_eq!(channels(&Color::CurrentColor), [0, 0, 0, 255]);
+    }
+}
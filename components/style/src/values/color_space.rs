@@ -0,0 +1,489 @@
+//! Conversions between the color spaces of CSS and sRGB
+//! https://www.w3.org/TR/css-color-4/#color-conversion-code
+
+type Matrix = [[f64; 3]; 3];
+
+const SRGB_TO_XYZ: Matrix = [
+    [506752. / 1228815., 87881. / 245763., 12673. / 70218.],
+    [87098. / 409605., 175762. / 245763., 12673. / 175545.],
+    [7918. / 409605., 87881. / 737289., 1001167. / 1053270.],
+];
+const XYZ_TO_SRGB: Matrix = [
+    [12831. / 3959., -329. / 214., -1974. / 3959.],
+    [-851781. / 878810., 1648619. / 878810., 36519. / 878810.],
+    [705. / 12673., -2585. / 12673., 705. / 667.],
+];
+const P3_TO_XYZ: Matrix = [
+    [608311. / 1250200., 189793. / 714400., 198249. / 1000160.],
+    [35783. / 156275., 247089. / 357200., 198249. / 2500400.],
+    [0., 32229. / 714400., 5220557. / 5000800.],
+];
+const XYZ_TO_P3: Matrix = [
+    [446124. / 178915., -333277. / 357830., -72051. / 178915.],
+    [-14852. / 17905., 63121. / 35810., 423. / 17905.],
+    [11844. / 330415., -50337. / 660830., 316169. / 330415.],
+];
+const A98_TO_XYZ: Matrix = [
+    [573536. / 994567., 263643. / 1420810., 187206. / 994567.],
+    [591459. / 1989134., 6239551. / 9945670., 374412. / 4972835.],
+    [53769. / 1989134., 351524. / 4972835., 4929758. / 4972835.],
+];
+const XYZ_TO_A98: Matrix = [
+    [1829569. / 896150., -506331. / 896150., -308931. / 896150.],
+    [-851781. / 878810., 1648619. / 878810., 36519. / 878810.],
+    [16779. / 1248040., -147721. / 1248040., 1266979. / 1248040.],
+];
+/// ProPhoto RGB is relative to the D50 white point
+const PROPHOTO_TO_XYZ_D50: Matrix = [
+    [0.797_766_6, 0.135_181_3, 0.031_347_7],
+    [0.288_074_8, 0.711_835_2, 0.000_089_9],
+    [0., 0., 0.825_104_6],
+];
+const XYZ_D50_TO_PROPHOTO: Matrix = [
+    [1.345_786_9, -0.255_572_1, -0.051_101_9],
+    [-0.544_630_7, 1.508_247_7, 0.020_527_4],
+    [0., 0., 1.211_967_5],
+];
+const REC2020_TO_XYZ: Matrix = [
+    [
+        63426534. / 99577255.,
+        20160776. / 139408157.,
+        47086771. / 278816314.,
+    ],
+    [
+        26158966. / 99577255.,
+        472592308. / 697040785.,
+        8267143. / 139408157.,
+    ],
+    [0., 19567812. / 697040785., 295819943. / 278816314.],
+];
+const XYZ_TO_REC2020: Matrix = [
+    [
+        30757411. / 17917100.,
+        -6372589. / 17917100.,
+        -4539589. / 17917100.,
+    ],
+    [
+        -19765991. / 29648200.,
+        47925759. / 29648200.,
+        467509. / 29648200.,
+    ],
+    [
+        792561. / 44930125.,
+        -1921689. / 44930125.,
+        42328811. / 44930125.,
+    ],
+];
+/// Bradford chromatic adaptation between the D50 and D65 white points
+const D50_TO_D65: Matrix = [
+    [0.955_473_4, -0.023_098_5, 0.063_259_2],
+    [-0.028_369_7, 1.009_995_4, 0.021_041_4],
+    [0.012_314_0, -0.020_507_6, 1.330_365_9],
+];
+const D65_TO_D50: Matrix = [
+    [1.047_929_8, 0.022_946_9, -0.050_192_3],
+    [0.029_627_8, 0.990_434_4, -0.017_073_8],
+    [-0.009_243_0, 0.015_055_2, 0.751_874_3],
+];
+const XYZ_TO_LMS: Matrix = [
+    [0.819_022_4, 0.361_906_3, -0.128_873_8],
+    [0.032_983_7, 0.929_286_9, 0.036_144_7],
+    [0.048_177_2, 0.264_239_5, 0.633_547_8],
+];
+const LMS_TO_XYZ: Matrix = [
+    [1.226_879_9, -0.557_815_0, 0.281_391_0],
+    [-0.040_575_7, 1.112_286_8, -0.071_711_1],
+    [-0.076_372_9, -0.421_493_3, 1.586_924_0],
+];
+const LMS_TO_OKLAB: Matrix = [
+    [0.210_454_3, 0.793_617_8, -0.004_072_0],
+    [1.977_998_5, -2.428_592_2, 0.450_593_7],
+    [0.025_904_0, 0.782_771_7, -0.808_675_8],
+];
+const OKLAB_TO_LMS: Matrix = [
+    [1., 0.396_337_8, 0.215_803_8],
+    [1., -0.105_561_3, -0.063_854_2],
+    [1., -0.089_484_2, -1.291_485_5],
+];
+/// The D50 white point of Lab
+const D50_WHITE: [f64; 3] = [0.3457 / 0.3585, 1., (1. - 0.3457 - 0.3585) / 0.3585];
+
+/// The color spaces of `color()`, of the color functions and
+/// the interpolation color spaces of `color-mix()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    Srgb,
+    SrgbLinear,
+    DisplayP3,
+    A98Rgb,
+    ProphotoRgb,
+    Rec2020,
+    XyzD50,
+    XyzD65,
+    Lab,
+    Oklab,
+    Hsl,
+    Hwb,
+    Lch,
+    Oklch,
+}
+
+/// How the hues of cylindrical colors are interpolated
+/// https://www.w3.org/TR/css-color-4/#hue-interpolation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HueInterpolation {
+    Shorter,
+    Longer,
+    Increasing,
+    Decreasing,
+}
+
+impl ColorSpace {
+    /// The predefined color spaces of `color()`
+    /// https://www.w3.org/TR/css-color-4/#predefined
+    pub fn parse_predefined(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "srgb" => Some(Self::Srgb),
+            "srgb-linear" => Some(Self::SrgbLinear),
+            "display-p3" => Some(Self::DisplayP3),
+            "a98-rgb" => Some(Self::A98Rgb),
+            "prophoto-rgb" => Some(Self::ProphotoRgb),
+            "rec2020" => Some(Self::Rec2020),
+            "xyz-d50" => Some(Self::XyzD50),
+            "xyz" | "xyz-d65" => Some(Self::XyzD65),
+            _ => None,
+        }
+    }
+
+    /// The color spaces that colors can be interpolated in
+    /// https://www.w3.org/TR/css-color-4/#interpolation-space
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "lab" => Some(Self::Lab),
+            "oklab" => Some(Self::Oklab),
+            "hsl" => Some(Self::Hsl),
+            "hwb" => Some(Self::Hwb),
+            "lch" => Some(Self::Lch),
+            "oklch" => Some(Self::Oklch),
+            name => Self::parse_predefined(name),
+        }
+    }
+
+    /// The position of the hue among the coordinates of a cylindrical space
+    pub fn hue_index(self) -> Option<usize> {
+        match self {
+            Self::Hsl | Self::Hwb => Some(0),
+            Self::Lch | Self::Oklch => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Whether the hue of the coordinates is powerless, as for grays
+    fn is_achromatic(self, coords: [f64; 3]) -> bool {
+        match self {
+            Self::Hsl => coords[1].abs() < 1e-4,
+            Self::Hwb => coords[1] + coords[2] >= 100. - 1e-4,
+            Self::Lch => coords[1] < 0.015,
+            Self::Oklch => coords[1] < 4e-5,
+            _ => false,
+        }
+    }
+
+    /// Convert coordinates in the space to sRGB, the channels are
+    /// between 0 and 1 when the color is in the sRGB gamut
+    pub fn to_srgb(self, coords: [f64; 3]) -> [f64; 3] {
+        match self {
+            Self::Srgb => coords,
+            Self::Hsl => hsl_to_srgb(coords),
+            Self::Hwb => hwb_to_srgb(coords),
+            _ => map(multiply(&XYZ_TO_SRGB, self.to_xyz(coords)), srgb_encode),
+        }
+    }
+
+    /// Convert sRGB channels to coordinates in the space. The hue of
+    /// achromatic colors is missing, it's `NaN`.
+    pub fn srgb_to_coords(self, rgb: [f64; 3]) -> [f64; 3] {
+        let mut coords = match self {
+            Self::Srgb => rgb,
+            Self::Hsl => srgb_to_hsl(rgb),
+            Self::Hwb => srgb_to_hwb(rgb),
+            _ => self.xyz_to_coords(multiply(&SRGB_TO_XYZ, map(rgb, srgb_decode))),
+        };
+        if let Some(hue) = self.hue_index() {
+            if self.is_achromatic(coords) {
+                coords[hue] = f64::NAN;
+            }
+        }
+        coords
+    }
+
+    /// Convert coordinates in the space to XYZ with the D65 white point
+    fn to_xyz(self, coords: [f64; 3]) -> [f64; 3] {
+        match self {
+            Self::Srgb => multiply(&SRGB_TO_XYZ, map(coords, srgb_decode)),
+            Self::Hsl => Self::Srgb.to_xyz(hsl_to_srgb(coords)),
+            Self::Hwb => Self::Srgb.to_xyz(hwb_to_srgb(coords)),
+            Self::SrgbLinear => multiply(&SRGB_TO_XYZ, coords),
+            Self::DisplayP3 => multiply(&P3_TO_XYZ, map(coords, srgb_decode)),
+            Self::A98Rgb => multiply(&A98_TO_XYZ, map(coords, a98_decode)),
+            Self::ProphotoRgb => multiply(
+                &D50_TO_D65,
+                multiply(&PROPHOTO_TO_XYZ_D50, map(coords, prophoto_decode)),
+            ),
+            Self::Rec2020 => multiply(&REC2020_TO_XYZ, map(coords, rec2020_decode)),
+            Self::XyzD50 => multiply(&D50_TO_D65, coords),
+            Self::XyzD65 => coords,
+            Self::Lab => multiply(&D50_TO_D65, lab_to_xyz_d50(coords)),
+            Self::Lch => Self::Lab.to_xyz(polar_to_rectangular(coords)),
+            Self::Oklab => {
+                let lms = multiply(&OKLAB_TO_LMS, coords);
+                multiply(&LMS_TO_XYZ, map(lms, |value| value.powi(3)))
+            }
+            Self::Oklch => Self::Oklab.to_xyz(polar_to_rectangular(coords)),
+        }
+    }
+
+    /// Convert XYZ with the D65 white point to coordinates in the space
+    fn xyz_to_coords(self, xyz: [f64; 3]) -> [f64; 3] {
+        match self {
+            Self::Srgb => map(multiply(&XYZ_TO_SRGB, xyz), srgb_encode),
+            Self::Hsl => srgb_to_hsl(Self::Srgb.xyz_to_coords(xyz)),
+            Self::Hwb => srgb_to_hwb(Self::Srgb.xyz_to_coords(xyz)),
+            Self::SrgbLinear => multiply(&XYZ_TO_SRGB, xyz),
+            Self::DisplayP3 => map(multiply(&XYZ_TO_P3, xyz), srgb_encode),
+            Self::A98Rgb => map(multiply(&XYZ_TO_A98, xyz), a98_encode),
+            Self::ProphotoRgb => map(
+                multiply(&XYZ_D50_TO_PROPHOTO, multiply(&D65_TO_D50, xyz)),
+                prophoto_encode,
+            ),
+            Self::Rec2020 => map(multiply(&XYZ_TO_REC2020, xyz), rec2020_encode),
+            Self::XyzD50 => multiply(&D65_TO_D50, xyz),
+            Self::XyzD65 => xyz,
+            Self::Lab => xyz_d50_to_lab(multiply(&D65_TO_D50, xyz)),
+            Self::Lch => rectangular_to_polar(Self::Lab.xyz_to_coords(xyz)),
+            Self::Oklab => {
+                let lms = multiply(&XYZ_TO_LMS, xyz);
+                multiply(&LMS_TO_OKLAB, map(lms, f64::cbrt))
+            }
+            Self::Oklch => rectangular_to_polar(Self::Oklab.xyz_to_coords(xyz)),
+        }
+    }
+}
+
+/// Interpolate two colors with premultiplied alpha, `progress` is
+/// the weight of the second color. Missing hues take the other hue.
+/// https://www.w3.org/TR/css-color-4/#interpolation
+pub fn interpolate(
+    space: ColorSpace,
+    hue_interpolation: HueInterpolation,
+    from: ([f64; 3], f64),
+    to: ([f64; 3], f64),
+    progress: f64,
+) -> ([f64; 3], f64) {
+    let ((mut from, from_alpha), (mut to, to_alpha)) = (from, to);
+    let lerp = |a: f64, b: f64| a + (b - a) * progress;
+    let hue = space.hue_index();
+
+    if let Some(hue) = hue {
+        match (from[hue].is_nan(), to[hue].is_nan()) {
+            (true, true) => (from[hue], to[hue]) = (0., 0.),
+            (true, false) => from[hue] = to[hue],
+            (false, true) => to[hue] = from[hue],
+            (false, false) => {}
+        }
+        let (a, b) = fix_hues(
+            from[hue].rem_euclid(360.),
+            to[hue].rem_euclid(360.),
+            hue_interpolation,
+        );
+        (from[hue], to[hue]) = (a, b);
+    }
+
+    let alpha = lerp(from_alpha, to_alpha);
+    let coords = [0, 1, 2].map(|index| match Some(index) == hue {
+        true => lerp(from[index], to[index]).rem_euclid(360.),
+        false if alpha == 0. => 0.,
+        false => lerp(from[index] * from_alpha, to[index] * to_alpha) / alpha,
+    });
+    (coords, alpha)
+}
+
+/// Adjust the hues so interpolating between them goes the right way around
+fn fix_hues(from: f64, to: f64, hue_interpolation: HueInterpolation) -> (f64, f64) {
+    let difference = to - from;
+    match hue_interpolation {
+        HueInterpolation::Shorter if difference > 180. => (from + 360., to),
+        HueInterpolation::Shorter if difference < -180. => (from, to + 360.),
+        HueInterpolation::Longer if difference > 0. && difference < 180. => (from + 360., to),
+        HueInterpolation::Longer if difference > -180. && difference <= 0. => (from, to + 360.),
+        HueInterpolation::Increasing if to < from => (from, to + 360.),
+        HueInterpolation::Decreasing if from < to => (from + 360., to),
+        _ => (from, to),
+    }
+}
+
+fn multiply(matrix: &Matrix, vector: [f64; 3]) -> [f64; 3] {
+    let row = |row: &[f64; 3]| row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2];
+    [row(&matrix[0]), row(&matrix[1]), row(&matrix[2])]
+}
+
+fn map(vector: [f64; 3], f: impl Fn(f64) -> f64) -> [f64; 3] {
+    [f(vector[0]), f(vector[1]), f(vector[2])]
+}
+
+/// Apply a transfer function to the magnitude of a channel, so
+/// the channels out of the gamut stay negative
+fn signed(value: f64, f: impl Fn(f64) -> f64) -> f64 {
+    value.signum() * f(value.abs())
+}
+
+fn srgb_decode(value: f64) -> f64 {
+    signed(value, |value| match value <= 0.04045 {
+        true => value / 12.92,
+        false => ((value + 0.055) / 1.055).powf(2.4),
+    })
+}
+
+fn srgb_encode(value: f64) -> f64 {
+    signed(value, |value| match value > 0.0031308 {
+        true => 1.055 * value.powf(1. / 2.4) - 0.055,
+        false => value * 12.92,
+    })
+}
+
+fn a98_decode(value: f64) -> f64 {
+    signed(value, |value| value.powf(563. / 256.))
+}
+
+fn a98_encode(value: f64) -> f64 {
+    signed(value, |value| value.powf(256. / 563.))
+}
+
+fn prophoto_decode(value: f64) -> f64 {
+    signed(value, |value| match value <= 16. / 512. {
+        true => value / 16.,
+        false => value.powf(1.8),
+    })
+}
+
+fn prophoto_encode(value: f64) -> f64 {
+    signed(value, |value| match value >= 1. / 512. {
+        true => value.powf(1. / 1.8),
+        false => value * 16.,
+    })
+}
+
+const REC2020_ALPHA: f64 = 1.099_296_826_809_44;
+const REC2020_BETA: f64 = 0.018_053_968_510_807;
+
+fn rec2020_decode(value: f64) -> f64 {
+    signed(value, |value| match value < REC2020_BETA * 4.5 {
+        true => value / 4.5,
+        false => ((value + REC2020_ALPHA - 1.) / REC2020_ALPHA).powf(1. / 0.45),
+    })
+}
+
+fn rec2020_encode(value: f64) -> f64 {
+    signed(value, |value| match value > REC2020_BETA {
+        true => REC2020_ALPHA * value.powf(0.45) - (REC2020_ALPHA - 1.),
+        false => value * 4.5,
+    })
+}
+
+const LAB_EPSILON: f64 = 216. / 24389.;
+const LAB_KAPPA: f64 = 24389. / 27.;
+
+fn lab_to_xyz_d50([lightness, a, b]: [f64; 3]) -> [f64; 3] {
+    let fy = (lightness + 16.) / 116.;
+    let fx = a / 500. + fy;
+    let fz = fy - b / 200.;
+    let f_inverse = |f: f64| match f.powi(3) > LAB_EPSILON {
+        true => f.powi(3),
+        false => (116. * f - 16.) / LAB_KAPPA,
+    };
+    let y = match lightness > LAB_KAPPA * LAB_EPSILON {
+        true => fy.powi(3),
+        false => lightness / LAB_KAPPA,
+    };
+    [
+        f_inverse(fx) * D50_WHITE[0],
+        y * D50_WHITE[1],
+        f_inverse(fz) * D50_WHITE[2],
+    ]
+}
+
+fn xyz_d50_to_lab(xyz: [f64; 3]) -> [f64; 3] {
+    let f = |index: usize| {
+        let value = xyz[index] / D50_WHITE[index];
+        match value > LAB_EPSILON {
+            true => value.cbrt(),
+            false => (LAB_KAPPA * value + 16.) / 116.,
+        }
+    };
+    let (fx, fy, fz) = (f(0), f(1), f(2));
+    [116. * fy - 16., 500. * (fx - fy), 200. * (fy - fz)]
+}
+
+/// Lightness, chroma and hue from lightness and the a and b axes
+fn rectangular_to_polar([lightness, a, b]: [f64; 3]) -> [f64; 3] {
+    let hue = b.atan2(a).to_degrees().rem_euclid(360.);
+    [lightness, (a * a + b * b).sqrt(), hue]
+}
+
+fn polar_to_rectangular([lightness, chroma, hue]: [f64; 3]) -> [f64; 3] {
+    let hue = hue.to_radians();
+    [lightness, chroma * hue.cos(), chroma * hue.sin()]
+}
+
+/// Hue in degrees, saturation and lightness in percentages
+fn srgb_to_hsl([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (min + max) / 2.;
+    let delta = max - min;
+    if delta == 0. {
+        return [f64::NAN, 0., lightness * 100.];
+    }
+    let saturation = match lightness == 0. || lightness == 1. {
+        true => 0.,
+        false => (max - lightness) / lightness.min(1. - lightness),
+    };
+    let hue = if max == r {
+        (g - b) / delta + if g < b { 6. } else { 0. }
+    } else if max == g {
+        (b - r) / delta + 2.
+    } else {
+        (r - g) / delta + 4.
+    };
+    [hue * 60., saturation * 100., lightness * 100.]
+}
+
+fn hsl_to_srgb([hue, saturation, lightness]: [f64; 3]) -> [f64; 3] {
+    let hue = hue.rem_euclid(360.);
+    let (saturation, lightness) = (saturation / 100., lightness / 100.);
+    let f = |n: f64| {
+        let k = (n + hue / 30.) % 12.;
+        let a = saturation * lightness.min(1. - lightness);
+        lightness - a * (k - 3.).min(9. - k).clamp(-1., 1.)
+    };
+    [f(0.), f(8.), f(4.)]
+}
+
+/// Hue in degrees, whiteness and blackness in percentages
+fn srgb_to_hwb(rgb: [f64; 3]) -> [f64; 3] {
+    let hue = srgb_to_hsl(rgb)[0];
+    let whiteness = rgb[0].min(rgb[1]).min(rgb[2]);
+    let blackness = 1. - rgb[0].max(rgb[1]).max(rgb[2]);
+    [hue, whiteness * 100., blackness * 100.]
+}
+
+fn hwb_to_srgb([hue, whiteness, blackness]: [f64; 3]) -> [f64; 3] {
+    let (whiteness, blackness) = (whiteness / 100., blackness / 100.);
+    if whiteness + blackness >= 1. {
+        let gray = whiteness / (whiteness + blackness);
+        return [gray, gray, gray];
+    }
+    map(hsl_to_srgb([hue, 100., 50.]), |value| {
+        value * (1. - whiteness - blackness) + whiteness
+    })
+}
@@ -0,0 +1,234 @@
+use css::parser::structs::{ComponentValue, Function};
+use css::tokenizer::token::Token;
+
+/// The `content` property value
+/// https://www.w3.org/TR/css-content-3/#content-property
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Content {
+    Normal,
+    None,
+    Items(Vec<ContentItem>),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum ContentItem {
+    String(String),
+    Attr(String),
+    Counter(String, CounterStyle),
+    OpenQuote,
+    CloseQuote,
+    NoOpenQuote,
+    NoCloseQuote,
+}
+
+/// The predefined counter styles supported in `counter()`
+/// https://www.w3.org/TR/css-counter-styles-3/#predefined-counters
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum CounterStyle {
+    Decimal,
+    DecimalLeadingZero,
+    LowerAlpha,
+    UpperAlpha,
+    LowerRoman,
+    UpperRoman,
+    None,
+}
+
+impl Content {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        let values = values
+            .iter()
+            .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+            .collect::<Vec<&ComponentValue>>();
+
+        if let [ComponentValue::PerservedToken(Token::Ident(keyword))] = values.as_slice() {
+            if keyword.eq_ignore_ascii_case("normal") {
+                return Some(Content::Normal);
+            }
+            if keyword.eq_ignore_ascii_case("none") {
+                return Some(Content::None);
+            }
+        }
+
+        if values.is_empty() {
+            return None;
+        }
+
+        values
+            .into_iter()
+            .map(ContentItem::parse)
+            .collect::<Option<Vec<ContentItem>>>()
+            .map(Content::Items)
+    }
+}
+
+impl ContentItem {
+    pub fn parse(value: &ComponentValue) -> Option<Self> {
+        match value {
+            ComponentValue::PerservedToken(Token::Str(value)) => {
+                Some(ContentItem::String(value.clone()))
+            }
+            ComponentValue::PerservedToken(Token::Ident(keyword)) => {
+                match keyword.to_ascii_lowercase().as_str() {
+                    "open-quote" => Some(ContentItem::OpenQuote),
+                    "close-quote" => Some(ContentItem::CloseQuote),
+                    "no-open-quote" => Some(ContentItem::NoOpenQuote),
+                    "no-close-quote" => Some(ContentItem::NoCloseQuote),
+                    _ => None,
+                }
+            }
+            ComponentValue::Function(function) => Self::parse_function(function),
+            _ => None,
+        }
+    }
+
+    fn parse_function(function: &Function) -> Option<Self> {
+        let args = function
+            .value
+            .iter()
+            .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+            .collect::<Vec<&ComponentValue>>();
+
+        match (function.name.to_ascii_lowercase().as_str(), args.as_slice()) {
+            ("attr", [ComponentValue::PerservedToken(Token::Ident(name))]) => {
+                Some(ContentItem::Attr(name.to_ascii_lowercase()))
+            }
+            ("counter", [ComponentValue::PerservedToken(Token::Ident(name))]) => {
+                Some(ContentItem::Counter(name.clone(), CounterStyle::Decimal))
+            }
+            (
+                "counter",
+                [ComponentValue::PerservedToken(Token::Ident(name)), ComponentValue::PerservedToken(Token::Comma), ComponentValue::PerservedToken(Token::Ident(style))],
+            ) => Some(ContentItem::Counter(
+                name.clone(),
+                CounterStyle::parse(style)?,
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl CounterStyle {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "decimal" => Some(CounterStyle::Decimal),
+            "decimal-leading-zero" => Some(CounterStyle::DecimalLeadingZero),
+            "lower-alpha" | "lower-latin" => Some(CounterStyle::LowerAlpha),
+            "upper-alpha" | "upper-latin" => Some(CounterStyle::UpperAlpha),
+            "lower-roman" => Some(CounterStyle::LowerRoman),
+            "upper-roman" => Some(CounterStyle::UpperRoman),
+            "none" => Some(CounterStyle::None),
+            _ => None,
+        }
+    }
+
+    /// Format a counter value into its representation.
+    /// Values outside the range of a style fall back to decimal.
+    pub fn format(&self, value: i32) -> String {
+        match self {
+            CounterStyle::Decimal => value.to_string(),
+            CounterStyle::DecimalLeadingZero if (0..10).contains(&value) => {
+                format!("0{}", value)
+            }
+            CounterStyle::DecimalLeadingZero => value.to_string(),
+            CounterStyle::LowerAlpha => format_alphabetic(value).unwrap_or(value.to_string()),
+            CounterStyle::UpperAlpha => format_alphabetic(value)
+                .map(|value| value.to_ascii_uppercase())
+                .unwrap_or(value.to_string()),
+            CounterStyle::LowerRoman => format_roman(value)
+                .map(|value| value.to_ascii_lowercase())
+                .unwrap_or(value.to_string()),
+            CounterStyle::UpperRoman => format_roman(value).unwrap_or(value.to_string()),
+            CounterStyle::None => String::new(),
+        }
+    }
+}
+
+fn format_alphabetic(value: i32) -> Option<String> {
+    if value < 1 {
+        return None;
+    }
+    let mut value = value;
+    let mut result = Vec::new();
+    while value > 0 {
+        value -= 1;
+        result.push((b'a' + (value % 26) as u8) as char);
+        value /= 26;
+    }
+    Some(result.into_iter().rev().collect())
+}
+
+fn format_roman(value: i32) -> Option<String> {
+    if !(1..4000).contains(&value) {
+        return None;
+    }
+    let numerals = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut value = value;
+    let mut result = String::new();
+    for (number, numeral) in numerals {
+        while value >= number {
+            result.push_str(numeral);
+            value -= number;
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use css::parser::Parser;
+    use css::tokenizer::Tokenizer;
+
+    fn parse_value_str(css: &str) -> Vec<ComponentValue> {
+        let tokenizer = Tokenizer::new(css.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        parser.parse_a_list_of_component_values()
+    }
+
+    #[test]
+    fn parse_content() {
+        assert_eq!(
+            Content::parse(&parse_value_str("none")),
+            Some(Content::None)
+        );
+        assert_eq!(
+            Content::parse(&parse_value_str(
+                "open-quote \"Chapter \" counter(chapter, upper-roman) attr(title) close-quote"
+            )),
+            Some(Content::Items(vec![
+                ContentItem::OpenQuote,
+                ContentItem::String("Chapter ".to_string()),
+                ContentItem::Counter("chapter".to_string(), CounterStyle::UpperRoman),
+                ContentItem::Attr("title".to_string()),
+                ContentItem::CloseQuote,
+            ]))
+        );
+        assert_eq!(Content::parse(&parse_value_str("none \"a\"")), None);
+        assert_eq!(Content::parse(&parse_value_str("counter(a, b)")), None);
+    }
+
+    #[test]
+    fn format_counter_styles() {
+        assert_eq!(CounterStyle::Decimal.format(-3), "-3");
+        assert_eq!(CounterStyle::DecimalLeadingZero.format(7), "07");
+        assert_eq!(CounterStyle::LowerAlpha.format(28), "ab");
+        assert_eq!(CounterStyle::UpperAlpha.format(0), "0");
+        assert_eq!(CounterStyle::UpperRoman.format(1994), "MCMXCIV");
+        assert_eq!(CounterStyle::LowerRoman.format(4), "iv");
+    }
+}
@@ -0,0 +1,53 @@
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// A list of counter names with an optional integer, used by
+/// `counter-reset` and `counter-increment`.
+/// https://www.w3.org/TR/css-lists-3/#counter-properties
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CounterList(pub Vec<(String, Option<i32>)>);
+
+impl CounterList {
+    pub fn none() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        let mut values = values
+            .iter()
+            .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+            .peekable();
+
+        if let Some(ComponentValue::PerservedToken(Token::Ident(keyword))) = values.peek() {
+            if keyword.eq_ignore_ascii_case("none") {
+                values.next();
+                return match values.next() {
+                    Some(_) => None,
+                    None => Some(Self::none()),
+                };
+            }
+        }
+
+        let mut counters = Vec::new();
+        while let Some(value) = values.next() {
+            let name = match value {
+                ComponentValue::PerservedToken(Token::Ident(name)) => name.clone(),
+                _ => return None,
+            };
+            let value = match values.peek() {
+                Some(ComponentValue::PerservedToken(Token::Number { value, .. })) => {
+                    let value = *value as i32;
+                    values.next();
+                    Some(value)
+                }
+                _ => None,
+            };
+            counters.push((name, value));
+        }
+
+        match counters.is_empty() {
+            true => None,
+            false => Some(Self(counters)),
+        }
+    }
+}
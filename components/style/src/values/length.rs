@@ -1,6 +1,11 @@
 use super::number::Number;
+use crate::computes::font_size::BASE_FONT_SIZE;
 use css::parser::structs::ComponentValue;
 use css::tokenizer::token::Token;
+use shared::primitive::Size;
+
+/// The used value of `line-height: normal` relative to the font size
+const NORMAL_LINE_HEIGHT: f32 = 1.2;
 
 #[derive(Debug, Clone, PartialEq, Hash)]
 pub struct Length {
@@ -12,11 +17,25 @@ impl Eq for Length {}
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum LengthUnit {
+    // font-relative lengths
     Em,
     Ex,
+    Ch,
+    Rem,
+    Lh,
+    Rlh,
+    // viewport-percentage lengths
+    Vw,
+    Vh,
+    Vi,
+    Vb,
+    Vmin,
+    Vmax,
+    // absolute lengths
     In,
     Cm,
     Mm,
+    Q,
     Pt,
     Pc,
     Px,
@@ -24,12 +43,31 @@ pub enum LengthUnit {
 
 impl LengthUnit {
     pub fn from_str(unit: &str) -> Option<Self> {
+        let unit = unit.to_ascii_lowercase();
+        // there are no user agent interfaces that expand or retract so the
+        // small, large and dynamic viewport sizes are all the viewport size
+        // https://www.w3.org/TR/css-values-4/#viewport-variants
+        let unit = match unit.strip_prefix(['s', 'l', 'd']) {
+            Some(unit @ ("vw" | "vh" | "vi" | "vb" | "vmin" | "vmax")) => unit,
+            _ => &unit,
+        };
         match unit {
             "em" => Some(LengthUnit::Em),
             "ex" => Some(LengthUnit::Ex),
+            "ch" => Some(LengthUnit::Ch),
+            "rem" => Some(LengthUnit::Rem),
+            "lh" => Some(LengthUnit::Lh),
+            "rlh" => Some(LengthUnit::Rlh),
+            "vw" => Some(LengthUnit::Vw),
+            "vh" => Some(LengthUnit::Vh),
+            "vi" => Some(LengthUnit::Vi),
+            "vb" => Some(LengthUnit::Vb),
+            "vmin" => Some(LengthUnit::Vmin),
+            "vmax" => Some(LengthUnit::Vmax),
             "in" => Some(LengthUnit::In),
             "cm" => Some(LengthUnit::Cm),
             "mm" => Some(LengthUnit::Mm),
+            "q" => Some(LengthUnit::Q),
             "pt" => Some(LengthUnit::Pt),
             "pc" => Some(LengthUnit::Pc),
             "px" => Some(LengthUnit::Px),
@@ -38,6 +76,31 @@ impl LengthUnit {
     }
 }
 
+/// The sizes that relative lengths are resolved against
+/// https://www.w3.org/TR/css-values-4/#relative-lengths
+#[derive(Debug, Clone)]
+pub struct LengthContext {
+    /// The computed font size of the element, or of its parent
+    /// when resolving `font-size`
+    pub font_size: f32,
+    /// The computed font size of the root element
+    pub root_font_size: f32,
+    pub viewport: Size,
+    /// The inline axis of `vi` is vertical in vertical writing modes
+    pub vertical: bool,
+}
+
+impl Default for LengthContext {
+    fn default() -> Self {
+        Self {
+            font_size: BASE_FONT_SIZE,
+            root_font_size: BASE_FONT_SIZE,
+            viewport: Size::default(),
+            vertical: false,
+        }
+    }
+}
+
 impl Length {
     pub fn new(value: f32, unit: LengthUnit) -> Self {
         Self {
@@ -57,11 +120,52 @@ impl Length {
         }
     }
 
+    /// Convert the length to px. Relative lengths are resolved to px
+    /// at computed-value time so they only use the initial font size
+    /// and an empty viewport here.
     pub fn to_px(&self) -> f32 {
-        match self.unit {
-            LengthUnit::Px => *self.value,
-            _ => unreachable!("Calling to_px on non-px length"),
-        }
+        self.resolve(&LengthContext::default())
+    }
+
+    /// Convert a length to px, resolving relative lengths with the context
+    pub fn resolve(&self, context: &LengthContext) -> f32 {
+        let Size { width, height } = context.viewport;
+        let (inline, block) = match context.vertical {
+            true => (height, width),
+            false => (width, height),
+        };
+        // TODO: use the font metrics and line height when we support them
+        let px_per_unit = match self.unit {
+            LengthUnit::Em => context.font_size,
+            LengthUnit::Ex | LengthUnit::Ch => context.font_size / 2.0,
+            LengthUnit::Rem => context.root_font_size,
+            LengthUnit::Lh => context.font_size * NORMAL_LINE_HEIGHT,
+            LengthUnit::Rlh => context.root_font_size * NORMAL_LINE_HEIGHT,
+            LengthUnit::Vw => width / 100.0,
+            LengthUnit::Vh => height / 100.0,
+            LengthUnit::Vi => inline / 100.0,
+            LengthUnit::Vb => block / 100.0,
+            LengthUnit::Vmin => width.min(height) / 100.0,
+            LengthUnit::Vmax => width.max(height) / 100.0,
+            _ => return self.absolute_px().unwrap_or_default(),
+        };
+        *self.value * px_per_unit
+    }
+
+    /// Convert an absolute length to px, relative lengths return `None`
+    /// https://www.w3.org/TR/css-values-4/#absolute-lengths
+    pub fn absolute_px(&self) -> Option<f32> {
+        let px_per_unit = match self.unit {
+            LengthUnit::Px => 1.0,
+            LengthUnit::In => 96.0,
+            LengthUnit::Cm => 96.0 / 2.54,
+            LengthUnit::Mm => 96.0 / 25.4,
+            LengthUnit::Q => 96.0 / 101.6,
+            LengthUnit::Pt => 96.0 / 72.0,
+            LengthUnit::Pc => 96.0 / 6.0,
+            _ => return None,
+        };
+        Some(*self.value * px_per_unit)
     }
 }
 
@@ -87,3 +191,39 @@ impl Length {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_lengths() {
+        let context = LengthContext {
+            font_size: 20.0,
+            root_font_size: 10.0,
+            viewport: Size::new(800.0, 600.0),
+            vertical: false,
+        };
+        let resolve = |value: f32, unit: &str| {
+            Length::new(value, LengthUnit::from_str(unit).unwrap()).resolve(&context)
+        };
+        assert_eq!(resolve(1.0, "in"), 96.0);
+        assert_eq!(resolve(4.0, "Q"), 96.0 / 25.4);
+        assert_eq!(resolve(2.0, "em"), 40.0);
+        assert_eq!(resolve(2.0, "ch"), 20.0);
+        assert_eq!(resolve(2.0, "REM"), 20.0);
+        assert_eq!(resolve(1.0, "lh"), 24.0);
+        assert_eq!(resolve(10.0, "vw"), 80.0);
+        assert_eq!(resolve(10.0, "dvh"), 60.0);
+        assert_eq!(resolve(10.0, "svmin"), 60.0);
+        assert_eq!(resolve(10.0, "lvmax"), 80.0);
+        assert_eq!(resolve(10.0, "vi"), 80.0);
+        assert_eq!(LengthUnit::from_str("xvw"), None);
+
+        let context = LengthContext {
+            vertical: true,
+            ..context
+        };
+        assert_eq!(Length::new(10.0, LengthUnit::Vi).resolve(&context), 60.0);
+    }
+}
@@ -1,5 +1,6 @@
 use css::parser::structs::ComponentValue;
 
+use super::calc::Calc;
 use super::length::Length;
 use super::percentage::Percentage;
 
@@ -34,3 +35,37 @@ impl LengthPercentage {
         }
     }
 }
+
+/// The computed value of the box sizes, margins, paddings and insets,
+/// whose percentages and math functions are resolved during layout
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub enum LengthPercentageOrAuto {
+    Length(Length),
+    Percentage(Percentage),
+    Calc(Calc),
+    Auto,
+}
+
+impl LengthPercentageOrAuto {
+    pub fn is_auto(&self) -> bool {
+        matches!(self, LengthPercentageOrAuto::Auto)
+    }
+
+    /// The size in px relative to the containing block, `auto` is 0
+    pub fn to_px(&self, containing: f32) -> f32 {
+        match self {
+            LengthPercentageOrAuto::Length(l) => l.to_px(),
+            LengthPercentageOrAuto::Percentage(p) => p.to_px(containing),
+            LengthPercentageOrAuto::Calc(c) => c.to_px(containing),
+            LengthPercentageOrAuto::Auto => 0.0,
+        }
+    }
+
+    /// The size in px when it doesn't depend on the containing block
+    pub fn to_absolute_px(&self) -> f32 {
+        match self {
+            LengthPercentageOrAuto::Length(l) => l.to_px(),
+            _ => 0.0,
+        }
+    }
+}
@@ -1,7 +1,12 @@
+pub mod animation;
 pub mod border_radius;
 pub mod border_style;
 pub mod border_width;
+pub mod calc;
 pub mod color;
+pub mod color_space;
+pub mod content;
+pub mod counter;
 pub mod direction;
 pub mod display;
 pub mod float;
@@ -10,19 +15,34 @@ pub mod length_percentage;
 pub mod number;
 pub mod percentage;
 pub mod position;
+pub mod time;
+pub mod timing_function;
+pub mod transform;
+pub mod unparsed;
+pub mod writing_mode;
 
 // Let this pub because in the future we may want to use this in other places.
 // Just maybe....
 pub mod prelude {
+    pub use super::animation::{
+        AnimationDirectionList, AnimationNameList, FillModeList, IterationCountList, TimeList,
+        TimingFunctionList, TransitionPropertyList,
+    };
     pub use super::border_radius::BorderRadius;
     pub use super::border_style::BorderStyle;
-    pub use super::border_width::BorderWidth;
+    pub use super::border_width::{BorderWidth, LineWidth};
+    pub use super::calc::Calc;
     pub use super::color::Color;
+    pub use super::content::Content;
+    pub use super::counter::CounterList;
     pub use super::direction::Direction;
     pub use super::display::Display;
     pub use super::float::Float;
     pub use super::length::Length;
-    pub use super::length_percentage::LengthPercentage;
+    pub use super::length_percentage::{LengthPercentage, LengthPercentageOrAuto};
     pub use super::percentage::Percentage;
     pub use super::position::Position;
+    pub use super::transform::Transform;
+    pub use super::unparsed::UnparsedValue;
+    pub use super::writing_mode::WritingMode;
 }
@@ -0,0 +1,37 @@
+use super::number::Number;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// A duration in milliseconds
+/// https://www.w3.org/TR/css-values-3/#time
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct Time(pub Number);
+
+impl Eq for Time {}
+
+impl Time {
+    pub fn zero() -> Self {
+        Self(0.0.into())
+    }
+
+    pub fn from_ms(ms: f32) -> Self {
+        Self(ms.into())
+    }
+
+    pub fn ms(&self) -> f32 {
+        *self.0
+    }
+
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values {
+            [ComponentValue::PerservedToken(Token::Dimension { value, unit, .. })] => {
+                match unit.to_ascii_lowercase().as_str() {
+                    "s" => Some(Self::from_ms(value * 1000.)),
+                    "ms" => Some(Self::from_ms(*value)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
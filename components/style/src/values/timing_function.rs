@@ -0,0 +1,218 @@
+use super::number::Number;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// An easing function mapping the progress of an animation
+/// https://www.w3.org/TR/css-easing-1/#easing-functions
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum TimingFunction {
+    Linear,
+    CubicBezier(Number, Number, Number, Number),
+    Steps(u32, StepPosition),
+}
+
+impl Eq for TimingFunction {}
+
+/// https://www.w3.org/TR/css-easing-1/#step-position
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum StepPosition {
+    JumpStart,
+    JumpEnd,
+    JumpNone,
+    JumpBoth,
+}
+
+impl TimingFunction {
+    pub fn ease() -> Self {
+        Self::cubic_bezier(0.25, 0.1, 0.25, 1.)
+    }
+
+    fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Self::CubicBezier(x1.into(), y1.into(), x2.into(), y2.into())
+    }
+
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values {
+            [ComponentValue::PerservedToken(Token::Ident(keyword))] => {
+                match keyword.to_ascii_lowercase().as_str() {
+                    "linear" => Some(Self::Linear),
+                    "ease" => Some(Self::ease()),
+                    "ease-in" => Some(Self::cubic_bezier(0.42, 0., 1., 1.)),
+                    "ease-out" => Some(Self::cubic_bezier(0., 0., 0.58, 1.)),
+                    "ease-in-out" => Some(Self::cubic_bezier(0.42, 0., 0.58, 1.)),
+                    "step-start" => Some(Self::Steps(1, StepPosition::JumpStart)),
+                    "step-end" => Some(Self::Steps(1, StepPosition::JumpEnd)),
+                    _ => None,
+                }
+            }
+            [ComponentValue::Function(function)] => {
+                let args = function
+                    .value
+                    .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+                    .map(|arg| {
+                        arg.iter()
+                            .filter(|value| {
+                                !matches!(value, ComponentValue::PerservedToken(Token::Whitespace))
+                            })
+                            .collect::<Vec<&ComponentValue>>()
+                    })
+                    .collect::<Vec<_>>();
+                match function.name.to_ascii_lowercase().as_str() {
+                    "cubic-bezier" => Self::parse_cubic_bezier(&args),
+                    "steps" => Self::parse_steps(&args),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_cubic_bezier(args: &[Vec<&ComponentValue>]) -> Option<Self> {
+        let numbers = args
+            .iter()
+            .map(|arg| match arg[..] {
+                [ComponentValue::PerservedToken(Token::Number { value, .. })] => Some(*value),
+                _ => None,
+            })
+            .collect::<Option<Vec<f32>>>()?;
+        match numbers[..] {
+            // the x values must be in the [0, 1] range
+            [x1, y1, x2, y2] if (0. ..=1.).contains(&x1) && (0. ..=1.).contains(&x2) => {
+                Some(Self::cubic_bezier(x1, y1, x2, y2))
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_steps(args: &[Vec<&ComponentValue>]) -> Option<Self> {
+        let steps = match args.first().map(|arg| &arg[..]) {
+            Some([ComponentValue::PerservedToken(Token::Number { value, .. })])
+                if *value >= 1. && value.fract() == 0. =>
+            {
+                *value as u32
+            }
+            _ => return None,
+        };
+        let position = match args.get(1).map(|arg| &arg[..]) {
+            None => StepPosition::JumpEnd,
+            Some([ComponentValue::PerservedToken(Token::Ident(position))]) => {
+                match position.to_ascii_lowercase().as_str() {
+                    "jump-start" | "start" => StepPosition::JumpStart,
+                    "jump-end" | "end" => StepPosition::JumpEnd,
+                    "jump-none" if steps > 1 => StepPosition::JumpNone,
+                    "jump-both" => StepPosition::JumpBoth,
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        match args.len() <= 2 {
+            true => Some(Self::Steps(steps, position)),
+            false => None,
+        }
+    }
+
+    /// Map the input progress to the output progress
+    pub fn apply(&self, progress: f32) -> f32 {
+        match self {
+            TimingFunction::Linear => progress,
+            TimingFunction::CubicBezier(x1, y1, x2, y2) => {
+                solve_cubic_bezier(**x1, **y1, **x2, **y2, progress)
+            }
+            TimingFunction::Steps(steps, position) => {
+                // https://www.w3.org/TR/css-easing-1/#step-easing-algo
+                let steps = *steps as f32;
+                let mut step = (progress * steps).floor();
+                if matches!(position, StepPosition::JumpStart | StepPosition::JumpBoth) {
+                    step += 1.;
+                }
+                if (0. ..=1.).contains(&progress) {
+                    step = step.max(0.);
+                }
+                let jumps = match position {
+                    StepPosition::JumpNone => steps - 1.,
+                    StepPosition::JumpBoth => steps + 1.,
+                    _ => steps,
+                };
+                if progress <= 1. {
+                    step = step.min(jumps);
+                }
+                step / jumps
+            }
+        }
+    }
+}
+
+/// Find the y value of the curve at the x value by Newton's method,
+/// falling back to bisection
+fn solve_cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    if x <= 0. || x >= 1. {
+        return x;
+    }
+    let sample = |a: f32, b: f32, t: f32| {
+        let t1 = 1. - t;
+        3. * t1 * t1 * t * a + 3. * t1 * t * t * b + t * t * t
+    };
+    let slope = |a: f32, b: f32, t: f32| {
+        let t1 = 1. - t;
+        3. * t1 * t1 * a + 6. * t1 * t * (b - a) + 3. * t * t * (1. - b)
+    };
+
+    let mut t = x;
+    for _ in 0..8 {
+        let error = sample(x1, x2, t) - x;
+        if error.abs() < 1e-5 {
+            return sample(y1, y2, t);
+        }
+        let derivative = slope(x1, x2, t);
+        if derivative.abs() < 1e-6 {
+            break;
+        }
+        t -= error / derivative;
+    }
+
+    let (mut low, mut high) = (0., 1.);
+    t = x;
+    while high - low > 1e-5 {
+        if sample(x1, x2, t) < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.;
+    }
+    sample(y1, y2, t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use css::parser::Parser;
+    use css::tokenizer::Tokenizer;
+
+    fn parse(value: &str) -> Option<TimingFunction> {
+        let tokenizer = Tokenizer::new(value.chars());
+        let mut parser = Parser::<Token>::new(tokenizer.run());
+        TimingFunction::parse(&parser.parse_a_list_of_component_values())
+    }
+
+    #[test]
+    fn apply_timing_functions() {
+        let steps = parse("steps(4, jump-start)").unwrap();
+        assert_eq!(steps.apply(0.), 0.25);
+        assert_eq!(steps.apply(0.5), 0.75);
+        assert_eq!(parse("steps(4)").unwrap().apply(0.3), 0.25);
+
+        let ease_in_out = parse("ease-in-out").unwrap();
+        assert_eq!(
+            ease_in_out,
+            parse("cubic-bezier(0.42, 0, 0.58, 1)").unwrap()
+        );
+        assert!((ease_in_out.apply(0.5) - 0.5).abs() < 1e-3);
+        assert!(ease_in_out.apply(0.25) < 0.25);
+        assert_eq!(ease_in_out.apply(1.), 1.);
+
+        assert_eq!(parse("cubic-bezier(2, 0, 0, 1)"), None);
+        assert_eq!(parse("steps(0)"), None);
+    }
+}
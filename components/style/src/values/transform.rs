@@ -0,0 +1,122 @@
+use super::length::Length;
+use super::number::Number;
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// A list of transform functions, empty for `none`
+/// https://www.w3.org/TR/css-transforms-1/#transform-property
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Transform(pub Vec<TransformFunction>);
+
+/// https://www.w3.org/TR/css-transforms-1/#two-d-transform-functions
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum TransformFunction {
+    Translate(Length, Length),
+    Scale(Number, Number),
+    /// An angle in degrees
+    Rotate(Number),
+}
+
+impl Eq for TransformFunction {}
+
+impl Transform {
+    pub fn none() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        let values = values
+            .iter()
+            .filter(|value| !matches!(value, ComponentValue::PerservedToken(Token::Whitespace)))
+            .collect::<Vec<&ComponentValue>>();
+
+        if let [ComponentValue::PerservedToken(Token::Ident(keyword))] = values[..] {
+            return match keyword.eq_ignore_ascii_case("none") {
+                true => Some(Self::none()),
+                false => None,
+            };
+        }
+
+        let functions = values
+            .into_iter()
+            .map(|value| match value {
+                ComponentValue::Function(function) => {
+                    TransformFunction::parse(&function.name, &function.value)
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<TransformFunction>>>()?;
+        match functions.is_empty() {
+            true => None,
+            false => Some(Self(functions)),
+        }
+    }
+}
+
+impl TransformFunction {
+    fn parse(name: &str, args: &[ComponentValue]) -> Option<Self> {
+        let args = args
+            .split(|value| matches!(value, ComponentValue::PerservedToken(Token::Comma)))
+            .map(|arg| {
+                arg.iter()
+                    .filter(|value| {
+                        !matches!(value, ComponentValue::PerservedToken(Token::Whitespace))
+                    })
+                    .cloned()
+                    .collect::<Vec<ComponentValue>>()
+            })
+            .collect::<Vec<_>>();
+
+        let function = match (name.to_ascii_lowercase().as_str(), &args[..]) {
+            ("translate", [x]) => Self::Translate(Length::parse(x)?, Length::zero()),
+            ("translate", [x, y]) => Self::Translate(Length::parse(x)?, Length::parse(y)?),
+            ("translatex", [x]) => Self::Translate(Length::parse(x)?, Length::zero()),
+            ("translatey", [y]) => Self::Translate(Length::zero(), Length::parse(y)?),
+            ("scale", [s]) => {
+                let s = parse_number(s)?;
+                Self::Scale(s.into(), s.into())
+            }
+            ("scale", [x, y]) => Self::Scale(parse_number(x)?.into(), parse_number(y)?.into()),
+            ("scalex", [x]) => Self::Scale(parse_number(x)?.into(), 1.0.into()),
+            ("scaley", [y]) => Self::Scale(1.0.into(), parse_number(y)?.into()),
+            ("rotate", [angle]) => Self::Rotate(parse_angle(angle)?.into()),
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    /// The function that does nothing, used to pad the shorter list
+    /// of an interpolation
+    pub fn identity(&self) -> Self {
+        match self {
+            Self::Translate(..) => Self::Translate(Length::zero(), Length::zero()),
+            Self::Scale(..) => Self::Scale(1.0.into(), 1.0.into()),
+            Self::Rotate(_) => Self::Rotate(0.0.into()),
+        }
+    }
+}
+
+fn parse_number(values: &[ComponentValue]) -> Option<f32> {
+    match values {
+        [ComponentValue::PerservedToken(Token::Number { value, .. })] => Some(*value),
+        _ => None,
+    }
+}
+
+/// Parse an angle to degrees
+/// https://www.w3.org/TR/css-values-3/#angles
+fn parse_angle(values: &[ComponentValue]) -> Option<f32> {
+    match values {
+        [ComponentValue::PerservedToken(Token::Dimension { value, unit, .. })] => {
+            match unit.to_ascii_lowercase().as_str() {
+                "deg" => Some(*value),
+                "grad" => Some(value * 0.9),
+                "rad" => Some(value.to_degrees()),
+                "turn" => Some(value * 360.),
+                _ => None,
+            }
+        }
+        [ComponentValue::PerservedToken(Token::Number { value, .. })] if *value == 0. => Some(0.),
+        _ => None,
+    }
+}
@@ -0,0 +1,53 @@
+use crate::logical::LogicalProperty;
+use css::parser::structs::ComponentValue;
+use css::serializer::ToCss;
+use std::hash::{Hash, Hasher};
+
+/// A declared value that contains `var()` references and therefore can only
+/// be parsed at computed-value time, after the references are substituted.
+/// https://www.w3.org/TR/css-variables-1/#variables-in-shorthands
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnparsedValue {
+    /// The raw token stream as written in the declaration
+    pub tokens: Vec<ComponentValue>,
+    /// The shorthand this value was declared with, if any. When present,
+    /// the substituted tokens need to be expanded again to get the value
+    /// for the longhand.
+    pub shorthand: Option<String>,
+    /// The flow-relative longhand this value is for when it was declared
+    /// with a flow-relative shorthand, e.g. `margin-inline-start` for
+    /// `margin-inline`, since it's mapped to a physical property before
+    /// the shorthand is expanded
+    pub logical_longhand: Option<LogicalProperty>,
+}
+
+impl Eq for UnparsedValue {}
+
+impl Hash for UnparsedValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // equal token streams serialize to the same CSS text
+        let mut css = String::new();
+        for token in &self.tokens {
+            token.to_css(&mut css);
+        }
+        css.hash(state);
+        self.shorthand.hash(state);
+        self.logical_longhand.hash(state);
+    }
+}
+
+impl UnparsedValue {
+    pub fn new(tokens: Vec<ComponentValue>, shorthand: Option<String>) -> Self {
+        Self {
+            tokens,
+            shorthand,
+            logical_longhand: None,
+        }
+    }
+
+    /// A value for a longhand of a flow-relative shorthand
+    pub fn with_logical_longhand(mut self, longhand: LogicalProperty) -> Self {
+        self.logical_longhand = Some(longhand);
+        self
+    }
+}
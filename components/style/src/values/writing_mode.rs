@@ -0,0 +1,34 @@
+use css::parser::structs::ComponentValue;
+use css::tokenizer::token::Token;
+
+/// The block flow direction and the orientation of lines
+/// https://www.w3.org/TR/css-writing-modes-4/#block-flow
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum WritingMode {
+    HorizontalTb,
+    VerticalRl,
+    VerticalLr,
+    SidewaysRl,
+    SidewaysLr,
+}
+
+impl WritingMode {
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        match values.iter().next() {
+            Some(ComponentValue::PerservedToken(Token::Ident(value))) => match value {
+                v if v.eq_ignore_ascii_case("horizontal-tb") => Some(WritingMode::HorizontalTb),
+                v if v.eq_ignore_ascii_case("vertical-rl") => Some(WritingMode::VerticalRl),
+                v if v.eq_ignore_ascii_case("vertical-lr") => Some(WritingMode::VerticalLr),
+                v if v.eq_ignore_ascii_case("sideways-rl") => Some(WritingMode::SidewaysRl),
+                v if v.eq_ignore_ascii_case("sideways-lr") => Some(WritingMode::SidewaysLr),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether lines are laid out vertically, so the inline axis is vertical
+    pub fn is_vertical(&self) -> bool {
+        !matches!(self, WritingMode::HorizontalTb)
+    }
+}
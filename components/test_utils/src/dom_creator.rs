@@ -36,8 +36,7 @@ pub fn element(selector: &str, doc: Rc<Node>, children: Vec<Rc<Node>>) -> Rc<Nod
     for part in selector_parts {
         match part.selector_type() {
             SimpleSelectorType::ID => {
-                node.as_element()
-                    .set_attribute("id", &part.value().clone().unwrap());
+                node.set_attribute("id", &part.value().clone().unwrap());
             }
             SimpleSelectorType::Class => {
                 classes.push(part.value().clone().unwrap());
@@ -47,8 +46,7 @@ pub fn element(selector: &str, doc: Rc<Node>, children: Vec<Rc<Node>>) -> Rc<Nod
     }
 
     if classes.len() > 0 {
-        node.as_element()
-            .set_attribute("class", &classes.join(" ").to_string());
+        node.set_attribute("class", &classes.join(" ").to_string());
     }
 
     for child in children {
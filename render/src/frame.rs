@@ -2,13 +2,25 @@ use std::rc::Rc;
 
 use super::loader::frame::FrameLoader;
 use css::cssom::css_rule::CSSRule;
+use css::media_queries::Device;
+use css::selector::parse_selector_str;
+use css::selector::structs::Selector;
 
+use dom::element_state::ElementState;
 use dom::node::Node;
+use dom::style_sheet_list::{StyleSheetLocation, StyleSheetOwner};
 use layout::dump_layout;
 use layout::formatting_context::{establish_context, FormattingContextType};
 use layout::{formatting_context::LayoutContext, layout_box::LayoutBox};
-use shared::primitive::Rect;
+use shared::primitive::{Point, Rect, Size};
+use std::time::Duration;
+use style::animation::AnimationEngine;
+use style::cascade_layers::LayerOrder;
 use style::render_tree::RenderTree;
+use style::rule_index::{RuleIndex, SelectorIndex};
+use style::selector_matching::is_match_selector;
+use style::tree_builder::TreeBuilder;
+use style::validation::validate_stylesheet;
 use style::value_processing::{CSSLocation, CascadeOrigin, ContextualRule};
 use url::Url;
 
@@ -16,6 +28,8 @@ pub type FrameSize = (u32, u32);
 
 pub struct Frame {
     document: Option<Rc<Node>>,
+    /// The sources of the user stylesheets of the documents
+    user_stylesheets: Vec<String>,
     layout: FrameLayout,
     size: FrameSize,
 }
@@ -23,11 +37,18 @@ pub struct Frame {
 pub struct FrameLayout {
     layout_tree: Option<Rc<LayoutBox>>,
     render_tree: Option<RenderTree>,
+    animations: AnimationEngine,
+    /// The selectors of the rules the render tree was built with,
+    /// kept until the stylesheets or the size change
+    selectors: Option<Rc<SelectorIndex>>,
 }
 
 #[derive(Debug)]
 pub enum ReflowType {
     All(Rc<Node>),
+    /// Restyle the elements invalidated by DOM mutations. The layout
+    /// tree is still built again in full if any box has to be laid out.
+    Restyle(Rc<Node>),
     LayoutOnly,
 }
 
@@ -35,6 +56,7 @@ impl Frame {
     pub fn new() -> Self {
         Self {
             document: None,
+            user_stylesheets: Vec::new(),
             layout: FrameLayout::new(),
             size: (0, 0),
         }
@@ -42,7 +64,11 @@ impl Frame {
 
     pub fn resize(&mut self, new_size: FrameSize) {
         self.size = new_size;
-        self.layout.reflow(self.size, ReflowType::LayoutOnly);
+        // media queries depend on the size so styles are recalculated too
+        match self.document.clone() {
+            Some(document) => self.layout.reflow(self.size, ReflowType::All(document)),
+            None => self.layout.reflow(self.size, ReflowType::LayoutOnly),
+        }
     }
 
     pub fn size(&self) -> FrameSize {
@@ -54,13 +80,104 @@ impl Frame {
         self.layout.reflow(self.size, ReflowType::All(document));
     }
 
+    /// Set the user stylesheets of the documents loaded after
+    pub fn set_user_stylesheets(&mut self, user_stylesheets: Vec<String>) {
+        self.user_stylesheets = user_stylesheets;
+    }
+
     pub fn load_html(&mut self, html: String, base_url: Url) {
-        self.set_document(FrameLoader::load_html(html, base_url));
+        let document = FrameLoader::load_html(html, base_url, &self.user_stylesheets);
+        self.set_document(document);
     }
 
     pub fn layout(&self) -> &FrameLayout {
         &self.layout
     }
+
+    /// Move the animation timeline to the time since the page loaded
+    /// and restyle the document at that time
+    pub fn set_animation_time(&mut self, time: Duration) {
+        self.layout.animations.set_time(time.as_secs_f32() * 1000.);
+        if let Some(document) = self.document.clone() {
+            self.layout.reflow(self.size, ReflowType::All(document));
+        }
+    }
+
+    /// Restyle and relayout the document if its styles were changed,
+    /// e.g. by a CSSOM mutation or a stylesheet finished loading,
+    /// or only the elements affected by DOM mutations
+    pub fn update_rendering(&mut self) {
+        if let Some(document) = self.document.clone() {
+            if document.as_document().is_style_dirty() {
+                self.layout.reflow(self.size, ReflowType::All(document));
+            } else if !document.dirty_flags().is_empty() {
+                self.layout.reflow(self.size, ReflowType::Restyle(document));
+            }
+        }
+    }
+
+    /// Force dynamic states on the elements matching a selector so the
+    /// page renders as if the user interacted with them, e.g. `#btn:hover`.
+    /// Returns false if the forced state is invalid.
+    pub fn force_element_state(&mut self, forced_state: &str) -> bool {
+        let (selector, state) = match parse_forced_state(forced_state) {
+            Some(forced) => forced,
+            None => return false,
+        };
+
+        if let Some(document) = self.document.clone() {
+            let mut elements = Vec::new();
+            collect_matching_elements(&document, &selector, &mut elements);
+            for element in &elements {
+                element.set_state(state, true);
+            }
+            log::debug!(
+                "Forced state {:?} on {} element(s)",
+                forced_state,
+                elements.len()
+            );
+            self.layout.reflow(self.size, ReflowType::All(document));
+        }
+        true
+    }
+}
+
+/// Split a forced state like `#btn:hover:focus` into the selector
+/// of the target elements and the states to force on them
+fn parse_forced_state(forced_state: &str) -> Option<(Selector, ElementState)> {
+    let mut selector = forced_state.trim();
+    let mut state = ElementState::empty();
+
+    while let Some((rest, name)) = selector.rsplit_once(':') {
+        match ElementState::from_name(name) {
+            Some(s) => state.insert(s),
+            None => break,
+        }
+        selector = rest;
+    }
+
+    if state.is_empty() {
+        return None;
+    }
+
+    let selector = if selector.is_empty() { "*" } else { selector };
+    parse_selector_str(selector).map(|selector| (selector, state))
+}
+
+/// The viewport that viewport units and the initial containing block refer to
+fn viewport(size: FrameSize) -> Size {
+    Size::new(size.0 as f32, size.1 as f32)
+}
+
+fn collect_matching_elements(node: &Rc<Node>, selector: &Selector, result: &mut Vec<Rc<Node>>) {
+    for child in node.child_nodes().into_iter() {
+        if child.is_element() {
+            if is_match_selector(child.clone(), selector) {
+                result.push(child.clone());
+            }
+            collect_matching_elements(&child, selector, result);
+        }
+    }
 }
 
 impl FrameLayout {
@@ -68,6 +185,8 @@ impl FrameLayout {
         Self {
             layout_tree: None,
             render_tree: None,
+            animations: AnimationEngine::new(),
+            selectors: None,
         }
     }
 
@@ -75,31 +194,139 @@ impl FrameLayout {
         self.layout_tree.clone()
     }
 
-    pub fn recalculate_styles(&mut self, document_node: Rc<Node>) {
+    /// Collect the style rules of the document that apply at the size
+    /// and update the keyframes of the animations
+    fn with_rules<R>(
+        &mut self,
+        document_node: &Rc<Node>,
+        size: FrameSize,
+        f: impl FnOnce(&mut Self, &[ContextualRule]) -> R,
+    ) -> R {
+        let device = Device::screen(size.0 as f32, size.1 as f32);
         let document = document_node.as_document();
-        let stylesheets = document.stylesheets();
+        let stylesheets = document.enabled_stylesheets();
+        let stylesheets = stylesheets
+            .iter()
+            .map(|entry| {
+                let origin = match entry.owner() {
+                    StyleSheetOwner::UserAgent => CascadeOrigin::UserAgent,
+                    StyleSheetOwner::User => CascadeOrigin::User,
+                    StyleSheetOwner::Node(_) => CascadeOrigin::Author,
+                };
+                let location = match entry.location() {
+                    StyleSheetLocation::Embedded => CSSLocation::Embedded,
+                    StyleSheetLocation::External => CSSLocation::External,
+                };
+                (entry.sheet(), origin, location)
+            })
+            .collect::<Vec<_>>();
+        for (stylesheet, _, _) in &stylesheets {
+            let mut stylesheet = stylesheet.borrow_mut();
+            if !stylesheet.is_validated() {
+                validate_stylesheet(&mut stylesheet);
+                for diagnostic in stylesheet.diagnostics() {
+                    log::debug!("<stylesheet>:{}", diagnostic);
+                }
+            }
+        }
+        let stylesheets = stylesheets
+            .iter()
+            .map(|(stylesheet, origin, location)| (stylesheet.borrow(), origin, location))
+            .filter(|(stylesheet, _, _)| stylesheet.matches_media(&device))
+            .collect::<Vec<_>>();
+        // the layers of rules inserted with the CSSOM are not declared
+        // by the stylesheet so they are ordered after the others
+        // and the layers declared in `@media` rules that don't apply are
+        // not ordered at all
+        let layers = LayerOrder::new(stylesheets.iter().enumerate().flat_map(
+            |(index, (stylesheet, _, _))| {
+                let layers = stylesheet
+                    .layers
+                    .iter()
+                    .filter(|layer| layer.matches_media(&device))
+                    .map(|layer| &layer.name);
+                let rule_layers = stylesheet.iter().filter_map(|rule| match rule {
+                    CSSRule::Style(style) if style.matches_media(&device) => Some(&style.layer),
+                    CSSRule::Style(_) => None,
+                });
+                layers.chain(rule_layers).map(move |name| (index, name))
+            },
+        ));
         // TODO: cache this step so we don't have to flat map on every reflow
         let contextual_rules: Vec<ContextualRule> = stylesheets
             .iter()
-            .flat_map(|stylesheet| {
-                stylesheet.iter().map(|rule| match rule {
-                    CSSRule::Style(style) => ContextualRule {
+            .enumerate()
+            .flat_map(|(index, &(ref stylesheet, origin, location))| {
+                let (device, layers) = (&device, &layers);
+                stylesheet.iter().filter_map(move |rule| match rule {
+                    CSSRule::Style(style) if style.matches_media(device) => Some(ContextualRule {
                         inner: style,
-                        location: CSSLocation::Embedded,
-                        origin: CascadeOrigin::User,
-                    },
+                        location: location.clone(),
+                        origin: origin.clone(),
+                        layer: layers.rank(index, &style.layer),
+                    }),
+                    CSSRule::Style(_) => None,
                 })
             })
             .collect();
+        let keyframes = stylesheets
+            .iter()
+            .flat_map(|(stylesheet, _, _)| stylesheet.keyframes.iter())
+            .filter(|keyframes| keyframes.matches_media(&device))
+            .collect::<Vec<_>>();
+        self.animations.set_keyframes(&keyframes);
 
-        log::debug!("Building render tree");
-        self.render_tree = Some(style::tree_builder::TreeBuilder::build(
-            document_node,
-            &contextual_rules,
-        ));
+        f(self, &contextual_rules)
+    }
+
+    pub fn recalculate_styles(&mut self, document_node: Rc<Node>, size: FrameSize) {
+        self.with_rules(&document_node, size, |layout, rules| {
+            log::debug!("Building render tree");
+            let index = RuleIndex::new(rules);
+            layout.render_tree = Some(TreeBuilder::build_with_index(
+                document_node.clone(),
+                &index,
+                viewport(size),
+                &mut layout.animations,
+            ));
+            layout.selectors = Some(index.selectors());
+        });
+        document_node.as_document().clear_style_dirty();
         log::debug!("Finished render tree");
     }
 
+    /// Restyle the elements invalidated by DOM mutations since the last
+    /// restyle. Returns the nodes whose boxes have to be laid out again.
+    pub fn restyle(&mut self, document_node: Rc<Node>, size: FrameSize) -> Vec<Rc<Node>> {
+        self.with_rules(&document_node, size, |layout, rules| {
+            // the rules only change with the stylesheets or the size,
+            // which restyle the whole document instead
+            let index = match layout.selectors.clone() {
+                Some(selectors) => RuleIndex::with_selectors(rules, selectors),
+                None => RuleIndex::new(rules),
+            };
+            layout.selectors = Some(index.selectors());
+            match &mut layout.render_tree {
+                Some(render_tree) => TreeBuilder::restyle(
+                    render_tree,
+                    document_node.clone(),
+                    &index,
+                    viewport(size),
+                    &mut layout.animations,
+                ),
+                None => {
+                    layout.render_tree = Some(TreeBuilder::build_with_index(
+                        document_node.clone(),
+                        &index,
+                        viewport(size),
+                        &mut layout.animations,
+                    ));
+                    vec![document_node.clone()]
+                }
+            }
+        })
+    }
+
     pub fn recalculate_layout(&mut self, size: FrameSize) {
         if let Some(render_tree) = &self.render_tree {
             log::debug!("Building layout tree");
@@ -110,15 +337,8 @@ impl FrameLayout {
 
             if let Some(root) = &self.layout_tree {
                 log::debug!("Starting layout process");
-                let (width, height) = size;
-
                 let layout_context = Rc::new(LayoutContext {
-                    viewport: Rect {
-                        x: 0.,
-                        y: 0.,
-                        width: width as f32,
-                        height: height as f32,
-                    },
+                    viewport: Rect::from((Point::new(0., 0.), viewport(size))),
                 });
 
                 let initial_block_box = Rc::new(LayoutBox::new_anonymous(
@@ -148,10 +368,161 @@ impl FrameLayout {
                 self.recalculate_layout(size);
             }
             ReflowType::All(document) => {
-                self.recalculate_styles(document.clone());
+                self.recalculate_styles(document.clone(), size);
                 self.recalculate_layout(size);
             }
+            ReflowType::Restyle(document) => {
+                let relayout = self.restyle(document.clone(), size);
+                log::debug!("Restyled with {} node(s) to lay out", relayout.len());
+                if !relayout.is_empty() {
+                    self.recalculate_layout(size);
+                }
+            }
         }
         log::debug!("Finished reflowing with type: {:?}", type_);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dom::style_sheet_list::StyleSheetEntry;
+    use test_utils::css::parse_stylesheet;
+    use test_utils::dom_creator::{document, element};
+
+    #[test]
+    fn parse_forced_states() {
+        let (selector, state) = parse_forced_state("#btn:hover:focus").unwrap();
+        assert_eq!(selector, parse_selector_str("#btn").unwrap());
+        assert_eq!(state, ElementState::HOVER | ElementState::FOCUS);
+
+        let (selector, state) = parse_forced_state(" :active ").unwrap();
+        assert_eq!(selector, parse_selector_str("*").unwrap());
+        assert_eq!(state, ElementState::ACTIVE);
+
+        assert!(parse_forced_state("#btn").is_none());
+        assert!(parse_forced_state("#btn:unknown").is_none());
+    }
+
+    #[test]
+    fn force_state_on_matching_elements() {
+        let doc = document();
+        let button = element("div#btn", doc.clone(), vec![]);
+        let other = element("div", doc.clone(), vec![]);
+        let html = element("html", doc.clone(), vec![button.clone(), other.clone()]);
+        Node::append_child(doc.clone(), html);
+
+        let mut frame = Frame::new();
+        frame.set_document(doc);
+        assert!(frame.force_element_state("#btn:hover"));
+        assert!(!frame.force_element_state("#btn"));
+        assert_eq!(button.as_element().state(), ElementState::HOVER);
+        assert_eq!(other.as_element().state(), ElementState::empty());
+    }
+
+    #[test]
+    fn reuse_selectors_until_stylesheets_change() {
+        let doc = document();
+        let div = element("div", doc.clone(), vec![]);
+        Node::append_child(doc.clone(), element("html", doc.clone(), vec![div.clone()]));
+        doc.as_document().add_stylesheet(StyleSheetEntry::new(
+            parse_stylesheet(".a { color: red }"),
+            StyleSheetOwner::User,
+        ));
+
+        let mut frame = Frame::new();
+        frame.set_document(doc.clone());
+        let selectors = frame.layout.selectors.clone().unwrap();
+
+        div.set_attribute("class", "a");
+        frame.update_rendering();
+        assert!(Rc::ptr_eq(
+            &selectors,
+            frame.layout.selectors.as_ref().unwrap()
+        ));
+
+        doc.as_document().mark_style_dirty();
+        frame.update_rendering();
+        assert!(!Rc::ptr_eq(
+            &selectors,
+            frame.layout.selectors.as_ref().unwrap()
+        ));
+    }
+
+    #[test]
+    fn skip_layers_of_media_that_do_not_apply() {
+        let doc = document();
+        Node::append_child(doc.clone(), element("html", doc.clone(), vec![]));
+        let css = r"
+            @media print { @layer late; }
+            @layer early, late;
+            @layer late { a { color: blue } }
+            @layer early { a { color: red } }
+        ";
+        doc.as_document().add_stylesheet(StyleSheetEntry::new(
+            parse_stylesheet(css),
+            StyleSheetOwner::User,
+        ));
+
+        let mut layout = FrameLayout::new();
+        let layers = layout.with_rules(&doc, (800, 600), |_, rules| {
+            rules.iter().map(|rule| rule.layer).collect::<Vec<_>>()
+        });
+        assert!(layers[0] > layers[1]);
+    }
+
+    #[test]
+    fn cascade_user_stylesheets_against_author() {
+        let html = "<html><head><style>p { margin-top: 1px; width: 1px !important }</style>\
+                    </head><body><p></p></body></html>";
+        let mut frame = Frame::new();
+        frame.set_user_stylesheets(vec![
+            "p { margin-top: 2px; width: 2px !important }".to_string()
+        ]);
+        frame.load_html(html.to_string(), Url::new());
+
+        let document = frame.document.clone().unwrap();
+        let origins = frame.layout.with_rules(&document, (800, 600), |_, rules| {
+            let origin = |selector: &str| {
+                rules
+                    .iter()
+                    .filter(|rule| rule.inner.selector_text() == selector)
+                    .map(|rule| rule.origin.clone())
+                    .collect::<Vec<_>>()
+            };
+            (origin("html"), origin("p"))
+        });
+        assert_eq!(origins.0, vec![CascadeOrigin::UserAgent]);
+        assert_eq!(origins.1[1..], [CascadeOrigin::User, CascadeOrigin::Author]);
+
+        let render_tree = frame.layout.render_tree.as_ref().unwrap();
+        let body = render_tree.root.as_ref().unwrap().children.borrow()[0].clone();
+        let p = body.children.borrow()[0].clone();
+        // author normal declarations win over the user ones
+        // but user important declarations win over the author ones
+        assert_eq!(p.style.margin_top().to_absolute_px(), 1.);
+        assert_eq!(p.style.width().to_absolute_px(), 2.);
+    }
+
+    #[test]
+    fn validate_loaded_stylesheets() {
+        let doc = document();
+        Node::append_child(doc.clone(), element("html", doc.clone(), vec![]));
+        let entry = StyleSheetEntry::new(
+            parse_stylesheet("a { colour: red; color: blue }"),
+            StyleSheetOwner::User,
+        );
+        let stylesheet = entry.sheet();
+        doc.as_document().add_stylesheet(entry);
+
+        let mut frame = Frame::new();
+        frame.set_document(doc);
+        let kinds = stylesheet
+            .borrow()
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic.kind.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec!["unknown property `colour`"]);
+    }
+}
@@ -3,22 +3,84 @@ mod loader;
 mod page;
 mod renderer;
 
+use css::parser::Parser;
+use css::tokenizer::token::Token;
+use css::tokenizer::Tokenizer;
 use gfx::Bitmap;
 use renderer::{Renderer, RendererInitializeParams};
+use std::time::Duration;
 use url::Url;
 
+pub use css::diagnostics::Diagnostic;
+
 pub fn version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
 
-pub async fn render_once(html: String, base_url: Url, size: (u32, u32)) -> Bitmap {
+pub async fn render_once(
+    html: String,
+    base_url: Url,
+    size: (u32, u32),
+    forced_states: &[String],
+    user_stylesheets: &[String],
+    time: Duration,
+) -> Bitmap {
+    render_frames(
+        html,
+        base_url,
+        size,
+        forced_states,
+        user_stylesheets,
+        &[time],
+    )
+    .await
+    .remove(0)
+}
+
+/// Render the page at each of the times since it loaded, with
+/// the sources of the user stylesheets. Without times, the page
+/// is rendered once when it loaded.
+pub async fn render_frames(
+    html: String,
+    base_url: Url,
+    size: (u32, u32),
+    forced_states: &[String],
+    user_stylesheets: &[String],
+    times: &[Duration],
+) -> Vec<Bitmap> {
     let mut renderer = Renderer::new().await;
 
-    renderer.initialize(RendererInitializeParams { viewport: size });
+    renderer.initialize(RendererInitializeParams {
+        viewport: size,
+        user_stylesheets: user_stylesheets.to_vec(),
+    });
 
     renderer.load_html(html, base_url);
 
-    renderer.paint();
+    for forced_state in forced_states {
+        renderer.force_element_state(forced_state);
+    }
+
+    let times = match times {
+        [] => &[Duration::ZERO],
+        times => times,
+    };
+    let mut frames = Vec::new();
+    for time in times {
+        renderer.set_time(*time);
+        renderer.paint();
+        frames.push(renderer.output().await);
+    }
+    frames
+}
 
-    renderer.output().await
+/// Parse and validate a stylesheet, returning the problems found
+/// in source order
+pub fn lint_css(css: &str) -> Vec<Diagnostic> {
+    let tokenizer = Tokenizer::new(css.chars());
+    let (tokens, spans) = tokenizer.run_with_spans();
+    let mut parser = Parser::<Token>::with_spans(tokens, spans);
+    let mut stylesheet = parser.parse_a_css_stylesheet();
+    style::validation::validate_stylesheet(&mut stylesheet);
+    stylesheet.diagnostics().to_vec()
 }
@@ -6,12 +6,14 @@ use css::tokenizer::Tokenizer;
 use document_loader::inprocess::InprocessLoader;
 use dom::document::Document;
 use dom::node::{Node, NodeData};
+use dom::style_sheet_list::{StyleSheetEntry, StyleSheetLocation, StyleSheetOwner};
 use url::Url;
 
 pub struct FrameLoader;
 
 impl FrameLoader {
-    pub fn load_html(html: String, url: Url) -> Rc<Node> {
+    /// Load a document with the UA stylesheet and the user stylesheets
+    pub fn load_html(html: String, url: Url, user_stylesheets: &[String]) -> Rc<Node> {
         let document = Rc::new(Node::new(NodeData::Document(Document::new())));
         document.as_document().set_loader(InprocessLoader::new());
 
@@ -19,7 +21,24 @@ impl FrameLoader {
         let tokenizer = Tokenizer::new(default_css.chars());
         let mut parser = Parser::<Token>::new(tokenizer.run());
         let stylesheet = parser.parse_a_css_stylesheet();
-        document.as_document().append_stylesheet(stylesheet);
+        document.as_document().add_stylesheet(
+            StyleSheetEntry::new(stylesheet, StyleSheetOwner::UserAgent)
+                .with_location(StyleSheetLocation::External),
+        );
+
+        for css in user_stylesheets {
+            let tokenizer = Tokenizer::new(css.chars());
+            let (tokens, spans) = tokenizer.run_with_spans();
+            let mut parser = Parser::<Token>::with_spans(tokens, spans);
+            let stylesheet = parser.parse_a_css_stylesheet();
+            for diagnostic in stylesheet.diagnostics() {
+                log::debug!("<user stylesheet>:{}", diagnostic);
+            }
+            document.as_document().add_stylesheet(
+                StyleSheetEntry::new(stylesheet, StyleSheetOwner::User)
+                    .with_location(StyleSheetLocation::External),
+            );
+        }
 
         log::debug!("Base URL: {}", url);
         document.as_document().set_base(Some(url));
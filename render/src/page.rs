@@ -1,15 +1,28 @@
+use std::time::Duration;
 use url::Url;
 
 use super::frame::Frame;
 
 pub struct Page {
     main_frame: Frame,
+    /// The virtual clock of the animations, the time since the page
+    /// loaded. It only moves when it is set.
+    time: Duration,
 }
 
 impl Page {
     pub fn new() -> Self {
         Self {
             main_frame: Frame::new(),
+            time: Duration::ZERO,
+        }
+    }
+
+    /// Move the clock and restyle the page if the time changed
+    pub fn set_time(&mut self, time: Duration) {
+        if time != self.time {
+            self.time = time;
+            self.main_frame.set_animation_time(time);
         }
     }
 
@@ -17,11 +30,24 @@ impl Page {
         &self.main_frame
     }
 
+    pub fn set_user_stylesheets(&mut self, user_stylesheets: Vec<String>) {
+        self.main_frame.set_user_stylesheets(user_stylesheets);
+    }
+
     pub fn resize(&mut self, size: (u32, u32)) {
         self.main_frame.resize(size);
     }
 
+    /// Load a document, its animations start at the current time
     pub fn load_html(&mut self, html: String, base_url: Url) {
         self.main_frame.load_html(html, base_url);
     }
+
+    pub fn update_rendering(&mut self) {
+        self.main_frame.update_rendering();
+    }
+
+    pub fn force_element_state(&mut self, forced_state: &str) -> bool {
+        self.main_frame.force_element_state(forced_state)
+    }
 }
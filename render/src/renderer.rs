@@ -3,6 +3,7 @@ use super::page::Page;
 use gfx::{Bitmap, Canvas};
 use painting::Painter;
 use shared::primitive::Size;
+use std::time::Duration;
 use url::Url;
 
 pub struct Renderer<'a> {
@@ -12,6 +13,8 @@ pub struct Renderer<'a> {
 
 pub struct RendererInitializeParams {
     pub viewport: FrameSize,
+    /// The sources of the stylesheets of the user origin
+    pub user_stylesheets: Vec<String>,
 }
 
 impl<'a> Renderer<'a> {
@@ -23,6 +26,7 @@ impl<'a> Renderer<'a> {
     }
 
     pub fn initialize(&mut self, params: RendererInitializeParams) {
+        self.page.set_user_stylesheets(params.user_stylesheets);
         self.page.resize(params.viewport);
         self.painter.resize(Size::new(
             params.viewport.0 as f32,
@@ -34,7 +38,22 @@ impl<'a> Renderer<'a> {
         self.page.load_html(html, base_url);
     }
 
+    /// Render the page as if the elements matching the selector were in
+    /// the given states, e.g. `#btn:hover`
+    pub fn force_element_state(&mut self, forced_state: &str) {
+        if !self.page.force_element_state(forced_state) {
+            log::warn!("Invalid forced element state: {}", forced_state);
+        }
+    }
+
+    /// Set the time of the page animations
+    pub fn set_time(&mut self, time: Duration) {
+        self.page.set_time(time);
+    }
+
     pub fn paint(&mut self) {
+        self.page.update_rendering();
+        self.painter.clear();
         let main_frame = self.page.main_frame();
 
         if let Some(layout_root) = main_frame.layout().layout_tree() {
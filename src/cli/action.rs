@@ -1,21 +1,46 @@
 use clap::ArgMatches;
 use std::str::FromStr;
+use std::time::Duration;
 
 pub enum Action {
     RenderOnce(RenderOnceParams),
+    LintCss(LintCssParams),
 }
 
 pub struct RenderOnceParams {
     pub html_path: String,
     pub viewport_size: (u32, u32),
     pub output_path: String,
+    pub forced_states: Vec<String>,
+    /// The paths of the user stylesheets
+    pub user_css_paths: Vec<String>,
+    /// The animation times of the frames to render, one output each
+    pub times: Vec<Duration>,
+}
+
+pub struct LintCssParams {
+    pub css_paths: Vec<String>,
 }
 
 pub fn get_action<'a>(matches: ArgMatches<'a>) -> Action {
+    if let Some(matches) = matches.subcommand_matches("lint-css") {
+        return Action::LintCss(LintCssParams {
+            css_paths: get_args(matches, "css"),
+        });
+    }
+
     if let Some(matches) = matches.subcommand_matches("render") {
         let html: String = get_arg(&matches, "html").unwrap();
         let raw_size: String = get_arg(&matches, "size").unwrap();
         let output_path: String = get_arg(&matches, "output").unwrap();
+        let forced_states = get_args(matches, "force-state");
+        let user_css_paths = get_args(matches, "user-css");
+        let time = get_time(matches, "time").unwrap_or_default();
+        let frames: u32 = get_arg(matches, "frames").unwrap_or(1);
+        let frame_interval = get_time(matches, "frame-interval").unwrap_or_default();
+        let times = (0..frames)
+            .map(|frame| time.saturating_add(frame_interval.saturating_mul(frame)))
+            .collect();
 
         let is_render_once = get_flag(&matches, "once");
 
@@ -26,6 +51,9 @@ pub fn get_action<'a>(matches: ArgMatches<'a>) -> Action {
                 html_path: html,
                 output_path,
                 viewport_size,
+                forced_states,
+                user_css_paths,
+                times,
             });
         }
     }
@@ -46,6 +74,41 @@ fn parse_size(raw_size: &str) -> (u32, u32) {
     }
 }
 
+/// Parse a time such as `1500ms` or `1.5s`
+fn parse_time(raw_time: &str) -> Option<Duration> {
+    let raw_time = raw_time.trim();
+    let ms = match raw_time.strip_suffix("ms") {
+        Some(ms) => ms.parse::<f64>().ok()?,
+        None => raw_time.strip_suffix('s')?.parse::<f64>().ok()? * 1000.,
+    };
+    // rejects negative and non-finite times, which `Duration` can't hold
+    Duration::try_from_secs_f64(ms / 1000.).ok()
+}
+
+pub fn validate_time(raw_time: String) -> Result<(), String> {
+    match parse_time(&raw_time) {
+        Some(_) => Ok(()),
+        None => Err(format!(
+            "Invalid time: {} (expected e.g. \"1500ms\" or \"1.5s\")",
+            raw_time
+        )),
+    }
+}
+
+pub fn validate_frames(raw_frames: String) -> Result<(), String> {
+    match raw_frames.parse::<u32>() {
+        Ok(frames) if frames > 0 => Ok(()),
+        _ => Err(format!(
+            "Invalid number of frames: {} (expected a positive integer)",
+            raw_frames
+        )),
+    }
+}
+
+fn get_time(matches: &ArgMatches, name: &str) -> Option<Duration> {
+    matches.value_of(name).and_then(parse_time)
+}
+
 fn get_arg<'a, T: FromStr>(matches: &ArgMatches, name: &'a str) -> Option<T> {
     matches
         .value_of(name)
@@ -53,6 +116,13 @@ fn get_arg<'a, T: FromStr>(matches: &ArgMatches, name: &'a str) -> Option<T> {
         .unwrap_or(None)
 }
 
+fn get_args(matches: &ArgMatches, name: &str) -> Vec<String> {
+    matches
+        .values_of(name)
+        .map(|values| values.map(|value| value.to_string()).collect())
+        .unwrap_or_default()
+}
+
 fn get_flag<'a>(matches: &ArgMatches, flag: &'a str) -> bool {
     matches.is_present(flag)
 }
@@ -6,6 +6,10 @@ use clap::{App, Arg, ArgMatches};
 const AUTHOR: &'static str = "Viet-Hung Nguyen <viethungax@gmail.com>";
 
 pub fn accept_cli<'a>() -> ArgMatches<'a> {
+    app().get_matches()
+}
+
+fn app<'a, 'b>() -> App<'a, 'b> {
     let html_file_arg = Arg::with_name("html")
         .long("html")
         .required(false)
@@ -23,6 +27,44 @@ pub fn accept_cli<'a>() -> ArgMatches<'a> {
         .required(true)
         .takes_value(true);
 
+    let force_state_arg = Arg::with_name("force-state")
+        .long("force-state")
+        .help("Render as if the matching elements were in a state, e.g. \"#btn:hover\"")
+        .required(false)
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1);
+
+    let time_arg = Arg::with_name("time")
+        .long("time")
+        .help("Render the animations at a time after the page loaded, e.g. \"1500ms\"")
+        .required(false)
+        .takes_value(true)
+        .validator(validate_time);
+
+    let frames_arg = Arg::with_name("frames")
+        .long("frames")
+        .help("Render a sequence of frames, numbered before the output extension")
+        .required(false)
+        .takes_value(true)
+        .validator(validate_frames);
+
+    let frame_interval_arg = Arg::with_name("frame-interval")
+        .long("frame-interval")
+        .help("The time between the frames of a sequence, e.g. \"100ms\"")
+        .required(false)
+        .takes_value(true)
+        .default_value("100ms")
+        .validator(validate_time);
+
+    let user_css_arg = Arg::with_name("user-css")
+        .long("user-css")
+        .help("A user stylesheet, applied with the user origin of the cascade")
+        .required(false)
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1);
+
     let render_once_subcommand = App::new("render")
         .about("Start a rendering process of Moon and render once")
         .version(render::version())
@@ -30,12 +72,94 @@ pub fn accept_cli<'a>() -> ArgMatches<'a> {
         .arg(html_file_arg.clone().required(true))
         .arg(size_arg.clone())
         .arg(once_flag.clone())
-        .arg(ouput_arg.clone());
+        .arg(ouput_arg.clone())
+        .arg(force_state_arg.clone())
+        .arg(user_css_arg)
+        .arg(time_arg)
+        .arg(frames_arg)
+        .arg(frame_interval_arg);
+
+    let css_files_arg = Arg::with_name("css")
+        .help("The stylesheets to check")
+        .required(true)
+        .multiple(true);
+
+    let lint_css_subcommand = App::new("lint-css")
+        .about("Report the parse errors and unsupported declarations of stylesheets")
+        .version(render::version())
+        .author(AUTHOR)
+        .arg(css_files_arg);
 
     App::new("Moon Renderer")
         .version("1.0")
         .author(AUTHOR)
         .about("Moon web browser!")
         .subcommand(render_once_subcommand)
-        .get_matches()
+        .subcommand(lint_css_subcommand)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RENDER_ARGS: [&str; 9] = [
+        "moon",
+        "render",
+        "--once",
+        "--html",
+        "index.html",
+        "--size",
+        "800x600",
+        "--output",
+        "out.png",
+    ];
+
+    fn render_params(args: &[&str]) -> RenderOnceParams {
+        let matches = app().get_matches_from(RENDER_ARGS.iter().chain(args));
+        match get_action(matches) {
+            Action::RenderOnce(params) => params,
+            _ => panic!("Expected a render action"),
+        }
+    }
+
+    #[test]
+    fn accept_forced_states() {
+        let params = render_params(&["--force-state", "#btn:hover", "--force-state", "a:focus"]);
+        assert_eq!(params.forced_states, vec!["#btn:hover", "a:focus"]);
+        assert!(render_params(&[]).forced_states.is_empty());
+    }
+
+    #[test]
+    fn accept_user_stylesheets() {
+        let params = render_params(&["--user-css", "user.css", "--user-css", "print.css"]);
+        assert_eq!(params.user_css_paths, vec!["user.css", "print.css"]);
+        assert!(render_params(&[]).user_css_paths.is_empty());
+    }
+
+    #[test]
+    fn accept_frame_sequences() {
+        let params = render_params(&["--time", "1s", "--frames", "3"]);
+        let times = params
+            .times
+            .iter()
+            .map(|time| time.as_millis())
+            .collect::<Vec<_>>();
+        assert_eq!(times, vec![1000, 1100, 1200]);
+        assert_eq!(render_params(&[]).times.len(), 1);
+    }
+
+    #[test]
+    fn reject_invalid_frames_and_times() {
+        let rejects = |args: &[&str]| {
+            app()
+                .get_matches_from_safe(RENDER_ARGS.iter().chain(args))
+                .is_err()
+        };
+        assert!(rejects(&["--frames", "0"]));
+        assert!(rejects(&["--frames", "abc"]));
+        assert!(rejects(&["--time", "soon"]));
+        assert!(rejects(&["--time", "infs"]));
+        assert!(rejects(&["--frame-interval", "-1ms"]));
+        assert!(!rejects(&["--frames", "2", "--time", "0.5s"]));
+    }
 }
@@ -15,6 +15,22 @@ fn read_file(path: String) -> String {
     return result;
 }
 
+/// Number a frame of a sequence before the extension of the output,
+/// e.g. `out.png` becomes `out-07.png`
+fn frame_output_path(output_path: &str, frame: usize, frame_count: usize) -> String {
+    let width = (frame_count - 1).to_string().len();
+    let path = std::path::Path::new(output_path);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("");
+    let file_name = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => format!("{}-{:0width$}.{}", stem, frame, extension, width = width),
+        None => format!("{}-{:0width$}", stem, frame, width = width),
+    };
+    path.with_file_name(file_name).to_string_lossy().to_string()
+}
+
 #[tokio::main]
 async fn main() {
     let config = ConfigBuilder::new()
@@ -43,12 +59,45 @@ async fn main() {
             let absolute_path = absolute_html_path.parent().unwrap();
             let absolute_path_url = format!("file://{}/", absolute_path.to_str().unwrap());
             let base_url = URLParser::parse(&absolute_path_url, None).unwrap();
-            let bitmap = render::render_once(html_code.to_string(), base_url, viewport).await;
+            let user_stylesheets = params
+                .user_css_paths
+                .into_iter()
+                .map(read_file)
+                .collect::<Vec<_>>();
+            let bitmaps = render::render_frames(
+                html_code.to_string(),
+                base_url,
+                viewport,
+                &params.forced_states,
+                &user_stylesheets,
+                &params.times,
+            )
+            .await;
 
             let (width, height) = viewport;
+            let frame_count = bitmaps.len();
 
-            let buffer = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, bitmap).unwrap();
-            buffer.save(output_path).unwrap();
+            for (frame, bitmap) in bitmaps.into_iter().enumerate() {
+                let output_path = match frame_count {
+                    1 => output_path.clone(),
+                    _ => frame_output_path(&output_path, frame, frame_count),
+                };
+                let buffer = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, bitmap).unwrap();
+                buffer.save(output_path).unwrap();
+            }
+        }
+        cli::Action::LintCss(params) => {
+            let mut has_problems = false;
+            for css_path in params.css_paths {
+                let css = read_file(css_path.clone());
+                for diagnostic in render::lint_css(&css) {
+                    println!("{}:{}", css_path, diagnostic);
+                    has_problems = true;
+                }
+            }
+            if has_problems {
+                std::process::exit(1);
+            }
         }
     }
 